@@ -18,6 +18,7 @@ use futures::{SinkExt, StreamExt, TryFutureExt};
 use block_cipher::{Aes128, BlockCipher};
 use mpz_core::commit::HashCommit;
 use mpz_garble::value::ValueRef;
+use subtle::ConstantTimeEq;
 use tlsn_stream_cipher::{Aes128Ctr, StreamCipher};
 use tlsn_universal_hash::UniversalHash;
 use utils_aio::expect_msg_or_err;
@@ -177,8 +178,9 @@ impl MpcAesGcm {
             .compute_tag(explicit_nonce, payload.clone(), aad)
             .await?;
 
-        // Reject if tag is incorrect.
-        if tag != purported_tag {
+        // Reject if tag is incorrect. The comparison must not leak how many bytes of a forged
+        // tag are correct.
+        if !bool::from(tag.as_slice().ct_eq(purported_tag.as_slice())) {
             return Err(AeadError::CorruptedTag);
         }
 
@@ -7,6 +7,12 @@
 //! For example, one party can privately provide the plaintext to encrypt, while both parties
 //! can see the ciphertext and the tag. Or, both parties can cooperate to decrypt a ciphertext
 //! and verify the tag, while only one party can see the plaintext.
+//!
+//! # Secrets
+//!
+//! The key, IV and any private plaintext are secret. Ciphertexts, tags, explicit nonces and
+//! associated data are public, but tags are still compared in constant time so that a forged
+//! tag is rejected without revealing how close it was.
 
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
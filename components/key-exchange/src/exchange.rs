@@ -5,9 +5,10 @@ use futures::{SinkExt, StreamExt};
 use mpz_garble::{value::ValueRef, Decode, Execute, Load, Memory};
 
 use mpz_fields::{p256::P256, Field};
-use p256::{EncodedPoint, PublicKey, SecretKey};
+use p256::{elliptic_curve::sec1::ToEncodedPoint, AffinePoint, EncodedPoint, PublicKey, SecretKey};
 use point_addition::PointAddition;
 use std::fmt::Debug;
+use zeroize::Zeroizing;
 
 use utils_aio::expect_msg_or_err;
 
@@ -163,6 +164,7 @@ where
             }
         };
 
+        // The private key is only used once, it is zeroized when dropped at the end of this scope
         let private_key = self
             .private_key
             .take()
@@ -173,17 +175,23 @@ where
         // We need to mimic the [diffie-hellman](p256::ecdh::diffie_hellman) function without the
         // [SharedSecret](p256::ecdh::SharedSecret) wrapper, because this makes it harder to get
         // the result as an EC curve point.
-        let shared_secret = {
-            let public_projective = server_key.to_projective();
-            (public_projective * private_key.to_nonzero_scalar().as_ref()).to_affine()
-        };
+        //
+        // The intermediate values are the party's share of the ECDH secret, so they are held in
+        // `Zeroizing` and wiped when dropped. The point addition wipes its copies of the point.
+        let scalar = Zeroizing::new(private_key.to_nonzero_scalar());
+        let product = Zeroizing::new(server_key.to_projective() * scalar.as_ref());
+        let shared_secret = Zeroizing::new(product.to_affine());
+
+        if *shared_secret == AffinePoint::IDENTITY {
+            return Err(p256::elliptic_curve::Error.into());
+        }
 
-        let encoded_point = EncodedPoint::from(PublicKey::from_affine(shared_secret)?);
+        let encoded_point = Zeroizing::new(shared_secret.to_encoded_point(false));
         let (sender_share, receiver_share) = futures::try_join!(
             self.point_addition_sender
-                .compute_x_coordinate_share(encoded_point),
+                .compute_x_coordinate_share(*encoded_point),
             self.point_addition_receiver
-                .compute_x_coordinate_share(encoded_point)
+                .compute_x_coordinate_share(*encoded_point)
         )?;
 
         self.state = State::KeyExchange {
@@ -222,26 +230,21 @@ where
             todo!()
         };
 
-        let pms_share1: [u8; 32] = pms_share1
-            .to_be_bytes()
-            .try_into()
-            .expect("pms share is 32 bytes");
-        let pms_share2: [u8; 32] = pms_share2
-            .to_be_bytes()
-            .try_into()
-            .expect("pms share is 32 bytes");
-
-        match self.config.role() {
-            Role::Leader => {
-                self.executor.assign(&share_a, pms_share1)?;
-                self.executor.assign(&share_c, pms_share2)?;
-            }
-            Role::Follower => {
-                self.executor.assign(&share_b, pms_share1)?;
-                self.executor.assign(&share_d, pms_share2)?;
-            }
+        // The PMS shares are serialized into buffers which are wiped once they have been assigned
+        // to the executor, including the heap buffers returned by `to_be_bytes`.
+        let mut pms_shares = Zeroizing::new([[0u8; 32]; 2]);
+        for (buf, share) in pms_shares.iter_mut().zip([pms_share1, pms_share2]) {
+            let bytes = Zeroizing::new(share.to_be_bytes());
+            buf.copy_from_slice(&bytes);
         }
 
+        let (share_1, share_2) = match self.config.role() {
+            Role::Leader => (&share_a, &share_c),
+            Role::Follower => (&share_b, &share_d),
+        };
+        self.executor.assign(share_1, pms_shares[0])?;
+        self.executor.assign(share_2, pms_shares[1])?;
+
         self.executor
             .execute(
                 build_pms_circuit(),
@@ -257,7 +260,8 @@ where
 
         let eq: [u8; 32] = outputs.remove(0).try_into().expect("eq is 32 bytes");
 
-        // Eq should be all zeros if pms_1 == pms_2
+        // Eq should be all zeros if pms_1 == pms_2. It is decoded to both parties, so it is
+        // public and needs no constant-time comparison.
         if eq != [0u8; 32] {
             return Err(KeyExchangeError::CheckFailed);
         }
@@ -7,11 +7,12 @@
 
 use std::marker::PhantomData;
 
-use super::{PointAddition, PointAdditionError};
+use super::{CurveField, PointAddition, PointAdditionError};
 use async_trait::async_trait;
 use mpz_fields::{p256::P256, Field};
 use mpz_share_conversion::ShareConversion;
 use p256::EncodedPoint;
+use zeroize::Zeroizing;
 
 /// The instance used for adding the curve points
 #[derive(Debug)]
@@ -95,37 +96,51 @@ where
 }
 
 #[async_trait]
-impl<C> PointAddition for MpcPointAddition<P256, C>
+impl<F, C> PointAddition for MpcPointAddition<F, C>
 where
-    C: ShareConversion<P256> + Send + Sync + std::fmt::Debug,
+    F: CurveField,
+    C: ShareConversion<F> + Send + Sync + std::fmt::Debug,
 {
-    type Point = EncodedPoint;
-    type XCoordinate = P256;
+    type Point = F::Point;
+    type XCoordinate = F;
 
     async fn compute_x_coordinate_share(
         &mut self,
         point: Self::Point,
     ) -> Result<Self::XCoordinate, PointAdditionError> {
-        let [x, y] = point_to_p256(point)?;
+        let [x, y] = F::point_to_coordinates(point)?;
         self.convert([x, y]).await
     }
 }
 
+impl CurveField for P256 {
+    type Point = EncodedPoint;
+
+    fn point_to_coordinates(point: Self::Point) -> Result<[Self; 2], PointAdditionError> {
+        point_to_p256(point)
+    }
+}
+
 /// Convert the external library's point type to our library's field type
 #[cfg_attr(
     feature = "tracing",
     tracing::instrument(level = "debug", skip(point), err)
 )]
 pub(crate) fn point_to_p256(point: EncodedPoint) -> Result<[P256; 2], PointAdditionError> {
-    let mut x: [u8; 32] = (*point.x().ok_or(PointAdditionError::Coordinates)?).into();
-    let mut y: [u8; 32] = (*point.y().ok_or(PointAdditionError::Coordinates)?).into();
+    // The point is the party's share of the ECDH secret. It and the coordinates are copied into
+    // buffers which are wiped when dropped, the field elements are owned by the share conversion.
+    let point = Zeroizing::new(point);
+    let mut x = Zeroizing::new([0u8; 32]);
+    let mut y = Zeroizing::new([0u8; 32]);
+    x.copy_from_slice(point.x().ok_or(PointAdditionError::Coordinates)?);
+    y.copy_from_slice(point.y().ok_or(PointAdditionError::Coordinates)?);
 
     // reverse to little endian
     x.reverse();
     y.reverse();
 
-    let x = P256::try_from(x).unwrap();
-    let y = P256::try_from(y).unwrap();
+    let x = P256::try_from(*x).unwrap();
+    let y = P256::try_from(*y).unwrap();
 
     Ok([x, y])
 }
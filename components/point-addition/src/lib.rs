@@ -48,6 +48,17 @@ pub trait PointAddition {
     ) -> Result<Self::XCoordinate, PointAdditionError>;
 }
 
+/// A field underlying an elliptic curve, over which point addition can be performed.
+///
+/// Implement this for the field of a curve to use [MpcPointAddition] with that curve.
+pub trait CurveField: Field + Send + Sync {
+    /// The elliptic curve point type
+    type Point: Send + 'static;
+
+    /// Returns the affine coordinates of the point as field elements.
+    fn point_to_coordinates(point: Self::Point) -> Result<[Self; 2], PointAdditionError>;
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{conversion::point_to_p256, mock::mock_point_converter_pair, PointAddition};
@@ -0,0 +1,48 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use hmac_sha256_circuits::{build_session_keys, build_verify_data};
+use mpz_circuits::evaluate;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("circuits");
+
+    group.bench_function("build_session_keys", |b| b.iter(build_session_keys));
+    group.bench_function("build_verify_data", |b| {
+        b.iter(|| build_verify_data(black_box(b"client finished")))
+    });
+
+    let session_keys = build_session_keys();
+    let pms = [42u8; 32];
+    let client_random = [0u8; 32];
+    let server_random = [1u8; 32];
+    group.bench_function("evaluate_session_keys", |b| {
+        b.iter(|| {
+            evaluate!(
+                session_keys,
+                fn(
+                    pms,
+                    client_random,
+                    server_random,
+                ) -> ([u8; 16], [u8; 16], [u8; 4], [u8; 4], [u32; 8], [u32; 8])
+            )
+            .unwrap()
+        })
+    });
+
+    let verify_data = build_verify_data(b"client finished");
+    let outer_state = [0u32; 8];
+    let inner_state = [1u32; 8];
+    let handshake_hash = [2u8; 32];
+    group.bench_function("evaluate_verify_data", |b| {
+        b.iter(|| {
+            evaluate!(
+                verify_data,
+                fn(outer_state, inner_state, handshake_hash) -> [u8; 12]
+            )
+            .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
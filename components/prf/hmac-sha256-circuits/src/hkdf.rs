@@ -0,0 +1,232 @@
+//! This module provides an implementation of HKDF-SHA256 as used by the TLS 1.3 key schedule,
+//! defined in [RFC 5869](https://www.rfc-editor.org/rfc/rfc5869) and [RFC 8446](https://www.rfc-editor.org/rfc/rfc8446#section-7.1).
+
+use std::cell::RefCell;
+
+use mpz_circuits::{types::U8, BuilderState, Tracer};
+
+use crate::hmac_sha256::{
+    hmac_sha256_finalize, hmac_sha256_finalize_trace, hmac_sha256_partial,
+    hmac_sha256_partial_trace,
+};
+
+/// Returns the HkdfLabel structure without the context, which is appended by the caller.
+///
+/// ```text
+/// struct {
+///     uint16 length = Length;
+///     opaque label<7..255> = "tls13 " + Label;
+///     opaque context<0..255> = Context;
+/// } HkdfLabel;
+/// ```
+fn hkdf_label_prefix(label: &[u8], context_len: usize, len: usize) -> Vec<u8> {
+    let label_len = 6 + label.len();
+    assert!(label_len <= 255, "label must be at most 249 bytes");
+    assert!(context_len <= 255, "context must be at most 255 bytes");
+
+    let mut prefix = Vec::with_capacity(4 + label_len);
+    prefix.extend_from_slice(&(len as u16).to_be_bytes());
+    prefix.push(label_len as u8);
+    prefix.extend_from_slice(b"tls13 ");
+    prefix.extend_from_slice(label);
+    prefix.push(context_len as u8);
+
+    prefix
+}
+
+/// Computes HKDF-Expand(PRK, info, L) using the provided HMAC finalization function.
+///
+/// This is shared by the circuit and reference implementations.
+fn hkdf_expand_with<T: Clone>(
+    hmac: impl Fn(&[T]) -> Vec<T>,
+    constant: impl Fn(u8) -> T,
+    info: &[T],
+    len: usize,
+) -> Vec<T> {
+    assert!(len <= 255 * 32, "HKDF output must be at most 255 blocks");
+
+    // T(0) = empty string
+    // T(i) = HMAC-Hash(PRK, T(i-1) | info | i)
+    let mut t: Vec<T> = Vec::new();
+    let mut output = Vec::with_capacity(len);
+    let mut i = 1u8;
+    while output.len() < len {
+        let mut msg = t;
+        msg.extend_from_slice(info);
+        msg.push(constant(i));
+
+        t = hmac(&msg);
+        output.extend_from_slice(&t);
+        i += 1;
+    }
+    output.truncate(len);
+
+    output
+}
+
+/// Computes HKDF-Extract(salt, IKM).
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `salt`            - N-byte salt (must be <= 64 bytes)
+/// * `ikm`             - The input keying material
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, salt, ikm))
+)]
+pub fn hkdf_extract_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    salt: &[Tracer<'a, U8>],
+    ikm: &[Tracer<'a, U8>],
+) -> [Tracer<'a, U8>; 32] {
+    let (outer_state, inner_state) = hmac_sha256_partial_trace(builder_state, salt);
+
+    hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, ikm)
+}
+
+/// Reference implementation of HKDF-Extract(salt, IKM).
+///
+/// # Arguments
+///
+/// * `salt` - N-byte salt (must be <= 64 bytes)
+/// * `ikm`  - The input keying material
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(salt, ikm)))]
+pub fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+    let (outer_state, inner_state) = hmac_sha256_partial(salt);
+
+    hmac_sha256_finalize(outer_state, inner_state, ikm)
+}
+
+/// Computes HKDF-Expand-Label(Secret, Label, Context, Length).
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `secret`          - 32-byte secret
+/// * `label`           - The label, without the "tls13 " prefix
+/// * `context`         - The context, eg. a transcript hash
+/// * `len`             - The number of bytes to output
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, secret, label, context))
+)]
+pub fn hkdf_expand_label_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    secret: &[Tracer<'a, U8>],
+    label: &[u8],
+    context: &[Tracer<'a, U8>],
+    len: usize,
+) -> Vec<Tracer<'a, U8>> {
+    let constant = |v: u8| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v));
+
+    let mut info = hkdf_label_prefix(label, context.len(), len)
+        .into_iter()
+        .map(constant)
+        .collect::<Vec<_>>();
+    info.extend_from_slice(context);
+
+    let (outer_state, inner_state) = hmac_sha256_partial_trace(builder_state, secret);
+
+    hkdf_expand_with(
+        |msg| hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, msg).to_vec(),
+        constant,
+        &info,
+        len,
+    )
+}
+
+/// Reference implementation of HKDF-Expand-Label(Secret, Label, Context, Length).
+///
+/// # Arguments
+///
+/// * `secret`  - 32-byte secret
+/// * `label`   - The label, without the "tls13 " prefix
+/// * `context` - The context, eg. a transcript hash
+/// * `len`     - The number of bytes to output
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(secret, label, context))
+)]
+pub fn hkdf_expand_label(secret: &[u8], label: &[u8], context: &[u8], len: usize) -> Vec<u8> {
+    let mut info = hkdf_label_prefix(label, context.len(), len);
+    info.extend_from_slice(context);
+
+    let (outer_state, inner_state) = hmac_sha256_partial(secret);
+
+    hkdf_expand_with(
+        |msg| hmac_sha256_finalize(outer_state, inner_state, msg).to_vec(),
+        |v| v,
+        &info,
+        len,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+    use ring::{hkdf, hmac};
+
+    use super::*;
+
+    struct Len(usize);
+
+    impl hkdf::KeyType for Len {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_hkdf_extract() {
+        let builder = CircuitBuilder::new();
+        let salt = builder.add_array_input::<u8, 32>();
+        let ikm = builder.add_array_input::<u8, 32>();
+        let prk = hkdf_extract_trace(builder.state(), &salt, &ikm);
+        builder.add_output(prk);
+        let circ = builder.build().unwrap();
+
+        let salt = [1u8; 32];
+        let ikm = [2u8; 32];
+
+        let expected = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &salt), &ikm);
+        assert_eq!(hkdf_extract(&salt, &ikm), expected.as_ref());
+
+        let actual = evaluate!(circ, fn(salt, ikm) -> [u8; 32]).unwrap();
+
+        assert_eq!(actual.as_slice(), expected.as_ref());
+    }
+
+    #[test]
+    fn test_hkdf_expand_label() {
+        let builder = CircuitBuilder::new();
+        let secret = builder.add_array_input::<u8, 32>();
+        let context = builder.add_array_input::<u8, 32>();
+        let output = hkdf_expand_label_trace(builder.state(), &secret, b"c hs traffic", &context, 40);
+        builder.add_output(output);
+        let circ = builder.build().unwrap();
+
+        let secret = [3u8; 32];
+        let context = [4u8; 32];
+
+        let expected = hkdf_expand_label(&secret, b"c hs traffic", &context, 40);
+
+        let info = [
+            hkdf_label_prefix(b"c hs traffic", context.len(), 40),
+            context.to_vec(),
+        ]
+        .concat();
+        let mut expected_ring = [0u8; 40];
+        hkdf::Prk::new_less_safe(hkdf::HKDF_SHA256, &secret)
+            .expand(&[&info], Len(40))
+            .unwrap()
+            .fill(&mut expected_ring)
+            .unwrap();
+
+        assert_eq!(expected, expected_ring);
+
+        let actual = evaluate!(circ, fn(secret, context) -> Vec<u8>).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,21 +1,37 @@
-//! HMAC-SHA256 circuits.
+//! HMAC-SHA256 and HMAC-SHA384 PRF circuits, and the TLS 1.3 HKDF key schedule circuits.
 
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+mod hkdf;
 mod hmac_sha256;
 mod prf;
 mod session_keys;
+mod sha384;
+mod tls13;
 mod verify_data;
 
+pub use hkdf::{hkdf_expand_label, hkdf_expand_label_trace, hkdf_extract, hkdf_extract_trace};
 pub use hmac_sha256::{
     hmac_sha256_finalize, hmac_sha256_finalize_trace, hmac_sha256_partial,
     hmac_sha256_partial_trace,
 };
 
 pub use prf::{prf, prf_trace};
-pub use session_keys::{session_keys, session_keys_trace};
+pub use session_keys::{
+    session_keys, session_keys_ems, session_keys_ems_trace, session_keys_trace,
+};
+pub use sha384::{
+    hmac_sha384_finalize, hmac_sha384_finalize_trace, hmac_sha384_partial,
+    hmac_sha384_partial_trace, prf_sha384, prf_sha384_trace, session_keys_ems_sha384,
+    session_keys_ems_sha384_trace, session_keys_sha384, session_keys_sha384_trace, sha384, sha384_trace, sha512_compress, sha512_compress_trace,
+    verify_data_sha384, verify_data_sha384_trace,
+};
+pub use tls13::{
+    application_keys, application_keys_trace, finished_vd, finished_vd_trace, handshake_keys,
+    handshake_keys_trace, key_update, key_update_trace,
+};
 pub use verify_data::{verify_data, verify_data_trace};
 
 use mpz_circuits::{Circuit, CircuitBuilder, Tracer};
@@ -39,6 +55,30 @@ pub fn build_session_keys() -> Arc<Circuit> {
     Arc::new(builder.build().expect("session keys should build"))
 }
 
+/// Builds session key derivation circuit with the extended master secret (RFC 7627).
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_session_keys_ems() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let pms = builder.add_array_input::<u8, 32>();
+    let session_hash = builder.add_array_input::<u8, 32>();
+    let client_random = builder.add_array_input::<u8, 32>();
+    let server_random = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv, outer_state, inner_state) = session_keys_ems_trace(
+        builder.state(),
+        pms,
+        session_hash,
+        client_random,
+        server_random,
+    );
+    builder.add_output(cwk);
+    builder.add_output(swk);
+    builder.add_output(civ);
+    builder.add_output(siv);
+    builder.add_output(outer_state);
+    builder.add_output(inner_state);
+    Arc::new(builder.build().expect("session keys should build"))
+}
+
 /// Builds a verify data circuit.
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(label)))]
 pub fn build_verify_data(label: &[u8]) -> Arc<Circuit> {
@@ -59,3 +99,125 @@ pub fn build_verify_data(label: &[u8]) -> Arc<Circuit> {
     builder.add_output(vd);
     Arc::new(builder.build().expect("verify data should build"))
 }
+
+/// Builds session key derivation circuit for cipher suites with a SHA384 PRF.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_session_keys_sha384() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let pms = builder.add_array_input::<u8, 32>();
+    let client_random = builder.add_array_input::<u8, 32>();
+    let server_random = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv, outer_state, inner_state) =
+        session_keys_sha384_trace(builder.state(), pms, client_random, server_random);
+    builder.add_output(cwk);
+    builder.add_output(swk);
+    builder.add_output(civ);
+    builder.add_output(siv);
+    builder.add_output(outer_state);
+    builder.add_output(inner_state);
+    Arc::new(builder.build().expect("session keys should build"))
+}
+
+/// Builds session key derivation circuit with the extended master secret for cipher suites
+/// with a SHA384 PRF.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_session_keys_ems_sha384() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let pms = builder.add_array_input::<u8, 32>();
+    let session_hash = builder.add_array_input::<u8, 48>();
+    let client_random = builder.add_array_input::<u8, 32>();
+    let server_random = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv, outer_state, inner_state) = session_keys_ems_sha384_trace(
+        builder.state(),
+        pms,
+        session_hash,
+        client_random,
+        server_random,
+    );
+    builder.add_output(cwk);
+    builder.add_output(swk);
+    builder.add_output(civ);
+    builder.add_output(siv);
+    builder.add_output(outer_state);
+    builder.add_output(inner_state);
+    Arc::new(builder.build().expect("session keys should build"))
+}
+
+/// Builds a verify data circuit for cipher suites with a SHA384 PRF.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info", skip(label)))]
+pub fn build_verify_data_sha384(label: &[u8]) -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let outer_state = builder.add_array_input::<u64, 8>();
+    let inner_state = builder.add_array_input::<u64, 8>();
+    let handshake_hash = builder.add_array_input::<u8, 48>();
+    let vd = verify_data_sha384_trace(
+        builder.state(),
+        outer_state,
+        inner_state,
+        &label
+            .iter()
+            .map(|v| Tracer::new(builder.state(), builder.get_constant(*v).to_inner()))
+            .collect::<Vec<_>>(),
+        handshake_hash,
+    );
+    builder.add_output(vd);
+    Arc::new(builder.build().expect("verify data should build"))
+}
+
+/// Builds the TLS 1.3 handshake traffic keys circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_tls13_handshake_keys() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let shared_secret = builder.add_array_input::<u8, 32>();
+    let hello_hash = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv, client_secret, server_secret, handshake_secret) =
+        handshake_keys_trace(builder.state(), shared_secret, hello_hash);
+    builder.add_output(cwk);
+    builder.add_output(swk);
+    builder.add_output(civ);
+    builder.add_output(siv);
+    builder.add_output(client_secret);
+    builder.add_output(server_secret);
+    builder.add_output(handshake_secret);
+    Arc::new(builder.build().expect("handshake keys should build"))
+}
+
+/// Builds the TLS 1.3 application traffic keys circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_tls13_application_keys() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let handshake_secret = builder.add_array_input::<u8, 32>();
+    let handshake_hash = builder.add_array_input::<u8, 32>();
+    let (cwk, swk, civ, siv, client_secret, server_secret) =
+        application_keys_trace(builder.state(), handshake_secret, handshake_hash);
+    builder.add_output(cwk);
+    builder.add_output(swk);
+    builder.add_output(civ);
+    builder.add_output(siv);
+    builder.add_output(client_secret);
+    builder.add_output(server_secret);
+    Arc::new(builder.build().expect("application keys should build"))
+}
+
+/// Builds the TLS 1.3 key update circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_tls13_key_update() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let traffic_secret = builder.add_array_input::<u8, 32>();
+    let (key, iv, next_secret) = key_update_trace(builder.state(), traffic_secret);
+    builder.add_output(key);
+    builder.add_output(iv);
+    builder.add_output(next_secret);
+    Arc::new(builder.build().expect("key update should build"))
+}
+
+/// Builds the TLS 1.3 Finished verify data circuit.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "info"))]
+pub fn build_tls13_finished_vd() -> Arc<Circuit> {
+    let builder = CircuitBuilder::new();
+    let traffic_secret = builder.add_array_input::<u8, 32>();
+    let handshake_hash = builder.add_array_input::<u8, 32>();
+    let vd = finished_vd_trace(builder.state(), traffic_secret, handshake_hash);
+    builder.add_output(vd);
+    Arc::new(builder.build().expect("finished verify data should build"))
+}
@@ -9,70 +9,72 @@ use mpz_circuits::{
 
 use crate::hmac_sha256::{hmac_sha256_finalize, hmac_sha256_finalize_trace};
 
-#[cfg_attr(
-    feature = "tracing",
-    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state, seed))
-)]
-fn p_hash_trace<'a>(
-    builder_state: &'a RefCell<BuilderState>,
-    outer_state: [Tracer<'a, U32>; 8],
-    inner_state: [Tracer<'a, U32>; 8],
-    seed: &[Tracer<'a, U8>],
+/// Computes P_hash(secret, seed) using the provided HMAC finalization function.
+///
+/// This is shared by the circuit and reference implementations of all hash families.
+///
+/// # Arguments
+///
+/// * `hmac`        - HMAC_hash(secret, msg), with the secret already bound.
+/// * `seed`        - The seed to use
+/// * `iterations`  - The number of iterations
+pub(crate) fn p_hash_with<T: Clone>(
+    hmac: impl Fn(&[T]) -> Vec<T>,
+    seed: &[T],
     iterations: usize,
-) -> Vec<Tracer<'a, U8>> {
+) -> Vec<T> {
     // A() is defined as:
     //
     // A(0) = seed
     // A(i) = HMAC_hash(secret, A(i-1))
-    let mut a_cache: Vec<_> = Vec::with_capacity(iterations + 1);
+    let mut a_cache: Vec<Vec<T>> = Vec::with_capacity(iterations + 1);
     a_cache.push(seed.to_vec());
 
     for i in 0..iterations {
-        let a_i = hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, &a_cache[i]);
-        a_cache.push(a_i.to_vec());
+        let a_i = hmac(&a_cache[i]);
+        a_cache.push(a_i);
     }
 
     // HMAC_hash(secret, A(i) + seed)
-    let mut output: Vec<_> = Vec::with_capacity(iterations * 32);
+    let mut output: Vec<_> = Vec::new();
     for i in 0..iterations {
         let mut a_i_seed = a_cache[i + 1].clone();
         a_i_seed.extend_from_slice(seed);
 
-        let hash = hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, &a_i_seed);
-        output.extend_from_slice(&hash);
+        output.extend(hmac(&a_i_seed));
     }
 
     output
 }
 
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state, seed))
+)]
+fn p_hash_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U32>; 8],
+    inner_state: [Tracer<'a, U32>; 8],
+    seed: &[Tracer<'a, U8>],
+    iterations: usize,
+) -> Vec<Tracer<'a, U8>> {
+    p_hash_with(
+        |msg| hmac_sha256_finalize_trace(builder_state, outer_state, inner_state, msg).to_vec(),
+        seed,
+        iterations,
+    )
+}
+
 #[cfg_attr(
     feature = "tracing",
     tracing::instrument(level = "trace", skip(outer_state, inner_state, seed))
 )]
 fn p_hash(outer_state: [u32; 8], inner_state: [u32; 8], seed: &[u8], iterations: usize) -> Vec<u8> {
-    // A() is defined as:
-    //
-    // A(0) = seed
-    // A(i) = HMAC_hash(secret, A(i-1))
-    let mut a_cache: Vec<_> = Vec::with_capacity(iterations + 1);
-    a_cache.push(seed.to_vec());
-
-    for i in 0..iterations {
-        let a_i = hmac_sha256_finalize(outer_state, inner_state, &a_cache[i]);
-        a_cache.push(a_i.to_vec());
-    }
-
-    // HMAC_hash(secret, A(i) + seed)
-    let mut output: Vec<_> = Vec::with_capacity(iterations * 32);
-    for i in 0..iterations {
-        let mut a_i_seed = a_cache[i + 1].clone();
-        a_i_seed.extend_from_slice(seed);
-
-        let hash = hmac_sha256_finalize(outer_state, inner_state, &a_i_seed);
-        output.extend_from_slice(&hash);
-    }
-
-    output
+    p_hash_with(
+        |msg| hmac_sha256_finalize(outer_state, inner_state, msg).to_vec(),
+        seed,
+        iterations,
+    )
 }
 
 /// Computes PRF(secret, label, seed)
@@ -47,24 +47,93 @@ pub fn session_keys_trace<'a>(
     [Tracer<'a, U8>; 4],
     [Tracer<'a, U32>; 8],
     [Tracer<'a, U32>; 8],
+) {
+    let seed = client_random
+        .iter()
+        .chain(&server_random)
+        .copied()
+        .collect::<Vec<_>>();
+
+    session_keys_with_ms_trace(
+        builder_state,
+        pms,
+        b"master secret",
+        &seed,
+        client_random,
+        server_random,
+    )
+}
+
+/// Session Keys with the extended master secret, as specified in RFC 7627.
+///
+/// The master secret is derived from the session hash instead of the client and server randoms,
+/// which are still used for the key expansion.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `pms`             - 32-byte premaster secret
+/// * `session_hash`    - 32-byte hash of the handshake messages up to and including ClientKeyExchange
+/// * `client_random`   - 32-byte client random
+/// * `server_random`   - 32-byte server random
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, pms))
+)]
+#[allow(clippy::type_complexity)]
+pub fn session_keys_ems_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: [Tracer<'a, U8>; 32],
+    session_hash: [Tracer<'a, U8>; 32],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
+) {
+    session_keys_with_ms_trace(
+        builder_state,
+        pms,
+        b"extended master secret",
+        &session_hash,
+        client_random,
+        server_random,
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn session_keys_with_ms_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: [Tracer<'a, U8>; 32],
+    ms_label: &[u8],
+    ms_seed: &[Tracer<'a, U8>],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U32>; 8],
+    [Tracer<'a, U32>; 8],
 ) {
     let (pms_outer_state, pms_inner_state) = hmac_sha256_partial_trace(builder_state, &pms);
 
     let master_secret = {
-        let seed = client_random
+        let label = ms_label
             .iter()
-            .chain(&server_random)
-            .copied()
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(*v)))
             .collect::<Vec<_>>();
 
-        let label = b"master secret"
-            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
-
         prf_trace(
             builder_state,
             pms_outer_state,
             pms_inner_state,
-            &seed,
+            ms_seed,
             &label,
             48,
         )
@@ -115,19 +184,42 @@ pub fn session_keys(
     client_random: [u8; 32],
     server_random: [u8; 32],
 ) -> ([u8; 16], [u8; 16], [u8; 4], [u8; 4]) {
-    let (pms_outer_state, pms_inner_state) = hmac_sha256_partial(&pms);
+    let seed = client_random
+        .iter()
+        .chain(&server_random)
+        .copied()
+        .collect::<Vec<_>>();
 
-    let master_secret = {
-        let seed = client_random
-            .iter()
-            .chain(&server_random)
-            .copied()
-            .collect::<Vec<_>>();
+    session_keys_with_ms(pms, b"master secret", &seed, client_random, server_random)
+}
 
-        let label = b"master secret";
+/// Reference implementation of session keys derivation with the extended master secret.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
+pub fn session_keys_ems(
+    pms: [u8; 32],
+    session_hash: [u8; 32],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+) -> ([u8; 16], [u8; 16], [u8; 4], [u8; 4]) {
+    session_keys_with_ms(
+        pms,
+        b"extended master secret",
+        &session_hash,
+        client_random,
+        server_random,
+    )
+}
 
-        prf(pms_outer_state, pms_inner_state, &seed, label, 48)
-    };
+fn session_keys_with_ms(
+    pms: [u8; 32],
+    ms_label: &[u8],
+    ms_seed: &[u8],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+) -> ([u8; 16], [u8; 16], [u8; 4], [u8; 4]) {
+    let (pms_outer_state, pms_inner_state) = hmac_sha256_partial(&pms);
+
+    let master_secret = prf(pms_outer_state, pms_inner_state, ms_seed, ms_label, 48);
 
     let (master_secret_outer_state, master_secret_inner_state) =
         hmac_sha256_partial(&master_secret);
@@ -202,4 +294,50 @@ mod tests {
         assert_eq!(civ, expected_civ);
         assert_eq!(siv, expected_siv);
     }
+
+    #[test]
+    fn test_session_keys_ems() {
+        let builder = CircuitBuilder::new();
+        let pms = builder.add_array_input::<u8, 32>();
+        let session_hash = builder.add_array_input::<u8, 32>();
+        let client_random = builder.add_array_input::<u8, 32>();
+        let server_random = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv, outer_state, inner_state) = session_keys_ems_trace(
+            builder.state(),
+            pms,
+            session_hash,
+            client_random,
+            server_random,
+        );
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        builder.add_output(outer_state);
+        builder.add_output(inner_state);
+        let circ = builder.build().unwrap();
+
+        let pms = [0u8; 32];
+        let session_hash = [1u8; 32];
+        let client_random = [42u8; 32];
+        let server_random = [69u8; 32];
+
+        let expected = session_keys_ems(pms, session_hash, client_random, server_random);
+
+        // The master secret must not depend on the randoms.
+        assert_ne!(expected, session_keys(pms, client_random, server_random));
+
+        let (cwk, swk, civ, siv, _, _) = evaluate!(
+            circ,
+            fn(
+                pms,
+                session_hash,
+                client_random,
+                server_random,
+            ) -> ([u8; 16], [u8; 16], [u8; 4], [u8; 4], [u32; 8], [u32; 8])
+        )
+        .unwrap();
+
+        assert_eq!((cwk, swk, civ, siv), expected);
+    }
 }
@@ -0,0 +1,171 @@
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    types::{U64, U8},
+    BuilderState, Tracer,
+};
+
+use super::sha512::{
+    sha384, sha384_trace, sha512_compress, sha512_compress_trace, BLOCK_LEN,
+    SHA384_INITIAL_STATE,
+};
+
+/// Returns the outer and inner states of HMAC-SHA384 with the provided key.
+///
+/// Outer state is H(key ⊕ opad)
+///
+/// Inner state is H(key ⊕ ipad)
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `key`             - N-byte key (must be <= 128 bytes)
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(key, builder_state))
+)]
+pub fn hmac_sha384_partial_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    key: &[Tracer<'a, U8>],
+) -> ([Tracer<'a, U64>; 8], [Tracer<'a, U64>; 8]) {
+    assert!(key.len() <= BLOCK_LEN);
+
+    let mut opad = [Tracer::new(
+        builder_state,
+        builder_state.borrow_mut().get_constant(0x5cu8),
+    ); BLOCK_LEN];
+
+    let mut ipad = [Tracer::new(
+        builder_state,
+        builder_state.borrow_mut().get_constant(0x36u8),
+    ); BLOCK_LEN];
+
+    key.iter().enumerate().for_each(|(i, k)| {
+        opad[i] = opad[i] ^ *k;
+        ipad[i] = ipad[i] ^ *k;
+    });
+
+    let sha384_initial_state: [_; 8] = SHA384_INITIAL_STATE
+        .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+
+    let outer_state = sha512_compress_trace(builder_state, sha384_initial_state, opad);
+    let inner_state = sha512_compress_trace(builder_state, sha384_initial_state, ipad);
+
+    (outer_state, inner_state)
+}
+
+/// Reference implementation of HMAC-SHA384 partial function.
+///
+/// Returns the outer and inner states of HMAC-SHA384 with the provided key.
+///
+/// # Arguments
+///
+/// * `key` - N-byte key (must be <= 128 bytes)
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(key)))]
+pub fn hmac_sha384_partial(key: &[u8]) -> ([u64; 8], [u64; 8]) {
+    assert!(key.len() <= BLOCK_LEN);
+
+    let mut opad = [0x5cu8; BLOCK_LEN];
+    let mut ipad = [0x36u8; BLOCK_LEN];
+
+    key.iter().enumerate().for_each(|(i, k)| {
+        opad[i] ^= k;
+        ipad[i] ^= k;
+    });
+
+    let outer_state = sha512_compress(SHA384_INITIAL_STATE, opad);
+    let inner_state = sha512_compress(SHA384_INITIAL_STATE, ipad);
+
+    (outer_state, inner_state)
+}
+
+/// HMAC-SHA384 finalization function.
+///
+/// Returns the HMAC-SHA384 digest of the provided message using existing outer and inner states.
+///
+/// # Arguments
+///
+/// * `outer_state` - 512-bit outer state
+/// * `inner_state` - 512-bit inner state
+/// * `msg`         - N-byte message
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state, msg))
+)]
+pub fn hmac_sha384_finalize_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U64>; 8],
+    inner_state: [Tracer<'a, U64>; 8],
+    msg: &[Tracer<'a, U8>],
+) -> [Tracer<'a, U8>; 48] {
+    sha384_trace(
+        builder_state,
+        outer_state,
+        BLOCK_LEN,
+        &sha384_trace(builder_state, inner_state, BLOCK_LEN, msg),
+    )
+}
+
+/// Reference implementation of the HMAC-SHA384 finalization function.
+///
+/// # Arguments
+///
+/// * `outer_state` - 512-bit outer state
+/// * `inner_state` - 512-bit inner state
+/// * `msg`         - N-byte message
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state, msg))
+)]
+pub fn hmac_sha384_finalize(outer_state: [u64; 8], inner_state: [u64; 8], msg: &[u8]) -> [u8; 48] {
+    sha384(outer_state, BLOCK_LEN, &sha384(inner_state, BLOCK_LEN, msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{test_circ, CircuitBuilder};
+    use ring::hmac;
+
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha384_partial() {
+        let builder = CircuitBuilder::new();
+        let key = builder.add_array_input::<u8, 48>();
+        let (outer_state, inner_state) = hmac_sha384_partial_trace(builder.state(), &key);
+        builder.add_output(outer_state);
+        builder.add_output(inner_state);
+        let circ = builder.build().unwrap();
+
+        let key = [69u8; 48];
+
+        test_circ!(circ, hmac_sha384_partial, fn(&key) -> ([u64; 8], [u64; 8]));
+    }
+
+    #[test]
+    fn test_hmac_sha384_finalize() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u64, 8>();
+        let inner_state = builder.add_array_input::<u64, 8>();
+        let msg = builder.add_array_input::<u8, 47>();
+        let hash = hmac_sha384_finalize_trace(builder.state(), outer_state, inner_state, &msg);
+        builder.add_output(hash);
+        let circ = builder.build().unwrap();
+
+        let key = [69u8; 48];
+        let (outer_state, inner_state) = hmac_sha384_partial(&key);
+        let msg = [42u8; 47];
+
+        let expected = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA384, &key), &msg);
+        assert_eq!(
+            hmac_sha384_finalize(outer_state, inner_state, &msg),
+            expected.as_ref()
+        );
+
+        test_circ!(
+            circ,
+            hmac_sha384_finalize,
+            fn(outer_state, inner_state, &msg) -> [u8; 48]
+        );
+    }
+}
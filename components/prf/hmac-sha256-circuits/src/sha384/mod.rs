@@ -0,0 +1,19 @@
+//! Circuits for the HMAC-SHA384 PRF, used by TLS 1.2 cipher suites with SHA384 PRFs.
+
+mod hmac;
+mod prf;
+mod session_keys;
+mod sha512;
+mod verify_data;
+
+pub use hmac::{
+    hmac_sha384_finalize, hmac_sha384_finalize_trace, hmac_sha384_partial,
+    hmac_sha384_partial_trace,
+};
+pub use prf::{prf_sha384, prf_sha384_trace};
+pub use session_keys::{
+    session_keys_ems_sha384, session_keys_ems_sha384_trace, session_keys_sha384,
+    session_keys_sha384_trace,
+};
+pub use sha512::{sha384, sha384_trace, sha512_compress, sha512_compress_trace};
+pub use verify_data::{verify_data_sha384, verify_data_sha384_trace};
@@ -0,0 +1,130 @@
+//! The HMAC-SHA384 PRF defined in [RFC 5246](https://www.rfc-editor.org/rfc/rfc5246#section-5), used by cipher suites with SHA384 PRFs.
+
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    types::{U64, U8},
+    BuilderState, Tracer,
+};
+
+use super::hmac::{hmac_sha384_finalize, hmac_sha384_finalize_trace};
+use crate::prf::p_hash_with;
+
+/// Computes PRF(secret, label, seed) with HMAC-SHA384.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state.
+/// * `outer_state`     - The outer state of HMAC-SHA384
+/// * `inner_state`     - The inner state of HMAC-SHA384
+/// * `seed`            - The seed to use
+/// * `label`           - The label to use
+/// * `bytes`           - The number of bytes to output
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        level = "trace",
+        skip(builder_state, outer_state, inner_state, seed, label)
+    )
+)]
+pub fn prf_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U64>; 8],
+    inner_state: [Tracer<'a, U64>; 8],
+    seed: &[Tracer<'a, U8>],
+    label: &[Tracer<'a, U8>],
+    bytes: usize,
+) -> Vec<Tracer<'a, U8>> {
+    let iterations = bytes / 48 + (bytes % 48 != 0) as usize;
+    let mut label_seed = label.to_vec();
+    label_seed.extend_from_slice(seed);
+
+    let mut output = p_hash_with(
+        |msg| hmac_sha384_finalize_trace(builder_state, outer_state, inner_state, msg).to_vec(),
+        &label_seed,
+        iterations,
+    );
+    output.truncate(bytes);
+
+    output
+}
+
+/// Reference implementation of PRF(secret, label, seed) with HMAC-SHA384.
+///
+/// # Arguments
+///
+/// * `outer_state` - The outer state of HMAC-SHA384
+/// * `inner_state` - The inner state of HMAC-SHA384
+/// * `seed`        - The seed to use
+/// * `label`       - The label to use
+/// * `bytes`       - The number of bytes to output
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state, seed, label))
+)]
+pub fn prf_sha384(
+    outer_state: [u64; 8],
+    inner_state: [u64; 8],
+    seed: &[u8],
+    label: &[u8],
+    bytes: usize,
+) -> Vec<u8> {
+    let iterations = bytes / 48 + (bytes % 48 != 0) as usize;
+    let mut label_seed = label.to_vec();
+    label_seed.extend_from_slice(seed);
+
+    let mut output = p_hash_with(
+        |msg| hmac_sha384_finalize(outer_state, inner_state, msg).to_vec(),
+        &label_seed,
+        iterations,
+    );
+    output.truncate(bytes);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+    use ring::{hmac, hmac::HMAC_SHA384};
+
+    use super::{super::hmac::hmac_sha384_partial, *};
+
+    #[test]
+    fn test_prf_sha384() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u64, 8>();
+        let inner_state = builder.add_array_input::<u64, 8>();
+        let seed = builder.add_array_input::<u8, 64>();
+        let label = builder.add_array_input::<u8, 13>();
+        let output = prf_sha384_trace(builder.state(), outer_state, inner_state, &seed, &label, 72);
+        builder.add_output(output);
+        let circ = builder.build().unwrap();
+
+        let master_secret = [0u8; 48];
+        let seed = [43u8; 64];
+        let label = b"key expansion";
+
+        let (outer_state, inner_state) = hmac_sha384_partial(&master_secret);
+
+        let expected = prf_sha384(outer_state, inner_state, &seed, label, 72);
+        let actual =
+            evaluate!(circ, fn(outer_state, inner_state, &seed, label) -> Vec<u8>).unwrap();
+
+        assert_eq!(actual, expected);
+
+        // P_SHA384 as specified in RFC 5246, Section 5.
+        let key = hmac::Key::new(HMAC_SHA384, &master_secret);
+        let label_seed = [label.as_slice(), &seed].concat();
+        let mut a = hmac::sign(&key, &label_seed);
+        let mut expected_ring = Vec::new();
+        while expected_ring.len() < 72 {
+            expected_ring
+                .extend_from_slice(hmac::sign(&key, &[a.as_ref(), &label_seed].concat()).as_ref());
+            a = hmac::sign(&key, a.as_ref());
+        }
+        expected_ring.truncate(72);
+
+        assert_eq!(actual, expected_ring);
+    }
+}
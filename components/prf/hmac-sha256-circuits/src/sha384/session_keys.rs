@@ -0,0 +1,295 @@
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    types::{U64, U8},
+    BuilderState, Tracer,
+};
+
+use super::{
+    hmac::{hmac_sha384_partial, hmac_sha384_partial_trace},
+    prf::{prf_sha384, prf_sha384_trace},
+};
+
+/// Session Keys for cipher suites with a SHA384 PRF, eg. AES-256-GCM.
+///
+/// Compute expanded p1 which consists of client_write_key + server_write_key
+/// Compute expanded p2 which consists of client_IV + server_IV
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `pms`             - 32-byte premaster secret
+/// * `client_random`   - 32-byte client random
+/// * `server_random`   - 32-byte server random
+///
+/// # Returns
+///
+/// * `client_write_key`    - 32-byte client write key
+/// * `server_write_key`    - 32-byte server write key
+/// * `client_IV`           - 4-byte client IV
+/// * `server_IV`           - 4-byte server IV
+/// * `outer_hash_state`    - 512-bit master-secret outer HMAC state
+/// * `inner_hash_state`    - 512-bit master-secret inner HMAC state
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, pms))
+)]
+#[allow(clippy::type_complexity)]
+pub fn session_keys_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: [Tracer<'a, U8>; 32],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U64>; 8],
+    [Tracer<'a, U64>; 8],
+) {
+    let seed = client_random
+        .iter()
+        .chain(&server_random)
+        .copied()
+        .collect::<Vec<_>>();
+
+    session_keys_with_ms_sha384_trace(
+        builder_state,
+        pms,
+        b"master secret",
+        &seed,
+        client_random,
+        server_random,
+    )
+}
+
+/// Session Keys with the extended master secret, as specified in RFC 7627.
+///
+/// The master secret is derived from the session hash instead of the client and server randoms,
+/// which are still used for the key expansion.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `pms`             - 32-byte premaster secret
+/// * `session_hash`    - 48-byte hash of the handshake messages up to and including ClientKeyExchange
+/// * `client_random`   - 32-byte client random
+/// * `server_random`   - 32-byte server random
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, pms))
+)]
+#[allow(clippy::type_complexity)]
+pub fn session_keys_ems_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: [Tracer<'a, U8>; 32],
+    session_hash: [Tracer<'a, U8>; 48],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U64>; 8],
+    [Tracer<'a, U64>; 8],
+) {
+    session_keys_with_ms_sha384_trace(
+        builder_state,
+        pms,
+        b"extended master secret",
+        &session_hash,
+        client_random,
+        server_random,
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn session_keys_with_ms_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    pms: [Tracer<'a, U8>; 32],
+    ms_label: &[u8],
+    ms_seed: &[Tracer<'a, U8>],
+    client_random: [Tracer<'a, U8>; 32],
+    server_random: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U8>; 4],
+    [Tracer<'a, U64>; 8],
+    [Tracer<'a, U64>; 8],
+) {
+    let (pms_outer_state, pms_inner_state) = hmac_sha384_partial_trace(builder_state, &pms);
+
+    let master_secret = {
+        let label = ms_label
+            .iter()
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(*v)))
+            .collect::<Vec<_>>();
+
+        prf_sha384_trace(
+            builder_state,
+            pms_outer_state,
+            pms_inner_state,
+            ms_seed,
+            &label,
+            48,
+        )
+    };
+
+    let (master_secret_outer_state, master_secret_inner_state) =
+        hmac_sha384_partial_trace(builder_state, &master_secret);
+
+    let key_material = {
+        let seed = server_random
+            .iter()
+            .chain(&client_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let label = b"key expansion"
+            .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+
+        prf_sha384_trace(
+            builder_state,
+            master_secret_outer_state,
+            master_secret_inner_state,
+            &seed,
+            &label,
+            72,
+        )
+    };
+
+    let cwk = key_material[0..32].try_into().unwrap();
+    let swk = key_material[32..64].try_into().unwrap();
+    let civ = key_material[64..68].try_into().unwrap();
+    let siv = key_material[68..72].try_into().unwrap();
+
+    (
+        cwk,
+        swk,
+        civ,
+        siv,
+        master_secret_outer_state,
+        master_secret_inner_state,
+    )
+}
+
+/// Reference implementation of session keys derivation with a SHA384 PRF.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
+pub fn session_keys_sha384(
+    pms: [u8; 32],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+) -> ([u8; 32], [u8; 32], [u8; 4], [u8; 4]) {
+    let seed = client_random
+        .iter()
+        .chain(&server_random)
+        .copied()
+        .collect::<Vec<_>>();
+
+    session_keys_with_ms_sha384(pms, b"master secret", &seed, client_random, server_random)
+}
+
+/// Reference implementation of session keys derivation with the extended master secret.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(pms)))]
+pub fn session_keys_ems_sha384(
+    pms: [u8; 32],
+    session_hash: [u8; 48],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+) -> ([u8; 32], [u8; 32], [u8; 4], [u8; 4]) {
+    session_keys_with_ms_sha384(
+        pms,
+        b"extended master secret",
+        &session_hash,
+        client_random,
+        server_random,
+    )
+}
+
+fn session_keys_with_ms_sha384(
+    pms: [u8; 32],
+    ms_label: &[u8],
+    ms_seed: &[u8],
+    client_random: [u8; 32],
+    server_random: [u8; 32],
+) -> ([u8; 32], [u8; 32], [u8; 4], [u8; 4]) {
+    let (pms_outer_state, pms_inner_state) = hmac_sha384_partial(&pms);
+
+    let master_secret = prf_sha384(pms_outer_state, pms_inner_state, ms_seed, ms_label, 48);
+
+    let (master_secret_outer_state, master_secret_inner_state) =
+        hmac_sha384_partial(&master_secret);
+
+    let key_material = {
+        let seed = server_random
+            .iter()
+            .chain(&client_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        prf_sha384(
+            master_secret_outer_state,
+            master_secret_inner_state,
+            &seed,
+            b"key expansion",
+            72,
+        )
+    };
+
+    let cwk = key_material[0..32].try_into().unwrap();
+    let swk = key_material[32..64].try_into().unwrap();
+    let civ = key_material[64..68].try_into().unwrap();
+    let siv = key_material[68..72].try_into().unwrap();
+
+    (cwk, swk, civ, siv)
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+
+    use super::*;
+
+    #[test]
+    fn test_session_keys_sha384() {
+        let builder = CircuitBuilder::new();
+        let pms = builder.add_array_input::<u8, 32>();
+        let client_random = builder.add_array_input::<u8, 32>();
+        let server_random = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv, outer_state, inner_state) =
+            session_keys_sha384_trace(builder.state(), pms, client_random, server_random);
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        builder.add_output(outer_state);
+        builder.add_output(inner_state);
+        let circ = builder.build().unwrap();
+
+        let pms = [0u8; 32];
+        let client_random = [42u8; 32];
+        let server_random = [69u8; 32];
+
+        let (expected_cwk, expected_swk, expected_civ, expected_siv) =
+            session_keys_sha384(pms, client_random, server_random);
+
+        let (cwk, swk, civ, siv, _, _) = evaluate!(
+            circ,
+            fn(
+                pms,
+                client_random,
+                server_random,
+            ) -> ([u8; 32], [u8; 32], [u8; 4], [u8; 4], [u64; 8], [u64; 8])
+        )
+        .unwrap();
+
+        assert_eq!(cwk, expected_cwk);
+        assert_eq!(swk, expected_swk);
+        assert_eq!(civ, expected_civ);
+        assert_eq!(siv, expected_siv);
+    }
+}
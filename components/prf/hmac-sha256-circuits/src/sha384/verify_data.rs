@@ -0,0 +1,103 @@
+use std::cell::RefCell;
+
+use mpz_circuits::{
+    types::{U64, U8},
+    BuilderState, Tracer,
+};
+
+use super::prf::{prf_sha384, prf_sha384_trace};
+
+/// Computes verify_data as specified in RFC 5246, Section 7.4.9, for cipher suites
+/// with a SHA384 PRF.
+///
+/// # Arguments
+///
+/// * `builder_state`   - The builder state
+/// * `outer_state`     - The outer HMAC state of the master secret
+/// * `inner_state`     - The inner HMAC state of the master secret
+/// * `label`           - The label to use
+/// * `hs_hash`         - The SHA384 handshake hash
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, outer_state, inner_state, label))
+)]
+pub fn verify_data_sha384_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    outer_state: [Tracer<'a, U64>; 8],
+    inner_state: [Tracer<'a, U64>; 8],
+    label: &[Tracer<'a, U8>],
+    hs_hash: [Tracer<'a, U8>; 48],
+) -> [Tracer<'a, U8>; 12] {
+    let vd = prf_sha384_trace(builder_state, outer_state, inner_state, &hs_hash, label, 12);
+
+    vd.try_into().expect("vd is 12 bytes")
+}
+
+/// Reference implementation of verify_data for cipher suites with a SHA384 PRF.
+///
+/// # Arguments
+///
+/// * `outer_state` - The outer HMAC state of the master secret
+/// * `inner_state` - The inner HMAC state of the master secret
+/// * `label`       - The label to use
+/// * `hs_hash`     - The SHA384 handshake hash
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(outer_state, inner_state, label))
+)]
+pub fn verify_data_sha384(
+    outer_state: [u64; 8],
+    inner_state: [u64; 8],
+    label: &[u8],
+    hs_hash: [u8; 48],
+) -> [u8; 12] {
+    let vd = prf_sha384(outer_state, inner_state, &hs_hash, label, 12);
+
+    vd.try_into().expect("vd is 12 bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+    use ring::{hmac, hmac::HMAC_SHA384};
+
+    use super::{super::hmac::hmac_sha384_partial, *};
+
+    const CF_LABEL: &[u8; 15] = b"client finished";
+
+    #[test]
+    fn test_verify_data_sha384() {
+        let builder = CircuitBuilder::new();
+        let outer_state = builder.add_array_input::<u64, 8>();
+        let inner_state = builder.add_array_input::<u64, 8>();
+        let label = builder.add_array_input::<u8, 15>();
+        let hs_hash = builder.add_array_input::<u8, 48>();
+        let vd =
+            verify_data_sha384_trace(builder.state(), outer_state, inner_state, &label, hs_hash);
+        builder.add_output(vd);
+        let circ = builder.build().unwrap();
+
+        let master_secret = [7u8; 48];
+        let hs_hash = [42u8; 48];
+
+        let (outer_state, inner_state) = hmac_sha384_partial(&master_secret);
+
+        let expected = verify_data_sha384(outer_state, inner_state, CF_LABEL, hs_hash);
+        let actual = evaluate!(
+            circ,
+            fn(outer_state, inner_state, CF_LABEL, hs_hash) -> [u8; 12]
+        )
+        .unwrap();
+
+        assert_eq!(actual, expected);
+
+        // PRF(master_secret, finished_label, Hash(handshake_messages))[0..11] as specified in
+        // RFC 5246, Section 7.4.9, with P_SHA384.
+        let key = hmac::Key::new(HMAC_SHA384, &master_secret);
+        let label_seed = [CF_LABEL.as_slice(), &hs_hash].concat();
+        let a = hmac::sign(&key, &label_seed);
+        let expected_ring = hmac::sign(&key, &[a.as_ref(), &label_seed].concat());
+
+        assert_eq!(actual, expected_ring.as_ref()[..12]);
+    }
+}
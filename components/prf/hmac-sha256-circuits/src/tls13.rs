@@ -0,0 +1,615 @@
+//! This module provides the TLS 1.3 key schedule for cipher suites with SHA-256, defined in
+//! [RFC 8446](https://www.rfc-editor.org/rfc/rfc8446#section-7.1).
+//!
+//! The early secret does not depend on any private input when no PSK is used, so the
+//! `derived` secret it produces is computed in the clear and embedded in the circuits as a constant.
+
+use std::cell::RefCell;
+
+use mpz_circuits::{types::U8, BuilderState, Tracer};
+
+use crate::hkdf::{hkdf_expand_label, hkdf_expand_label_trace, hkdf_extract, hkdf_extract_trace};
+
+/// SHA-256 hash of the empty string, Transcript-Hash("").
+static EMPTY_HASH: [u8; 32] = [
+    0xe3, 0xb0, 0xc4, 0x42, 0x98, 0xfc, 0x1c, 0x14, 0x9a, 0xfb, 0xf4, 0xc8, 0x99, 0x6f, 0xb9, 0x24,
+    0x27, 0xae, 0x41, 0xe4, 0x64, 0x9b, 0x93, 0x4c, 0xa4, 0x95, 0x99, 0x1b, 0x78, 0x52, 0xb8, 0x55,
+];
+
+/// Returns Derive-Secret(Early Secret, "derived", "") for a handshake without a PSK.
+fn derived_early_secret() -> [u8; 32] {
+    let early_secret = hkdf_extract(&[0u8; 32], &[0u8; 32]);
+
+    hkdf_expand_label(&early_secret, b"derived", &EMPTY_HASH, 32)
+        .try_into()
+        .expect("derived secret is 32 bytes")
+}
+
+/// Returns the master secret derived from the handshake secret.
+fn master_secret(handshake_secret: &[u8]) -> [u8; 32] {
+    let derived = hkdf_expand_label(handshake_secret, b"derived", &EMPTY_HASH, 32);
+
+    hkdf_extract(&derived, &[0u8; 32])
+}
+
+/// Traffic keys derived from a traffic secret.
+fn traffic_keys(secret: &[u8]) -> ([u8; 16], [u8; 12]) {
+    let key = hkdf_expand_label(secret, b"key", &[], 16);
+    let iv = hkdf_expand_label(secret, b"iv", &[], 12);
+
+    (key.try_into().unwrap(), iv.try_into().unwrap())
+}
+
+#[allow(clippy::type_complexity)]
+fn traffic_keys_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    secret: &[Tracer<'a, U8>],
+) -> ([Tracer<'a, U8>; 16], [Tracer<'a, U8>; 12]) {
+    let key = hkdf_expand_label_trace(builder_state, secret, b"key", &[], 16);
+    let iv = hkdf_expand_label_trace(builder_state, secret, b"iv", &[], 12);
+
+    (key.try_into().unwrap(), iv.try_into().unwrap())
+}
+
+/// Handshake traffic keys.
+///
+/// Computes the handshake secret from the (EC)DHE shared secret, and the client and server
+/// handshake traffic secrets and keys bound to the transcript hash of ClientHello..ServerHello.
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `shared_secret`   - 32-byte (EC)DHE shared secret
+/// * `hello_hash`      - Transcript-Hash(ClientHello..ServerHello)
+///
+/// # Returns
+///
+/// * `client_write_key`    - 16-byte client handshake write key
+/// * `server_write_key`    - 16-byte server handshake write key
+/// * `client_IV`           - 12-byte client handshake IV
+/// * `server_IV`           - 12-byte server handshake IV
+/// * `client_secret`       - 32-byte client handshake traffic secret
+/// * `server_secret`       - 32-byte server handshake traffic secret
+/// * `handshake_secret`    - 32-byte handshake secret
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, shared_secret))
+)]
+#[allow(clippy::type_complexity)]
+pub fn handshake_keys_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    shared_secret: [Tracer<'a, U8>; 32],
+    hello_hash: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 32],
+) {
+    let derived = derived_early_secret()
+        .map(|v| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v)));
+
+    let handshake_secret = hkdf_extract_trace(builder_state, &derived, &shared_secret);
+
+    let client_secret: [_; 32] = hkdf_expand_label_trace(
+        builder_state,
+        &handshake_secret,
+        b"c hs traffic",
+        &hello_hash,
+        32,
+    )
+    .try_into()
+    .unwrap();
+    let server_secret: [_; 32] = hkdf_expand_label_trace(
+        builder_state,
+        &handshake_secret,
+        b"s hs traffic",
+        &hello_hash,
+        32,
+    )
+    .try_into()
+    .unwrap();
+
+    let (cwk, civ) = traffic_keys_trace(builder_state, &client_secret);
+    let (swk, siv) = traffic_keys_trace(builder_state, &server_secret);
+
+    (
+        cwk,
+        swk,
+        civ,
+        siv,
+        client_secret,
+        server_secret,
+        handshake_secret,
+    )
+}
+
+/// Reference implementation of the handshake traffic keys derivation.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(shared_secret))
+)]
+#[allow(clippy::type_complexity)]
+pub fn handshake_keys(
+    shared_secret: [u8; 32],
+    hello_hash: [u8; 32],
+) -> (
+    [u8; 16],
+    [u8; 16],
+    [u8; 12],
+    [u8; 12],
+    [u8; 32],
+    [u8; 32],
+    [u8; 32],
+) {
+    let handshake_secret = hkdf_extract(&derived_early_secret(), &shared_secret);
+
+    let client_secret: [u8; 32] =
+        hkdf_expand_label(&handshake_secret, b"c hs traffic", &hello_hash, 32)
+            .try_into()
+            .unwrap();
+    let server_secret: [u8; 32] =
+        hkdf_expand_label(&handshake_secret, b"s hs traffic", &hello_hash, 32)
+            .try_into()
+            .unwrap();
+
+    let (cwk, civ) = traffic_keys(&client_secret);
+    let (swk, siv) = traffic_keys(&server_secret);
+
+    (
+        cwk,
+        swk,
+        civ,
+        siv,
+        client_secret,
+        server_secret,
+        handshake_secret,
+    )
+}
+
+/// Application traffic keys.
+///
+/// Computes the master secret from the handshake secret, and the client and server
+/// application traffic keys bound to the transcript hash of ClientHello..server Finished.
+///
+/// # Arguments
+///
+/// * `builder_state`       - Reference to builder state
+/// * `handshake_secret`    - 32-byte handshake secret
+/// * `handshake_hash`      - Transcript-Hash(ClientHello..server Finished)
+///
+/// # Returns
+///
+/// * `client_write_key`    - 16-byte client application write key
+/// * `server_write_key`    - 16-byte server application write key
+/// * `client_IV`           - 12-byte client application IV
+/// * `server_IV`           - 12-byte server application IV
+/// * `client_secret`       - 32-byte client application traffic secret
+/// * `server_secret`       - 32-byte server application traffic secret
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, handshake_secret))
+)]
+#[allow(clippy::type_complexity)]
+pub fn application_keys_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    handshake_secret: [Tracer<'a, U8>; 32],
+    handshake_hash: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 32],
+    [Tracer<'a, U8>; 32],
+) {
+    let constant = |v: u8| Tracer::new(builder_state, builder_state.borrow_mut().get_constant(v));
+
+    let derived = hkdf_expand_label_trace(
+        builder_state,
+        &handshake_secret,
+        b"derived",
+        &EMPTY_HASH.map(constant),
+        32,
+    );
+    let master_secret = hkdf_extract_trace(builder_state, &derived, &[0u8; 32].map(constant));
+
+    let client_secret: [_; 32] = hkdf_expand_label_trace(
+        builder_state,
+        &master_secret,
+        b"c ap traffic",
+        &handshake_hash,
+        32,
+    )
+    .try_into()
+    .unwrap();
+    let server_secret: [_; 32] = hkdf_expand_label_trace(
+        builder_state,
+        &master_secret,
+        b"s ap traffic",
+        &handshake_hash,
+        32,
+    )
+    .try_into()
+    .unwrap();
+
+    let (cwk, civ) = traffic_keys_trace(builder_state, &client_secret);
+    let (swk, siv) = traffic_keys_trace(builder_state, &server_secret);
+
+    (cwk, swk, civ, siv, client_secret, server_secret)
+}
+
+/// Reference implementation of the application traffic keys derivation.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(handshake_secret))
+)]
+#[allow(clippy::type_complexity)]
+pub fn application_keys(
+    handshake_secret: [u8; 32],
+    handshake_hash: [u8; 32],
+) -> ([u8; 16], [u8; 16], [u8; 12], [u8; 12], [u8; 32], [u8; 32]) {
+    let master_secret = master_secret(&handshake_secret);
+
+    let client_secret: [u8; 32] =
+        hkdf_expand_label(&master_secret, b"c ap traffic", &handshake_hash, 32)
+            .try_into()
+            .unwrap();
+    let server_secret: [u8; 32] =
+        hkdf_expand_label(&master_secret, b"s ap traffic", &handshake_hash, 32)
+            .try_into()
+            .unwrap();
+
+    let (cwk, civ) = traffic_keys(&client_secret);
+    let (swk, siv) = traffic_keys(&server_secret);
+
+    (cwk, swk, civ, siv, client_secret, server_secret)
+}
+
+/// Updated traffic keys, as specified in RFC 8446, Section 7.2.
+///
+/// ```text
+/// application_traffic_secret_N+1 = HKDF-Expand-Label(application_traffic_secret_N, "traffic upd", "", Hash.length)
+/// ```
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `traffic_secret`  - 32-byte current application traffic secret
+///
+/// # Returns
+///
+/// * `write_key`       - 16-byte updated write key
+/// * `IV`              - 12-byte updated IV
+/// * `traffic_secret`  - 32-byte updated application traffic secret
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, traffic_secret))
+)]
+#[allow(clippy::type_complexity)]
+pub fn key_update_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    traffic_secret: [Tracer<'a, U8>; 32],
+) -> (
+    [Tracer<'a, U8>; 16],
+    [Tracer<'a, U8>; 12],
+    [Tracer<'a, U8>; 32],
+) {
+    let next_secret: [_; 32] =
+        hkdf_expand_label_trace(builder_state, &traffic_secret, b"traffic upd", &[], 32)
+            .try_into()
+            .unwrap();
+
+    let (key, iv) = traffic_keys_trace(builder_state, &next_secret);
+
+    (key, iv, next_secret)
+}
+
+/// Reference implementation of the traffic keys update.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(traffic_secret))
+)]
+pub fn key_update(traffic_secret: [u8; 32]) -> ([u8; 16], [u8; 12], [u8; 32]) {
+    let next_secret: [u8; 32] = hkdf_expand_label(&traffic_secret, b"traffic upd", &[], 32)
+        .try_into()
+        .unwrap();
+
+    let (key, iv) = traffic_keys(&next_secret);
+
+    (key, iv, next_secret)
+}
+
+/// Computes the Finished verify_data as specified in RFC 8446, Section 4.4.4.
+///
+/// ```text
+/// finished_key = HKDF-Expand-Label(BaseKey, "finished", "", Hash.length)
+/// verify_data = HMAC(finished_key, Transcript-Hash(Handshake Context, Certificate*, CertificateVerify*))
+/// ```
+///
+/// # Arguments
+///
+/// * `builder_state`   - Reference to builder state
+/// * `traffic_secret`  - 32-byte handshake traffic secret of the sender
+/// * `handshake_hash`  - The transcript hash
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(builder_state, traffic_secret))
+)]
+pub fn finished_vd_trace<'a>(
+    builder_state: &'a RefCell<BuilderState>,
+    traffic_secret: [Tracer<'a, U8>; 32],
+    handshake_hash: [Tracer<'a, U8>; 32],
+) -> [Tracer<'a, U8>; 32] {
+    let finished_key =
+        hkdf_expand_label_trace(builder_state, &traffic_secret, b"finished", &[], 32);
+
+    hkdf_extract_trace(builder_state, &finished_key, &handshake_hash)
+}
+
+/// Reference implementation of the Finished verify_data.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip(traffic_secret))
+)]
+pub fn finished_vd(traffic_secret: [u8; 32], handshake_hash: [u8; 32]) -> [u8; 32] {
+    let finished_key = hkdf_expand_label(&traffic_secret, b"finished", &[], 32);
+
+    // HMAC(finished_key, hash) is HKDF-Extract with the finished key as salt.
+    hkdf_extract(&finished_key, &handshake_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::{evaluate, CircuitBuilder};
+
+    use super::*;
+
+    // RFC 8448, Section 3: Simple 1-RTT Handshake.
+    const SHARED_SECRET: [u8; 32] = [
+        0x8b, 0xd4, 0x05, 0x4f, 0xb5, 0x5b, 0x9d, 0x63, 0xfd, 0xfb, 0xac, 0xf9, 0xf0, 0x4b, 0x9f,
+        0x0d, 0x35, 0xe6, 0xd6, 0x3f, 0x53, 0x75, 0x63, 0xef, 0xd4, 0x62, 0x72, 0x90, 0x0f, 0x89,
+        0x49, 0x2d,
+    ];
+    const HELLO_HASH: [u8; 32] = [
+        0x86, 0x0c, 0x06, 0xed, 0xc0, 0x78, 0x58, 0xee, 0x8e, 0x78, 0xf0, 0xe7, 0x42, 0x8c, 0x58,
+        0xed, 0xd6, 0xb4, 0x3f, 0x2c, 0xa3, 0xe6, 0xe9, 0x5f, 0x02, 0xed, 0x06, 0x3c, 0xf0, 0xe1,
+        0xca, 0xd8,
+    ];
+    const HANDSHAKE_SECRET: [u8; 32] = [
+        0x1d, 0xc8, 0x26, 0xe9, 0x36, 0x06, 0xaa, 0x6f, 0xdc, 0x0a, 0xad, 0xc1, 0x2f, 0x74, 0x1b,
+        0x01, 0x04, 0x6a, 0xa6, 0xb9, 0x9f, 0x69, 0x1e, 0xd2, 0x21, 0xa9, 0xf0, 0xca, 0x04, 0x3f,
+        0xbe, 0xac,
+    ];
+    const CLIENT_HS_SECRET: [u8; 32] = [
+        0xb3, 0xed, 0xdb, 0x12, 0x6e, 0x06, 0x7f, 0x35, 0xa7, 0x80, 0xb3, 0xab, 0xf4, 0x5e, 0x2d,
+        0x8f, 0x3b, 0x1a, 0x95, 0x07, 0x38, 0xf5, 0x2e, 0x96, 0x00, 0x74, 0x6a, 0x0e, 0x27, 0xa5,
+        0x5a, 0x21,
+    ];
+    const SERVER_HS_SECRET: [u8; 32] = [
+        0xb6, 0x7b, 0x7d, 0x69, 0x0c, 0xc1, 0x6c, 0x4e, 0x75, 0xe5, 0x42, 0x13, 0xcb, 0x2d, 0x37,
+        0xb4, 0xe9, 0xc9, 0x12, 0xbc, 0xde, 0xd9, 0x10, 0x5d, 0x42, 0xbe, 0xfd, 0x59, 0xd3, 0x91,
+        0xad, 0x38,
+    ];
+    const CLIENT_HS_KEY: [u8; 16] = [
+        0xdb, 0xfa, 0xa6, 0x93, 0xd1, 0x76, 0x2c, 0x5b, 0x66, 0x6a, 0xf5, 0xd9, 0x50, 0x25, 0x8d,
+        0x01,
+    ];
+    const CLIENT_HS_IV: [u8; 12] = [
+        0x5b, 0xd3, 0xc7, 0x1b, 0x83, 0x6e, 0x0b, 0x76, 0xbb, 0x73, 0x26, 0x5f,
+    ];
+    const SERVER_HS_KEY: [u8; 16] = [
+        0x3f, 0xce, 0x51, 0x60, 0x09, 0xc2, 0x17, 0x27, 0xd0, 0xf2, 0xe4, 0xe8, 0x6e, 0xe4, 0x03,
+        0xbc,
+    ];
+    const SERVER_HS_IV: [u8; 12] = [
+        0x5d, 0x31, 0x3e, 0xb2, 0x67, 0x12, 0x76, 0xee, 0x13, 0x00, 0x0b, 0x30,
+    ];
+    const SERVER_FINISHED_HASH: [u8; 32] = [
+        0xed, 0xb7, 0x72, 0x5f, 0xa7, 0xa3, 0x47, 0x3b, 0x03, 0x1e, 0xc8, 0xef, 0x65, 0xa2, 0x48,
+        0x54, 0x93, 0x90, 0x01, 0x38, 0xa2, 0xb9, 0x12, 0x91, 0x40, 0x7d, 0x79, 0x51, 0xa0, 0x61,
+        0x10, 0xed,
+    ];
+    const SERVER_FINISHED_VD: [u8; 32] = [
+        0x9b, 0x9b, 0x14, 0x1d, 0x90, 0x63, 0x37, 0xfb, 0xd2, 0xcb, 0xdc, 0xe7, 0x1d, 0xf4, 0xde,
+        0xda, 0x4a, 0xb4, 0x2c, 0x30, 0x95, 0x72, 0xcb, 0x7f, 0xff, 0xee, 0x54, 0x54, 0xb7, 0x8f,
+        0x07, 0x18,
+    ];
+    const MASTER_SECRET: [u8; 32] = [
+        0x18, 0xdf, 0x06, 0x84, 0x3d, 0x13, 0xa0, 0x8b, 0xf2, 0xa4, 0x49, 0x84, 0x4c, 0x5f, 0x8a,
+        0x47, 0x80, 0x01, 0xbc, 0x4d, 0x4c, 0x62, 0x79, 0x84, 0xd5, 0xa4, 0x1d, 0xa8, 0xd0, 0x40,
+        0x29, 0x19,
+    ];
+    const SERVER_AP_SECRET: [u8; 32] = [
+        0xa1, 0x1a, 0xf9, 0xf0, 0x55, 0x31, 0xf8, 0x56, 0xad, 0x47, 0x11, 0x6b, 0x45, 0xa9, 0x50,
+        0x32, 0x82, 0x04, 0xb4, 0xf4, 0x4b, 0xfb, 0x6b, 0x3a, 0x4b, 0x4f, 0x1f, 0x3f, 0xcb, 0x63,
+        0x16, 0x43,
+    ];
+    const SERVER_AP_KEY: [u8; 16] = [
+        0x9f, 0x02, 0x28, 0x3b, 0x6c, 0x9c, 0x07, 0xef, 0xc2, 0x6b, 0xb9, 0xf2, 0xac, 0x92, 0xe3,
+        0x56,
+    ];
+    const SERVER_AP_IV: [u8; 12] = [
+        0xcf, 0x78, 0x2b, 0x88, 0xdd, 0x83, 0x54, 0x9a, 0xad, 0xf1, 0xe9, 0x84,
+    ];
+
+    #[test]
+    fn test_handshake_keys_rfc8448() {
+        let expected = (
+            CLIENT_HS_KEY,
+            SERVER_HS_KEY,
+            CLIENT_HS_IV,
+            SERVER_HS_IV,
+            CLIENT_HS_SECRET,
+            SERVER_HS_SECRET,
+            HANDSHAKE_SECRET,
+        );
+
+        assert_eq!(handshake_keys(SHARED_SECRET, HELLO_HASH), expected);
+
+        let circ = crate::build_tls13_handshake_keys();
+        let (shared_secret, hello_hash) = (SHARED_SECRET, HELLO_HASH);
+        let actual = evaluate!(
+            circ,
+            fn(
+                shared_secret,
+                hello_hash,
+            ) -> (
+                [u8; 16],
+                [u8; 16],
+                [u8; 12],
+                [u8; 12],
+                [u8; 32],
+                [u8; 32],
+                [u8; 32]
+            )
+        )
+        .unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_application_keys_rfc8448() {
+        // The application traffic secrets are bound to a transcript hash, the derivation from them
+        // is covered by comparing the circuit against the reference in `test_application_keys`.
+        assert_eq!(master_secret(&HANDSHAKE_SECRET), MASTER_SECRET);
+        assert_eq!(
+            traffic_keys(&SERVER_AP_SECRET),
+            (SERVER_AP_KEY, SERVER_AP_IV)
+        );
+
+        let circ = crate::build_tls13_key_update();
+        let traffic_secret = SERVER_AP_SECRET;
+        let (key, iv, next_secret) =
+            evaluate!(circ, fn(traffic_secret) -> ([u8; 16], [u8; 12], [u8; 32])).unwrap();
+
+        assert_eq!((key, iv), traffic_keys(&next_secret));
+    }
+
+    #[test]
+    fn test_finished_vd_rfc8448() {
+        assert_eq!(
+            finished_vd(SERVER_HS_SECRET, SERVER_FINISHED_HASH),
+            SERVER_FINISHED_VD
+        );
+
+        let circ = crate::build_tls13_finished_vd();
+        let (traffic_secret, handshake_hash) = (SERVER_HS_SECRET, SERVER_FINISHED_HASH);
+        let actual = evaluate!(circ, fn(traffic_secret, handshake_hash) -> [u8; 32]).unwrap();
+
+        assert_eq!(actual, SERVER_FINISHED_VD);
+    }
+
+    #[test]
+    fn test_derived_early_secret() {
+        // RFC 8448, Section 3: "derived" secret from the early secret without a PSK.
+        assert_eq!(
+            derived_early_secret(),
+            [
+                0x6f, 0x26, 0x15, 0xa1, 0x08, 0xc7, 0x02, 0xc5, 0x67, 0x8f, 0x54, 0xfc, 0x9d, 0xba,
+                0xb6, 0x97, 0x16, 0xc0, 0x76, 0x18, 0x9c, 0x48, 0x25, 0x0c, 0xeb, 0xea, 0xc3, 0x57,
+                0x6c, 0x36, 0x11, 0xba,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_handshake_keys() {
+        let builder = CircuitBuilder::new();
+        let shared_secret = builder.add_array_input::<u8, 32>();
+        let hello_hash = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv, client_secret, server_secret, handshake_secret) =
+            handshake_keys_trace(builder.state(), shared_secret, hello_hash);
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        builder.add_output(client_secret);
+        builder.add_output(server_secret);
+        builder.add_output(handshake_secret);
+        let circ = builder.build().unwrap();
+
+        let shared_secret = [42u8; 32];
+        let hello_hash = [69u8; 32];
+
+        let expected = handshake_keys(shared_secret, hello_hash);
+
+        let actual = evaluate!(
+            circ,
+            fn(
+                shared_secret,
+                hello_hash,
+            ) -> (
+                [u8; 16],
+                [u8; 16],
+                [u8; 12],
+                [u8; 12],
+                [u8; 32],
+                [u8; 32],
+                [u8; 32]
+            )
+        )
+        .unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_application_keys() {
+        let builder = CircuitBuilder::new();
+        let handshake_secret = builder.add_array_input::<u8, 32>();
+        let handshake_hash = builder.add_array_input::<u8, 32>();
+        let (cwk, swk, civ, siv, client_secret, server_secret) =
+            application_keys_trace(builder.state(), handshake_secret, handshake_hash);
+        builder.add_output(cwk);
+        builder.add_output(swk);
+        builder.add_output(civ);
+        builder.add_output(siv);
+        builder.add_output(client_secret);
+        builder.add_output(server_secret);
+        let circ = builder.build().unwrap();
+
+        let handshake_secret = [1u8; 32];
+        let handshake_hash = [2u8; 32];
+
+        let expected = application_keys(handshake_secret, handshake_hash);
+
+        let actual = evaluate!(
+            circ,
+            fn(
+                handshake_secret,
+                handshake_hash,
+            ) -> ([u8; 16], [u8; 16], [u8; 12], [u8; 12], [u8; 32], [u8; 32])
+        )
+        .unwrap();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_key_update() {
+        let builder = CircuitBuilder::new();
+        let traffic_secret = builder.add_array_input::<u8, 32>();
+        let (key, iv, next_secret) = key_update_trace(builder.state(), traffic_secret);
+        builder.add_output(key);
+        builder.add_output(iv);
+        builder.add_output(next_secret);
+        let circ = builder.build().unwrap();
+
+        let traffic_secret = [7u8; 32];
+
+        let expected = key_update(traffic_secret);
+
+        // Each update must produce a fresh secret.
+        assert_ne!(expected.2, traffic_secret);
+        assert_ne!(key_update(expected.2).2, expected.2);
+
+        let actual = evaluate!(circ, fn(traffic_secret) -> ([u8; 16], [u8; 12], [u8; 32])).unwrap();
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -78,13 +78,13 @@ async fn prf() {
     .unwrap();
 
     let _ = futures::try_join!(
-        leader.compute_client_finished_vd_private(cf_hs_hash),
+        leader.compute_client_finished_vd_private(&cf_hs_hash),
         follower.compute_client_finished_vd_blind()
     )
     .unwrap();
 
     let _ = futures::try_join!(
-        leader.compute_server_finished_vd_private(sf_hs_hash),
+        leader.compute_server_finished_vd_private(&sf_hs_hash),
         follower.compute_server_finished_vd_blind()
     )
     .unwrap();
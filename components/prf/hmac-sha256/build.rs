@@ -0,0 +1,53 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+fn main() {
+    // Keys the circuit cache by a hash of everything the cached circuits are built from: the
+    // circuit definitions, the code building them and the manifests pinning the mpz-circuits rev.
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let inputs = [
+        manifest_dir.join("src"),
+        manifest_dir.join("Cargo.toml"),
+        manifest_dir.join("../hmac-sha256-circuits/src"),
+        manifest_dir.join("../hmac-sha256-circuits/Cargo.toml"),
+        manifest_dir.join("../Cargo.toml"),
+    ];
+
+    let mut hasher = Sha256::new();
+    hasher.update(env::var("CARGO_PKG_VERSION").unwrap());
+    for input in &inputs {
+        println!("cargo:rerun-if-changed={}", input.display());
+        hash_path(&mut hasher, input);
+    }
+
+    let key: String = hasher.finalize()[..16]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    println!("cargo:rustc-env=CIRCUIT_CACHE_KEY={key}");
+}
+
+/// Hashes the file or, recursively, the directory at `path`, missing paths are skipped, eg. when
+/// built from a published crate.
+fn hash_path(hasher: &mut Sha256, path: &Path) {
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        entries.sort();
+
+        for entry in entries {
+            hash_path(hasher, &entry);
+        }
+    } else if let Ok(contents) = fs::read(path) {
+        hasher.update(path.file_name().unwrap().to_string_lossy().as_bytes());
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(contents);
+    }
+}
@@ -0,0 +1,140 @@
+//! Persistent cache for the compiled PRF circuits.
+//!
+//! Building the PRF circuits takes a noticeable amount of time, so they can optionally be cached
+//! on disk across restarts, see [`set_circuit_cache_dir`]. Only the circuit descriptions are
+//! cached, garbled circuits must never be reused.
+//!
+//! Entries are keyed by a hash of the circuit definitions, the code building them and the
+//! manifests pinning the mpz-circuits revision, computed at build time, so that a change to any
+//! of them invalidates the cache.
+//!
+//! The cache directory is trusted: each entry is checked against an unkeyed digest, which detects
+//! corruption but not tampering. Anyone able to write to the directory can substitute a circuit
+//! which computes something else, so it must only be writable by the process using it.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+};
+
+use mpz_circuits::Circuit;
+use sha2::{Digest, Sha256};
+
+/// Directory of the circuit cache, caching is disabled if unset.
+static CACHE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Length of the digest prepended to each cached circuit.
+const DIGEST_LEN: usize = 32;
+
+/// Sets the directory in which compiled circuits are cached.
+///
+/// Must be called before the first PRF is set up to take effect, and can only be set once per
+/// process. Returns `false` if the directory was already set.
+///
+/// Cache entries are keyed by circuit name and the hash of the circuits' dependencies, and each
+/// entry is checked against its digest when loaded, so corrupted entries are rebuilt. The directory
+/// must only be writable by this process, see the [module docs](self).
+pub fn set_circuit_cache_dir(dir: impl Into<PathBuf>) -> bool {
+    CACHE_DIR.set(dir.into()).is_ok()
+}
+
+/// Loads the named circuit from the cache if one is configured, otherwise builds it.
+pub(crate) fn load_or_build(name: &str, build: impl FnOnce() -> Arc<Circuit>) -> Arc<Circuit> {
+    match CACHE_DIR.get() {
+        Some(dir) => load_or_build_in(dir, name, build),
+        None => build(),
+    }
+}
+
+fn load_or_build_in(dir: &Path, name: &str, build: impl FnOnce() -> Arc<Circuit>) -> Arc<Circuit> {
+    let path = dir.join(entry_name(name));
+
+    if let Some(circ) = load(&path) {
+        return Arc::new(circ);
+    }
+
+    let circ = build();
+
+    // The cache is best-effort, failing to store a circuit only costs a rebuild next time.
+    if let Err(_err) = store(&path, &circ) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!("failed to cache circuit {name}: {_err}");
+    }
+
+    circ
+}
+
+/// Returns the file name of the named circuit's cache entry.
+fn entry_name(name: &str) -> String {
+    format!("{name}-{}.bin", env!("CIRCUIT_CACHE_KEY"))
+}
+
+fn load(path: &Path) -> Option<Circuit> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.len() < DIGEST_LEN {
+        return None;
+    }
+
+    let (digest, circ) = bytes.split_at(DIGEST_LEN);
+    if Sha256::digest(circ).as_slice() != digest {
+        return None;
+    }
+
+    bincode::deserialize(circ).ok()
+}
+
+fn store(path: &Path, circ: &Circuit) -> std::io::Result<()> {
+    let circ = bincode::serialize(circ)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+
+    let mut bytes = Sha256::digest(&circ).to_vec();
+    bytes.extend(circ);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // Write to a temporary file first so that concurrent processes never read a partial entry.
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_circuits::CircuitBuilder;
+
+    use super::*;
+
+    fn build() -> Arc<Circuit> {
+        let builder = CircuitBuilder::new();
+        let a = builder.add_array_input::<u8, 4>();
+        builder.add_output(a);
+        Arc::new(builder.build().unwrap())
+    }
+
+    #[test]
+    fn test_circuit_cache() {
+        let dir = std::env::temp_dir().join(format!("tlsn-circuit-cache-{}", std::process::id()));
+        let expected = bincode::serialize(&*build()).unwrap();
+
+        let circ = load_or_build_in(&dir, "test", build);
+        assert_eq!(bincode::serialize(&*circ).unwrap(), expected);
+
+        // The second call must be served from the cache.
+        let circ = load_or_build_in(&dir, "test", || panic!("circuit should be cached"));
+        assert_eq!(bincode::serialize(&*circ).unwrap(), expected);
+
+        // A corrupted entry is rebuilt.
+        let path = dir.join(entry_name("test"));
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, bytes).unwrap();
+
+        let circ = load_or_build_in(&dir, "test", build);
+        assert_eq!(bincode::serialize(&*circ).unwrap(), expected);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
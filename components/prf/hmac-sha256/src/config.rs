@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use derive_builder::Builder;
 
 /// Role of this party in the PRF.
@@ -9,11 +11,53 @@ pub enum Role {
     Follower,
 }
 
+/// Hash function used by the PRF, as determined by the negotiated cipher suite.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PrfHash {
+    /// HMAC-SHA256, used by cipher suites with AES-128 keys.
+    #[default]
+    Sha256,
+    /// HMAC-SHA384, used by cipher suites with SHA384 PRFs and AES-256 keys.
+    Sha384,
+}
+
+impl PrfHash {
+    /// Returns the length of the handshake hash in bytes.
+    pub fn handshake_hash_len(&self) -> usize {
+        match self {
+            PrfHash::Sha256 => 32,
+            PrfHash::Sha384 => 48,
+        }
+    }
+
+    /// Returns the length of the session write keys in bytes.
+    pub fn key_len(&self) -> usize {
+        match self {
+            PrfHash::Sha256 => 16,
+            PrfHash::Sha384 => 32,
+        }
+    }
+}
+
 /// Configuration for the PRF.
 #[derive(Debug, Builder)]
 pub struct PrfConfig {
     /// The role of this party in the PRF.
     pub(crate) role: Role,
+    /// The hash function used by the PRF.
+    #[builder(default)]
+    pub(crate) hash: PrfHash,
+    /// Whether to derive the master secret from the session hash, as specified in RFC 7627.
+    ///
+    /// Must match the extended master secret negotiation of the handshake.
+    #[builder(default)]
+    pub(crate) extended_master_secret: bool,
+    /// Timeout for each step of the PRF, `None` to disable.
+    ///
+    /// If the other party stalls, a step fails with [`PrfError::Timeout`](crate::PrfError::Timeout)
+    /// instead of blocking indefinitely.
+    #[builder(default)]
+    pub(crate) step_timeout: Option<Duration>,
 }
 
 impl PrfConfig {
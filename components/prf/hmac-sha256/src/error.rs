@@ -12,6 +12,12 @@ pub enum PrfError {
     RoleError(String),
     #[error("Invalid state: {0}")]
     InvalidState(String),
+    #[error("invalid handshake hash length: expected {expected}, got {actual}")]
+    InvalidHandshakeHash { expected: usize, actual: usize },
+    #[error("step timed out: {step}")]
+    Timeout { step: &'static str },
+    #[error("step was cancelled: {step}")]
+    Cancelled { step: &'static str },
 }
 
 impl From<StateError> for PrfError {
@@ -20,6 +26,12 @@ impl From<StateError> for PrfError {
     }
 }
 
+impl From<crate::key_schedule::state::StateError> for PrfError {
+    fn from(err: crate::key_schedule::state::StateError) -> Self {
+        PrfError::InvalidState(err.to_string())
+    }
+}
+
 impl From<mpz_garble::MemoryError> for PrfError {
     fn from(err: mpz_garble::MemoryError) -> Self {
         PrfError::Mpc(Box::new(err))
@@ -0,0 +1,605 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, OnceLock},
+};
+
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+
+use hmac_sha256_circuits::{
+    build_tls13_application_keys, build_tls13_finished_vd, build_tls13_handshake_keys,
+    build_tls13_key_update,
+};
+use mpz_circuits::Circuit;
+use mpz_garble::{config::Visibility, value::ValueRef, DecodePrivate, Execute, Memory};
+use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
+
+use crate::{
+    cache, prf::run_step, KeySchedule, PrfConfig, PrfError, Role, SessionKeys, TrafficKeys,
+};
+
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+/// Circuit for computing TLS 1.3 handshake traffic keys.
+static HANDSHAKE_KEYS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS 1.3 application traffic keys.
+static APPLICATION_KEYS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for updating TLS 1.3 application traffic keys.
+static KEY_UPDATE_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS 1.3 Finished verify data.
+static FINISHED_VD_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+
+/// Direction of the traffic keys.
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Client,
+    Server,
+}
+
+/// MPC TLS 1.3 key schedule for cipher suites with SHA-256.
+pub struct MpcKeySchedule<E> {
+    config: PrfConfig,
+    state: state::State,
+    thread: E,
+    cancel: Option<CancellationToken>,
+}
+
+impl<E> Debug for MpcKeySchedule<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MpcKeySchedule")
+            .field("config", &self.config)
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<E> MpcKeySchedule<E>
+where
+    E: Memory + Execute + DecodePrivate + Send,
+{
+    /// Creates a new instance of the key schedule.
+    pub fn new(config: PrfConfig, thread: E) -> MpcKeySchedule<E> {
+        MpcKeySchedule {
+            config,
+            state: state::State::Initialized,
+            thread,
+            cancel: None,
+        }
+    }
+
+    /// Sets a token which can be used to cancel the key schedule.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancel = Some(token);
+    }
+
+    fn visibility(&self) -> Visibility {
+        match self.config.role {
+            Role::Leader => Visibility::Private,
+            Role::Follower => Visibility::Blind,
+        }
+    }
+
+    fn check_role(&self, role: Role) -> Result<(), PrfError> {
+        if self.config.role != role {
+            return Err(PrfError::RoleError(match role {
+                Role::Leader => "only leader can provide inputs".to_string(),
+                Role::Follower => "leader must provide inputs".to_string(),
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Transitions into the error state if `res` is an error, discarding any intermediate state.
+    fn abort_on_err<T>(&mut self, res: Result<T, PrfError>) -> Result<T, PrfError> {
+        if res.is_err() {
+            self.state = state::State::Error;
+        }
+
+        res
+    }
+
+    /// Returns the handshake traffic secrets, which are available once the handshake keys are
+    /// computed until the key schedule is dropped.
+    fn handshake_secrets(&self) -> Result<state::HandshakeSecrets, PrfError> {
+        match &self.state {
+            state::State::ApplicationKeys(state::ApplicationKeys { handshake, .. })
+            | state::State::Established(state::Established { handshake, .. }) => {
+                Ok(handshake.clone())
+            }
+            state => Err(PrfError::InvalidState(format!(
+                "handshake traffic secrets are not computed in state {state:?}"
+            ))),
+        }
+    }
+
+    /// Executes a circuit which computes the handshake traffic keys.
+    async fn execute_handshake_keys(
+        &mut self,
+        hello_hash: Option<[u8; 32]>,
+    ) -> Result<SessionKeys, PrfError> {
+        let state::HandshakeKeys { shared_secret } =
+            std::mem::replace(&mut self.state, state::State::Error).try_into_handshake_keys()?;
+
+        let visibility = self.visibility();
+        let hello_hash_ref = self
+            .thread
+            .new_input::<[u8; 32]>("tls13/hello_hash", visibility)?;
+        if let Some(hello_hash) = hello_hash {
+            self.thread.assign(&hello_hash_ref, hello_hash)?;
+        }
+
+        let keys = new_traffic_keys(&mut self.thread, "tls13/handshake")?;
+        let client_secret = self
+            .thread
+            .new_output::<[u8; 32]>("tls13/client_hs_traffic_secret")?;
+        let server_secret = self
+            .thread
+            .new_output::<[u8; 32]>("tls13/server_hs_traffic_secret")?;
+        let handshake_secret = self
+            .thread
+            .new_output::<[u8; 32]>("tls13/handshake_secret")?;
+
+        let circ = circ(
+            &HANDSHAKE_KEYS_CIRC,
+            "tls13_handshake_keys",
+            build_tls13_handshake_keys,
+        )
+        .await;
+
+        self.thread
+            .execute(
+                circ,
+                &[shared_secret, hello_hash_ref],
+                &[
+                    keys.client_write_key.clone(),
+                    keys.server_write_key.clone(),
+                    keys.client_iv.clone(),
+                    keys.server_iv.clone(),
+                    client_secret.clone(),
+                    server_secret.clone(),
+                    handshake_secret.clone(),
+                ],
+            )
+            .await?;
+
+        self.state = state::State::ApplicationKeys(state::ApplicationKeys {
+            handshake_secret,
+            handshake: state::HandshakeSecrets {
+                client: client_secret,
+                server: server_secret,
+            },
+        });
+
+        Ok(keys)
+    }
+
+    /// Executes a circuit which computes the application traffic keys.
+    async fn execute_application_keys(
+        &mut self,
+        handshake_hash: Option<[u8; 32]>,
+    ) -> Result<SessionKeys, PrfError> {
+        let state::ApplicationKeys {
+            handshake_secret,
+            handshake,
+        } = std::mem::replace(&mut self.state, state::State::Error).try_into_application_keys()?;
+
+        let visibility = self.visibility();
+        let handshake_hash_ref = self
+            .thread
+            .new_input::<[u8; 32]>("tls13/handshake_hash", visibility)?;
+        if let Some(handshake_hash) = handshake_hash {
+            self.thread.assign(&handshake_hash_ref, handshake_hash)?;
+        }
+
+        let keys = new_traffic_keys(&mut self.thread, "tls13/application")?;
+        let client_secret = self
+            .thread
+            .new_output::<[u8; 32]>("tls13/client_ap_traffic_secret/0")?;
+        let server_secret = self
+            .thread
+            .new_output::<[u8; 32]>("tls13/server_ap_traffic_secret/0")?;
+
+        let circ = circ(
+            &APPLICATION_KEYS_CIRC,
+            "tls13_application_keys",
+            build_tls13_application_keys,
+        )
+        .await;
+
+        self.thread
+            .execute(
+                circ,
+                &[handshake_secret, handshake_hash_ref],
+                &[
+                    keys.client_write_key.clone(),
+                    keys.server_write_key.clone(),
+                    keys.client_iv.clone(),
+                    keys.server_iv.clone(),
+                    client_secret.clone(),
+                    server_secret.clone(),
+                ],
+            )
+            .await?;
+
+        self.state = state::State::Established(state::Established {
+            handshake,
+            client: state::TrafficSecret {
+                secret: client_secret,
+                generation: 0,
+            },
+            server: state::TrafficSecret {
+                secret: server_secret,
+                generation: 0,
+            },
+        });
+
+        Ok(keys)
+    }
+
+    /// Executes a circuit which computes the Finished verify data of one direction, decoding it
+    /// only to the leader.
+    async fn execute_finished_vd(
+        &mut self,
+        direction: Direction,
+        handshake_hash: Option<[u8; 32]>,
+    ) -> Result<Option<[u8; 32]>, PrfError> {
+        let handshake = self.handshake_secrets()?;
+        let (name, traffic_secret) = match direction {
+            Direction::Client => ("client", handshake.client),
+            Direction::Server => ("server", handshake.server),
+        };
+
+        let visibility = self.visibility();
+        let handshake_hash_ref = self
+            .thread
+            .new_input::<[u8; 32]>(&format!("tls13/{name}_finished/hash"), visibility)?;
+        if let Some(handshake_hash) = handshake_hash {
+            self.thread.assign(&handshake_hash_ref, handshake_hash)?;
+        }
+
+        let vd = self
+            .thread
+            .new_output::<[u8; 32]>(&format!("tls13/{name}_finished/vd"))?;
+
+        let circ = circ(
+            &FINISHED_VD_CIRC,
+            "tls13_finished_vd",
+            build_tls13_finished_vd,
+        )
+        .await;
+
+        self.thread
+            .execute(circ, &[traffic_secret, handshake_hash_ref], &[vd.clone()])
+            .await?;
+
+        let vd = if handshake_hash.is_some() {
+            let mut outputs = self.thread.decode_private(&[vd]).await?;
+            let vd: [u8; 32] = outputs.remove(0).try_into().expect("vd is 32 bytes");
+
+            Some(vd)
+        } else {
+            self.thread.decode_blind(&[vd]).await?;
+
+            None
+        };
+
+        Ok(vd)
+    }
+
+    /// Executes a circuit which derives the next generation of traffic keys for one direction.
+    async fn execute_key_update(&mut self, direction: Direction) -> Result<TrafficKeys, PrfError> {
+        let mut established =
+            std::mem::replace(&mut self.state, state::State::Error).try_into_established()?;
+
+        let (name, traffic_secret) = match direction {
+            Direction::Client => ("client", &mut established.client),
+            Direction::Server => ("server", &mut established.server),
+        };
+
+        let generation = traffic_secret.generation + 1;
+        let key = self
+            .thread
+            .new_output::<[u8; 16]>(&format!("tls13/{name}_write_key/{generation}"))?;
+        let iv = self
+            .thread
+            .new_output::<[u8; 12]>(&format!("tls13/{name}_write_iv/{generation}"))?;
+        let next_secret = self
+            .thread
+            .new_output::<[u8; 32]>(&format!("tls13/{name}_ap_traffic_secret/{generation}"))?;
+
+        let circ = circ(&KEY_UPDATE_CIRC, "tls13_key_update", build_tls13_key_update).await;
+
+        self.thread
+            .execute(
+                circ,
+                &[traffic_secret.secret.clone()],
+                &[key.clone(), iv.clone(), next_secret.clone()],
+            )
+            .await?;
+
+        // The previous secret is no longer referenced and must not be used again.
+        *traffic_secret = state::TrafficSecret {
+            secret: next_secret,
+            generation,
+        };
+
+        self.state = state::State::Established(established);
+
+        Ok(TrafficKeys { key, iv })
+    }
+}
+
+#[async_trait]
+impl<E> KeySchedule for MpcKeySchedule<E>
+where
+    E: Memory + Execute + DecodePrivate + Send,
+{
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn setup(&mut self, shared_secret: ValueRef) -> Result<(), PrfError> {
+        std::mem::replace(&mut self.state, state::State::Error).try_into_initialized()?;
+
+        // Build the circuits ahead of time, they are shared by all instances.
+        _ = circ(
+            &HANDSHAKE_KEYS_CIRC,
+            "tls13_handshake_keys",
+            build_tls13_handshake_keys,
+        )
+        .await;
+        _ = circ(
+            &APPLICATION_KEYS_CIRC,
+            "tls13_application_keys",
+            build_tls13_application_keys,
+        )
+        .await;
+        _ = circ(&KEY_UPDATE_CIRC, "tls13_key_update", build_tls13_key_update).await;
+        _ = circ(
+            &FINISHED_VD_CIRC,
+            "tls13_finished_vd",
+            build_tls13_finished_vd,
+        )
+        .await;
+
+        self.state = state::State::HandshakeKeys(state::HandshakeKeys { shared_secret });
+
+        Ok(())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_handshake_keys_private(
+        &mut self,
+        hello_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Leader)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "handshake_keys",
+            self.execute_handshake_keys(Some(hello_hash)),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_handshake_keys_blind(&mut self) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Follower)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "handshake_keys",
+            self.execute_handshake_keys(None),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_application_keys_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Leader)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "application_keys",
+            self.execute_application_keys(Some(handshake_hash)),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_application_keys_blind(&mut self) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Follower)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "application_keys",
+            self.execute_application_keys(None),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_client_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError> {
+        self.check_role(Role::Leader)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(
+            timeout,
+            cancel,
+            "client_finished",
+            self.execute_finished_vd(Direction::Client, Some(handshake_hash)),
+        )
+        .await;
+
+        self.abort_on_err(vd).map(|vd| vd.expect("vd is decoded"))
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_client_finished_vd_blind(&mut self) -> Result<(), PrfError> {
+        self.check_role(Role::Follower)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(
+            timeout,
+            cancel,
+            "client_finished",
+            self.execute_finished_vd(Direction::Client, None),
+        )
+        .await;
+
+        self.abort_on_err(vd).map(|_| ())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_server_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError> {
+        self.check_role(Role::Leader)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(
+            timeout,
+            cancel,
+            "server_finished",
+            self.execute_finished_vd(Direction::Server, Some(handshake_hash)),
+        )
+        .await;
+
+        self.abort_on_err(vd).map(|vd| vd.expect("vd is decoded"))
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError> {
+        self.check_role(Role::Follower)?;
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(
+            timeout,
+            cancel,
+            "server_finished",
+            self.execute_finished_vd(Direction::Server, None),
+        )
+        .await;
+
+        self.abort_on_err(vd).map(|_| ())
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn update_client_keys(&mut self) -> Result<TrafficKeys, PrfError> {
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "key_update",
+            self.execute_key_update(Direction::Client),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn update_server_keys(&mut self) -> Result<TrafficKeys, PrfError> {
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "key_update",
+            self.execute_key_update(Direction::Server),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+}
+
+/// Returns the circuit, building it if necessary.
+async fn circ(
+    circ: &'static OnceLock<Arc<Circuit>>,
+    name: &'static str,
+    build: fn() -> Arc<Circuit>,
+) -> Arc<Circuit> {
+    if circ.get().is_none() {
+        _ = circ.set(Backend::spawn(move || cache::load_or_build(name, build)).await);
+    }
+
+    circ.get().expect("circuit is set").clone()
+}
+
+fn new_traffic_keys<T: Memory>(thread: &mut T, name: &str) -> Result<SessionKeys, PrfError> {
+    Ok(SessionKeys {
+        client_write_key: thread.new_output::<[u8; 16]>(&format!("{name}/client_write_key"))?,
+        server_write_key: thread.new_output::<[u8; 16]>(&format!("{name}/server_write_key"))?,
+        client_iv: thread.new_output::<[u8; 12]>(&format!("{name}/client_write_iv"))?,
+        server_iv: thread.new_output::<[u8; 12]>(&format!("{name}/server_write_iv"))?,
+    })
+}
+
+pub(crate) mod state {
+    use super::*;
+    use enum_try_as_inner::EnumTryAsInner;
+
+    #[derive(Debug, EnumTryAsInner)]
+    #[derive_err(Debug)]
+    pub(crate) enum State {
+        Initialized,
+        HandshakeKeys(HandshakeKeys),
+        ApplicationKeys(ApplicationKeys),
+        Established(Established),
+        Error,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct HandshakeKeys {
+        pub(crate) shared_secret: ValueRef,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct ApplicationKeys {
+        pub(crate) handshake_secret: ValueRef,
+        pub(crate) handshake: HandshakeSecrets,
+    }
+
+    #[derive(Debug)]
+    pub(crate) struct Established {
+        pub(crate) handshake: HandshakeSecrets,
+        pub(crate) client: TrafficSecret,
+        pub(crate) server: TrafficSecret,
+    }
+
+    /// The handshake traffic secrets, from which the Finished verify data is computed.
+    #[derive(Debug, Clone)]
+    pub(crate) struct HandshakeSecrets {
+        pub(crate) client: ValueRef,
+        pub(crate) server: ValueRef,
+    }
+
+    /// The current application traffic secret of one direction.
+    #[derive(Debug)]
+    pub(crate) struct TrafficSecret {
+        pub(crate) secret: ValueRef,
+        pub(crate) generation: usize,
+    }
+}
@@ -1,15 +1,34 @@
-//! This module contains the protocol for computing TLS SHA-256 HMAC PRF.
+//! This module contains the protocol for computing the TLS HMAC PRF with SHA-256 or SHA-384.
+//!
+//! # Secrets
+//!
+//! The PMS, master secret and session keys are secret and never leave the MPC VM. The client and
+//! server randoms, session hash and handshake hashes are private inputs of the leader, the
+//! follower only provides its encodings of them blindly and does not learn them. The hashes
+//! commit to the handshake transcript, which the leader keeps from the follower. The verify data
+//! is decoded only to the leader, which compares it in constant time in the TLS client.
+//!
+//! The same holds for the TLS 1.3 [`KeySchedule`], with the (EC)DHE shared secret in place of the
+//! PMS and the transcript hashes as private inputs of the leader.
 
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+mod cache;
 mod config;
 mod error;
+mod key_schedule;
+#[cfg(feature = "insecure")]
+mod plain;
 mod prf;
 
-pub use config::{PrfConfig, PrfConfigBuilder, PrfConfigBuilderError, Role};
+pub use cache::set_circuit_cache_dir;
+pub use config::{PrfConfig, PrfConfigBuilder, PrfConfigBuilderError, PrfHash, Role};
 pub use error::PrfError;
+pub use key_schedule::MpcKeySchedule;
+#[cfg(feature = "insecure")]
+pub use plain::PlainPrf;
 pub use prf::MpcPrf;
 
 use async_trait::async_trait;
@@ -35,8 +54,15 @@ pub struct SessionKeys {
 /// PRF trait for computing TLS PRF.
 #[async_trait]
 pub trait Prf {
+    /// Returns the hash function of the PRF.
+    fn hash(&self) -> PrfHash;
+
     /// Performs any necessary one-time setup.
     ///
+    /// This loads all circuits of the PRF, doing the garbling and OT work up front. It only
+    /// depends on the (not yet computed) PMS reference, so it should be called before the
+    /// connection to the server is opened, leaving only the evaluation for the online phase.
+    ///
     /// # Arguments
     ///
     /// * `pms` - The pre-master secret.
@@ -49,16 +75,31 @@ pub trait Prf {
         server_random: [u8; 32],
     ) -> Result<SessionKeys, PrfError>;
 
+    /// Computes the session keys with the extended master secret (RFC 7627), using the provided
+    /// client random, server random, session hash and PMS.
+    ///
+    /// The length of the session hash must match the configured [`PrfHash`].
+    async fn compute_session_keys_ems_private(
+        &mut self,
+        client_random: [u8; 32],
+        server_random: [u8; 32],
+        session_hash: &[u8],
+    ) -> Result<SessionKeys, PrfError>;
+
     /// Computes the client finished verify data using the provided handshake hash.
+    ///
+    /// The length of the handshake hash must match the configured [`PrfHash`].
     async fn compute_client_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError>;
 
     /// Computes the server finished verify data using the provided handshake hash.
+    ///
+    /// The length of the handshake hash must match the configured [`PrfHash`].
     async fn compute_server_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError>;
 
     /// Computes the session keys using randoms provided by the other party.
@@ -71,11 +112,89 @@ pub trait Prf {
     async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError>;
 }
 
+/// Traffic keys for a single direction, computed by a TLS 1.3 key update.
+#[derive(Debug, Clone)]
+pub struct TrafficKeys {
+    /// Write key.
+    pub key: ValueRef,
+    /// IV.
+    pub iv: ValueRef,
+}
+
+/// Key schedule trait for computing the TLS 1.3 traffic keys.
+///
+/// Unlike the TLS 1.2 PRF, the client and server IVs are 12 bytes.
+#[async_trait]
+pub trait KeySchedule {
+    /// Performs any necessary one-time setup.
+    ///
+    /// # Arguments
+    ///
+    /// * `shared_secret` - The (EC)DHE shared secret.
+    async fn setup(&mut self, shared_secret: ValueRef) -> Result<(), PrfError>;
+
+    /// Computes the handshake traffic keys using the provided ClientHello..ServerHello transcript hash.
+    async fn compute_handshake_keys_private(
+        &mut self,
+        hello_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the handshake traffic keys using the transcript hash provided by the other party.
+    async fn compute_handshake_keys_blind(&mut self) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the application traffic keys using the provided ClientHello..server Finished
+    /// transcript hash.
+    async fn compute_application_keys_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the application traffic keys using the transcript hash provided by the other party.
+    async fn compute_application_keys_blind(&mut self) -> Result<SessionKeys, PrfError>;
+
+    /// Computes the client Finished verify data from the client handshake traffic secret, using
+    /// the provided ClientHello..server Finished transcript hash.
+    async fn compute_client_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError>;
+
+    /// Computes the client Finished verify data using the transcript hash provided by the other
+    /// party.
+    async fn compute_client_finished_vd_blind(&mut self) -> Result<(), PrfError>;
+
+    /// Computes the server Finished verify data from the server handshake traffic secret, using
+    /// the provided ClientHello..CertificateVerify transcript hash.
+    async fn compute_server_finished_vd_private(
+        &mut self,
+        handshake_hash: [u8; 32],
+    ) -> Result<[u8; 32], PrfError>;
+
+    /// Computes the server Finished verify data using the transcript hash provided by the other
+    /// party.
+    async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError>;
+
+    /// Derives the next generation of client application traffic keys from the current
+    /// client application traffic secret, eg. after sending a KeyUpdate.
+    ///
+    /// Neither party provides an input, so this is the same for the leader and follower.
+    async fn update_client_keys(&mut self) -> Result<TrafficKeys, PrfError>;
+
+    /// Derives the next generation of server application traffic keys from the current
+    /// server application traffic secret, eg. after receiving a KeyUpdate.
+    ///
+    /// Neither party provides an input, so this is the same for the leader and follower.
+    async fn update_server_keys(&mut self) -> Result<TrafficKeys, PrfError>;
+}
+
 #[cfg(test)]
 mod tests {
     use mpz_garble::{protocol::deap::mock::create_mock_deap_vm, Decode, Memory, Vm};
 
-    use hmac_sha256_circuits::{hmac_sha256_partial, prf, session_keys};
+    use hmac_sha256_circuits::{
+        application_keys, finished_vd, handshake_keys, hmac_sha256_partial, key_update, prf,
+        session_keys,
+    };
 
     use super::*;
 
@@ -195,7 +314,7 @@ mod tests {
         let sf_hs_hash = [2u8; 32];
 
         let (cf_vd, _) = futures::try_join!(
-            leader.compute_client_finished_vd_private(cf_hs_hash),
+            leader.compute_client_finished_vd_private(&cf_hs_hash),
             follower.compute_client_finished_vd_blind()
         )
         .unwrap();
@@ -205,7 +324,7 @@ mod tests {
         assert_eq!(cf_vd, expected_cf_vd);
 
         let (sf_vd, _) = futures::try_join!(
-            leader.compute_server_finished_vd_private(sf_hs_hash),
+            leader.compute_server_finished_vd_private(&sf_hs_hash),
             follower.compute_server_finished_vd_blind()
         )
         .unwrap();
@@ -214,4 +333,123 @@ mod tests {
 
         assert_eq!(sf_vd, expected_sf_vd);
     }
+
+    #[tokio::test]
+    async fn test_key_schedule() {
+        // RFC 8448, Section 3: Simple 1-RTT Handshake.
+        let shared_secret: [u8; 32] = [
+            0x8b, 0xd4, 0x05, 0x4f, 0xb5, 0x5b, 0x9d, 0x63, 0xfd, 0xfb, 0xac, 0xf9, 0xf0, 0x4b,
+            0x9f, 0x0d, 0x35, 0xe6, 0xd6, 0x3f, 0x53, 0x75, 0x63, 0xef, 0xd4, 0x62, 0x72, 0x90,
+            0x0f, 0x89, 0x49, 0x2d,
+        ];
+        let hello_hash: [u8; 32] = [
+            0x86, 0x0c, 0x06, 0xed, 0xc0, 0x78, 0x58, 0xee, 0x8e, 0x78, 0xf0, 0xe7, 0x42, 0x8c,
+            0x58, 0xed, 0xd6, 0xb4, 0x3f, 0x2c, 0xa3, 0xe6, 0xe9, 0x5f, 0x02, 0xed, 0x06, 0x3c,
+            0xf0, 0xe1, 0xca, 0xd8,
+        ];
+        let sf_hash: [u8; 32] = [
+            0xed, 0xb7, 0x72, 0x5f, 0xa7, 0xa3, 0x47, 0x3b, 0x03, 0x1e, 0xc8, 0xef, 0x65, 0xa2,
+            0x48, 0x54, 0x93, 0x90, 0x01, 0x38, 0xa2, 0xb9, 0x12, 0x91, 0x40, 0x7d, 0x79, 0x51,
+            0xa0, 0x61, 0x10, 0xed,
+        ];
+        let expected_hs_cwk: [u8; 16] = [
+            0xdb, 0xfa, 0xa6, 0x93, 0xd1, 0x76, 0x2c, 0x5b, 0x66, 0x6a, 0xf5, 0xd9, 0x50, 0x25,
+            0x8d, 0x01,
+        ];
+        let expected_sf_vd: [u8; 32] = [
+            0x9b, 0x9b, 0x14, 0x1d, 0x90, 0x63, 0x37, 0xfb, 0xd2, 0xcb, 0xdc, 0xe7, 0x1d, 0xf4,
+            0xde, 0xda, 0x4a, 0xb4, 0x2c, 0x30, 0x95, 0x72, 0xcb, 0x7f, 0xff, 0xee, 0x54, 0x54,
+            0xb7, 0x8f, 0x07, 0x18,
+        ];
+        let handshake_hash = [2u8; 32];
+
+        let (mut leader_vm, mut follower_vm) = create_mock_deap_vm("test").await;
+
+        let mut leader_test_thread = leader_vm.new_thread("test").await.unwrap();
+        let mut follower_test_thread = follower_vm.new_thread("test").await.unwrap();
+
+        // Setup public shared secret for testing
+        let leader_secret = leader_test_thread
+            .new_public_input::<[u8; 32]>("shared_secret")
+            .unwrap();
+        let follower_secret = follower_test_thread
+            .new_public_input::<[u8; 32]>("shared_secret")
+            .unwrap();
+
+        leader_test_thread
+            .assign(&leader_secret, shared_secret)
+            .unwrap();
+        follower_test_thread
+            .assign(&follower_secret, shared_secret)
+            .unwrap();
+
+        let mut leader = MpcKeySchedule::new(
+            PrfConfig::builder().role(Role::Leader).build().unwrap(),
+            leader_vm.new_thread("ks").await.unwrap(),
+        );
+        let mut follower = MpcKeySchedule::new(
+            PrfConfig::builder().role(Role::Follower).build().unwrap(),
+            follower_vm.new_thread("ks").await.unwrap(),
+        );
+
+        futures::try_join!(leader.setup(leader_secret), follower.setup(follower_secret)).unwrap();
+
+        let (leader_hs_keys, follower_hs_keys) = futures::try_join!(
+            leader.compute_handshake_keys_private(hello_hash),
+            follower.compute_handshake_keys_blind()
+        )
+        .unwrap();
+
+        let (sf_vd, _) = futures::try_join!(
+            leader.compute_server_finished_vd_private(sf_hash),
+            follower.compute_server_finished_vd_blind()
+        )
+        .unwrap();
+
+        assert_eq!(sf_vd, expected_sf_vd);
+
+        let (leader_app_keys, follower_app_keys) = futures::try_join!(
+            leader.compute_application_keys_private(handshake_hash),
+            follower.compute_application_keys_blind()
+        )
+        .unwrap();
+
+        let (cf_vd, _) = futures::try_join!(
+            leader.compute_client_finished_vd_private(handshake_hash),
+            follower.compute_client_finished_vd_blind()
+        )
+        .unwrap();
+
+        let (leader_updated_keys, follower_updated_keys) =
+            futures::try_join!(leader.update_client_keys(), follower.update_client_keys()).unwrap();
+
+        let (leader_keys, _) = futures::try_join!(
+            leader_test_thread.decode(&[
+                leader_hs_keys.client_write_key,
+                leader_app_keys.client_write_key,
+                leader_updated_keys.key,
+            ]),
+            follower_test_thread.decode(&[
+                follower_hs_keys.client_write_key,
+                follower_app_keys.client_write_key,
+                follower_updated_keys.key,
+            ]),
+        )
+        .unwrap();
+
+        let (_, _, _, _, client_hs_secret, _, handshake_secret) =
+            handshake_keys(shared_secret, hello_hash);
+        let (expected_app_cwk, _, _, _, app_client_secret, _) =
+            application_keys(handshake_secret, handshake_hash);
+        let (expected_updated_cwk, _, _) = key_update(app_client_secret);
+
+        let hs_cwk: [u8; 16] = leader_keys[0].clone().try_into().unwrap();
+        let app_cwk: [u8; 16] = leader_keys[1].clone().try_into().unwrap();
+        let updated_cwk: [u8; 16] = leader_keys[2].clone().try_into().unwrap();
+
+        assert_eq!(hs_cwk, expected_hs_cwk);
+        assert_eq!(cf_vd, finished_vd(client_hs_secret, handshake_hash));
+        assert_eq!(app_cwk, expected_app_cwk);
+        assert_eq!(updated_cwk, expected_updated_cwk);
+    }
 }
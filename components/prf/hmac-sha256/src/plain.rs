@@ -0,0 +1,385 @@
+//! An insecure PRF backend which computes the PRF in the clear.
+//!
+//! The PMS is revealed to the leader, which then computes the master secret, session keys and
+//! verify data locally and assigns the keys as private inputs to the VM. This is only meant for
+//! tests and development loops, where garbling the PRF circuits dominates the run time.
+
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+
+use hmac_sha256_circuits::{hmac_sha256_partial, hmac_sha384_partial, prf, prf_sha384};
+use mpz_garble::{config::Visibility, value::ValueRef, Decode, DecodePrivate, Memory};
+
+use crate::{Prf, PrfConfig, PrfError, PrfHash, Role, SessionKeys, CF_LABEL, SF_LABEL};
+
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+/// PRF which computes the TLS HMAC PRF in the clear, **without any security**.
+///
+/// It implements the same [`Prf`] trait as [`MpcPrf`](crate::MpcPrf) so it can be swapped in
+/// wherever the PRF is boxed. Both parties must use this backend.
+pub struct PlainPrf<E> {
+    config: PrfConfig,
+    step: Step,
+    thread: E,
+    pms: Option<ValueRef>,
+    keys: Option<SessionKeys>,
+    /// The master secret, only known to the leader.
+    master_secret: Option<Vec<u8>>,
+}
+
+/// A round boundary of the PRF protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Initialized,
+    SessionKeys,
+    ClientFinished,
+    ServerFinished,
+    Complete,
+    Error,
+}
+
+impl<E> Debug for PlainPrf<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlainPrf")
+            .field("config", &self.config)
+            .field("step", &self.step)
+            .finish()
+    }
+}
+
+impl<E> PlainPrf<E>
+where
+    E: Memory + Decode + DecodePrivate + Send,
+{
+    /// Creates a new instance of the insecure PRF.
+    pub fn new(config: PrfConfig, thread: E) -> PlainPrf<E> {
+        PlainPrf {
+            config,
+            step: Step::Initialized,
+            thread,
+            pms: None,
+            keys: None,
+            master_secret: None,
+        }
+    }
+
+    /// Transitions to `next` if the PRF is at `expected`, otherwise into the error state.
+    fn advance(&mut self, expected: Step, next: Step) -> Result<(), PrfError> {
+        if self.step != expected {
+            let step = self.step;
+            self.step = Step::Error;
+            return Err(PrfError::InvalidState(format!(
+                "expected {expected:?}, found {step:?}"
+            )));
+        }
+
+        self.step = next;
+
+        Ok(())
+    }
+
+    fn check_role(&self, role: Role) -> Result<(), PrfError> {
+        if self.config.role == role {
+            return Ok(());
+        }
+
+        Err(PrfError::RoleError(match role {
+            Role::Leader => "only leader can provide inputs".to_string(),
+            Role::Follower => "leader must provide inputs".to_string(),
+        }))
+    }
+
+    /// Decodes the PMS to the leader, and computes and assigns the session keys.
+    async fn compute_session_keys(
+        &mut self,
+        ms_label: &[u8],
+        ms_seed: &[u8],
+        client_random: [u8; 32],
+        server_random: [u8; 32],
+    ) -> Result<SessionKeys, PrfError> {
+        self.advance(Step::SessionKeys, Step::ClientFinished)?;
+
+        let pms = self.pms.take().expect("pms is set in setup");
+        let keys = self.keys.clone().expect("keys are set in setup");
+
+        let mut outputs = self.thread.decode_private(&[pms]).await?;
+        let pms: [u8; 32] = outputs.remove(0).try_into().expect("pms is 32 bytes");
+
+        let hash = self.config.hash;
+        let master_secret = plain_prf(hash, &pms, ms_seed, ms_label, 48);
+
+        let seed = server_random
+            .iter()
+            .chain(&client_random)
+            .copied()
+            .collect::<Vec<_>>();
+        let key_len = hash.key_len();
+        let key_material = plain_prf(
+            hash,
+            &master_secret,
+            &seed,
+            b"key expansion",
+            2 * key_len + 8,
+        );
+
+        let (cwk, rest) = key_material.split_at(key_len);
+        let (swk, rest) = rest.split_at(key_len);
+        let (civ, siv) = rest.split_at(4);
+
+        assign_key(&mut self.thread, hash, &keys.client_write_key, cwk)?;
+        assign_key(&mut self.thread, hash, &keys.server_write_key, swk)?;
+        self.thread
+            .assign(&keys.client_iv, <[u8; 4]>::try_from(civ).unwrap())?;
+        self.thread
+            .assign(&keys.server_iv, <[u8; 4]>::try_from(siv).unwrap())?;
+
+        self.master_secret = Some(master_secret);
+
+        Ok(keys)
+    }
+
+    fn compute_vd(&mut self, label: &[u8], handshake_hash: &[u8]) -> Result<[u8; 12], PrfError> {
+        let hash = self.config.hash;
+        if handshake_hash.len() != hash.handshake_hash_len() {
+            self.step = Step::Error;
+            return Err(PrfError::InvalidHandshakeHash {
+                expected: hash.handshake_hash_len(),
+                actual: handshake_hash.len(),
+            });
+        }
+
+        let master_secret = self
+            .master_secret
+            .as_ref()
+            .expect("master secret is computed with the session keys");
+
+        Ok(plain_prf(hash, master_secret, handshake_hash, label, 12)
+            .try_into()
+            .expect("vd is 12 bytes"))
+    }
+}
+
+#[async_trait]
+impl<E> Prf for PlainPrf<E>
+where
+    E: Memory + Decode + DecodePrivate + Send,
+{
+    fn hash(&self) -> PrfHash {
+        self.config.hash
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn setup(&mut self, pms: ValueRef) -> Result<SessionKeys, PrfError> {
+        self.advance(Step::Initialized, Step::SessionKeys)?;
+
+        let visibility = match self.config.role {
+            Role::Leader => Visibility::Private,
+            Role::Follower => Visibility::Blind,
+        };
+
+        let thread = &mut self.thread;
+        let (client_write_key, server_write_key) = match self.config.hash {
+            PrfHash::Sha256 => (
+                thread.new_input::<[u8; 16]>("client_write_key", visibility)?,
+                thread.new_input::<[u8; 16]>("server_write_key", visibility)?,
+            ),
+            PrfHash::Sha384 => (
+                thread.new_input::<[u8; 32]>("client_write_key", visibility)?,
+                thread.new_input::<[u8; 32]>("server_write_key", visibility)?,
+            ),
+        };
+        let client_iv = thread.new_input::<[u8; 4]>("client_write_iv", visibility)?;
+        let server_iv = thread.new_input::<[u8; 4]>("server_write_iv", visibility)?;
+
+        let keys = SessionKeys {
+            client_write_key,
+            server_write_key,
+            client_iv,
+            server_iv,
+        };
+
+        self.pms = Some(pms);
+        self.keys = Some(keys.clone());
+
+        Ok(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_session_keys_private(
+        &mut self,
+        client_random: [u8; 32],
+        server_random: [u8; 32],
+    ) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Leader)?;
+
+        if self.config.extended_master_secret {
+            return Err(PrfError::InvalidState(
+                "extended master secret requires a session hash".to_string(),
+            ));
+        }
+
+        let seed = client_random
+            .iter()
+            .chain(&server_random)
+            .copied()
+            .collect::<Vec<_>>();
+
+        self.compute_session_keys(b"master secret", &seed, client_random, server_random)
+            .await
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_session_keys_ems_private(
+        &mut self,
+        client_random: [u8; 32],
+        server_random: [u8; 32],
+        session_hash: &[u8],
+    ) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Leader)?;
+
+        if !self.config.extended_master_secret {
+            return Err(PrfError::InvalidState(
+                "extended master secret is not enabled".to_string(),
+            ));
+        }
+
+        let hash = self.config.hash;
+        if session_hash.len() != hash.handshake_hash_len() {
+            return Err(PrfError::InvalidHandshakeHash {
+                expected: hash.handshake_hash_len(),
+                actual: session_hash.len(),
+            });
+        }
+
+        self.compute_session_keys(
+            b"extended master secret",
+            session_hash,
+            client_random,
+            server_random,
+        )
+        .await
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_client_finished_vd_private(
+        &mut self,
+        handshake_hash: &[u8],
+    ) -> Result<[u8; 12], PrfError> {
+        self.check_role(Role::Leader)?;
+        self.advance(Step::ClientFinished, Step::ServerFinished)?;
+
+        self.compute_vd(CF_LABEL, handshake_hash)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_server_finished_vd_private(
+        &mut self,
+        handshake_hash: &[u8],
+    ) -> Result<[u8; 12], PrfError> {
+        self.check_role(Role::Leader)?;
+        self.advance(Step::ServerFinished, Step::Complete)?;
+
+        let vd = self.compute_vd(SF_LABEL, handshake_hash);
+
+        // The master secret is no longer needed.
+        self.master_secret = None;
+
+        vd
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_session_keys_blind(&mut self) -> Result<SessionKeys, PrfError> {
+        self.check_role(Role::Follower)?;
+        self.advance(Step::SessionKeys, Step::ClientFinished)?;
+
+        let pms = self.pms.take().expect("pms is set in setup");
+        self.thread.decode_blind(&[pms]).await?;
+
+        Ok(self.keys.clone().expect("keys are set in setup"))
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_client_finished_vd_blind(&mut self) -> Result<(), PrfError> {
+        self.check_role(Role::Follower)?;
+        self.advance(Step::ClientFinished, Step::ServerFinished)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_server_finished_vd_blind(&mut self) -> Result<(), PrfError> {
+        self.check_role(Role::Follower)?;
+        self.advance(Step::ServerFinished, Step::Complete)
+    }
+}
+
+/// Computes the TLS PRF in the clear with the given hash function.
+fn plain_prf(hash: PrfHash, key: &[u8], seed: &[u8], label: &[u8], bytes: usize) -> Vec<u8> {
+    match hash {
+        PrfHash::Sha256 => {
+            let (outer_state, inner_state) = hmac_sha256_partial(key);
+            prf(outer_state, inner_state, seed, label, bytes)
+        }
+        PrfHash::Sha384 => {
+            let (outer_state, inner_state) = hmac_sha384_partial(key);
+            prf_sha384(outer_state, inner_state, seed, label, bytes)
+        }
+    }
+}
+
+/// Assigns a session write key, whose length depends on the hash function.
+fn assign_key<T: Memory>(
+    thread: &mut T,
+    hash: PrfHash,
+    value_ref: &ValueRef,
+    key: &[u8],
+) -> Result<(), PrfError> {
+    match hash {
+        PrfHash::Sha256 => thread.assign(value_ref, <[u8; 16]>::try_from(key).unwrap())?,
+        PrfHash::Sha384 => thread.assign(value_ref, <[u8; 32]>::try_from(key).unwrap())?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hmac_sha256_circuits::{session_keys, verify_data};
+
+    use super::*;
+
+    #[test]
+    fn test_plain_prf_matches_reference() {
+        let pms = [42u8; 32];
+        let client_random = [69u8; 32];
+        let server_random = [96u8; 32];
+        let hs_hash = [1u8; 32];
+
+        let seed = client_random
+            .iter()
+            .chain(&server_random)
+            .copied()
+            .collect::<Vec<_>>();
+        let ms = plain_prf(PrfHash::Sha256, &pms, &seed, b"master secret", 48);
+
+        let seed = server_random
+            .iter()
+            .chain(&client_random)
+            .copied()
+            .collect::<Vec<_>>();
+        let key_material = plain_prf(PrfHash::Sha256, &ms, &seed, b"key expansion", 40);
+
+        let (cwk, swk, civ, siv) = session_keys(pms, client_random, server_random);
+        assert_eq!(
+            key_material,
+            [&cwk[..], &swk[..], &civ[..], &siv[..]].concat()
+        );
+
+        let (outer_state, inner_state) = hmac_sha256_partial(&ms);
+        assert_eq!(
+            plain_prf(PrfHash::Sha256, &ms, &hs_hash, CF_LABEL, 12),
+            verify_data(outer_state, inner_state, CF_LABEL, hs_hash)
+        );
+    }
+}
@@ -1,18 +1,26 @@
 use std::{
     fmt::Debug,
+    future::Future,
     sync::{Arc, OnceLock},
+    time::Duration,
 };
 
 use async_trait::async_trait;
+use futures::FutureExt;
+use futures_timer::Delay;
+use tokio_util::sync::CancellationToken;
 
-use hmac_sha256_circuits::{build_session_keys, build_verify_data};
+use hmac_sha256_circuits::{
+    build_session_keys, build_session_keys_ems, build_session_keys_ems_sha384,
+    build_session_keys_sha384, build_verify_data, build_verify_data_sha384,
+};
 use mpz_circuits::Circuit;
 use mpz_garble::{
     config::Visibility, value::ValueRef, Decode, DecodePrivate, Execute, Load, Memory,
 };
 use utils_aio::non_blocking_backend::{Backend, NonBlockingBackend};
 
-use crate::{Prf, PrfConfig, PrfError, Role, SessionKeys, CF_LABEL, SF_LABEL};
+use crate::{cache, Prf, PrfConfig, PrfError, PrfHash, Role, SessionKeys, CF_LABEL, SF_LABEL};
 
 #[cfg(feature = "tracing")]
 use tracing::instrument;
@@ -23,6 +31,64 @@ static SESSION_KEYS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
 static CLIENT_VD_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
 /// Circuit for computing TLS server verify data.
 static SERVER_VD_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS session keys with a SHA384 PRF.
+static SESSION_KEYS_SHA384_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS session keys with the extended master secret.
+static SESSION_KEYS_EMS_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS session keys with the extended master secret and a SHA384 PRF.
+static SESSION_KEYS_EMS_SHA384_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS client verify data with a SHA384 PRF.
+static CLIENT_VD_SHA384_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+/// Circuit for computing TLS server verify data with a SHA384 PRF.
+static SERVER_VD_SHA384_CIRC: OnceLock<Arc<Circuit>> = OnceLock::new();
+
+/// Returns the session keys circuit for the given hash, building it if necessary.
+async fn session_keys_circ(hash: PrfHash, extended_master_secret: bool) -> Arc<Circuit> {
+    let (circ, name, build): (_, _, fn() -> Arc<Circuit>) = match (hash, extended_master_secret) {
+        (PrfHash::Sha256, false) => (&SESSION_KEYS_CIRC, "session_keys", build_session_keys),
+        (PrfHash::Sha384, false) => (
+            &SESSION_KEYS_SHA384_CIRC,
+            "session_keys_sha384",
+            build_session_keys_sha384,
+        ),
+        (PrfHash::Sha256, true) => (
+            &SESSION_KEYS_EMS_CIRC,
+            "session_keys_ems",
+            build_session_keys_ems,
+        ),
+        (PrfHash::Sha384, true) => (
+            &SESSION_KEYS_EMS_SHA384_CIRC,
+            "session_keys_ems_sha384",
+            build_session_keys_ems_sha384,
+        ),
+    };
+
+    if circ.get().is_none() {
+        _ = circ.set(Backend::spawn(move || cache::load_or_build(name, build)).await);
+    }
+
+    circ.get().expect("session keys circuit is set").clone()
+}
+
+/// Returns the verify data circuit for the given hash and message, building it if necessary.
+async fn verify_data_circ(hash: PrfHash, msg: &Msg) -> Arc<Circuit> {
+    let (circ, name, label) = match (hash, msg) {
+        (PrfHash::Sha256, Msg::Cf) => (&CLIENT_VD_CIRC, "client_vd", CF_LABEL),
+        (PrfHash::Sha256, Msg::Sf) => (&SERVER_VD_CIRC, "server_vd", SF_LABEL),
+        (PrfHash::Sha384, Msg::Cf) => (&CLIENT_VD_SHA384_CIRC, "client_vd_sha384", CF_LABEL),
+        (PrfHash::Sha384, Msg::Sf) => (&SERVER_VD_SHA384_CIRC, "server_vd_sha384", SF_LABEL),
+    };
+
+    if circ.get().is_none() {
+        let build: fn(&[u8]) -> Arc<Circuit> = match hash {
+            PrfHash::Sha256 => build_verify_data,
+            PrfHash::Sha384 => build_verify_data_sha384,
+        };
+        _ = circ.set(Backend::spawn(move || cache::load_or_build(name, || build(label))).await);
+    }
+
+    circ.get().expect("verify data circuit is set").clone()
+}
 
 enum Msg {
     Cf,
@@ -31,10 +97,23 @@ enum Msg {
 
 #[derive(Debug)]
 pub(crate) struct Randoms {
+    /// The session hash, only present when using the extended master secret.
+    pub(crate) session_hash: Option<ValueRef>,
     pub(crate) client_random: ValueRef,
     pub(crate) server_random: ValueRef,
 }
 
+impl Randoms {
+    /// Returns the inputs to the session keys circuit.
+    fn circuit_inputs(&self, pms: ValueRef) -> Vec<ValueRef> {
+        let mut inputs = vec![pms];
+        inputs.extend(self.session_hash.clone());
+        inputs.push(self.client_random.clone());
+        inputs.push(self.server_random.clone());
+        inputs
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct HashState {
     pub(crate) ms_outer_hash_state: ValueRef,
@@ -47,12 +126,15 @@ pub(crate) struct VerifyData {
     pub(crate) vd: ValueRef,
 }
 
-/// MPC PRF for computing TLS HMAC-SHA256 PRF.
+/// MPC PRF for computing the TLS HMAC PRF.
+///
+/// The hash function is selected by [`PrfConfig`], both families share the same protocol.
 pub struct MpcPrf<E> {
     config: PrfConfig,
     state: state::State,
     thread_0: E,
     thread_1: E,
+    cancel: Option<CancellationToken>,
 }
 
 impl<E> Debug for MpcPrf<E> {
@@ -75,13 +157,34 @@ where
             state: state::State::Initialized,
             thread_0,
             thread_1,
+            cancel: None,
         }
     }
 
+    /// Sets a token which can be used to cancel the PRF.
+    ///
+    /// Once the token is cancelled, the step in progress is aborted at the next await point and
+    /// the PRF transitions into an error state, dropping its references to the intermediate
+    /// values. The values themselves are owned by the VM and are not zeroized, they are only
+    /// freed once the VM is dropped.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancel = Some(token);
+    }
+
+    /// Transitions into the error state if `res` is an error, discarding any intermediate state.
+    fn abort_on_err<T>(&mut self, res: Result<T, PrfError>) -> Result<T, PrfError> {
+        if res.is_err() {
+            self.state = state::State::Error;
+        }
+
+        res
+    }
+
     /// Executes a circuit which computes TLS session keys.
     async fn execute_session_keys(
         &mut self,
         randoms: Option<([u8; 32], [u8; 32])>,
+        session_hash: Option<&[u8]>,
     ) -> Result<SessionKeys, PrfError> {
         let state::SessionKeys {
             pms,
@@ -92,9 +195,18 @@ where
             sf_vd,
         } = std::mem::replace(&mut self.state, state::State::Error).try_into_session_keys()?;
 
-        let circ = SESSION_KEYS_CIRC
-            .get()
-            .expect("session keys circuit is set");
+        let circ = session_keys_circ(self.config.hash, self.config.extended_master_secret).await;
+
+        if let (Some(session_hash), Some(session_hash_ref)) =
+            (session_hash, randoms_refs.session_hash.as_ref())
+        {
+            assign_handshake_hash(
+                &mut self.thread_0,
+                self.config.hash,
+                session_hash_ref,
+                session_hash,
+            )?;
+        }
 
         if let Some((client_random, server_random)) = randoms {
             self.thread_0
@@ -105,8 +217,8 @@ where
 
         self.thread_0
             .execute(
-                circ.clone(),
-                &[pms, randoms_refs.client_random, randoms_refs.server_random],
+                circ,
+                &randoms_refs.circuit_inputs(pms),
                 &[
                     keys.client_write_key.clone(),
                     keys.server_write_key.clone(),
@@ -129,7 +241,7 @@ where
 
     async fn execute_cf_vd(
         &mut self,
-        handshake_hash: Option<[u8; 32]>,
+        handshake_hash: Option<&[u8]>,
     ) -> Result<Option<[u8; 12]>, PrfError> {
         let state::ClientFinished {
             hash_state,
@@ -137,16 +249,20 @@ where
             sf_vd,
         } = std::mem::replace(&mut self.state, state::State::Error).try_into_client_finished()?;
 
-        let circ = CLIENT_VD_CIRC.get().expect("client vd circuit is set");
+        let circ = verify_data_circ(self.config.hash, &Msg::Cf).await;
 
         if let Some(handshake_hash) = handshake_hash {
-            self.thread_0
-                .assign(&cf_vd.handshake_hash, handshake_hash)?;
+            assign_handshake_hash(
+                &mut self.thread_0,
+                self.config.hash,
+                &cf_vd.handshake_hash,
+                handshake_hash,
+            )?;
         }
 
         self.thread_0
             .execute(
-                circ.clone(),
+                circ,
                 &[
                     hash_state.ms_outer_hash_state.clone(),
                     hash_state.ms_inner_hash_state.clone(),
@@ -174,21 +290,25 @@ where
 
     async fn execute_sf_vd(
         &mut self,
-        handshake_hash: Option<[u8; 32]>,
+        handshake_hash: Option<&[u8]>,
     ) -> Result<Option<[u8; 12]>, PrfError> {
         let state::ServerFinished { hash_state, sf_vd } =
             std::mem::replace(&mut self.state, state::State::Error).try_into_server_finished()?;
 
-        let circ = SERVER_VD_CIRC.get().expect("server vd circuit is set");
+        let circ = verify_data_circ(self.config.hash, &Msg::Sf).await;
 
         if let Some(handshake_hash) = handshake_hash {
-            self.thread_1
-                .assign(&sf_vd.handshake_hash, handshake_hash)?;
+            assign_handshake_hash(
+                &mut self.thread_1,
+                self.config.hash,
+                &sf_vd.handshake_hash,
+                handshake_hash,
+            )?;
         }
 
         self.thread_1
             .execute(
-                circ.clone(),
+                circ,
                 &[
                     hash_state.ms_outer_hash_state,
                     hash_state.ms_inner_hash_state,
@@ -220,6 +340,10 @@ impl<E> Prf for MpcPrf<E>
 where
     E: Memory + Load + Execute + Decode + DecodePrivate + Send,
 {
+    fn hash(&self) -> PrfHash {
+        self.config.hash
+    }
+
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
     async fn setup(&mut self, pms: ValueRef) -> Result<SessionKeys, PrfError> {
         std::mem::replace(&mut self.state, state::State::Error).try_into_initialized()?;
@@ -231,11 +355,26 @@ where
 
         // Perform pre-computation for all circuits.
         let (randoms, hash_state, keys) =
-            setup_session_keys(&mut self.thread_0, pms.clone(), visibility).await?;
+            setup_session_keys(&mut self.thread_0, &self.config, pms.clone(), visibility).await?;
 
+        // The finished circuits take the master secret hash state computed by the session keys
+        // circuit as input, so they can only be loaded once it is loaded.
+        let hash = self.config.hash;
         let (cf_vd, sf_vd) = futures::try_join!(
-            setup_finished_msg(&mut self.thread_0, Msg::Cf, hash_state.clone(), visibility),
-            setup_finished_msg(&mut self.thread_1, Msg::Sf, hash_state.clone(), visibility),
+            setup_finished_msg(
+                &mut self.thread_0,
+                hash,
+                Msg::Cf,
+                hash_state.clone(),
+                visibility
+            ),
+            setup_finished_msg(
+                &mut self.thread_1,
+                hash,
+                Msg::Sf,
+                hash_state.clone(),
+                visibility
+            ),
         )?;
 
         self.state = state::State::SessionKeys(state::SessionKeys {
@@ -262,14 +401,59 @@ where
             ));
         }
 
-        self.execute_session_keys(Some((client_random, server_random)))
-            .await
+        if self.config.extended_master_secret {
+            return Err(PrfError::InvalidState(
+                "extended master secret requires a session hash".to_string(),
+            ));
+        }
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "session_keys",
+            self.execute_session_keys(Some((client_random, server_random)), None),
+        )
+        .await;
+
+        self.abort_on_err(keys)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
+    async fn compute_session_keys_ems_private(
+        &mut self,
+        client_random: [u8; 32],
+        server_random: [u8; 32],
+        session_hash: &[u8],
+    ) -> Result<SessionKeys, PrfError> {
+        if self.config.role != Role::Leader {
+            return Err(PrfError::RoleError(
+                "only leader can provide inputs".to_string(),
+            ));
+        }
+
+        if !self.config.extended_master_secret {
+            return Err(PrfError::InvalidState(
+                "extended master secret is not enabled".to_string(),
+            ));
+        }
+
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "session_keys",
+            self.execute_session_keys(Some((client_random, server_random)), Some(session_hash)),
+        )
+        .await;
+
+        self.abort_on_err(keys)
     }
 
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
     async fn compute_client_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError> {
         if self.config.role != Role::Leader {
             return Err(PrfError::RoleError(
@@ -277,15 +461,23 @@ where
             ));
         }
 
-        self.execute_cf_vd(Some(handshake_hash))
-            .await
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(
+            timeout,
+            cancel,
+            "client_finished",
+            self.execute_cf_vd(Some(handshake_hash)),
+        )
+        .await;
+
+        self.abort_on_err(vd)
             .map(|hash| hash.expect("vd is decoded"))
     }
 
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
     async fn compute_server_finished_vd_private(
         &mut self,
-        handshake_hash: [u8; 32],
+        handshake_hash: &[u8],
     ) -> Result<[u8; 12], PrfError> {
         if self.config.role != Role::Leader {
             return Err(PrfError::RoleError(
@@ -293,8 +485,16 @@ where
             ));
         }
 
-        self.execute_sf_vd(Some(handshake_hash))
-            .await
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(
+            timeout,
+            cancel,
+            "server_finished",
+            self.execute_sf_vd(Some(handshake_hash)),
+        )
+        .await;
+
+        self.abort_on_err(vd)
             .map(|hash| hash.expect("vd is decoded"))
     }
 
@@ -306,7 +506,16 @@ where
             ));
         }
 
-        self.execute_session_keys(None).await
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let keys = run_step(
+            timeout,
+            cancel,
+            "session_keys",
+            self.execute_session_keys(None, None),
+        )
+        .await;
+
+        self.abort_on_err(keys)
     }
 
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
@@ -317,7 +526,10 @@ where
             ));
         }
 
-        self.execute_cf_vd(None).await.map(|_| ())
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(timeout, cancel, "client_finished", self.execute_cf_vd(None)).await;
+
+        self.abort_on_err(vd).map(|_| ())
     }
 
     #[cfg_attr(feature = "tracing", instrument(level = "debug", skip(self), err))]
@@ -328,7 +540,39 @@ where
             ));
         }
 
-        self.execute_sf_vd(None).await.map(|_| ())
+        let (timeout, cancel) = (self.config.step_timeout, self.cancel.clone());
+        let vd = run_step(timeout, cancel, "server_finished", self.execute_sf_vd(None)).await;
+
+        self.abort_on_err(vd).map(|_| ())
+    }
+}
+
+/// Runs a step of the PRF, returning an error if it does not complete within `timeout` or if
+/// `cancel` is cancelled first.
+pub(crate) async fn run_step<T>(
+    timeout: Option<Duration>,
+    cancel: Option<CancellationToken>,
+    step: &'static str,
+    fut: impl Future<Output = Result<T, PrfError>>,
+) -> Result<T, PrfError> {
+    let timeout = async {
+        match timeout {
+            Some(timeout) => Delay::new(timeout).await,
+            None => futures::future::pending().await,
+        }
+    };
+
+    let cancelled = async {
+        match cancel {
+            Some(cancel) => cancel.cancelled().await,
+            None => futures::future::pending().await,
+        }
+    };
+
+    futures::select! {
+        res = fut.fuse() => res,
+        _ = timeout.fuse() => Err(PrfError::Timeout { step }),
+        _ = cancelled.fuse() => Err(PrfError::Cancelled { step }),
     }
 }
 
@@ -373,32 +617,58 @@ pub(crate) mod state {
 
 async fn setup_session_keys<T: Memory + Load + Send>(
     thread: &mut T,
+    config: &PrfConfig,
     pms: ValueRef,
     visibility: Visibility,
 ) -> Result<(Randoms, HashState, SessionKeys), PrfError> {
+    let hash = config.hash;
+    let session_hash = if config.extended_master_secret {
+        Some(match hash {
+            PrfHash::Sha256 => thread.new_input::<[u8; 32]>("session_hash", visibility)?,
+            PrfHash::Sha384 => thread.new_input::<[u8; 48]>("session_hash", visibility)?,
+        })
+    } else {
+        None
+    };
     let client_random = thread.new_input::<[u8; 32]>("client_finished", visibility)?;
     let server_random = thread.new_input::<[u8; 32]>("server_finished", visibility)?;
 
-    let client_write_key = thread.new_output::<[u8; 16]>("client_write_key")?;
-    let server_write_key = thread.new_output::<[u8; 16]>("server_write_key")?;
+    let (client_write_key, server_write_key) = match hash {
+        PrfHash::Sha256 => (
+            thread.new_output::<[u8; 16]>("client_write_key")?,
+            thread.new_output::<[u8; 16]>("server_write_key")?,
+        ),
+        PrfHash::Sha384 => (
+            thread.new_output::<[u8; 32]>("client_write_key")?,
+            thread.new_output::<[u8; 32]>("server_write_key")?,
+        ),
+    };
     let client_iv = thread.new_output::<[u8; 4]>("client_write_iv")?;
     let server_iv = thread.new_output::<[u8; 4]>("server_write_iv")?;
 
-    let ms_outer_hash_state = thread.new_output::<[u32; 8]>("ms_outer_hash_state")?;
-    let ms_inner_hash_state = thread.new_output::<[u32; 8]>("ms_inner_hash_state")?;
+    let (ms_outer_hash_state, ms_inner_hash_state) = match hash {
+        PrfHash::Sha256 => (
+            thread.new_output::<[u32; 8]>("ms_outer_hash_state")?,
+            thread.new_output::<[u32; 8]>("ms_inner_hash_state")?,
+        ),
+        PrfHash::Sha384 => (
+            thread.new_output::<[u64; 8]>("ms_outer_hash_state")?,
+            thread.new_output::<[u64; 8]>("ms_inner_hash_state")?,
+        ),
+    };
 
-    if SESSION_KEYS_CIRC.get().is_none() {
-        _ = SESSION_KEYS_CIRC.set(Backend::spawn(build_session_keys).await);
-    }
+    let randoms = Randoms {
+        session_hash,
+        client_random,
+        server_random,
+    };
 
-    let circ = SESSION_KEYS_CIRC
-        .get()
-        .expect("session keys circuit is set");
+    let circ = session_keys_circ(hash, config.extended_master_secret).await;
 
     thread
         .load(
-            circ.clone(),
-            &[pms, client_random.clone(), server_random.clone()],
+            circ,
+            &randoms.circuit_inputs(pms),
             &[
                 client_write_key.clone(),
                 server_write_key.clone(),
@@ -411,10 +681,7 @@ async fn setup_session_keys<T: Memory + Load + Send>(
         .await?;
 
     Ok((
-        Randoms {
-            client_random,
-            server_random,
-        },
+        randoms,
         HashState {
             ms_outer_hash_state,
             ms_inner_hash_state,
@@ -430,6 +697,7 @@ async fn setup_session_keys<T: Memory + Load + Send>(
 
 async fn setup_finished_msg<T: Memory + Load + Send>(
     thread: &mut T,
+    hash: PrfHash,
     msg: Msg,
     hash_state: HashState,
     visibility: Visibility,
@@ -439,29 +707,18 @@ async fn setup_finished_msg<T: Memory + Load + Send>(
         Msg::Sf => String::from("server_finished"),
     };
 
-    let handshake_hash =
-        thread.new_input::<[u8; 32]>(&format!("{name}/handshake_hash"), visibility)?;
-    let vd = thread.new_output::<[u8; 12]>(&format!("{name}/vd"))?;
-
-    let circ = match msg {
-        Msg::Cf => &CLIENT_VD_CIRC,
-        Msg::Sf => &SERVER_VD_CIRC,
-    };
-
-    let label = match msg {
-        Msg::Cf => CF_LABEL,
-        Msg::Sf => SF_LABEL,
+    let handshake_hash_name = format!("{name}/handshake_hash");
+    let handshake_hash = match hash {
+        PrfHash::Sha256 => thread.new_input::<[u8; 32]>(&handshake_hash_name, visibility)?,
+        PrfHash::Sha384 => thread.new_input::<[u8; 48]>(&handshake_hash_name, visibility)?,
     };
+    let vd = thread.new_output::<[u8; 12]>(&format!("{name}/vd"))?;
 
-    if circ.get().is_none() {
-        _ = circ.set(Backend::spawn(move || build_verify_data(label)).await);
-    }
-
-    let circ = circ.get().expect("session keys circuit is set");
+    let circ = verify_data_circ(hash, &msg).await;
 
     thread
         .load(
-            circ.clone(),
+            circ,
             &[
                 hash_state.ms_outer_hash_state,
                 hash_state.ms_inner_hash_state,
@@ -473,3 +730,63 @@ async fn setup_finished_msg<T: Memory + Load + Send>(
 
     Ok(VerifyData { handshake_hash, vd })
 }
+
+/// Assigns the handshake hash, checking that its length matches the hash function.
+fn assign_handshake_hash<T: Memory>(
+    thread: &mut T,
+    hash: PrfHash,
+    value_ref: &ValueRef,
+    handshake_hash: &[u8],
+) -> Result<(), PrfError> {
+    let err = || PrfError::InvalidHandshakeHash {
+        expected: hash.handshake_hash_len(),
+        actual: handshake_hash.len(),
+    };
+
+    match hash {
+        PrfHash::Sha256 => {
+            let handshake_hash: [u8; 32] = handshake_hash.try_into().map_err(|_| err())?;
+            thread.assign(value_ref, handshake_hash)?;
+        }
+        PrfHash::Sha384 => {
+            let handshake_hash: [u8; 48] = handshake_hash.try_into().map_err(|_| err())?;
+            thread.assign(value_ref, handshake_hash)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_step_cancelled() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let res = run_step(
+            None,
+            Some(cancel),
+            "test",
+            futures::future::pending::<Result<(), PrfError>>(),
+        )
+        .await;
+
+        assert!(matches!(res, Err(PrfError::Cancelled { step: "test" })));
+    }
+
+    #[tokio::test]
+    async fn test_run_step_timeout() {
+        let res = run_step(
+            Some(Duration::from_millis(10)),
+            None,
+            "test",
+            futures::future::pending::<Result<(), PrfError>>(),
+        )
+        .await;
+
+        assert!(matches!(res, Err(PrfError::Timeout { step: "test" })));
+    }
+}
@@ -0,0 +1,211 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+};
+
+use tls_core::msgs::{
+    base::Payload,
+    enums::{ContentType, ProtocolVersion},
+    message::OpaqueMessage,
+};
+
+// Content type, protocol version and payload length.
+const HEADER_LEN: usize = 1 + 2 + 4;
+
+/// A FIFO queue of TLS records which spills to a temporary file.
+///
+/// Records are kept in memory until their payloads exceed the limit, after which further records
+/// are appended to an unnamed temporary file and read back in order once the records in memory
+/// are drained. The file is removed by the OS when the buffer is dropped.
+#[derive(Debug)]
+pub(crate) struct RecordBuffer {
+    /// Maximum number of payload bytes kept in memory, `None` to never spill.
+    limit: Option<usize>,
+    memory: VecDeque<OpaqueMessage>,
+    memory_bytes: usize,
+    spill: Option<Spill>,
+}
+
+#[derive(Debug)]
+struct Spill {
+    file: File,
+    /// Offset of the next record to read.
+    read_pos: u64,
+    /// Offset at which the next record is written.
+    write_pos: u64,
+    /// Number of records in the file which have not been read.
+    len: usize,
+}
+
+impl RecordBuffer {
+    /// Creates a new buffer keeping at most `limit` payload bytes in memory.
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            memory: VecDeque::new(),
+            memory_bytes: 0,
+            spill: None,
+        }
+    }
+
+    /// Returns the number of records in the buffer.
+    pub(crate) fn len(&self) -> usize {
+        self.memory.len() + self.spill.as_ref().map_or(0, |spill| spill.len)
+    }
+
+    /// Returns `true` if the buffer holds no records.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if any records are held in the temporary file.
+    pub(crate) fn is_spilled(&self) -> bool {
+        self.spill.as_ref().is_some_and(|spill| spill.len > 0)
+    }
+
+    /// Appends a record to the back of the buffer.
+    pub(crate) fn push_back(&mut self, msg: OpaqueMessage) -> std::io::Result<()> {
+        let len = msg.payload.0.len();
+        let fits = !self
+            .limit
+            .is_some_and(|limit| self.memory_bytes + len > limit);
+
+        // Once spilled, records stay in the file until it is drained to preserve their order.
+        if fits && !self.is_spilled() {
+            self.memory_bytes += len;
+            self.memory.push_back(msg);
+            return Ok(());
+        }
+
+        let spill = match &mut self.spill {
+            Some(spill) => spill,
+            spill @ None => spill.insert(Spill {
+                file: tempfile::tempfile()?,
+                read_pos: 0,
+                write_pos: 0,
+                len: 0,
+            }),
+        };
+
+        let payload_len = u32::try_from(len).map_err(|_| std::io::ErrorKind::InvalidInput)?;
+
+        spill.file.seek(SeekFrom::Start(spill.write_pos))?;
+        let mut writer = BufWriter::new(&mut spill.file);
+        writer.write_all(&[msg.typ.get_u8()])?;
+        writer.write_all(&msg.version.get_u16().to_be_bytes())?;
+        writer.write_all(&payload_len.to_be_bytes())?;
+        writer.write_all(&msg.payload.0)?;
+        writer.flush()?;
+        drop(writer);
+
+        spill.write_pos += (HEADER_LEN + len) as u64;
+        spill.len += 1;
+
+        Ok(())
+    }
+
+    /// Removes the record at the front of the buffer.
+    pub(crate) fn pop_front(&mut self) -> std::io::Result<Option<OpaqueMessage>> {
+        if let Some(msg) = self.memory.pop_front() {
+            self.memory_bytes -= msg.payload.0.len();
+            return Ok(Some(msg));
+        }
+
+        let Some(spill) = self.spill.as_mut().filter(|spill| spill.len > 0) else {
+            return Ok(None);
+        };
+
+        spill.file.seek(SeekFrom::Start(spill.read_pos))?;
+        let mut reader = BufReader::new(&mut spill.file);
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+
+        let typ = ContentType::from(header[0]);
+        let version = ProtocolVersion::from(u16::from_be_bytes([header[1], header[2]]));
+        let len = u32::from_be_bytes([header[3], header[4], header[5], header[6]]) as usize;
+
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload)?;
+
+        spill.read_pos += (HEADER_LEN + len) as u64;
+        spill.len -= 1;
+
+        // Reuse the space of the file once it is drained.
+        if spill.len == 0 {
+            spill.file.set_len(0)?;
+            spill.read_pos = 0;
+            spill.write_pos = 0;
+        }
+
+        Ok(Some(OpaqueMessage {
+            typ,
+            version,
+            payload: Payload::new(payload),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(i: u8, len: usize) -> OpaqueMessage {
+        OpaqueMessage {
+            typ: ContentType::ApplicationData,
+            version: ProtocolVersion::TLSv1_2,
+            payload: Payload::new(vec![i; len]),
+        }
+    }
+
+    fn assert_record(msg: Option<OpaqueMessage>, i: u8, len: usize) {
+        let msg = msg.unwrap();
+        assert_eq!(msg.typ, ContentType::ApplicationData);
+        assert_eq!(msg.version, ProtocolVersion::TLSv1_2);
+        assert_eq!(msg.payload.0, vec![i; len]);
+    }
+
+    #[test]
+    fn test_record_buffer_in_memory() {
+        let mut buffer = RecordBuffer::new(None);
+        for i in 0..4 {
+            buffer.push_back(record(i, 1024)).unwrap();
+        }
+
+        assert!(!buffer.is_spilled());
+        assert_eq!(buffer.len(), 4);
+
+        for i in 0..4 {
+            assert_record(buffer.pop_front().unwrap(), i, 1024);
+        }
+        assert!(buffer.is_empty());
+        assert!(buffer.pop_front().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_record_buffer_spills_in_order() {
+        let mut buffer = RecordBuffer::new(Some(2048));
+        for i in 0..8 {
+            buffer.push_back(record(i, 1024)).unwrap();
+        }
+
+        assert!(buffer.is_spilled());
+        assert_eq!(buffer.len(), 8);
+
+        // A record which fits in memory is still queued behind the spilled ones.
+        assert_record(buffer.pop_front().unwrap(), 0, 1024);
+        buffer.push_back(record(8, 16)).unwrap();
+
+        for i in 1..8 {
+            assert_record(buffer.pop_front().unwrap(), i, 1024);
+        }
+        assert_record(buffer.pop_front().unwrap(), 8, 16);
+        assert!(buffer.is_empty());
+        assert!(!buffer.is_spilled());
+
+        // The buffer keeps records in memory again once the file is drained.
+        buffer.push_back(record(9, 1024)).unwrap();
+        assert!(!buffer.is_spilled());
+        assert_record(buffer.pop_front().unwrap(), 9, 1024);
+    }
+}
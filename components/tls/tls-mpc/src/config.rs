@@ -1,4 +1,8 @@
+use std::time::Duration;
+
 use derive_builder::Builder;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use tokio_util::sync::CancellationToken;
 
 static DEFAULT_OPAQUE_TX_TRANSCRIPT_ID: &str = "opaque_tx";
 static DEFAULT_OPAQUE_RX_TRANSCRIPT_ID: &str = "opaque_rx";
@@ -82,6 +86,16 @@ pub struct MpcTlsCommonConfig {
     /// Whether the leader commits to the handshake data.
     #[builder(default = "true")]
     handshake_commit: bool,
+    /// Seed of the TLS randomness, i.e. the client random and the key share, for reproducing a
+    /// session in tests. Drawn from the OS if not set.
+    #[builder(setter(strip_option), default)]
+    rng_seed: Option<[u8; 32]>,
+    /// Timeout for each step of the PRF, `None` to disable.
+    #[builder(default)]
+    prf_step_timeout: Option<Duration>,
+    /// Token which aborts the PRF step in progress once cancelled.
+    #[builder(default)]
+    cancellation_token: Option<CancellationToken>,
 }
 
 impl MpcTlsCommonConfig {
@@ -114,6 +128,29 @@ impl MpcTlsCommonConfig {
     pub fn handshake_commit(&self) -> bool {
         self.handshake_commit
     }
+
+    /// Returns the seed of the TLS randomness, if set.
+    pub fn rng_seed(&self) -> Option<[u8; 32]> {
+        self.rng_seed
+    }
+
+    /// Returns the timeout for each step of the PRF.
+    pub fn prf_step_timeout(&self) -> Option<Duration> {
+        self.prf_step_timeout
+    }
+
+    /// Returns the token which aborts the PRF step in progress, if set.
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    /// Returns a generator of the TLS randomness.
+    pub(crate) fn rng(&self) -> ChaCha20Rng {
+        match self.rng_seed {
+            Some(seed) => ChaCha20Rng::from_seed(seed),
+            None => ChaCha20Rng::from_entropy(),
+        }
+    }
 }
 
 /// Configuration for the leader
@@ -140,6 +177,10 @@ impl MpcTlsLeaderConfig {
 #[derive(Debug, Clone, Builder)]
 pub struct MpcTlsFollowerConfig {
     common: MpcTlsCommonConfig,
+    /// Maximum number of bytes of received records buffered in memory for deferred decryption,
+    /// `None` to never spill. Further records are buffered in a temporary file.
+    #[builder(default)]
+    max_buffered_bytes: Option<usize>,
 }
 
 impl MpcTlsFollowerConfig {
@@ -152,4 +193,9 @@ impl MpcTlsFollowerConfig {
     pub fn common(&self) -> &MpcTlsCommonConfig {
         &self.common
     }
+
+    /// Returns the maximum number of bytes of received records buffered in memory.
+    pub fn max_buffered_bytes(&self) -> Option<usize> {
+        self.max_buffered_bytes
+    }
 }
@@ -1,4 +1,4 @@
-use std::{collections::VecDeque, future::Future, mem};
+use std::{future::Future, mem};
 
 use futures::{
     stream::{SplitSink, SplitStream},
@@ -10,6 +10,7 @@ use ludi::{Address, FuturesAddress};
 use mpz_core::hash::Hash;
 
 use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand_chacha::ChaCha20Rng;
 
 use aead::Aead;
 use hmac_sha256::Prf;
@@ -26,6 +27,7 @@ use tls_core::{
 };
 
 use crate::{
+    buffer::RecordBuffer,
     error::Kind,
     msg::{CloseConnection, Commit, MpcTlsFollowerMsg, MpcTlsMessage},
     record_layer::{Decrypter, Encrypter},
@@ -48,6 +50,8 @@ pub struct MpcTlsFollower {
     prf: Box<dyn Prf + Send>,
     encrypter: Encrypter,
     decrypter: Decrypter,
+    /// Generator of the TLS randomness.
+    rng: ChaCha20Rng,
 
     /// Whether the server has sent a CloseNotify alert.
     close_notify: bool,
@@ -115,6 +119,7 @@ impl MpcTlsFollower {
         );
 
         let (_sink, stream) = channel.split();
+        let rng = config.common().rng();
 
         Self {
             state: State::Init,
@@ -125,6 +130,7 @@ impl MpcTlsFollower {
             prf,
             encrypter,
             decrypter,
+            rng,
             close_notify: false,
             committed: false,
         }
@@ -270,7 +276,7 @@ impl MpcTlsFollower {
 
         _ = self
             .ke
-            .compute_client_key(p256::SecretKey::random(&mut rand::rngs::OsRng))
+            .compute_client_key(p256::SecretKey::random(&mut self.rng))
             .await?;
 
         self.state = State::ClientKey;
@@ -356,7 +362,7 @@ impl MpcTlsFollower {
         self.state = State::Active(Active {
             handshake_commitment,
             server_key,
-            buffer: Default::default(),
+            buffer: RecordBuffer::new(self.config.max_buffered_bytes()),
         });
 
         Ok(())
@@ -445,7 +451,7 @@ impl MpcTlsFollower {
             typ: ContentType::ApplicationData,
             version: ProtocolVersion::TLSv1_2,
             payload: Payload::new(payload),
-        });
+        })?;
 
         Ok(())
     }
@@ -507,7 +513,7 @@ impl MpcTlsFollower {
     async fn decrypt_message(&mut self) -> Result<(), MpcTlsError> {
         let Active { buffer, .. } = self.state.try_as_active_mut()?;
 
-        let msg = buffer.pop_front().ok_or(MpcTlsError::new(
+        let msg = buffer.pop_front()?.ok_or(MpcTlsError::new(
             Kind::PeerMisbehaved,
             "attempted to decrypt message when no messages are committed",
         ))?;
@@ -693,7 +699,7 @@ mod state {
         ///
         /// The follower must verify the authenticity of these messages with AEAD verification
         /// (i.e. by verifying the authentication tag).
-        pub(super) buffer: VecDeque<OpaqueMessage>,
+        pub(super) buffer: RecordBuffer,
     }
 
     #[derive(Debug)]
@@ -11,6 +11,8 @@ use hmac_sha256::Prf;
 use ke::KeyExchange;
 
 use p256::SecretKey;
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
 use tls_backend::{
     Backend, BackendError, BackendNotifier, BackendNotify, DecryptMode, EncryptMode,
 };
@@ -56,6 +58,8 @@ pub struct MpcTlsLeader {
     prf: Box<dyn Prf + Send>,
     encrypter: Encrypter,
     decrypter: Decrypter,
+    /// Generator of the TLS randomness.
+    rng: ChaCha20Rng,
 
     /// When set, notifies the backend that there are TLS messages which need to be decrypted.
     notifier: BackendNotifier,
@@ -102,15 +106,18 @@ impl MpcTlsLeader {
             config.common().rx_config().id().to_string(),
             config.common().rx_config().opaque_id().to_string(),
         );
+        let mut rng = config.common().rng();
+        let state = State::new(Random(rng.gen()));
 
         Self {
             config,
             channel,
-            state: State::default(),
+            state,
             ke,
             prf,
             encrypter,
             decrypter,
+            rng,
             notifier: BackendNotifier::new(),
             is_decrypting: true,
             buffer: VecDeque::new(),
@@ -464,7 +471,7 @@ impl Backend for MpcTlsLeader {
 
         let pk = self
             .ke
-            .compute_client_key(SecretKey::random(&mut rand::rngs::OsRng))
+            .compute_client_key(SecretKey::random(&mut self.rng))
             .await
             .map_err(MpcTlsError::from)?
             .expect("client key is returned as leader");
@@ -536,9 +543,16 @@ impl Backend for MpcTlsLeader {
     }
 
     async fn get_server_finished_vd(&mut self, hash: Vec<u8>) -> Result<Vec<u8>, BackendError> {
-        let hash: [u8; 32] = hash
-            .try_into()
-            .map_err(|_| MpcTlsError::other("server finished handshake hash is not 32 bytes"))?;
+        // Check the hash before notifying the follower, which would otherwise wait for a
+        // computation the leader can't perform.
+        let expected_len = self.prf.hash().handshake_hash_len();
+        if hash.len() != expected_len {
+            return Err(MpcTlsError::other(format!(
+                "server finished handshake hash is {} bytes, expected {expected_len}",
+                hash.len()
+            ))
+            .into());
+        }
 
         self.channel
             .send(MpcTlsMessage::ServerFinishedVd(ServerFinishedVd))
@@ -547,7 +561,7 @@ impl Backend for MpcTlsLeader {
 
         let vd = self
             .prf
-            .compute_server_finished_vd_private(hash)
+            .compute_server_finished_vd_private(&hash)
             .await
             .map_err(MpcTlsError::from)?;
 
@@ -555,9 +569,16 @@ impl Backend for MpcTlsLeader {
     }
 
     async fn get_client_finished_vd(&mut self, hash: Vec<u8>) -> Result<Vec<u8>, BackendError> {
-        let hash: [u8; 32] = hash
-            .try_into()
-            .map_err(|_| MpcTlsError::other("client finished handshake hash is not 32 bytes"))?;
+        // Check the hash before notifying the follower, which would otherwise wait for a
+        // computation the leader can't perform.
+        let expected_len = self.prf.hash().handshake_hash_len();
+        if hash.len() != expected_len {
+            return Err(MpcTlsError::other(format!(
+                "client finished handshake hash is {} bytes, expected {expected_len}",
+                hash.len()
+            ))
+            .into());
+        }
 
         self.channel
             .send(MpcTlsMessage::ClientFinishedVd(ClientFinishedVd))
@@ -566,7 +587,7 @@ impl Backend for MpcTlsLeader {
 
         let vd = self
             .prf
-            .compute_client_finished_vd_private(hash)
+            .compute_client_finished_vd_private(&hash)
             .await
             .map_err(MpcTlsError::from)?;
 
@@ -785,12 +806,12 @@ mod state {
         }
     }
 
-    impl Default for State {
-        fn default() -> Self {
+    impl State {
+        pub(super) fn new(client_random: Random) -> Self {
             State::Ke(Ke {
                 protocol_version: None,
                 cipher_suite: None,
-                client_random: Random::new().expect("rng is available"),
+                client_random,
                 server_random: None,
                 server_cert_details: None,
                 server_public_key: None,
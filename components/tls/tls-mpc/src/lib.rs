@@ -7,6 +7,7 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+pub(crate) mod buffer;
 mod config;
 pub(crate) mod error;
 pub(crate) mod follower;
@@ -86,10 +86,27 @@ where
         TlsRole::Leader => prf::Role::Leader,
         TlsRole::Follower => prf::Role::Follower,
     };
-    let prf = prf::MpcPrf::new(
+    #[cfg(not(feature = "insecure-prf"))]
+    let prf = {
+        let mut prf = prf::MpcPrf::new(
+            prf::PrfConfig::builder()
+                .role(prf_role)
+                .step_timeout(config.prf_step_timeout())
+                .build()
+                .unwrap(),
+            vm.new_thread("prf/0").await?,
+            vm.new_thread("prf/1").await?,
+        );
+        if let Some(token) = config.cancellation_token() {
+            prf.set_cancellation_token(token.clone());
+        }
+        prf
+    };
+    // Computes the PRF in the clear, revealing the PMS to the leader. Only for testing.
+    #[cfg(feature = "insecure-prf")]
+    let prf = prf::PlainPrf::new(
         prf::PrfConfig::builder().role(prf_role).build().unwrap(),
         vm.new_thread("prf/0").await?,
-        vm.new_thread("prf/1").await?,
     );
 
     // Encrypter
@@ -0,0 +1,506 @@
+//! Fault injection into a transport, for testing the resilience of the protocol.
+//!
+//! A [ChaosStream] wraps the socket of a party, eg. before attaching the multiplexer, and injects
+//! faults into its reads and writes. The faults follow a schedule derived from a seed, so a
+//! failure found with one seed can be reproduced by running again with the same seed.
+//!
+//! Writes can be delayed, dropped, reordered, partially performed or fail with a reset connection.
+//! Reads can be delayed, partially performed or fail with a reset connection.
+
+use std::{
+    future::Future,
+    io::{Error, ErrorKind},
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use futures::{AsyncRead, AsyncWrite};
+use futures_timer::Delay;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+/// Configuration of the faults injected by a [ChaosStream].
+///
+/// Each rate is the probability that an operation is affected by the fault, no fault is injected
+/// by default.
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// Seed of the schedule.
+    pub seed: u64,
+    /// Probability that an operation is delayed.
+    pub delay_rate: f64,
+    /// Maximum delay of an operation.
+    pub max_delay: Duration,
+    /// Probability that a write is dropped silently.
+    pub drop_rate: f64,
+    /// Probability that a write is held back until after the next write, or until the stream is
+    /// flushed or closed.
+    pub reorder_rate: f64,
+    /// Probability that only a part of a read or write is performed.
+    pub partial_rate: f64,
+    /// Probability that an operation resets the connection, failing it and all later operations.
+    pub disconnect_rate: f64,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            delay_rate: 0.0,
+            max_delay: Duration::from_millis(100),
+            drop_rate: 0.0,
+            reorder_rate: 0.0,
+            partial_rate: 0.0,
+            disconnect_rate: 0.0,
+        }
+    }
+}
+
+/// A fault injected into an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The operation is delayed.
+    Delay(Duration),
+    /// The write is reported as successful but is not performed.
+    Drop,
+    /// The write is held back until after the next write, or until the stream is flushed or
+    /// closed.
+    Reorder,
+    /// Only the given number of bytes is read or written.
+    Partial(usize),
+    /// The connection is reset.
+    Disconnect,
+}
+
+/// Direction of an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Read,
+    Write,
+}
+
+/// A schedule of faults, deterministic for a given seed and sequence of operations.
+#[derive(Debug)]
+struct Schedule {
+    config: ChaosConfig,
+    rng: ChaCha12Rng,
+}
+
+impl Schedule {
+    fn new(config: ChaosConfig, op: Op) -> Self {
+        let mut rng = ChaCha12Rng::seed_from_u64(config.seed);
+        // Reads and writes draw from separate streams, so that the faults of one don't depend on
+        // how the operations of the other are interleaved.
+        rng.set_stream(op as u64);
+
+        Self { config, rng }
+    }
+
+    /// Returns the fault of the next operation of `len` bytes, if any.
+    fn next(&mut self, op: Op, len: usize) -> Option<Fault> {
+        let config = &self.config;
+        let mut roll: f64 = self.rng.gen();
+
+        let mut hit = |rate: f64| {
+            if roll < rate {
+                true
+            } else {
+                roll -= rate;
+                false
+            }
+        };
+
+        if hit(config.disconnect_rate) {
+            Some(Fault::Disconnect)
+        } else if op == Op::Write && hit(config.drop_rate) {
+            Some(Fault::Drop)
+        } else if op == Op::Write && hit(config.reorder_rate) {
+            Some(Fault::Reorder)
+        } else if hit(config.partial_rate) {
+            (len > 1).then(|| Fault::Partial(self.rng.gen_range(1..len)))
+        } else if hit(config.delay_rate) {
+            let max_delay = config.max_delay.as_micros() as u64;
+            Some(Fault::Delay(Duration::from_micros(
+                self.rng.gen_range(0..=max_delay),
+            )))
+        } else {
+            None
+        }
+    }
+}
+
+/// The state of an operation which is in progress.
+#[derive(Debug)]
+enum Pending {
+    /// No operation is in progress.
+    Idle,
+    /// The fault of the operation is decided.
+    Decided(Option<Fault>),
+}
+
+/// A stream which injects faults into the reads and writes of the wrapped stream.
+pub struct ChaosStream<S> {
+    inner: S,
+    reads: Schedule,
+    writes: Schedule,
+    read: Pending,
+    write: Pending,
+    read_delay: Option<Delay>,
+    write_delay: Option<Delay>,
+    /// Bytes accepted from the caller but not yet written to the wrapped stream.
+    buffered: Vec<u8>,
+    /// A write held back by [Fault::Reorder].
+    held: Option<Vec<u8>>,
+    disconnected: bool,
+    faults: Vec<Fault>,
+}
+
+impl<S> std::fmt::Debug for ChaosStream<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ChaosStream")
+            .field("inner", &"{{ ... }}")
+            .field("buffered", &self.buffered.len())
+            .field("held", &self.held.as_ref().map(Vec::len))
+            .field("disconnected", &self.disconnected)
+            .field("faults", &self.faults)
+            .finish()
+    }
+}
+
+impl<S> ChaosStream<S> {
+    /// Wraps a stream, injecting faults according to the provided configuration.
+    pub fn new(inner: S, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            reads: Schedule::new(config.clone(), Op::Read),
+            writes: Schedule::new(config, Op::Write),
+            read: Pending::Idle,
+            write: Pending::Idle,
+            read_delay: None,
+            write_delay: None,
+            buffered: Vec::new(),
+            held: None,
+            disconnected: false,
+            faults: Vec::new(),
+        }
+    }
+
+    /// Returns the faults injected so far, in order.
+    pub fn faults(&self) -> &[Fault] {
+        &self.faults
+    }
+
+    /// Returns the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Decides the fault of the operation, unless it is already in progress.
+    fn decide(&mut self, op: Op, len: usize) -> Option<Fault> {
+        let (pending, schedule) = match op {
+            Op::Read => (&mut self.read, &mut self.reads),
+            Op::Write => (&mut self.write, &mut self.writes),
+        };
+
+        match pending {
+            Pending::Decided(fault) => *fault,
+            Pending::Idle => {
+                let fault = schedule.next(op, len);
+                *pending = Pending::Decided(fault);
+                if let Some(fault) = fault {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(?op, ?fault, "injecting fault");
+                    self.faults.push(fault);
+                }
+                fault
+            }
+        }
+    }
+}
+
+fn reset() -> Error {
+    Error::new(
+        ErrorKind::ConnectionReset,
+        "connection reset by chaos layer",
+    )
+}
+
+/// Waits for the delay, creating it on the first poll.
+fn poll_delay(delay: &mut Option<Delay>, duration: Duration, cx: &mut Context<'_>) -> Poll<()> {
+    ready!(Pin::new(delay.get_or_insert_with(|| Delay::new(duration))).poll(cx));
+    *delay = None;
+    Poll::Ready(())
+}
+
+impl<S: AsyncWrite + Unpin> ChaosStream<S> {
+    /// Writes the buffered bytes to the wrapped stream.
+    fn poll_write_buffered(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while !self.buffered.is_empty() {
+            let n = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.buffered))?;
+            if n == 0 {
+                return Poll::Ready(Err(ErrorKind::WriteZero.into()));
+            }
+            self.buffered.drain(..n);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for ChaosStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        if this.disconnected {
+            return Poll::Ready(Err(reset()));
+        }
+
+        let len = match this.decide(Op::Read, buf.len()) {
+            Some(Fault::Disconnect) => {
+                this.disconnected = true;
+                this.read = Pending::Idle;
+                return Poll::Ready(Err(reset()));
+            }
+            Some(Fault::Delay(duration)) => {
+                ready!(poll_delay(&mut this.read_delay, duration, cx));
+                this.read = Pending::Decided(None);
+                buf.len()
+            }
+            Some(Fault::Partial(n)) => n.min(buf.len()),
+            _ => buf.len(),
+        };
+
+        let poll = Pin::new(&mut this.inner).poll_read(cx, &mut buf[..len]);
+        if poll.is_ready() {
+            this.read = Pending::Idle;
+        }
+
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for ChaosStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        if this.disconnected {
+            return Poll::Ready(Err(reset()));
+        }
+        ready!(this.poll_write_buffered(cx))?;
+
+        let len = match this.decide(Op::Write, buf.len()) {
+            Some(Fault::Disconnect) => {
+                this.disconnected = true;
+                this.write = Pending::Idle;
+                return Poll::Ready(Err(reset()));
+            }
+            Some(Fault::Delay(duration)) => {
+                ready!(poll_delay(&mut this.write_delay, duration, cx));
+                this.write = Pending::Decided(None);
+                buf.len()
+            }
+            Some(Fault::Drop) => {
+                this.write = Pending::Idle;
+                return Poll::Ready(Ok(buf.len()));
+            }
+            Some(Fault::Reorder) if this.held.is_none() => {
+                this.held = Some(buf.to_vec());
+                this.write = Pending::Idle;
+                return Poll::Ready(Ok(buf.len()));
+            }
+            Some(Fault::Partial(n)) => n.min(buf.len()),
+            _ => buf.len(),
+        };
+
+        // Sends the held back write after this one.
+        if let Some(held) = this.held.take() {
+            this.buffered.extend_from_slice(&buf[..len]);
+            this.buffered.extend_from_slice(&held);
+            this.write = Pending::Idle;
+            return Poll::Ready(Ok(len));
+        }
+
+        let poll = Pin::new(&mut this.inner).poll_write(cx, &buf[..len]);
+        if poll.is_ready() {
+            this.write = Pending::Idle;
+        }
+
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        if this.disconnected {
+            return Poll::Ready(Err(reset()));
+        }
+        // A held back write is only reordered with later writes, a flush sends it, as the peer
+        // may wait for it before writing anything else.
+        if let Some(held) = this.held.take() {
+            this.buffered.extend_from_slice(&held);
+        }
+        ready!(this.poll_write_buffered(cx))?;
+
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = &mut *self;
+        if this.disconnected {
+            return Poll::Ready(Err(reset()));
+        }
+        if let Some(held) = this.held.take() {
+            this.buffered.extend_from_slice(&held);
+        }
+        ready!(this.poll_write_buffered(cx))?;
+
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{io::Cursor, AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn schedule(config: ChaosConfig, n: usize) -> Vec<Option<Fault>> {
+        let mut schedule = Schedule::new(config, Op::Write);
+        (0..n).map(|_| schedule.next(Op::Write, 64)).collect()
+    }
+
+    fn write_chunks(config: ChaosConfig, chunks: &[&[u8]]) -> (Vec<u8>, Vec<Fault>) {
+        let mut stream = ChaosStream::new(Cursor::new(Vec::new()), config);
+        futures::executor::block_on(async {
+            for chunk in chunks {
+                stream.write_all(chunk).await.unwrap();
+            }
+            stream.flush().await.unwrap();
+        });
+
+        let faults = stream.faults().to_vec();
+        (stream.into_inner().into_inner(), faults)
+    }
+
+    #[test]
+    fn test_schedule_is_deterministic() {
+        let config = ChaosConfig {
+            seed: 1,
+            delay_rate: 0.2,
+            drop_rate: 0.2,
+            reorder_rate: 0.2,
+            partial_rate: 0.2,
+            ..Default::default()
+        };
+
+        let faults = schedule(config.clone(), 100);
+        assert_eq!(faults, schedule(config.clone(), 100));
+        assert_ne!(faults, schedule(ChaosConfig { seed: 2, ..config }, 100));
+        assert!(faults.iter().any(Option::is_some));
+        assert!(faults.iter().any(Option::is_none));
+    }
+
+    #[test]
+    fn test_no_faults() {
+        let (written, faults) = write_chunks(ChaosConfig::default(), &[b"hello", b"world"]);
+
+        assert_eq!(written, b"helloworld");
+        assert!(faults.is_empty());
+    }
+
+    #[test]
+    fn test_drop() {
+        let config = ChaosConfig {
+            drop_rate: 1.0,
+            ..Default::default()
+        };
+        let (written, faults) = write_chunks(config, &[b"hello"]);
+
+        assert!(written.is_empty());
+        assert_eq!(faults, vec![Fault::Drop]);
+    }
+
+    #[test]
+    fn test_reorder() {
+        let config = ChaosConfig {
+            reorder_rate: 1.0,
+            ..Default::default()
+        };
+        let (written, _) = write_chunks(config, &[b"a", b"b", b"c"]);
+
+        // `a` is sent after `b`, `c` is held back until the flush.
+        assert_eq!(written, b"bac");
+    }
+
+    #[test]
+    fn test_reorder_close() {
+        let config = ChaosConfig {
+            reorder_rate: 1.0,
+            ..Default::default()
+        };
+        let mut stream = ChaosStream::new(Cursor::new(Vec::new()), config);
+        futures::executor::block_on(async {
+            stream.write_all(b"last").await.unwrap();
+            stream.close().await.unwrap();
+        });
+
+        assert_eq!(stream.into_inner().into_inner(), b"last");
+    }
+
+    #[test]
+    fn test_partial_and_delay() {
+        let config = ChaosConfig {
+            seed: 3,
+            partial_rate: 0.5,
+            delay_rate: 0.5,
+            max_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+        let (written, faults) = write_chunks(config, &[b"hello", b"world"]);
+
+        assert_eq!(written, b"helloworld");
+        assert!(!faults.is_empty());
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let config = ChaosConfig {
+            disconnect_rate: 1.0,
+            ..Default::default()
+        };
+        let mut stream = ChaosStream::new(Cursor::new(b"hello".to_vec()), config);
+
+        futures::executor::block_on(async {
+            let err = stream.write_all(b"hello").await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+
+            let mut buf = [0u8; 5];
+            let err = stream.read_exact(&mut buf).await.unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::ConnectionReset);
+        });
+    }
+
+    #[test]
+    fn test_partial_read() {
+        let config = ChaosConfig {
+            partial_rate: 1.0,
+            ..Default::default()
+        };
+        let mut stream = ChaosStream::new(Cursor::new(b"hello world".to_vec()), config);
+
+        let mut read = Vec::new();
+        futures::executor::block_on(stream.read_to_end(&mut read)).unwrap();
+
+        assert_eq!(read, b"hello world");
+        assert!(stream
+            .faults()
+            .iter()
+            .all(|fault| matches!(fault, Fault::Partial(_))));
+    }
+}
@@ -25,6 +25,20 @@ use utils_aio::mux::{MuxStream, MuxerError};
 
 pub use yamux;
 
+#[cfg(feature = "chaos")]
+pub mod chaos;
+#[cfg(feature = "recorder")]
+pub mod recorder;
+#[cfg(feature = "recorder")]
+pub mod replay;
+
+/// A stream opened by [UidYamuxControl].
+#[cfg(not(feature = "recorder"))]
+pub type UidStream = yamux::Stream;
+/// A stream opened by [UidYamuxControl], recorded if a recorder is set.
+#[cfg(feature = "recorder")]
+pub type UidStream = recorder::RecordedStream<yamux::Stream>;
+
 #[derive(Debug, Default)]
 struct MuxState {
     stream_ids: HashSet<String>,
@@ -38,6 +52,8 @@ pub struct UidYamux<T> {
     conn: Option<yamux::ControlledConnection<T>>,
     control: yamux::Control,
     state: Arc<Mutex<MuxState>>,
+    #[cfg(feature = "recorder")]
+    recorder: Option<recorder::Recorder>,
 }
 
 impl<T> std::fmt::Debug for UidYamux<T> {
@@ -57,6 +73,8 @@ pub struct UidYamuxControl {
     mode: yamux::Mode,
     control: yamux::Control,
     state: Arc<Mutex<MuxState>>,
+    #[cfg(feature = "recorder")]
+    recorder: Option<recorder::Recorder>,
 }
 
 impl UidYamuxControl {
@@ -86,9 +104,18 @@ where
             conn: Some(conn),
             control,
             state: Arc::new(Mutex::new(MuxState::default())),
+            #[cfg(feature = "recorder")]
+            recorder: None,
         }
     }
 
+    /// Sets a recorder which captures the traffic of all streams opened by controls created
+    /// afterwards.
+    #[cfg(feature = "recorder")]
+    pub fn set_recorder(&mut self, recorder: recorder::Recorder) {
+        self.recorder = Some(recorder);
+    }
+
     /// Runs the muxer.
     ///
     /// This method will poll the underlying connection for new streams and
@@ -150,6 +177,8 @@ where
             mode: self.mode,
             control: self.control.clone(),
             state: self.state.clone(),
+            #[cfg(feature = "recorder")]
+            recorder: self.recorder.clone(),
         }
     }
 }
@@ -193,15 +222,8 @@ async fn read_stream_id<T: AsyncRead + Unpin>(stream: &mut T) -> Result<String,
     Ok(String::from_utf8_lossy(&id).to_string())
 }
 
-#[async_trait]
-impl MuxStream for UidYamuxControl {
-    type Stream = yamux::Stream;
-
-    #[cfg_attr(
-        feature = "tracing",
-        tracing::instrument(level = "info", skip(self), err)
-    )]
-    async fn get_stream(&mut self, id: &str) -> Result<Self::Stream, MuxerError> {
+impl UidYamuxControl {
+    async fn open_stream(&mut self, id: &str) -> Result<yamux::Stream, MuxerError> {
         match self.mode {
             yamux::Mode::Client => {
                 if !self.state.lock().unwrap().stream_ids.insert(id.to_string()) {
@@ -244,6 +266,27 @@ impl MuxStream for UidYamuxControl {
     }
 }
 
+#[async_trait]
+impl MuxStream for UidYamuxControl {
+    type Stream = UidStream;
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "info", skip(self), err)
+    )]
+    async fn get_stream(&mut self, id: &str) -> Result<Self::Stream, MuxerError> {
+        let stream = self.open_stream(id).await?;
+
+        #[cfg(feature = "recorder")]
+        let stream = match &self.recorder {
+            Some(recorder) => recorder.wrap(id, stream),
+            None => recorder::RecordedStream::passthrough(stream),
+        };
+
+        Ok(stream)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use futures::{AsyncReadExt, AsyncWriteExt, FutureExt};
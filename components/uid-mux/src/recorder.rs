@@ -0,0 +1,275 @@
+//! Recording of the frames sent and received on multiplexed streams.
+//!
+//! A [Recorder] set on a [UidYamux](crate::UidYamux) captures every chunk of bytes written to or
+//! read from its streams, so that a session between two parties can be diagnosed offline.
+//!
+//! Each chunk is written as one line:
+//!
+//! ```text
+//! <micros since start> <stream id> <send|recv> <hex payload or len=<length>>
+//! ```
+//!
+//! Payloads are stripped by default, see [Recorder::strip_payload].
+
+use std::{
+    fs::File,
+    io::{LineWriter, Write},
+    path::Path,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use futures::{AsyncRead, AsyncWrite};
+
+/// Direction of a recorded chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Send,
+    Recv,
+}
+
+impl Direction {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Send => "send",
+            Direction::Recv => "recv",
+        }
+    }
+}
+
+/// A recorder of stream frames, cheap to clone.
+#[derive(Clone)]
+pub struct Recorder {
+    sink: Arc<Mutex<Box<dyn Write + Send>>>,
+    start: Instant,
+    strip_payload: bool,
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("sink", &"{{ ... }}")
+            .field("start", &self.start)
+            .field("strip_payload", &self.strip_payload)
+            .finish()
+    }
+}
+
+impl Recorder {
+    /// Creates a new recorder which writes to the provided sink, recording only the length of
+    /// payloads.
+    pub fn new(sink: impl Write + Send + 'static) -> Self {
+        Self {
+            sink: Arc::new(Mutex::new(Box::new(sink))),
+            start: Instant::now(),
+            strip_payload: true,
+        }
+    }
+
+    /// Creates a new recorder which writes to a file at the provided path, truncating it.
+    pub fn to_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::new(LineWriter::new(File::create(path)?)))
+    }
+
+    /// Sets whether to strip the payloads, recording only their length. Defaults to `true`.
+    ///
+    /// Streams carry secret material such as garbled circuit labels and OT messages, so payloads
+    /// should only be kept if the recording stays on a trusted machine. Replaying a recording
+    /// requires its payloads.
+    pub fn strip_payload(mut self, strip_payload: bool) -> Self {
+        self.strip_payload = strip_payload;
+        self
+    }
+
+    /// Wraps a stream so that all chunks written to or read from it are recorded.
+    pub fn wrap<S>(&self, id: &str, stream: S) -> RecordedStream<S> {
+        RecordedStream {
+            id: id.to_string(),
+            inner: stream,
+            recorder: Some(self.clone()),
+        }
+    }
+
+    fn record(&self, id: &str, direction: Direction, payload: &[u8]) {
+        let mut line = format!(
+            "{} {} {} ",
+            self.start.elapsed().as_micros(),
+            id,
+            direction.as_str()
+        );
+
+        if self.strip_payload {
+            line.push_str(&format!("len={}", payload.len()));
+        } else {
+            payload
+                .iter()
+                .for_each(|byte| line.push_str(&format!("{byte:02x}")));
+        }
+        line.push('\n');
+
+        // Recording is best-effort, it must never interfere with the protocol, so a sink poisoned
+        // by a panicking writer is still written to.
+        _ = self
+            .sink
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .write_all(line.as_bytes());
+    }
+}
+
+/// A sink which writes to a shared buffer, so that a recording can be read back in tests.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug, Clone, Default)]
+pub struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+#[cfg(any(test, feature = "test-utils"))]
+impl SharedBuf {
+    /// Returns the recording written so far.
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A stream whose traffic is captured by a [Recorder].
+#[derive(Debug)]
+pub struct RecordedStream<S> {
+    id: String,
+    inner: S,
+    /// The recorder, `None` if recording is disabled.
+    recorder: Option<Recorder>,
+}
+
+impl<S> RecordedStream<S> {
+    /// Wraps a stream without recording its traffic.
+    pub(crate) fn passthrough(stream: S) -> Self {
+        Self {
+            id: String::new(),
+            inner: stream,
+            recorder: None,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for RecordedStream<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            if let (Some(recorder), true) = (&this.recorder, n > 0) {
+                recorder.record(&this.id, Direction::Recv, &buf[..n]);
+            }
+        }
+
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for RecordedStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(Ok(n)) = poll {
+            if let (Some(recorder), true) = (&this.recorder, n > 0) {
+                recorder.record(&this.id, Direction::Send, &buf[..n]);
+            }
+        }
+
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{io::Cursor, AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    fn lines(buf: &SharedBuf) -> Vec<Vec<String>> {
+        buf.contents()
+            .lines()
+            .map(|line| line.split(' ').skip(1).map(String::from).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_recorder() {
+        let buf = SharedBuf::default();
+        let recorder = Recorder::new(buf.clone()).strip_payload(false);
+
+        let mut stream = recorder.wrap("test", Cursor::new(vec![0xde, 0xad]));
+
+        futures::executor::block_on(async {
+            let mut read = [0u8; 2];
+            stream.read_exact(&mut read).await.unwrap();
+            stream.write_all(&[0xbe, 0xef]).await.unwrap();
+        });
+
+        assert_eq!(
+            lines(&buf),
+            vec![vec!["test", "recv", "dead"], vec!["test", "send", "beef"]]
+        );
+    }
+
+    #[test]
+    fn test_recorder_strip_payload() {
+        let buf = SharedBuf::default();
+        let recorder = Recorder::new(buf.clone());
+
+        let mut stream = recorder.wrap("test", Cursor::new(Vec::new()));
+
+        futures::executor::block_on(stream.write_all(&[1, 2, 3])).unwrap();
+
+        assert_eq!(lines(&buf), vec![vec!["test", "send", "len=3"]]);
+    }
+
+    #[test]
+    fn test_recorder_poisoned_sink() {
+        let buf = SharedBuf::default();
+        let recorder = Recorder::new(buf.clone());
+
+        // Poison the sink by panicking while holding its lock
+        let sink = recorder.sink.clone();
+        _ = std::thread::spawn(move || {
+            let _guard = sink.lock().unwrap();
+            panic!("poison the sink");
+        })
+        .join();
+        assert!(recorder.sink.is_poisoned());
+
+        let mut stream = recorder.wrap("test", Cursor::new(Vec::new()));
+        futures::executor::block_on(stream.write_all(&[1, 2, 3])).unwrap();
+
+        assert_eq!(lines(&buf), vec![vec!["test", "send", "len=3"]]);
+    }
+}
@@ -0,0 +1,438 @@
+//! Replay of the frames captured by a [Recorder](crate::recorder::Recorder).
+//!
+//! A [ReplayMux] stands in for one party of a recorded session. Each stream it opens yields the
+//! bytes which the party received on it, and checks that the bytes written to it are the ones the
+//! party sent. Driving a protocol with a [ReplayMux] reproduces a session offline, turning a
+//! failure seen between two live parties into a deterministic test. Writes which diverge from the
+//! recording fail with an [InvalidData](std::io::ErrorKind::InvalidData) error naming the stream
+//! and offset.
+//!
+//! The party must draw the same randomness as in the recorded session, eg. from a fixed seed,
+//! otherwise its writes diverge at the first random value. Recordings with stripped payloads can
+//! not be replayed.
+//!
+//! [ReplayMux::finished] returns a future which plays the role of the muxer future of a live
+//! session: it resolves once every recorded stream was opened and fully replayed, and fails if a
+//! stream is dropped before that.
+
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use async_trait::async_trait;
+use futures::{AsyncRead, AsyncWrite};
+use utils_aio::mux::{MuxStream, MuxerError};
+
+/// An error that can occur while parsing a recording.
+#[derive(Debug, thiserror::Error)]
+#[error("invalid recording at line {line}: {reason}")]
+pub struct ParseError {
+    line: usize,
+    reason: &'static str,
+}
+
+/// The traffic of one stream.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct StreamData {
+    sent: Vec<u8>,
+    received: Vec<u8>,
+}
+
+/// A recording of the traffic of all streams of one party.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Recording {
+    streams: HashMap<String, StreamData>,
+}
+
+impl Recording {
+    /// Parses a recording written by a [Recorder](crate::recorder::Recorder).
+    pub fn parse(recording: &str) -> Result<Self, ParseError> {
+        let mut streams: HashMap<String, StreamData> = HashMap::new();
+        for (idx, line) in recording.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let err = |reason| ParseError {
+                line: idx + 1,
+                reason,
+            };
+
+            let mut parts = line.split(' ');
+            let (Some(_), Some(id), Some(direction), Some(payload), None) = (
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                parts.next(),
+            ) else {
+                return Err(err("expected 4 fields"));
+            };
+
+            if payload.starts_with("len=") {
+                return Err(err(
+                    "payload is stripped, record with `strip_payload(false)` to replay",
+                ));
+            }
+
+            let payload = decode_hex(payload).ok_or_else(|| err("payload is not hex"))?;
+            let stream = streams.entry(id.to_string()).or_default();
+            match direction {
+                "send" => stream.sent.extend_from_slice(&payload),
+                "recv" => stream.received.extend_from_slice(&payload),
+                _ => return Err(err("direction is neither send nor recv")),
+            }
+        }
+
+        Ok(Self { streams })
+    }
+
+    /// Returns the ids of the recorded streams.
+    pub fn stream_ids(&self) -> impl Iterator<Item = &str> {
+        self.streams.keys().map(String::as_str)
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// The state shared by a [ReplayMux] and its streams.
+#[derive(Debug, Default)]
+struct ReplayState {
+    /// The streams which were not opened yet.
+    unopened: HashMap<String, StreamData>,
+    /// The number of opened streams which were not fully replayed yet.
+    unfinished: usize,
+    /// The first stream dropped before it was fully replayed.
+    error: Option<String>,
+    waker: Option<Waker>,
+}
+
+impl ReplayState {
+    fn wake(&mut self) {
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A muxer which replays a [Recording], cheap to clone.
+#[derive(Debug, Clone)]
+pub struct ReplayMux {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayMux {
+    /// Creates a muxer replaying the provided recording.
+    pub fn new(recording: Recording) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReplayState {
+                unopened: recording.streams,
+                ..Default::default()
+            })),
+        }
+    }
+
+    /// Returns a future which resolves once every recorded stream was opened and fully replayed.
+    pub fn finished(&self) -> ReplayFinished {
+        ReplayFinished {
+            state: self.state.clone(),
+        }
+    }
+}
+
+/// A future which resolves once a replay is complete, see [ReplayMux::finished].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ReplayFinished {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl std::future::Future for ReplayFinished {
+    type Output = Result<(), MuxerError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock().unwrap();
+
+        if let Some(error) = &state.error {
+            return Poll::Ready(Err(MuxerError::InternalError(error.clone())));
+        }
+
+        if state.unopened.is_empty() && state.unfinished == 0 {
+            return Poll::Ready(Ok(()));
+        }
+
+        state.waker = Some(cx.waker().clone());
+
+        Poll::Pending
+    }
+}
+
+#[async_trait]
+impl MuxStream for ReplayMux {
+    type Stream = ReplayStream;
+
+    async fn get_stream(&mut self, id: &str) -> Result<Self::Stream, MuxerError> {
+        // Each recorded stream can be opened once, like a live one.
+        let data = {
+            let mut state = self.state.lock().unwrap();
+            let data = state.unopened.remove(id).ok_or_else(|| {
+                MuxerError::InternalError(format!("stream {id} is not in the recording"))
+            })?;
+            state.unfinished += 1;
+            data
+        };
+
+        let mut stream = ReplayStream {
+            id: id.to_string(),
+            data,
+            read_pos: 0,
+            write_pos: 0,
+            finished: false,
+            state: self.state.clone(),
+        };
+        // Streams without recorded traffic are replayed as soon as they are opened.
+        stream.update_finished();
+
+        Ok(stream)
+    }
+}
+
+/// A stream replaying the recorded traffic of one stream.
+#[derive(Debug)]
+pub struct ReplayStream {
+    id: String,
+    data: StreamData,
+    read_pos: usize,
+    write_pos: usize,
+    finished: bool,
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayStream {
+    /// Marks the stream as finished once all of its recorded traffic was replayed.
+    fn update_finished(&mut self) {
+        if self.finished
+            || self.read_pos < self.data.received.len()
+            || self.write_pos < self.data.sent.len()
+        {
+            return;
+        }
+
+        self.finished = true;
+        let mut state = self.state.lock().unwrap();
+        state.unfinished -= 1;
+        state.wake();
+    }
+}
+
+impl Drop for ReplayStream {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.error.get_or_insert_with(|| {
+            format!(
+                "stream {} was dropped after reading {} of {} bytes and writing {} of {} bytes",
+                self.id,
+                self.read_pos,
+                self.data.received.len(),
+                self.write_pos,
+                self.data.sent.len()
+            )
+        });
+        state.wake();
+    }
+}
+
+impl AsyncRead for ReplayStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let remaining = &this.data.received[this.read_pos..];
+        let n = remaining.len().min(buf.len());
+        buf[..n].copy_from_slice(&remaining[..n]);
+        this.read_pos += n;
+        this.update_finished();
+
+        Poll::Ready(Ok(n))
+    }
+}
+
+impl AsyncWrite for ReplayStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = &mut *self;
+        let remaining = &this.data.sent[this.write_pos..];
+
+        if let Some(offset) = buf
+            .iter()
+            .zip(remaining)
+            .position(|(written, recorded)| written != recorded)
+        {
+            return Poll::Ready(Err(diverged(&this.id, this.write_pos + offset)));
+        }
+
+        if buf.len() > remaining.len() {
+            return Poll::Ready(Err(diverged(&this.id, this.data.sent.len())));
+        }
+
+        this.write_pos += buf.len();
+        this.update_finished();
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn diverged(id: &str, offset: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("stream {id} diverged from the recording at byte {offset}"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::recorder::{Recorder, SharedBuf};
+
+    fn recording() -> Recording {
+        let buf = SharedBuf::default();
+        let recorder = Recorder::new(buf.clone()).strip_payload(false);
+        let mut stream = recorder.wrap("test", futures::io::Cursor::new(vec![0xde, 0xad]));
+
+        futures::executor::block_on(async {
+            let mut read = [0u8; 2];
+            stream.read_exact(&mut read).await.unwrap();
+            stream.write_all(&[0xbe, 0xef]).await.unwrap();
+        });
+
+        Recording::parse(&buf.contents()).unwrap()
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut mux = ReplayMux::new(recording());
+
+        futures::executor::block_on(async {
+            let mut stream = mux.get_stream("test").await.unwrap();
+
+            let mut read = Vec::new();
+            stream.read_to_end(&mut read).await.unwrap();
+            assert_eq!(read, vec![0xde, 0xad]);
+
+            stream.write_all(&[0xbe, 0xef]).await.unwrap();
+
+            // Each stream can only be opened once.
+            assert!(mux.get_stream("test").await.is_err());
+            assert!(mux.get_stream("other").await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_replay_diverged() {
+        let mut mux = ReplayMux::new(recording());
+
+        futures::executor::block_on(async {
+            let mut stream = mux.get_stream("test").await.unwrap();
+
+            let err = stream.write_all(&[0xbe, 0x00]).await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            assert!(err.to_string().contains("at byte 1"));
+        });
+    }
+
+    #[test]
+    fn test_replay_write_past_end() {
+        let mut mux = ReplayMux::new(recording());
+
+        futures::executor::block_on(async {
+            let mut stream = mux.get_stream("test").await.unwrap();
+
+            let err = stream.write_all(&[0xbe, 0xef, 0x00]).await.unwrap_err();
+            assert!(err.to_string().contains("at byte 2"));
+        });
+    }
+
+    #[test]
+    fn test_replay_finished() {
+        let mut mux = ReplayMux::new(recording());
+        let mut finished = mux.finished();
+
+        futures::executor::block_on(async {
+            let mut stream = mux.get_stream("test").await.unwrap();
+
+            let mut read = [0u8; 2];
+            stream.read_exact(&mut read).await.unwrap();
+            assert!(futures::poll!(&mut finished).is_pending());
+
+            stream.write_all(&[0xbe, 0xef]).await.unwrap();
+            drop(stream);
+            assert!(finished.await.is_ok());
+        });
+    }
+
+    #[test]
+    fn test_replay_dropped_unfinished() {
+        let mut mux = ReplayMux::new(recording());
+
+        futures::executor::block_on(async {
+            let mut stream = mux.get_stream("test").await.unwrap();
+
+            let mut read = [0u8; 2];
+            stream.read_exact(&mut read).await.unwrap();
+            drop(stream);
+
+            let err = mux.finished().await.unwrap_err();
+            assert!(err.to_string().contains("writing 0 of 2 bytes"));
+        });
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Recording::parse("0 test send 3").is_err());
+        assert!(Recording::parse("0 test send zz").is_err());
+        assert!(Recording::parse("0 test sent 00").is_err());
+        assert!(Recording::parse("0 test send").is_err());
+
+        // A stripped payload is rejected rather than read as hex.
+        let err = Recording::parse("0 test send len=12").unwrap_err();
+        assert!(err.to_string().contains("stripped"), "{err}");
+        assert_eq!(
+            Recording::parse("0 a send 00\n\n1 a recv 01\n")
+                .unwrap()
+                .stream_ids()
+                .collect::<Vec<_>>(),
+            vec!["a"]
+        );
+    }
+}
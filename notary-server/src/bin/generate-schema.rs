@@ -0,0 +1,23 @@
+use std::{fs, path::PathBuf};
+
+use eyre::Result;
+use notary_server::api_schemas;
+
+/// Writes the JSON schemas of the HTTP API types to the directory given as the first argument,
+/// `schema` by default
+fn main() -> Result<()> {
+    let out_dir = PathBuf::from(
+        std::env::args()
+            .nth(1)
+            .unwrap_or_else(|| "schema".to_string()),
+    );
+    fs::create_dir_all(&out_dir)?;
+
+    for (name, schema) in api_schemas() {
+        let path = out_dir.join(format!("{name}.json"));
+        fs::write(&path, serde_json::to_string_pretty(&schema)?)?;
+        println!("Wrote {}", path.display());
+    }
+
+    Ok(())
+}
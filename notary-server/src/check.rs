@@ -0,0 +1,153 @@
+use eyre::{ensure, eyre, Result};
+use p256::{ecdsa::VerifyingKey, pkcs8::DecodePublicKey};
+use std::collections::HashSet;
+
+use crate::{
+    config::NotaryServerProperties,
+    server::{
+        listeners, load_authorization_whitelist_from_source, load_notary_keys, load_tls_acceptor,
+    },
+    server_tracing::build_filter,
+};
+
+/// Outcome of a single check of the server config
+#[derive(Debug)]
+pub struct ConfigCheck {
+    /// Name of the part of the config that is checked
+    pub name: &'static str,
+    pub result: Result<()>,
+}
+
+/// Validate the server config without starting the server, i.e. check that the addresses parse and
+/// that the key, certificate and whitelist can be loaded from their files or Vault
+///
+/// Checks of optional modules that are turned off on all listeners are skipped
+pub async fn check_config(config: &NotaryServerProperties) -> Vec<ConfigCheck> {
+    let mut checks = vec![
+        ConfigCheck {
+            name: "server address",
+            result: check_server_addresses(config),
+        },
+        ConfigCheck {
+            name: "notary signing key",
+            result: check_notary_signing_key(config).await,
+        },
+        ConfigCheck {
+            name: "logging",
+            result: build_filter(&config.logging).map(|_| ()),
+        },
+    ];
+
+    let listeners = listeners(config);
+    if listeners.iter().any(|listener| listener.tls_enabled) {
+        checks.push(ConfigCheck {
+            name: "tls private key and certificate",
+            result: load_tls_acceptor(config).await.map(|_| ()),
+        });
+    }
+
+    if listeners
+        .iter()
+        .any(|listener| listener.authorization_enabled)
+    {
+        checks.push(ConfigCheck {
+            name: "authorization whitelist",
+            result: load_authorization_whitelist_from_source(config)
+                .await
+                .and_then(|whitelist| {
+                    ensure!(
+                        whitelist.is_some_and(|whitelist| !whitelist.is_empty()),
+                        "Authorization whitelist is empty, all requests would be rejected"
+                    );
+                    Ok(())
+                }),
+        });
+    }
+
+    if config.explorer.enabled {
+        checks.push(ConfigCheck {
+            name: "explorer",
+            result: check_explorer(config),
+        });
+    }
+
+    checks
+}
+
+/// Check that the explorer can be served, i.e. that the server is built with the `explorer` feature
+fn check_explorer(config: &NotaryServerProperties) -> Result<()> {
+    ensure!(
+        cfg!(feature = "explorer"),
+        "Explorer is turned on but the server is built without the `explorer` feature"
+    );
+    ensure!(
+        config.explorer.max_attestations > 0,
+        "Explorer is turned on but `max-attestations` is 0, no attestation would be shown"
+    );
+    Ok(())
+}
+
+/// Check the addresses of all listeners, which must be distinct
+fn check_server_addresses(config: &NotaryServerProperties) -> Result<()> {
+    let mut addresses = HashSet::new();
+    for listener in listeners(config) {
+        let address = listener.address()?;
+        ensure!(address.port() != 0, "Notary server port must not be 0");
+        ensure!(
+            addresses.insert(address),
+            "Notary server address {address} is used by more than one listener"
+        );
+    }
+    Ok(())
+}
+
+/// Check that the signing key can be loaded and that it matches the public key served by /info
+async fn check_notary_signing_key(config: &NotaryServerProperties) -> Result<()> {
+    let (signing_key, public_key) = load_notary_keys(config).await?;
+    let public_key = VerifyingKey::from_public_key_pem(&public_key)
+        .map_err(|err| eyre!("Failed to parse notary public signing key: {err}"))?;
+    ensure!(
+        &public_key == signing_key.verifying_key(),
+        "Notary public key does not match the signing key"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::parse_config_file;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_check_config() {
+        let mut config: NotaryServerProperties = parse_config_file("./config/config.yaml").unwrap();
+        config.authorization.enabled = true;
+        let checks = check_config(&config).await;
+        assert_eq!(checks.len(), 5);
+        assert!(checks.iter().all(|check| check.result.is_ok()));
+    }
+
+    #[tokio::test]
+    async fn test_check_config_reports_failures() {
+        let mut config: NotaryServerProperties = parse_config_file("./config/config.yaml").unwrap();
+        config.server.host = "localhost".to_string();
+        config.notary_key.public_key_pem_path = config.tls.certificate_pem_path.clone();
+        config.tls.certificate_pem_path = "./fixture/tls/missing.crt".to_string();
+
+        let failed: Vec<_> = check_config(&config)
+            .await
+            .into_iter()
+            .filter(|check| check.result.is_err())
+            .map(|check| check.name)
+            .collect();
+        assert_eq!(
+            failed,
+            [
+                "server address",
+                "notary signing key",
+                "tls private key and certificate"
+            ]
+        );
+    }
+}
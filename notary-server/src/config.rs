@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use std::time::Duration;
 
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -15,12 +16,135 @@ pub struct NotaryServerProperties {
     pub logging: LoggingProperties,
     /// Setting for authorization
     pub authorization: AuthorizationProperties,
+    /// Switches to turn off endpoints and protocols that are not needed
+    #[serde(default)]
+    pub endpoints: EndpointProperties,
+    /// Setting for loading secrets from HashiCorp Vault instead of files
+    #[serde(default)]
+    pub vault: Option<VaultProperties>,
+    /// Limits on sessions, none of them is enforced unless set
+    #[serde(default)]
+    pub limits: LimitsProperties,
+    /// Setting for the attestation explorer web UI
+    #[serde(default)]
+    pub explorer: ExplorerProperties,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct ExplorerProperties {
+    /// Serve the attestation explorer at /explorer, requires the `explorer` feature
+    pub enabled: bool,
+    /// Maximum number of attestations kept in memory, the oldest ones are dropped first
+    pub max_attestations: usize,
+    /// Session ids of the attestations that are revoked
+    pub revoked_sessions: Vec<String>,
+}
+
+impl Default for ExplorerProperties {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_attestations: 1000,
+            revoked_sessions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct LimitsProperties {
+    /// Maximum wall-clock time in seconds a notarization may take before it is aborted
+    #[serde(default)]
+    pub max_session_duration_secs: Option<u64>,
+    /// Maximum size in bytes of the request body of the /session API
+    #[serde(default)]
+    pub max_request_size: Option<usize>,
+    /// Maximum number of sessions per API key that are pending or being notarized at the same time,
+    /// only enforced when authorization is turned on
+    #[serde(default)]
+    pub max_sessions_per_api_key: Option<usize>,
+    /// Maximum number of sessions that are created but not yet notarized
+    #[serde(default)]
+    pub max_pending_sessions: Option<usize>,
+    /// Time in seconds after which a session that is created but not yet notarized expires,
+    /// defaults to 300 seconds
+    #[serde(default)]
+    pub pending_session_ttl_secs: Option<u64>,
+}
+
+impl LimitsProperties {
+    /// Default of `pending_session_ttl_secs`
+    pub const DEFAULT_PENDING_SESSION_TTL_SECS: u64 = 300;
+
+    /// Time after which a session that is created but not yet notarized expires
+    pub fn pending_session_ttl(&self) -> Duration {
+        Duration::from_secs(
+            self.pending_session_ttl_secs
+                .unwrap_or(Self::DEFAULT_PENDING_SESSION_TTL_SECS),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct VaultProperties {
+    /// Address of the Vault server, e.g. https://vault.example.com:8200, which must use https. The
+    /// token is read from the VAULT_TOKEN environment variable
+    pub address: String,
+    /// Mount path of the KV version 2 secrets engine
+    #[serde(default = "default_kv_mount")]
+    pub kv_mount: String,
+    /// Path of the secret holding the notary signing key (`private-key-pem` field), which is loaded
+    /// from the `notary-key` files if not set
+    #[serde(default)]
+    pub notary_key_path: Option<String>,
+    /// Path of the secret holding the TLS private key and certificate (`private-key-pem` and
+    /// `certificate-pem` fields), which are loaded from the `tls` files if not set
+    #[serde(default)]
+    pub tls_path: Option<String>,
+    /// Path of the secret holding the whitelist API key csv (`whitelist-csv` field), which is loaded
+    /// from the `authorization` file if not set
+    #[serde(default)]
+    pub whitelist_path: Option<String>,
+}
+
+fn default_kv_mount() -> String {
+    "secret".to_string()
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct EndpointProperties {
+    /// Serve the static html at the API root endpoint "/"
+    pub html_info: bool,
+    /// Serve the /healthcheck endpoint
+    pub healthcheck: bool,
+    /// Serve the /info endpoint
+    pub info: bool,
+    /// Accept notarization from TCP clients, i.e. upgrading /notarize to the underlying tcp connection
+    pub tcp: bool,
+    /// Accept notarization from WebSocket clients, i.e. upgrading /notarize to websocket
+    pub websocket: bool,
+}
+
+impl Default for EndpointProperties {
+    /// Everything is turned on by default
+    fn default() -> Self {
+        Self {
+            html_info: true,
+            healthcheck: true,
+            info: true,
+            tcp: true,
+            websocket: true,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct AuthorizationProperties {
-    /// Switch to turn on or off auth middleware
+    /// Switch to turn on or off auth middleware, also the default for additional listeners
     pub enabled: bool,
     /// File path of the whitelist API key csv
     pub whitelist_csv_path: String,
@@ -31,6 +155,31 @@ pub struct AuthorizationProperties {
 pub struct NotarizationProperties {
     /// Global limit for maximum transcript size in bytes
     pub max_transcript_size: usize,
+    /// Maximum memory in bytes a single notarization may use, sessions exceeding it are rejected
+    /// and received records beyond it are spilled to a temporary file
+    #[serde(default)]
+    pub memory_budget: Option<usize>,
+    /// Maximum number of VM threads a single notarization may use
+    #[serde(default)]
+    pub max_threads: Option<usize>,
+    /// Maximum number of VM threads all notarizations may use at once, each session reserves its
+    /// `max-threads` from this pool and sessions which don't fit wait in arrival order. Only read
+    /// at startup
+    #[serde(default)]
+    pub max_total_threads: Option<usize>,
+    /// Maximum time in seconds a single notarization may spend being polled before it is aborted,
+    /// work offloaded to backend threads is not counted
+    #[serde(default)]
+    pub max_poll_time_secs: Option<u64>,
+    /// Maximum number of bytes a single notarization may have allocated at once before it is
+    /// aborted, allocations on backend threads are not counted
+    #[serde(default)]
+    pub max_memory_bytes: Option<usize>,
+    /// Seed from which session ids and the randomness of notarizations are derived, for
+    /// reproducing a session in tests
+    #[cfg(feature = "deterministic")]
+    #[serde(default)]
+    pub rng_seed: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
@@ -43,12 +192,31 @@ pub struct ServerProperties {
     /// Static html response returned from API root endpoint "/". Default html response contains
     /// placeholder strings that will be replaced with actual values in server.rs, e.g. {version}, {public_key}
     pub html_info: String,
+    /// Other addresses to listen on at the same time as the one above, each with its own settings
+    #[serde(default)]
+    pub additional_listeners: Vec<ListenerProperties>,
+}
+
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ListenerProperties {
+    pub host: String,
+    pub port: u16,
+    /// Flag to turn on/off TLS on this listener, defaults to the `tls` setting. The TLS private key
+    /// and certificate of the `tls` setting are used
+    #[serde(default)]
+    pub tls_enabled: Option<bool>,
+    /// Flag to turn on/off authorization on this listener, defaults to the `authorization` setting.
+    /// The whitelist of the `authorization` setting is used
+    #[serde(default)]
+    pub authorization_enabled: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
 pub struct TLSProperties {
-    /// Flag to turn on/off TLS between prover and notary (should always be turned on unless TLS is handled by external setup e.g. reverse proxy, cloud)
+    /// Flag to turn on/off TLS between prover and notary (should always be turned on unless TLS is handled by external setup e.g. reverse proxy, cloud),
+    /// also the default for additional listeners
     pub enabled: bool,
     pub private_key_pem_path: String,
     pub certificate_pem_path: String,
@@ -2,10 +2,11 @@ pub mod auth;
 pub mod cli;
 pub mod notary;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Response object of the /info API
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct InfoResponse {
     /// Current version of notary-server
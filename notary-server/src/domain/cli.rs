@@ -1,10 +1,187 @@
+use eyre::Result;
 use structopt::StructOpt;
 
+use crate::{config::NotaryServerProperties, util::parse_config_file_with_profile};
+
 /// Fields loaded from the command line when launching this server.
+///
+/// Settings given here take precedence over environment variables, which in turn take precedence
+/// over the config file.
 #[derive(Clone, Debug, StructOpt)]
 #[structopt(name = "Notary Server")]
 pub struct CliFields {
     /// Configuration file location
     #[structopt(long, default_value = "./config/config.yaml")]
     pub config_file: String,
+    /// Profile of the configuration file to apply over its base
+    #[structopt(long, env = "NOTARY_PROFILE")]
+    pub profile: Option<String>,
+    /// Host address to listen on
+    #[structopt(long)]
+    pub host: Option<String>,
+    /// Port to listen on
+    #[structopt(long)]
+    pub port: Option<u16>,
+    /// Turn on or off TLS between prover and notary
+    #[structopt(long)]
+    pub tls_enabled: Option<bool>,
+    /// File path of the TLS private key (in PEM format)
+    #[structopt(long)]
+    pub tls_private_key_path: Option<String>,
+    /// File path of the TLS certificate (in PEM format)
+    #[structopt(long)]
+    pub tls_certificate_path: Option<String>,
+    /// File path of the notary signing private key (in PEM format)
+    #[structopt(long)]
+    pub notary_private_key_path: Option<String>,
+    /// File path of the notary signing public key (in PEM format)
+    #[structopt(long)]
+    pub notary_public_key_path: Option<String>,
+    /// Global limit for maximum transcript size in bytes
+    #[structopt(long)]
+    pub max_transcript_size: Option<usize>,
+    /// Maximum memory in bytes a single notarization may use
+    #[structopt(long)]
+    pub memory_budget: Option<usize>,
+    /// Maximum number of VM threads a single notarization may use
+    #[structopt(long)]
+    pub max_threads: Option<usize>,
+    /// Maximum time in seconds a single notarization may spend being polled
+    #[structopt(long)]
+    pub max_poll_time_secs: Option<u64>,
+    /// Maximum number of bytes a single notarization may have allocated at once
+    #[structopt(long)]
+    pub max_memory_bytes: Option<usize>,
+    /// Log verbosity level
+    #[structopt(long)]
+    pub log_level: Option<String>,
+    /// Turn on or off the authorization whitelist
+    #[structopt(long)]
+    pub auth_enabled: Option<bool>,
+    /// File path of the whitelist API key csv
+    #[structopt(long)]
+    pub whitelist_path: Option<String>,
+    /// Command to run instead of the server
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands that can be run instead of the server
+#[derive(Clone, Debug, StructOpt)]
+pub enum Command {
+    /// Generate a new notary signing key pair at the configured paths
+    GenKey {
+        /// Overwrite the key files if they already exist
+        #[structopt(long)]
+        force: bool,
+    },
+    /// Print the public key of the configured notary signing key
+    PrintPubkey,
+    /// Validate the config and the files it refers to, then exit with an error if any check fails
+    CheckConfig,
+}
+
+impl CliFields {
+    /// Load the config file with the selected profile, then apply the overrides from environment
+    /// variables and the command line
+    pub fn load_config(&self) -> Result<NotaryServerProperties> {
+        let mut config: NotaryServerProperties =
+            parse_config_file_with_profile(&self.config_file, self.profile.as_deref())?;
+        self.apply_overrides(&mut config);
+        Ok(config)
+    }
+
+    /// Override the config with the settings given on the command line
+    fn apply_overrides(&self, config: &mut NotaryServerProperties) {
+        fn set<T: Clone>(field: &mut T, value: &Option<T>) {
+            if let Some(value) = value {
+                *field = value.clone();
+            }
+        }
+
+        set(&mut config.server.host, &self.host);
+        set(&mut config.server.port, &self.port);
+        set(&mut config.tls.enabled, &self.tls_enabled);
+        set(
+            &mut config.tls.private_key_pem_path,
+            &self.tls_private_key_path,
+        );
+        set(
+            &mut config.tls.certificate_pem_path,
+            &self.tls_certificate_path,
+        );
+        set(
+            &mut config.notary_key.private_key_pem_path,
+            &self.notary_private_key_path,
+        );
+        set(
+            &mut config.notary_key.public_key_pem_path,
+            &self.notary_public_key_path,
+        );
+        set(
+            &mut config.notarization.max_transcript_size,
+            &self.max_transcript_size,
+        );
+        if self.memory_budget.is_some() {
+            config.notarization.memory_budget = self.memory_budget;
+        }
+        if self.max_threads.is_some() {
+            config.notarization.max_threads = self.max_threads;
+        }
+        if self.max_poll_time_secs.is_some() {
+            config.notarization.max_poll_time_secs = self.max_poll_time_secs;
+        }
+        if self.max_memory_bytes.is_some() {
+            config.notarization.max_memory_bytes = self.max_memory_bytes;
+        }
+        set(&mut config.logging.level, &self.log_level);
+        set(&mut config.authorization.enabled, &self.auth_enabled);
+        set(
+            &mut config.authorization.whitelist_csv_path,
+            &self.whitelist_path,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::util::parse_config_file;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_overrides_config_file() {
+        let cli_fields = CliFields::from_iter_safe([
+            "notary-server",
+            "--port",
+            "8080",
+            "--tls-enabled",
+            "false",
+            "--max-threads",
+            "4",
+        ])
+        .unwrap();
+        let config = cli_fields.load_config().unwrap();
+        let file_config: NotaryServerProperties =
+            parse_config_file(&cli_fields.config_file).unwrap();
+
+        assert_eq!(config.server.port, 8080);
+        assert!(!config.tls.enabled);
+        assert_eq!(config.notarization.max_threads, Some(4));
+        assert_eq!(config.server.host, file_config.server.host);
+        assert_eq!(
+            config.notarization.max_transcript_size,
+            file_config.notarization.max_transcript_size
+        );
+    }
+
+    #[test]
+    fn test_parse_subcommand() {
+        let cli_fields =
+            CliFields::from_iter_safe(["notary-server", "gen-key", "--force"]).unwrap();
+        assert!(matches!(
+            cli_fields.command,
+            Some(Command::GenKey { force: true })
+        ));
+    }
 }
@@ -2,14 +2,22 @@ use std::{collections::HashMap, sync::Arc};
 
 use chrono::{DateTime, Utc};
 use p256::ecdsa::SigningKey;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
+use tlsn_core::SessionHeader;
 use tokio::sync::Mutex as AsyncMutex;
 
-use crate::{config::NotarizationProperties, domain::auth::AuthorizationWhitelistRecord};
+#[cfg(feature = "explorer")]
+use crate::service::explorer::{AttestationRecord, AttestationStore};
+use crate::{
+    config::{EndpointProperties, ExplorerProperties, LimitsProperties, NotarizationProperties},
+    domain::auth::AuthorizationWhitelistRecord,
+    service::threads::ThreadPool,
+};
 
 /// Response object of the /session API
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct NotarizationSessionResponse {
     /// Unique session id that is generated by notary and shared to prover
@@ -17,9 +25,10 @@ pub struct NotarizationSessionResponse {
 }
 
 /// Request object of the /session API
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct NotarizationSessionRequest {
+    /// Type of client that the prover is using
     pub client_type: ClientType,
     /// Maximum data that can be sent by the prover
     pub max_sent_data: Option<usize>,
@@ -27,8 +36,20 @@ pub struct NotarizationSessionRequest {
     pub max_recv_data: Option<usize>,
 }
 
+impl NotarizationSessionRequest {
+    /// Total transcript size requested, if any, saturating instead of overflowing as both sizes
+    /// are chosen by the prover
+    pub fn requested_transcript_size(&self) -> Option<usize> {
+        (self.max_sent_data.is_some() || self.max_recv_data.is_some()).then(|| {
+            self.max_sent_data
+                .unwrap_or_default()
+                .saturating_add(self.max_recv_data.unwrap_or_default())
+        })
+    }
+}
+
 /// Request query of the /notarize API
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct NotarizationRequestQuery {
     /// Session id that is returned from /session API
@@ -36,7 +57,7 @@ pub struct NotarizationRequestQuery {
 }
 
 /// Types of client that the prover is using
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 pub enum ClientType {
     /// Client that has access to the transport layer
     Tcp,
@@ -50,17 +71,36 @@ pub struct SessionData {
     pub max_sent_data: Option<usize>,
     pub max_recv_data: Option<usize>,
     pub created_at: DateTime<Utc>,
+    /// API key the session was created with, if authorization is turned on
+    pub api_key: Option<String>,
 }
 
 /// Global data that needs to be shared with the axum handlers
 #[derive(Clone, Debug)]
 pub struct NotaryGlobals {
     pub notary_signing_key: SigningKey,
-    pub notarization_config: NotarizationProperties,
+    /// Notarization limits, which can be hot reloaded from the config file
+    pub notarization_config: Arc<Mutex<NotarizationProperties>>,
     /// A temporary storage to store configuration data, mainly used for WebSocket client
     pub store: Arc<AsyncMutex<HashMap<String, SessionData>>>,
     /// Whitelist of API keys for authorization purpose
     pub authorization_whitelist: Option<Arc<Mutex<HashMap<String, AuthorizationWhitelistRecord>>>>,
+    /// Endpoints and protocols that are turned on
+    pub endpoints: EndpointProperties,
+    /// Session limits, which can be hot reloaded from the config file
+    pub limits: Arc<Mutex<LimitsProperties>>,
+    /// Number of sessions being notarized per API key
+    pub active_sessions: Arc<Mutex<HashMap<String, usize>>>,
+    /// VM threads shared by all sessions, if `notarization.max-total-threads` is set at startup
+    pub thread_pool: Option<ThreadPool>,
+    /// Setting for the attestation explorer
+    pub explorer: ExplorerProperties,
+    /// Latest attestations shown by the explorer
+    #[cfg(feature = "explorer")]
+    pub attestations: Arc<Mutex<AttestationStore>>,
+    /// Generator of session ids, which is seeded if `notarization.rng-seed` is set at startup
+    #[cfg(feature = "deterministic")]
+    pub session_id_rng: Option<Arc<Mutex<rand_chacha::ChaCha20Rng>>>,
 }
 
 impl NotaryGlobals {
@@ -68,12 +108,106 @@ impl NotaryGlobals {
         notary_signing_key: SigningKey,
         notarization_config: NotarizationProperties,
         authorization_whitelist: Option<Arc<Mutex<HashMap<String, AuthorizationWhitelistRecord>>>>,
+        endpoints: EndpointProperties,
+        limits: LimitsProperties,
+        explorer: ExplorerProperties,
     ) -> Self {
+        #[cfg(feature = "deterministic")]
+        let session_id_rng = notarization_config.rng_seed.map(|seed| {
+            Arc::new(Mutex::new(tlsn_common::rng::seeded_rng(
+                seed,
+                tlsn_common::rng::RngStream::SessionId,
+            )))
+        });
+
+        let thread_pool = notarization_config.max_total_threads.map(ThreadPool::new);
+
         Self {
             notary_signing_key,
-            notarization_config,
+            #[cfg(feature = "deterministic")]
+            session_id_rng,
+            notarization_config: Arc::new(Mutex::new(notarization_config)),
             store: Default::default(),
             authorization_whitelist,
+            endpoints,
+            limits: Arc::new(Mutex::new(limits)),
+            active_sessions: Default::default(),
+            thread_pool,
+            #[cfg(feature = "explorer")]
+            attestations: Arc::new(Mutex::new(AttestationStore::new(explorer.max_attestations))),
+            explorer,
+        }
+    }
+
+    /// Keep the attestation of a notarized session for the explorer, if it is turned on
+    #[cfg_attr(not(feature = "explorer"), allow(unused_variables))]
+    pub fn record_attestation(&self, session_id: &str, header: &SessionHeader) {
+        #[cfg(feature = "explorer")]
+        if self.explorer.enabled {
+            self.attestations
+                .lock()
+                .unwrap()
+                .record(AttestationRecord::new(
+                    session_id,
+                    header,
+                    &p256::PublicKey::from(self.notary_signing_key.verifying_key()).into(),
+                ));
+        }
+    }
+
+    /// Whether notarization from the given type of client is turned on
+    pub fn client_type_enabled(&self, client_type: &ClientType) -> bool {
+        match client_type {
+            ClientType::Tcp => self.endpoints.tcp,
+            ClientType::Websocket => self.endpoints.websocket,
+        }
+    }
+
+    /// Generate the id of a new session
+    pub fn new_session_id(&self) -> String {
+        #[cfg(feature = "deterministic")]
+        if let Some(rng) = &self.session_id_rng {
+            use rand::Rng;
+
+            let bytes = rng.lock().unwrap().gen();
+            return uuid::Builder::from_random_bytes(bytes)
+                .into_uuid()
+                .to_string();
+        }
+
+        uuid::Uuid::new_v4().to_string()
+    }
+
+    /// Count a session as being notarized for the API key until the returned guard is dropped
+    pub fn start_active_session(&self, api_key: String) -> ActiveSessionGuard {
+        *self
+            .active_sessions
+            .lock()
+            .unwrap()
+            .entry(api_key.clone())
+            .or_default() += 1;
+        ActiveSessionGuard {
+            active_sessions: self.active_sessions.clone(),
+            api_key,
+        }
+    }
+}
+
+/// Guard which counts a session as being notarized for its API key until dropped
+#[derive(Debug)]
+pub struct ActiveSessionGuard {
+    active_sessions: Arc<Mutex<HashMap<String, usize>>>,
+    api_key: String,
+}
+
+impl Drop for ActiveSessionGuard {
+    fn drop(&mut self) {
+        let mut active_sessions = self.active_sessions.lock().unwrap();
+        if let Some(count) = active_sessions.get_mut(&self.api_key) {
+            *count -= 1;
+            if *count == 0 {
+                active_sessions.remove(&self.api_key);
+            }
         }
     }
 }
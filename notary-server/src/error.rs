@@ -19,6 +19,14 @@ pub enum NotaryServerError {
     BadProverRequest(String),
     #[error("Unauthorized request from prover: {0}")]
     UnauthorizedProverRequest(String),
+    #[error("Session exceeded its poll time limit of {0:?}")]
+    PollTimeExceeded(std::time::Duration),
+    #[error("Session exceeded its memory limit of {0} bytes")]
+    MemoryExceeded(usize),
+    #[error("Session exceeded its duration limit of {0:?}")]
+    SessionDurationExceeded(std::time::Duration),
+    #[error("Too many sessions: {0}")]
+    TooManySessions(String),
 }
 
 impl From<VerifierError> for NotaryServerError {
@@ -45,6 +53,11 @@ impl IntoResponse for NotaryServerError {
                 unauthorized_request_error.to_string(),
             )
                 .into_response(),
+            too_many_sessions_error @ NotaryServerError::TooManySessions(_) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                too_many_sessions_error.to_string(),
+            )
+                .into_response(),
             _ => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Something wrong happened.",
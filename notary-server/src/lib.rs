@@ -1,21 +1,28 @@
+mod check;
 mod config;
 mod domain;
 mod error;
 mod middleware;
+mod schema;
 mod server;
 mod server_tracing;
 mod service;
 mod util;
+mod vault;
 
+pub use check::{check_config, ConfigCheck};
 pub use config::{
-    AuthorizationProperties, LoggingProperties, NotarizationProperties, NotaryServerProperties,
-    NotarySigningKeyProperties, ServerProperties, TLSProperties,
+    AuthorizationProperties, EndpointProperties, ExplorerProperties, LimitsProperties,
+    ListenerProperties, LoggingProperties, NotarizationProperties, NotaryServerProperties,
+    NotarySigningKeyProperties, ServerProperties, TLSProperties, VaultProperties,
 };
 pub use domain::{
-    cli::CliFields,
+    cli::{CliFields, Command},
     notary::{ClientType, NotarizationSessionRequest, NotarizationSessionResponse},
 };
 pub use error::NotaryServerError;
+pub use schema::api_schemas;
 pub use server::{read_pem_file, run_server};
 pub use server_tracing::init_tracing;
-pub use util::parse_config_file;
+pub use service::guard::TrackingAllocator;
+pub use util::{parse_config_file, parse_config_file_with_profile};
@@ -1,17 +1,36 @@
-use eyre::{eyre, Result};
+use eyre::{ensure, eyre, Result};
+use p256::{
+    ecdsa::SigningKey,
+    pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey, LineEnding},
+};
+use rand::rngs::OsRng;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::{fs::OpenOptions, io::Write, path::Path};
 use structopt::StructOpt;
 use tracing::debug;
 
 use notary_server::{
-    init_tracing, parse_config_file, run_server, CliFields, NotaryServerError,
-    NotaryServerProperties,
+    check_config, init_tracing, run_server, CliFields, Command, NotaryServerError,
+    NotaryServerProperties, NotarySigningKeyProperties, TrackingAllocator,
 };
 
+// Attributes allocations to notarization sessions, for `notarization.max-memory-bytes`.
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
 #[tokio::main]
 async fn main() -> Result<(), NotaryServerError> {
-    // Load command line arguments which contains the config file location
+    // Load command line arguments which contains the config file location and overrides
     let cli_fields: CliFields = CliFields::from_args();
-    let config: NotaryServerProperties = parse_config_file(&cli_fields.config_file)?;
+    let config: NotaryServerProperties = cli_fields.load_config()?;
+
+    match &cli_fields.command {
+        Some(Command::GenKey { force }) => return Ok(gen_key(&config.notary_key, *force)?),
+        Some(Command::PrintPubkey) => return Ok(print_pubkey(&config.notary_key)?),
+        Some(Command::CheckConfig) => return Ok(run_check_config(&config).await?),
+        None => {}
+    }
 
     // Set up tracing for logging
     init_tracing(&config).map_err(|err| eyre!("Failed to set up tracing: {err}"))?;
@@ -19,7 +38,87 @@ async fn main() -> Result<(), NotaryServerError> {
     debug!(?config, "Server config loaded");
 
     // Run the server
-    run_server(&config).await?;
+    run_server(&config, Some(&cli_fields)).await?;
+
+    Ok(())
+}
+
+/// Run all config checks and print their outcomes
+async fn run_check_config(config: &NotaryServerProperties) -> Result<()> {
+    let checks = check_config(config).await;
+    let mut failed = 0;
+    for check in &checks {
+        match &check.result {
+            Ok(()) => println!("[ok] {}", check.name),
+            Err(err) => {
+                failed += 1;
+                println!("[failed] {}: {err:#}", check.name);
+            }
+        }
+    }
+
+    ensure!(
+        failed == 0,
+        "{failed} of {} config checks failed",
+        checks.len()
+    );
+    println!("Config is valid");
+    Ok(())
+}
+
+/// Generate a new notary signing key pair and write it to the configured paths
+fn gen_key(config: &NotarySigningKeyProperties, force: bool) -> Result<()> {
+    for path in [&config.private_key_pem_path, &config.public_key_pem_path] {
+        ensure!(
+            force || !Path::new(path).exists(),
+            "{path} already exists, use --force to overwrite it"
+        );
+    }
+
+    let signing_key = SigningKey::random(&mut OsRng);
+    let private_key_pem = signing_key
+        .to_pkcs8_pem(LineEnding::LF)
+        .map_err(|err| eyre!("Failed to encode notary signing key: {err}"))?;
+    let public_key_pem = signing_key
+        .verifying_key()
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|err| eyre!("Failed to encode notary public key: {err}"))?;
+
+    write_private_key(&config.private_key_pem_path, private_key_pem.as_bytes())?;
+    std::fs::write(&config.public_key_pem_path, public_key_pem.as_bytes())?;
+
+    println!(
+        "Wrote notary signing key to {} and {}",
+        config.private_key_pem_path, config.public_key_pem_path
+    );
+    Ok(())
+}
+
+/// Write the private key to a file only readable by its owner
+fn write_private_key(path: &str, contents: &[u8]) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let mut file = options.open(path)?;
+    // The mode only applies to new files, an overwritten key may have been readable by others
+    #[cfg(unix)]
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents)?;
+
+    Ok(())
+}
+
+/// Print the public key of the configured notary signing key
+fn print_pubkey(config: &NotarySigningKeyProperties) -> Result<()> {
+    let signing_key = SigningKey::read_pkcs8_pem_file(&config.private_key_pem_path)
+        .map_err(|err| eyre!("Failed to load notary signing key: {err}"))?;
+    let public_key_pem = signing_key
+        .verifying_key()
+        .to_public_key_pem(LineEnding::LF)
+        .map_err(|err| eyre!("Failed to encode notary public key: {err}"))?;
 
+    print!("{public_key_pem}");
     Ok(())
 }
@@ -0,0 +1,50 @@
+use schemars::{schema::RootSchema, schema_for};
+
+use crate::domain::{
+    notary::{
+        ClientType, NotarizationRequestQuery, NotarizationSessionRequest,
+        NotarizationSessionResponse,
+    },
+    InfoResponse,
+};
+
+/// Returns the JSON schemas of the HTTP API types, keyed by type name
+///
+/// Error responses are plain text bodies and have no schema
+pub fn api_schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("InfoResponse", schema_for!(InfoResponse)),
+        (
+            "NotarizationSessionRequest",
+            schema_for!(NotarizationSessionRequest),
+        ),
+        (
+            "NotarizationSessionResponse",
+            schema_for!(NotarizationSessionResponse),
+        ),
+        (
+            "NotarizationRequestQuery",
+            schema_for!(NotarizationRequestQuery),
+        ),
+        ("ClientType", schema_for!(ClientType)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_schemas_use_wire_names() {
+        let schemas = api_schemas();
+        let (_, request) = schemas
+            .iter()
+            .find(|(name, _)| *name == "NotarizationSessionRequest")
+            .unwrap();
+
+        let properties = &request.schema.object.as_ref().unwrap().properties;
+        assert!(properties.contains_key("clientType"));
+        assert!(properties.contains_key("maxSentData"));
+        assert!(properties.contains_key("maxRecvData"));
+    }
+}
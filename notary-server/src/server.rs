@@ -1,4 +1,5 @@
 use axum::{
+    extract::DefaultBodyLimit,
     http::{Request, StatusCode},
     middleware::from_extractor_with_state,
     response::{Html, IntoResponse},
@@ -11,143 +12,311 @@ use hyper::server::{
     accept::Accept,
     conn::{AddrIncoming, Http},
 };
+use listenfd::ListenFd;
 use notify::{
     event::ModifyKind, Error, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher,
 };
-use p256::{ecdsa::SigningKey, pkcs8::DecodePrivateKey};
+use p256::{
+    ecdsa::SigningKey,
+    pkcs8::{DecodePrivateKey, EncodePublicKey, LineEnding},
+};
 use rustls::{Certificate, PrivateKey, ServerConfig};
 use std::{
     collections::HashMap,
     fs::File as StdFile,
-    io::BufReader,
+    io::{BufRead, BufReader},
     net::{IpAddr, SocketAddr},
-    path::Path,
+    path::{Path, PathBuf},
     pin::Pin,
     sync::{Arc, Mutex},
 };
 use tower_http::cors::CorsLayer;
 
-use tokio::{fs::File, net::TcpListener};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::{fs::File, net::TcpListener, sync::mpsc};
 use tokio_rustls::TlsAcceptor;
 use tower::MakeService;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    config::{NotaryServerProperties, NotarySigningKeyProperties},
+    config::{
+        LimitsProperties, NotarizationProperties, NotaryServerProperties,
+        NotarySigningKeyProperties, TLSProperties,
+    },
     domain::{
         auth::{authorization_whitelist_vec_into_hashmap, AuthorizationWhitelistRecord},
+        cli::CliFields,
         notary::NotaryGlobals,
         InfoResponse,
     },
     error::NotaryServerError,
     middleware::AuthorizationMiddleware,
+    server_tracing::reload_tracing,
     service::{initialize, upgrade_protocol},
     util::parse_csv_file,
+    vault::{spawn_token_renewal, VaultClient},
 };
 
+type AuthorizationWhitelist = Arc<Mutex<HashMap<String, AuthorizationWhitelistRecord>>>;
+
 /// Start a TCP server (with or without TLS) to accept notarization request for both TCP and WebSocket clients
+///
+/// If the command line fields which the config is loaded with are provided, changes to the config
+/// file are hot reloaded
 #[tracing::instrument(skip(config))]
-pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotaryServerError> {
+pub async fn run_server(
+    config: &NotaryServerProperties,
+    cli_fields: Option<&CliFields>,
+) -> Result<(), NotaryServerError> {
+    let listeners = listeners(config);
+
+    // Keep the Vault token alive so that secrets can be reloaded
+    if let Some(vault_client) = vault_client(config)? {
+        spawn_token_renewal(vault_client);
+    }
+
     // Load the private key for notarized transcript signing
-    let notary_signing_key = load_notary_signing_key(&config.notary_key).await?;
-    // Build TLS acceptor if it is turned on
-    let tls_acceptor = if !config.tls.enabled {
+    let (notary_signing_key, public_key) = load_notary_keys(config).await?;
+    // Build TLS acceptor if it is turned on for any listener
+    let tls_acceptor = if !listeners.iter().any(|listener| listener.tls_enabled) {
         debug!("Skipping TLS setup as it is turned off.");
         None
     } else {
-        let (tls_private_key, tls_certificates) = load_tls_key_and_cert(
-            &config.tls.private_key_pem_path,
-            &config.tls.certificate_pem_path,
-        )
-        .await?;
-
-        let mut server_config = ServerConfig::builder()
-            .with_safe_defaults()
-            .with_no_client_auth()
-            .with_single_cert(tls_certificates, tls_private_key)
-            .map_err(|err| eyre!("Failed to instantiate notary server tls config: {err}"))?;
-
-        // Set the http protocols we support
-        server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
-        let tls_config = Arc::new(server_config);
-        Some(TlsAcceptor::from(tls_config))
+        Some(Arc::new(Mutex::new(load_tls_acceptor(config).await?)))
     };
 
     // Load the authorization whitelist csv if it is turned on
-    let authorization_whitelist =
-        load_authorization_whitelist(config)?.map(|whitelist| Arc::new(Mutex::new(whitelist)));
-    // Enable hot reload if authorization whitelist is available
-    let watcher = watch_and_reload_authorization_whitelist(
-        config.clone(),
-        authorization_whitelist.as_ref().map(Arc::clone),
-    )?;
+    let authorization_whitelist = load_authorization_whitelist_from_source(config)
+        .await?
+        .map(|whitelist| Arc::new(Mutex::new(whitelist)));
+    // Enable hot reload if authorization whitelist is available as a file
+    let watcher = if whitelist_in_vault(config) {
+        None
+    } else {
+        watch_and_reload_authorization_whitelist(
+            config.clone(),
+            authorization_whitelist.as_ref().map(Arc::clone),
+        )?
+    };
     if watcher.is_some() {
         debug!("Successfully setup watcher for hot reload of authorization whitelist!");
     }
+    let authorization_whitelist_watcher = Arc::new(Mutex::new(watcher));
 
-    let notary_address = SocketAddr::new(
-        IpAddr::V4(config.server.host.parse().map_err(|err| {
-            eyre!("Failed to parse notary host address from server config: {err}")
-        })?),
-        config.server.port,
-    );
-    let listener = TcpListener::bind(notary_address)
-        .await
-        .map_err(|err| eyre!("Failed to bind server address to tcp listener: {err}"))?;
-    let mut listener = AddrIncoming::from_listener(listener)
-        .map_err(|err| eyre!("Failed to build hyper tcp listener: {err}"))?;
+    // Bind all listeners before serving any of them, so that a bad address fails the startup
+    let mut listen_fd = ListenFd::from_env();
+    let activated_sockets = listen_fd.len();
+    if activated_sockets > 0 && activated_sockets != listeners.len() {
+        warn!(
+            "Received {} sockets from socket activation for {} listeners",
+            activated_sockets,
+            listeners.len()
+        );
+    }
+    let mut incomings = Vec::with_capacity(listeners.len());
+    for (index, listener) in listeners.iter().enumerate() {
+        let tcp_listener = bind_listener(&mut listen_fd, index, listener).await?;
+        let notary_address = tcp_listener
+            .local_addr()
+            .map_err(|err| eyre!("Failed to get address of tcp listener: {err}"))?;
+        let incoming = AddrIncoming::from_listener(tcp_listener)
+            .map_err(|err| eyre!("Failed to build hyper tcp listener: {err}"))?;
 
-    info!("Listening for TCP traffic at {}", notary_address);
+        info!("Listening for TCP traffic at {}", notary_address);
+        incomings.push(incoming);
+    }
 
-    let protocol = Arc::new(Http::new());
     let notary_globals = NotaryGlobals::new(
         notary_signing_key,
         config.notarization.clone(),
-        authorization_whitelist,
+        authorization_whitelist.as_ref().map(Arc::clone),
+        config.endpoints.clone(),
+        config.limits.clone(),
+        config.explorer.clone(),
     );
 
+    // Enable hot reload if the config file location is available
+    let _config_watcher = match cli_fields {
+        Some(cli_fields) => {
+            let watcher = watch_and_reload_config(
+                cli_fields.clone(),
+                config.clone(),
+                ReloadableState {
+                    notarization_config: notary_globals.notarization_config.clone(),
+                    limits: notary_globals.limits.clone(),
+                    tls_acceptor: tls_acceptor.as_ref().map(Arc::clone),
+                    authorization_whitelist,
+                    authorization_whitelist_watcher,
+                },
+            )?;
+            debug!("Successfully setup watcher for hot reload of config file!");
+            Some(watcher)
+        }
+        None => None,
+    };
+
     // Parameters needed for the info endpoint
-    let public_key = std::fs::read_to_string(&config.notary_key.public_key_pem_path)
-        .map_err(|err| eyre!("Failed to load notary public signing key for notarization: {err}"))?;
-    let version = env!("CARGO_PKG_VERSION").to_string();
-    let git_commit_hash = env!("GIT_COMMIT_HASH").to_string();
-    let git_commit_timestamp = env!("GIT_COMMIT_TIMESTAMP").to_string();
+    let info = InfoResponse {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        public_key,
+        git_commit_hash: env!("GIT_COMMIT_HASH").to_string(),
+        git_commit_timestamp: env!("GIT_COMMIT_TIMESTAMP").to_string(),
+    };
 
     // Parameters needed for the root / endpoint
     let html_string = config.server.html_info.clone();
     let html_info = Html(
         html_string
-            .replace("{version}", &version)
-            .replace("{git_commit_hash}", &git_commit_hash)
-            .replace("{git_commit_timestamp}", &git_commit_timestamp)
-            .replace("{public_key}", &public_key),
+            .replace("{version}", &info.version)
+            .replace("{git_commit_hash}", &info.git_commit_hash)
+            .replace("{git_commit_timestamp}", &info.git_commit_timestamp)
+            .replace("{public_key}", &info.public_key),
     );
 
-    let router = Router::new()
-        .route(
+    let servers = listeners.iter().zip(incomings).map(|(listener, incoming)| {
+        // All listeners share the session store, so that a session can be started and
+        // notarized through different listeners
+        let mut notary_globals = notary_globals.clone();
+        if !listener.authorization_enabled {
+            notary_globals.authorization_whitelist = None;
+        }
+        let tls_acceptor = if listener.tls_enabled {
+            tls_acceptor.as_ref().map(Arc::clone)
+        } else {
+            None
+        };
+        let router = build_router(notary_globals, html_info.clone(), info.clone());
+        serve(incoming, tls_acceptor, router)
+    });
+    futures_util::future::join_all(servers).await;
+
+    Ok(())
+}
+
+/// Settings of a listener, with the defaults from the rest of the config applied
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Listener {
+    pub host: String,
+    pub port: u16,
+    pub tls_enabled: bool,
+    pub authorization_enabled: bool,
+}
+
+impl Listener {
+    /// Parse the address to listen on
+    pub(crate) fn address(&self) -> Result<SocketAddr> {
+        Ok(SocketAddr::new(
+            IpAddr::V4(self.host.parse().map_err(|err| {
+                eyre!("Failed to parse notary host address from server config: {err}")
+            })?),
+            self.port,
+        ))
+    }
+}
+
+/// Take the socket passed at the given index by systemd socket activation (i.e. via `LISTEN_FDS`),
+/// or bind the address of the listener if there is none
+///
+/// With socket activation, the listening socket is kept open across restarts so that no connection
+/// is refused while the server restarts
+async fn bind_listener(
+    listen_fd: &mut ListenFd,
+    index: usize,
+    listener: &Listener,
+) -> Result<TcpListener> {
+    let activated_listener = listen_fd
+        .take_tcp_listener(index)
+        .map_err(|err| eyre!("Failed to take tcp listener from socket activation: {err}"))?;
+    if let Some(activated_listener) = activated_listener {
+        debug!("Using tcp listener {index} from socket activation");
+        activated_listener
+            .set_nonblocking(true)
+            .map_err(|err| eyre!("Failed to set tcp listener to non-blocking: {err}"))?;
+        return TcpListener::from_std(activated_listener)
+            .map_err(|err| eyre!("Failed to register tcp listener from socket activation: {err}"));
+    }
+
+    TcpListener::bind(listener.address()?)
+        .await
+        .map_err(|err| eyre!("Failed to bind server address to tcp listener: {err}"))
+}
+
+/// All listeners of the server, i.e. the one in the server config followed by the additional ones
+pub(crate) fn listeners(config: &NotaryServerProperties) -> Vec<Listener> {
+    let main_listener = Listener {
+        host: config.server.host.clone(),
+        port: config.server.port,
+        tls_enabled: config.tls.enabled,
+        authorization_enabled: config.authorization.enabled,
+    };
+    let additional_listeners = config
+        .server
+        .additional_listeners
+        .iter()
+        .map(|listener| Listener {
+            host: listener.host.clone(),
+            port: listener.port,
+            tls_enabled: listener.tls_enabled.unwrap_or(config.tls.enabled),
+            authorization_enabled: listener
+                .authorization_enabled
+                .unwrap_or(config.authorization.enabled),
+        });
+
+    std::iter::once(main_listener)
+        .chain(additional_listeners)
+        .collect()
+}
+
+/// Build the router serving the endpoints that are turned on, with the auth middleware applied if
+/// the whitelist is set
+fn build_router(
+    notary_globals: NotaryGlobals,
+    html_info: Html<String>,
+    info: InfoResponse,
+) -> Router {
+    let endpoints = &notary_globals.endpoints;
+    let max_request_size = notary_globals.limits.lock().unwrap().max_request_size;
+    let mut router = Router::new();
+    if endpoints.html_info {
+        router = router.route(
             "/",
             get(|| async move { (StatusCode::OK, html_info).into_response() }),
-        )
-        .route(
+        );
+    }
+    if endpoints.healthcheck {
+        router = router.route(
             "/healthcheck",
             get(|| async move { (StatusCode::OK, "Ok").into_response() }),
-        )
-        .route(
+        );
+    }
+    if endpoints.info {
+        router = router.route(
             "/info",
-            get(|| async move {
-                (
-                    StatusCode::OK,
-                    Json(InfoResponse {
-                        version,
-                        public_key,
-                        git_commit_hash,
-                        git_commit_timestamp,
-                    }),
-                )
-                    .into_response()
-            }),
-        )
-        .route("/session", post(initialize))
+            get(|| async move { (StatusCode::OK, Json(info)).into_response() }),
+        );
+    }
+
+    #[cfg(feature = "explorer")]
+    if notary_globals.explorer.enabled {
+        router = router
+            .route(
+                "/explorer/api/attestations",
+                get(crate::service::explorer::list_attestations),
+            )
+            .route(
+                "/explorer/api/verify",
+                post(crate::service::explorer::verify_proof),
+            );
+    }
+
+    let mut session_route = post(initialize);
+    if let Some(max_request_size) = max_request_size {
+        session_route = session_route.layer(DefaultBodyLimit::max(max_request_size));
+    }
+
+    let router = router
+        .route("/session", session_route)
         // Not applying auth middleware to /notarize endpoint for now as we can rely on our
         // short-lived session id generated from /session endpoint, as it is not possible
         // to use header for API key for websocket /notarize endpoint due to browser restriction
@@ -157,9 +326,29 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
             AuthorizationMiddleware,
             NotaryGlobals,
         >(notary_globals.clone()))
-        .route("/notarize", get(upgrade_protocol))
+        .route("/notarize", get(upgrade_protocol));
+
+    // The explorer page itself is public as browsers can't send the API key when loading it, the
+    // page sends it with its requests to the explorer APIs instead
+    #[cfg(feature = "explorer")]
+    let router = if notary_globals.explorer.enabled {
+        router.route("/explorer", get(crate::service::explorer::explorer_page))
+    } else {
+        router
+    };
+
+    router
         .layer(CorsLayer::permissive())
-        .with_state(notary_globals);
+        .with_state(notary_globals)
+}
+
+/// Accept connections on a listener and serve them with the router
+async fn serve(
+    mut listener: AddrIncoming,
+    tls_acceptor: Option<Arc<Mutex<TlsAcceptor>>>,
+    router: Router,
+) {
+    let protocol = Arc::new(Http::new());
     let mut app = router.into_make_service();
 
     loop {
@@ -174,7 +363,10 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
         };
         debug!("Received a prover's TCP connection");
 
-        let tls_acceptor = tls_acceptor.clone();
+        // Use the latest TLS acceptor as the certificate may have been hot reloaded
+        let tls_acceptor = tls_acceptor
+            .as_ref()
+            .map(|acceptor| acceptor.lock().unwrap().clone());
         let protocol = protocol.clone();
         let service = MakeService::<_, Request<hyper::Body>>::make_service(&mut app, &stream);
 
@@ -214,8 +406,93 @@ pub async fn run_server(config: &NotaryServerProperties) -> Result<(), NotarySer
     }
 }
 
+/// Create a Vault client if loading secrets from Vault is configured
+pub(crate) fn vault_client(config: &NotaryServerProperties) -> Result<Option<VaultClient>> {
+    config.vault.as_ref().map(VaultClient::new).transpose()
+}
+
+/// Whether the authorization whitelist is loaded from Vault instead of a file
+fn whitelist_in_vault(config: &NotaryServerProperties) -> bool {
+    config
+        .vault
+        .as_ref()
+        .is_some_and(|vault| vault.whitelist_path.is_some())
+}
+
+/// Path of the notary signing key in Vault, if it's loaded from Vault
+fn notary_key_vault_path(config: &NotaryServerProperties) -> Option<&str> {
+    config
+        .vault
+        .as_ref()
+        .and_then(|vault| vault.notary_key_path.as_deref())
+}
+
+/// Load notary signing key and its PEM-formatted public key, from Vault if configured or from
+/// static files otherwise
+pub(crate) async fn load_notary_keys(
+    config: &NotaryServerProperties,
+) -> Result<(SigningKey, String)> {
+    match (vault_client(config)?, notary_key_vault_path(config)) {
+        (Some(vault_client), Some(path)) => {
+            let notary_signing_key = vault_client.load_notary_signing_key(path).await?;
+            // Derive the public key so that only the private key needs to be stored in Vault
+            let public_key = notary_signing_key
+                .verifying_key()
+                .to_public_key_pem(LineEnding::LF)
+                .map_err(|err| eyre!("Failed to encode notary public signing key: {err}"))?;
+            Ok((notary_signing_key, public_key))
+        }
+        _ => {
+            let notary_signing_key = load_notary_signing_key(&config.notary_key).await?;
+            let public_key = std::fs::read_to_string(&config.notary_key.public_key_pem_path)
+                .map_err(|err| {
+                    eyre!("Failed to load notary public signing key for notarization: {err}")
+                })?;
+            Ok((notary_signing_key, public_key))
+        }
+    }
+}
+
+/// Build TLS acceptor, from Vault if configured or from static files otherwise
+pub(crate) async fn load_tls_acceptor(config: &NotaryServerProperties) -> Result<TlsAcceptor> {
+    let vault_path = config
+        .vault
+        .as_ref()
+        .and_then(|vault| vault.tls_path.as_deref());
+    match (vault_client(config)?, vault_path) {
+        (Some(vault_client), Some(path)) => vault_client.build_tls_acceptor(path).await,
+        _ => build_tls_acceptor(&config.tls).await,
+    }
+}
+
+/// Load authorization whitelist if it is enabled, from Vault if configured or from the csv file
+/// otherwise
+pub(crate) async fn load_authorization_whitelist_from_source(
+    config: &NotaryServerProperties,
+) -> Result<Option<HashMap<String, AuthorizationWhitelistRecord>>> {
+    let vault_path = config
+        .vault
+        .as_ref()
+        .and_then(|vault| vault.whitelist_path.as_deref());
+    match (vault_client(config)?, vault_path) {
+        (Some(vault_client), Some(path)) if authorization_enabled(config) => {
+            Ok(Some(vault_client.load_authorization_whitelist(path).await?))
+        }
+        _ => load_authorization_whitelist(config),
+    }
+}
+
+/// Whether authorization is turned on for any listener
+fn authorization_enabled(config: &NotaryServerProperties) -> bool {
+    listeners(config)
+        .iter()
+        .any(|listener| listener.authorization_enabled)
+}
+
 /// Load notary signing key from static file
-async fn load_notary_signing_key(config: &NotarySigningKeyProperties) -> Result<SigningKey> {
+pub(crate) async fn load_notary_signing_key(
+    config: &NotarySigningKeyProperties,
+) -> Result<SigningKey> {
     debug!("Loading notary server's signing key");
 
     let notary_signing_key = SigningKey::read_pkcs8_pem_file(&config.private_key_pem_path)
@@ -238,29 +515,64 @@ async fn load_tls_key_and_cert(
 ) -> Result<(PrivateKey, Vec<Certificate>)> {
     debug!("Loading notary server's tls private key and certificate");
 
-    let mut private_key_file_reader = read_pem_file(private_key_pem_path).await?;
-    let mut private_keys = rustls_pemfile::pkcs8_private_keys(&mut private_key_file_reader)?;
+    let private_key_file_reader = read_pem_file(private_key_pem_path).await?;
+    let certificate_file_reader = read_pem_file(certificate_pem_path).await?;
+    let (private_key, certificates) =
+        parse_tls_key_and_cert(private_key_file_reader, certificate_file_reader)?;
+
+    debug!("Successfully loaded notary server's tls private key and certificate!");
+    Ok((private_key, certificates))
+}
+
+/// Parse notary tls private key and cert from PEM-formatted buffers
+pub(crate) fn parse_tls_key_and_cert(
+    mut private_key_pem: impl BufRead,
+    mut certificate_pem: impl BufRead,
+) -> Result<(PrivateKey, Vec<Certificate>)> {
+    let mut private_keys = rustls_pemfile::pkcs8_private_keys(&mut private_key_pem)?;
     ensure!(
         private_keys.len() == 1,
         "More than 1 key found in the tls private key pem file"
     );
     let private_key = PrivateKey(private_keys.remove(0));
 
-    let mut certificate_file_reader = read_pem_file(certificate_pem_path).await?;
-    let certificates = rustls_pemfile::certs(&mut certificate_file_reader)?
+    let certificates = rustls_pemfile::certs(&mut certificate_pem)?
         .into_iter()
         .map(Certificate)
         .collect();
 
-    debug!("Successfully loaded notary server's tls private key and certificate!");
     Ok((private_key, certificates))
 }
 
+/// Build TLS acceptor using the tls private key and cert from static files
+pub(crate) async fn build_tls_acceptor(config: &TLSProperties) -> Result<TlsAcceptor> {
+    let (tls_private_key, tls_certificates) =
+        load_tls_key_and_cert(&config.private_key_pem_path, &config.certificate_pem_path).await?;
+    tls_acceptor_from_key_and_cert(tls_private_key, tls_certificates)
+}
+
+/// Build TLS acceptor using the tls private key and cert
+pub(crate) fn tls_acceptor_from_key_and_cert(
+    tls_private_key: PrivateKey,
+    tls_certificates: Vec<Certificate>,
+) -> Result<TlsAcceptor> {
+    let mut server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(tls_certificates, tls_private_key)
+        .map_err(|err| eyre!("Failed to instantiate notary server tls config: {err}"))?;
+
+    // Set the http protocols we support
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let tls_config = Arc::new(server_config);
+    Ok(TlsAcceptor::from(tls_config))
+}
+
 /// Load authorization whitelist if it is enabled
-fn load_authorization_whitelist(
+pub(crate) fn load_authorization_whitelist(
     config: &NotaryServerProperties,
 ) -> Result<Option<HashMap<String, AuthorizationWhitelistRecord>>> {
-    let authorization_whitelist = if !config.authorization.enabled {
+    let authorization_whitelist = if !authorization_enabled(config) {
         debug!("Skipping authorization as it is turned off.");
         None
     } else {
@@ -281,7 +593,7 @@ fn load_authorization_whitelist(
 // The watcher is setup in a separate thread by the notify library which is synchronous
 fn watch_and_reload_authorization_whitelist(
     config: NotaryServerProperties,
-    authorization_whitelist: Option<Arc<Mutex<HashMap<String, AuthorizationWhitelistRecord>>>>,
+    authorization_whitelist: Option<AuthorizationWhitelist>,
 ) -> Result<Option<RecommendedWatcher>> {
     // Only setup the watcher if auth whitelist is loaded
     let watcher = if let Some(authorization_whitelist) = authorization_whitelist {
@@ -333,13 +645,197 @@ fn watch_and_reload_authorization_whitelist(
     Ok(watcher)
 }
 
+/// Server state that is updated when the config file is hot reloaded
+struct ReloadableState {
+    notarization_config: Arc<Mutex<NotarizationProperties>>,
+    limits: Arc<Mutex<LimitsProperties>>,
+    /// Only available if TLS is turned on at startup
+    tls_acceptor: Option<Arc<Mutex<TlsAcceptor>>>,
+    /// Only available if authorization is turned on at startup
+    authorization_whitelist: Option<AuthorizationWhitelist>,
+    /// Watcher of the whitelist file, replaced when the whitelist path changes
+    authorization_whitelist_watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+}
+
+// Setup a watcher to detect any changes to the config file
+// When the file is modified, the watcher thread notifies a tokio task which reloads the config,
+// as rebuilding the TLS acceptor is asynchronous
+// The directory of the config file is watched rather than the file itself, as editors and
+// deployments (e.g. Kubernetes ConfigMaps) replace the file by renaming another file or swapping a
+// symlink over it, after which a watch on the replaced file no longer fires
+// On unix, SIGHUP also reloads the config, e.g. after a renewed TLS certificate was written
+// Only the notarization and session limits, logging, TLS certificate and whitelist path are
+// reloaded, other settings (e.g. server address, signing key, turning TLS or authorization on/off,
+// max request size) require a restart
+fn watch_and_reload_config(
+    cli_fields: CliFields,
+    config: NotaryServerProperties,
+    state: ReloadableState,
+) -> Result<RecommendedWatcher> {
+    let config_path = PathBuf::from(&cli_fields.config_file);
+    let config_dir = match config_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let watcher_sender = sender.clone();
+    // Setup watcher by giving it a function that will be triggered when an event is detected
+    let mut watcher = RecommendedWatcher::new(
+        move |event: Result<Event, Error>| match event {
+            Ok(event) => {
+                if is_config_change(&event, &config_path) {
+                    debug!("Config file is modified");
+                    // Receiver is only dropped together with the reload task
+                    let _ = watcher_sender.send(());
+                }
+            }
+            Err(err) => {
+                error!("Error occured when watcher detected an event: {err}")
+            }
+        },
+        notify::Config::default(),
+    )
+    .map_err(|err| eyre!("Error occured when setting up watcher for hot reload: {err}"))?;
+
+    // Start watcher to listen to any changes in the directory of the config file
+    watcher
+        .watch(&config_dir, RecursiveMode::NonRecursive)
+        .map_err(|err| eyre!("Error occured when starting up watcher for hot reload: {err}"))?;
+
+    #[cfg(unix)]
+    {
+        let mut hangup = signal(SignalKind::hangup())
+            .map_err(|err| eyre!("Error occured when listening for SIGHUP: {err}"))?;
+        tokio::spawn(async move {
+            while hangup.recv().await.is_some() {
+                debug!("Received SIGHUP");
+                if sender.send(()).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    tokio::spawn(async move {
+        let mut current_config = config;
+        while receiver.recv().await.is_some() {
+            // A single save or swap fires several events, which are handled by one reload
+            while receiver.try_recv().is_ok() {}
+            match reload_config(&cli_fields, &current_config, &state).await {
+                Ok(new_config) => {
+                    current_config = new_config;
+                    info!("Successfully reloaded config file!");
+                }
+                // Ensure that error from reloading doesn't bring the server down
+                Err(err) => error!("Failed to reload config file: {err}"),
+            }
+        }
+    });
+
+    // Need to return the watcher to parent function, else it will be dropped and stop listening
+    Ok(watcher)
+}
+
+/// Whether an event in the directory of the config file may have changed the config
+///
+/// Events on other files are ignored, unless the config file is a symlink, as its target may have
+/// been swapped by renaming another symlink in the directory
+fn is_config_change(event: &Event, config_path: &Path) -> bool {
+    if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+        return false;
+    }
+    config_path.is_symlink()
+        || event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == config_path.file_name())
+}
+
+/// Reload the config file and apply the changes that are safe to apply at runtime
+///
+/// Overrides from environment variables and the command line are applied again on top of the file.
+/// Nothing is applied if any part of the new config fails to load
+async fn reload_config(
+    cli_fields: &CliFields,
+    current_config: &NotaryServerProperties,
+    state: &ReloadableState,
+) -> Result<NotaryServerProperties> {
+    let new_config = cli_fields.load_config()?;
+
+    let new_listeners = listeners(&new_config);
+    if new_listeners != listeners(current_config)
+        || new_config.endpoints != current_config.endpoints
+        || new_config.server.html_info != current_config.server.html_info
+        || new_config.notary_key.private_key_pem_path
+            != current_config.notary_key.private_key_pem_path
+        || new_config.notary_key.public_key_pem_path
+            != current_config.notary_key.public_key_pem_path
+        || notary_key_vault_path(&new_config) != notary_key_vault_path(current_config)
+        || new_config.limits.max_request_size != current_config.limits.max_request_size
+    {
+        warn!("Changes to listeners (incl. turning TLS or authorization on/off), endpoints, notary key or max request size require a restart");
+    }
+
+    // Load everything that can fail before applying any change
+    let tls_acceptor = match &state.tls_acceptor {
+        Some(_) if new_listeners.iter().any(|listener| listener.tls_enabled) => {
+            Some(load_tls_acceptor(&new_config).await?)
+        }
+        _ => None,
+    };
+    let authorization_whitelist = match &state.authorization_whitelist {
+        Some(_) => load_authorization_whitelist_from_source(&new_config).await?,
+        None => None,
+    };
+    let whitelist_path_changed = !whitelist_in_vault(&new_config)
+        && (whitelist_in_vault(current_config)
+            || new_config.authorization.whitelist_csv_path
+                != current_config.authorization.whitelist_csv_path);
+    let authorization_whitelist_watcher = match &state.authorization_whitelist {
+        Some(whitelist) if authorization_whitelist.is_some() && whitelist_path_changed => {
+            watch_and_reload_authorization_whitelist(new_config.clone(), Some(whitelist.clone()))?
+        }
+        _ => None,
+    };
+
+    *state.notarization_config.lock().unwrap() = new_config.notarization.clone();
+    *state.limits.lock().unwrap() = new_config.limits.clone();
+    if let (Some(current), Some(new)) = (&state.tls_acceptor, tls_acceptor) {
+        *current.lock().unwrap() = new;
+        debug!("Reloaded TLS certificate");
+    }
+    if let (Some(current), Some(new)) = (&state.authorization_whitelist, authorization_whitelist) {
+        *current.lock().unwrap() = new;
+        debug!("Reloaded authorization whitelist");
+    }
+    if authorization_whitelist_watcher.is_some() {
+        *state.authorization_whitelist_watcher.lock().unwrap() = authorization_whitelist_watcher;
+        debug!("Watching new authorization whitelist file");
+    } else if whitelist_in_vault(&new_config) {
+        // Stop watching the file as the whitelist is now loaded from Vault
+        *state.authorization_whitelist_watcher.lock().unwrap() = None;
+    }
+    if new_config.logging.level != current_config.logging.level
+        || new_config.logging.filter != current_config.logging.filter
+    {
+        // Tracing may have been set up outside of the notary server, in which case it's left as is
+        if let Err(err) = reload_tracing(&new_config.logging) {
+            warn!("Logging config is not reloaded: {err}");
+        }
+    }
+
+    Ok(new_config)
+}
+
 #[cfg(test)]
 mod test {
     use std::{fs::OpenOptions, time::Duration};
 
     use csv::WriterBuilder;
+    use structopt::StructOpt;
 
-    use crate::AuthorizationProperties;
+    use crate::{AuthorizationProperties, ListenerProperties, ServerProperties};
 
     use super::*;
 
@@ -352,6 +848,46 @@ mod test {
         assert!(result.is_ok(), "Could not load tls private key and cert");
     }
 
+    #[test]
+    fn test_listeners() {
+        let config = NotaryServerProperties {
+            server: ServerProperties {
+                host: "0.0.0.0".to_string(),
+                port: 7047,
+                additional_listeners: vec![
+                    ListenerProperties {
+                        host: "10.0.0.1".to_string(),
+                        port: 7048,
+                        tls_enabled: Some(false),
+                        authorization_enabled: None,
+                    },
+                    ListenerProperties {
+                        host: "0.0.0.0".to_string(),
+                        port: 443,
+                        tls_enabled: None,
+                        authorization_enabled: Some(true),
+                    },
+                ],
+                ..Default::default()
+            },
+            tls: TLSProperties {
+                enabled: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let listeners = listeners(&config);
+        assert_eq!(listeners.len(), 3);
+        assert!(listeners[0].tls_enabled && !listeners[0].authorization_enabled);
+        assert!(!listeners[1].tls_enabled && !listeners[1].authorization_enabled);
+        assert!(listeners[2].tls_enabled && listeners[2].authorization_enabled);
+        assert_eq!(
+            listeners[1].address().unwrap(),
+            "10.0.0.1:7048".parse::<SocketAddr>().unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn test_load_notary_signing_key() {
         let config = NotarySigningKeyProperties {
@@ -419,4 +955,148 @@ mod test {
         // Delete the cloned whitelist
         std::fs::remove_file(&config.authorization.whitelist_csv_path).unwrap();
     }
+
+    // Start watching the config file, returning the reloadable notarization config
+    fn watch_config_file(
+        config_file: &Path,
+    ) -> (
+        RecommendedWatcher,
+        NotaryServerProperties,
+        Arc<Mutex<NotarizationProperties>>,
+    ) {
+        let cli_fields = CliFields::from_iter_safe([
+            "notary-server",
+            "--config-file",
+            config_file.to_str().unwrap(),
+        ])
+        .unwrap();
+        let config = cli_fields.load_config().unwrap();
+        let notarization_config = Arc::new(Mutex::new(config.notarization.clone()));
+        let watcher = watch_and_reload_config(
+            cli_fields,
+            config.clone(),
+            ReloadableState {
+                notarization_config: notarization_config.clone(),
+                limits: Arc::new(Mutex::new(config.limits.clone())),
+                tls_acceptor: None,
+                authorization_whitelist: None,
+                authorization_whitelist_watcher: Arc::new(Mutex::new(None)),
+            },
+        )
+        .expect("Watcher should be able to be setup successfully");
+        (watcher, config, notarization_config)
+    }
+
+    // Fixture config with the max transcript size changed
+    fn config_with_max_transcript_size(max_transcript_size: usize) -> String {
+        let config = CliFields::from_iter_safe(["notary-server"])
+            .unwrap()
+            .load_config()
+            .unwrap();
+        std::fs::read_to_string("./config/config.yaml")
+            .unwrap()
+            .replace(
+                &format!(
+                    "max-transcript-size: {}",
+                    config.notarization.max_transcript_size
+                ),
+                &format!("max-transcript-size: {max_transcript_size}"),
+            )
+    }
+
+    // Wait for the config to be hot reloaded, failing after a timeout
+    async fn wait_for_max_transcript_size(
+        notarization_config: &Mutex<NotarizationProperties>,
+        max_transcript_size: usize,
+    ) {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while notarization_config.lock().unwrap().max_transcript_size != max_transcript_size {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Config should be reloaded");
+    }
+
+    #[tokio::test]
+    async fn test_watch_and_reload_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("config.yaml");
+        std::fs::copy("./config/config.yaml", &config_file).unwrap();
+        let (_watcher, config, notarization_config) = watch_config_file(&config_file);
+
+        // Lower the max transcript size to trigger modify event
+        let new_max_transcript_size = config.notarization.max_transcript_size / 2;
+        std::fs::write(
+            &config_file,
+            config_with_max_transcript_size(new_max_transcript_size),
+        )
+        .unwrap();
+
+        wait_for_max_transcript_size(&notarization_config, new_max_transcript_size).await;
+    }
+
+    #[tokio::test]
+    async fn test_watch_and_reload_config_replaced_by_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_file = dir.path().join("config.yaml");
+        std::fs::copy("./config/config.yaml", &config_file).unwrap();
+        let (_watcher, config, notarization_config) = watch_config_file(&config_file);
+
+        // Replace the config file atomically, as editors do
+        let new_max_transcript_size = config.notarization.max_transcript_size / 2;
+        let new_config_file = dir.path().join("config.yaml.tmp");
+        std::fs::write(
+            &new_config_file,
+            config_with_max_transcript_size(new_max_transcript_size),
+        )
+        .unwrap();
+        std::fs::rename(&new_config_file, &config_file).unwrap();
+
+        wait_for_max_transcript_size(&notarization_config, new_max_transcript_size).await;
+
+        // The replacing file is watched as well
+        let newer_max_transcript_size = new_max_transcript_size / 2;
+        std::fs::write(
+            &config_file,
+            config_with_max_transcript_size(newer_max_transcript_size),
+        )
+        .unwrap();
+
+        wait_for_max_transcript_size(&notarization_config, newer_max_transcript_size).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_watch_and_reload_config_symlink_swap() {
+        use std::os::unix::fs::symlink;
+
+        // Layout of a Kubernetes ConfigMap volume, where the data directory symlink is swapped
+        let dir = tempfile::tempdir().unwrap();
+        let config = CliFields::from_iter_safe(["notary-server"])
+            .unwrap()
+            .load_config()
+            .unwrap();
+        let new_max_transcript_size = config.notarization.max_transcript_size / 2;
+        for (version, max_transcript_size) in [
+            ("v1", config.notarization.max_transcript_size),
+            ("v2", new_max_transcript_size),
+        ] {
+            std::fs::create_dir(dir.path().join(version)).unwrap();
+            std::fs::write(
+                dir.path().join(version).join("config.yaml"),
+                config_with_max_transcript_size(max_transcript_size),
+            )
+            .unwrap();
+        }
+        symlink("v1", dir.path().join("..data")).unwrap();
+        let config_file = dir.path().join("config.yaml");
+        symlink("..data/config.yaml", &config_file).unwrap();
+        let (_watcher, _, notarization_config) = watch_config_file(&config_file);
+
+        symlink("v2", dir.path().join("..data_tmp")).unwrap();
+        std::fs::rename(dir.path().join("..data_tmp"), dir.path().join("..data")).unwrap();
+
+        wait_for_max_transcript_size(&notarization_config, new_max_transcript_size).await;
+    }
 }
@@ -1,22 +1,17 @@
-use eyre::Result;
-use std::str::FromStr;
+use eyre::{eyre, Result};
+use std::{str::FromStr, sync::OnceLock};
 use tracing::Level;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Registry};
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
 
-use crate::config::NotaryServerProperties;
+use crate::config::{LoggingProperties, NotaryServerProperties};
+
+/// Handle to swap the log filter of the global subscriber, used for hot reload of the logging config
+static FILTER_RELOAD_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
 
 pub fn init_tracing(config: &NotaryServerProperties) -> Result<()> {
-    // Retrieve log filtering logic from config
-    let directives = match &config.logging.filter {
-        // Use custom filter that is provided by user
-        Some(filter) => filter.clone(),
-        // Use the default filter when only verbosity level is provided
-        None => {
-            let level = Level::from_str(&config.logging.level)?;
-            format!("notary_server={level},tlsn_verifier={level},tls_mpc={level}")
-        }
-    };
-    let filter_layer = EnvFilter::builder().parse(directives)?;
+    let (filter_layer, reload_handle) = reload::Layer::new(build_filter(&config.logging)?);
 
     // Format the log
     let format_layer = tracing_subscriber::fmt::layer()
@@ -30,5 +25,33 @@ pub fn init_tracing(config: &NotaryServerProperties) -> Result<()> {
         .with(format_layer)
         .try_init()?;
 
+    let _ = FILTER_RELOAD_HANDLE.set(reload_handle);
+
     Ok(())
 }
+
+/// Replace the log filter of the global subscriber with the one from the given logging config
+pub fn reload_tracing(config: &LoggingProperties) -> Result<()> {
+    // Tracing may have been set up by the caller instead, e.g. in tests
+    let Some(reload_handle) = FILTER_RELOAD_HANDLE.get() else {
+        return Err(eyre!("Tracing was not set up by the notary server"));
+    };
+    reload_handle
+        .reload(build_filter(config)?)
+        .map_err(|err| eyre!("Failed to reload log filter: {err}"))
+}
+
+/// Build the log filter from the logging config
+pub(crate) fn build_filter(config: &LoggingProperties) -> Result<EnvFilter> {
+    // Retrieve log filtering logic from config
+    let directives = match &config.filter {
+        // Use custom filter that is provided by user
+        Some(filter) => filter.clone(),
+        // Use the default filter when only verbosity level is provided
+        None => {
+            let level = Level::from_str(&config.level)?;
+            format!("notary_server={level},tlsn_verifier={level},tls_mpc={level}")
+        }
+    };
+    Ok(EnvFilter::builder().parse(directives)?)
+}
@@ -1,31 +1,40 @@
 pub mod axum_websocket;
+#[cfg(feature = "explorer")]
+pub mod explorer;
+pub mod guard;
 pub mod tcp;
+pub mod threads;
 pub mod websocket;
 
 use async_trait::async_trait;
 use axum::{
     extract::{rejection::JsonRejection, FromRequestParts, Query, State},
-    http::{header, request::Parts, StatusCode},
+    http::{header, request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Json, Response},
 };
 use axum_macros::debug_handler;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use p256::ecdsa::{Signature, SigningKey};
+use std::{collections::HashMap, time::Duration};
+use tlsn_common::config::DEFAULT_MAX_THREADS;
+use tlsn_core::SessionHeader;
 use tlsn_verifier::tls::{Verifier, VerifierConfig};
 use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_util::compat::TokioAsyncReadCompatExt;
 use tracing::{debug, error, info, trace};
-use uuid::Uuid;
 
 use crate::{
+    config::{LimitsProperties, NotarizationProperties},
     domain::notary::{
-        NotarizationRequestQuery, NotarizationSessionRequest, NotarizationSessionResponse,
-        NotaryGlobals, SessionData,
+        ClientType, NotarizationRequestQuery, NotarizationSessionRequest,
+        NotarizationSessionResponse, NotaryGlobals, SessionData,
     },
     error::NotaryServerError,
     service::{
         axum_websocket::{header_eq, WebSocketUpgrade},
+        guard::{ResourceGuard, ResourceLimits},
         tcp::{tcp_notarize, TcpUpgrade},
+        threads::{ThreadLease, ThreadPool},
         websocket::websocket_notarize,
     },
 };
@@ -74,21 +83,37 @@ pub async fn upgrade_protocol(
     Query(params): Query<NotarizationRequestQuery>,
 ) -> Response {
     info!("Received upgrade protocol request");
+    let client_type = match &protocol_upgrade {
+        ProtocolUpgrade::Ws(_) => ClientType::Websocket,
+        ProtocolUpgrade::Tcp(_) => ClientType::Tcp,
+    };
+    if !notary_globals.client_type_enabled(&client_type) {
+        let err_msg = format!("{:?} client is turned off", client_type);
+        error!(err_msg);
+        return NotaryServerError::BadProverRequest(err_msg).into_response();
+    }
     let session_id = params.session_id;
     // Fetch the configuration data from the store using the session_id
     // This also removes the configuration data from the store as each session_id can only be used once
-    let (max_sent_data, max_recv_data) = match notary_globals.store.lock().await.remove(&session_id)
-    {
-        Some(data) => (data.max_sent_data, data.max_recv_data),
+    let mut store = notary_globals.store.lock().await;
+    let pending_session_ttl = notary_globals.limits.lock().unwrap().pending_session_ttl();
+    remove_expired_sessions(&mut store, pending_session_ttl, Utc::now());
+    let (max_sent_data, max_recv_data, api_key) = match store.remove(&session_id) {
+        Some(data) => (data.max_sent_data, data.max_recv_data, data.api_key),
         None => {
             let err_msg = format!("Session id {} does not exist", session_id);
             error!(err_msg);
             return NotaryServerError::BadProverRequest(err_msg).into_response();
         }
     };
+    // Count the session against its API key until the notarization finishes, while still holding
+    // the store lock so that the session is never missed by the limit checks in /session
+    let active_session_guard = api_key.map(|api_key| notary_globals.start_active_session(api_key));
+    drop(store);
     // This completes the HTTP Upgrade request and returns a successful response to the client, meanwhile initiating the websocket or tcp connection
     match protocol_upgrade {
-        ProtocolUpgrade::Ws(ws) => ws.on_upgrade(move |socket| {
+        ProtocolUpgrade::Ws(ws) => ws.on_upgrade(move |socket| async move {
+            let _active_session_guard = active_session_guard;
             websocket_notarize(
                 socket,
                 notary_globals,
@@ -96,8 +121,10 @@ pub async fn upgrade_protocol(
                 max_sent_data,
                 max_recv_data,
             )
+            .await
         }),
-        ProtocolUpgrade::Tcp(tcp) => tcp.on_upgrade(move |stream| {
+        ProtocolUpgrade::Tcp(tcp) => tcp.on_upgrade(move |stream| async move {
+            let _active_session_guard = active_session_guard;
             tcp_notarize(
                 stream,
                 notary_globals,
@@ -105,6 +132,7 @@ pub async fn upgrade_protocol(
                 max_sent_data,
                 max_recv_data,
             )
+            .await
         }),
     }
 }
@@ -113,6 +141,7 @@ pub async fn upgrade_protocol(
 #[debug_handler(state = NotaryGlobals)]
 pub async fn initialize(
     State(notary_globals): State<NotaryGlobals>,
+    headers: HeaderMap,
     payload: Result<Json<NotarizationSessionRequest>, JsonRejection>,
 ) -> impl IntoResponse {
     info!(
@@ -129,14 +158,24 @@ pub async fn initialize(
         }
     };
 
+    // Reject early if the client won't be able to notarize
+    if !notary_globals.client_type_enabled(&payload.client_type) {
+        let err_msg = format!("{:?} client is turned off", payload.client_type);
+        error!(err_msg);
+        return NotaryServerError::BadProverRequest(err_msg).into_response();
+    }
+
     // Ensure that the max_transcript_size submitted is not larger than the global max limit configured in notary server
-    if payload.max_sent_data.is_some() || payload.max_recv_data.is_some() {
-        let requested_transcript_size =
-            payload.max_sent_data.unwrap_or_default() + payload.max_recv_data.unwrap_or_default();
-        if requested_transcript_size > notary_globals.notarization_config.max_transcript_size {
+    if let Some(requested_transcript_size) = payload.requested_transcript_size() {
+        let max_transcript_size = notary_globals
+            .notarization_config
+            .lock()
+            .unwrap()
+            .max_transcript_size;
+        if requested_transcript_size > max_transcript_size {
             error!(
                 "Max transcript size requested {:?} exceeds the maximum threshold {:?}",
-                requested_transcript_size, notary_globals.notarization_config.max_transcript_size
+                requested_transcript_size, max_transcript_size
             );
             return NotaryServerError::BadProverRequest(
                 "Max transcript size requested exceeds the maximum threshold".to_string(),
@@ -145,17 +184,41 @@ pub async fn initialize(
         }
     }
 
-    let prover_session_id = Uuid::new_v4().to_string();
+    // The API key has already been checked by the authorization middleware if the whitelist is set
+    let api_key = notary_globals
+        .authorization_whitelist
+        .as_ref()
+        .and_then(|_| headers.get(header::AUTHORIZATION))
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let prover_session_id = notary_globals.new_session_id();
+
+    // Hold the store lock while checking the limits so that concurrent requests can't exceed them
+    let mut store = notary_globals.store.lock().await;
+    let limits = notary_globals.limits.lock().unwrap().clone();
+    remove_expired_sessions(&mut store, limits.pending_session_ttl(), Utc::now());
+    if let Err(err) = check_session_limits(
+        &limits,
+        &store,
+        &notary_globals.active_sessions.lock().unwrap(),
+        api_key.as_deref(),
+    ) {
+        error!("{err}");
+        return err.into_response();
+    }
 
     // Store the configuration data in a temporary store
-    notary_globals.store.lock().await.insert(
+    store.insert(
         prover_session_id.clone(),
         SessionData {
             max_sent_data: payload.max_sent_data,
             max_recv_data: payload.max_recv_data,
             created_at: Utc::now(),
+            api_key,
         },
     );
+    drop(store);
 
     trace!("Latest store state: {:?}", notary_globals.store);
 
@@ -169,6 +232,59 @@ pub async fn initialize(
         .into_response()
 }
 
+/// Remove the sessions that were created but not notarized within the ttl, so that abandoned
+/// sessions don't count against the session limits forever
+fn remove_expired_sessions(
+    store: &mut HashMap<String, SessionData>,
+    ttl: Duration,
+    now: DateTime<Utc>,
+) {
+    store.retain(|session_id, data| {
+        let expired = now
+            .signed_duration_since(data.created_at)
+            .to_std()
+            .is_ok_and(|age| age >= ttl);
+        if expired {
+            debug!(?session_id, "Removing expired session");
+        }
+        !expired
+    });
+}
+
+/// Check that creating another session doesn't exceed the max pending sessions, or the max
+/// sessions of its API key
+fn check_session_limits(
+    limits: &LimitsProperties,
+    store: &HashMap<String, SessionData>,
+    active_sessions: &HashMap<String, usize>,
+    api_key: Option<&str>,
+) -> Result<(), NotaryServerError> {
+    if let Some(max_pending_sessions) = limits.max_pending_sessions {
+        if store.len() >= max_pending_sessions {
+            return Err(NotaryServerError::TooManySessions(format!(
+                "Pending sessions have reached the maximum of {max_pending_sessions}"
+            )));
+        }
+    }
+
+    if let (Some(max_sessions_per_api_key), Some(api_key)) =
+        (limits.max_sessions_per_api_key, api_key)
+    {
+        let pending_sessions = store
+            .values()
+            .filter(|data| data.api_key.as_deref() == Some(api_key))
+            .count();
+        let active_sessions = active_sessions.get(api_key).copied().unwrap_or_default();
+        if pending_sessions + active_sessions >= max_sessions_per_api_key {
+            return Err(NotaryServerError::TooManySessions(format!(
+                "Sessions of the API key have reached the maximum of {max_sessions_per_api_key}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
 /// Run the notarization
 pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
     socket: T,
@@ -176,7 +292,10 @@ pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
     session_id: &str,
     max_sent_data: Option<usize>,
     max_recv_data: Option<usize>,
-) -> Result<(), NotaryServerError> {
+    notarization_config: &NotarizationProperties,
+    limits: &LimitsProperties,
+    thread_pool: Option<&ThreadPool>,
+) -> Result<SessionHeader, NotaryServerError> {
     debug!(?session_id, "Starting notarization...");
 
     let mut config_builder = VerifierConfig::builder();
@@ -191,11 +310,150 @@ pub async fn notary_service<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
         config_builder = config_builder.max_recv_data(max_recv_data);
     }
 
-    let config = config_builder.build()?;
+    config_builder = config_builder.memory_budget(notarization_config.memory_budget);
 
-    Verifier::new(config)
-        .notarize::<_, Signature>(socket.compat(), signing_key)
+    #[cfg(feature = "deterministic")]
+    if let Some(rng_seed) = notarization_config.rng_seed {
+        config_builder = config_builder.rng_seed(rng_seed);
+    }
+
+    let max_threads = notarization_config
+        .max_threads
+        .unwrap_or(DEFAULT_MAX_THREADS);
+    let resource_limits = ResourceLimits {
+        poll_time: notarization_config
+            .max_poll_time_secs
+            .map(Duration::from_secs),
+        memory: notarization_config.max_memory_bytes,
+    };
+    let notarize = async move {
+        // Reserve the session's threads before any setup, the wait counts against the session
+        // duration
+        let lease = match thread_pool {
+            Some(thread_pool) => Some(thread_pool.acquire(max_threads).await),
+            None => None,
+        };
+        let max_threads = lease.as_ref().map_or(max_threads, ThreadLease::threads);
+        debug!(?session_id, max_threads, "Reserved VM threads");
+
+        let config = config_builder.max_threads(max_threads).build()?;
+        let header = ResourceGuard::new(
+            async move {
+                Verifier::new(config)
+                    .notarize::<_, Signature>(socket.compat(), signing_key)
+                    .await
+                    .map_err(NotaryServerError::from)
+            },
+            resource_limits,
+        )
         .await?;
 
-    Ok(())
+        drop(lease);
+        Ok::<_, NotaryServerError>(header)
+    };
+
+    let header = match limits.max_session_duration_secs.map(Duration::from_secs) {
+        Some(max_session_duration) => tokio::time::timeout(max_session_duration, notarize)
+            .await
+            .map_err(|_| NotaryServerError::SessionDurationExceeded(max_session_duration))??,
+        None => notarize.await?,
+    };
+
+    Ok(header)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn session_data(api_key: Option<&str>) -> SessionData {
+        SessionData {
+            max_sent_data: None,
+            max_recv_data: None,
+            created_at: Utc::now(),
+            api_key: api_key.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_check_session_limits() {
+        let limits = LimitsProperties {
+            max_sessions_per_api_key: Some(2),
+            max_pending_sessions: Some(3),
+            ..Default::default()
+        };
+        let mut store = HashMap::new();
+        let mut active_sessions = HashMap::new();
+        store.insert("0".to_string(), session_data(Some("key-0")));
+        active_sessions.insert("key-0".to_string(), 1);
+
+        // Pending and active sessions both count against the API key
+        assert!(matches!(
+            check_session_limits(&limits, &store, &active_sessions, Some("key-0")),
+            Err(NotaryServerError::TooManySessions(_))
+        ));
+        assert!(check_session_limits(&limits, &store, &active_sessions, Some("key-1")).is_ok());
+
+        // Active sessions don't count against the max pending sessions
+        store.insert("1".to_string(), session_data(None));
+        assert!(check_session_limits(&limits, &store, &active_sessions, None).is_ok());
+        store.insert("2".to_string(), session_data(None));
+        assert!(matches!(
+            check_session_limits(&limits, &store, &active_sessions, None),
+            Err(NotaryServerError::TooManySessions(_))
+        ));
+
+        // Nothing is enforced without limits
+        assert!(check_session_limits(
+            &LimitsProperties::default(),
+            &store,
+            &active_sessions,
+            Some("key-0")
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_expired_sessions_dont_count_against_limits() {
+        let limits = LimitsProperties {
+            max_sessions_per_api_key: Some(1),
+            max_pending_sessions: Some(1),
+            pending_session_ttl_secs: Some(60),
+            ..Default::default()
+        };
+        let mut store = HashMap::new();
+        let active_sessions = HashMap::new();
+        let abandoned = session_data(Some("key-0"));
+        let now = abandoned.created_at;
+        store.insert("0".to_string(), abandoned);
+
+        // An abandoned session counts against the limits until it expires
+        remove_expired_sessions(&mut store, limits.pending_session_ttl(), now);
+        assert!(matches!(
+            check_session_limits(&limits, &store, &active_sessions, Some("key-0")),
+            Err(NotaryServerError::TooManySessions(_))
+        ));
+
+        let later = now + chrono::Duration::seconds(60);
+        remove_expired_sessions(&mut store, limits.pending_session_ttl(), later);
+        assert!(store.is_empty());
+        assert!(check_session_limits(&limits, &store, &active_sessions, Some("key-0")).is_ok());
+    }
+
+    #[test]
+    fn test_requested_transcript_size_saturates() {
+        let request = NotarizationSessionRequest {
+            client_type: ClientType::Tcp,
+            max_sent_data: Some(usize::MAX),
+            max_recv_data: Some(2),
+        };
+        assert_eq!(request.requested_transcript_size(), Some(usize::MAX));
+
+        let request = NotarizationSessionRequest {
+            max_sent_data: None,
+            max_recv_data: None,
+            ..request
+        };
+        assert_eq!(request.requested_transcript_size(), None);
+    }
 }
@@ -0,0 +1,245 @@
+use axum::{
+    body::Bytes,
+    extract::State,
+    response::{Html, IntoResponse, Json},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::VecDeque;
+use tlsn_core::{
+    proof::{default_cert_verifier, VerificationReport},
+    NotaryPublicKey, SessionHeader,
+};
+use tlsn_verifier::compat::CompatProof;
+use tracing::debug;
+
+use crate::{domain::notary::NotaryGlobals, error::NotaryServerError};
+
+/// The single page of the explorer, which calls the APIs below
+const EXPLORER_HTML: &str = include_str!("explorer.html");
+/// Scope of the nullifiers identifying attestations in the explorer
+const ATTESTATION_ID_SCOPE: &[u8] = b"notary-server/explorer";
+
+/// Returns the hex encoded id of the attestation of a session header signed by the notary
+///
+/// The id is derived from the whole signed header, unlike the Merkle root which is the same for
+/// all handshake-only sessions
+pub fn attestation_id(header: &SessionHeader, notary_key: &NotaryPublicKey) -> String {
+    to_hex(&header.nullifier(notary_key, ATTESTATION_ID_SCOPE))
+}
+
+/// Metadata of an attestation signed by this notary
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationRecord {
+    /// Hex encoded id of the attestation, see [`attestation_id`]
+    pub attestation_id: String,
+    pub session_id: String,
+    pub notarized_at: DateTime<Utc>,
+    /// Time of the TLS handshake in seconds since the unix epoch
+    pub session_time: u64,
+    pub sent_len: usize,
+    pub recv_len: usize,
+    /// Hex encoded root of the transcript commitments
+    pub merkle_root: String,
+    pub handshake_only: bool,
+}
+
+impl AttestationRecord {
+    pub fn new(session_id: &str, header: &SessionHeader, notary_key: &NotaryPublicKey) -> Self {
+        Self {
+            attestation_id: attestation_id(header, notary_key),
+            session_id: session_id.to_string(),
+            notarized_at: Utc::now(),
+            session_time: header.time(),
+            sent_len: header.sent_len(),
+            recv_len: header.recv_len(),
+            merkle_root: to_hex(&header.merkle_root().to_inner()),
+            handshake_only: header.is_handshake_only(),
+        }
+    }
+}
+
+/// In-memory store of the latest attestations, which are lost on restart
+#[derive(Debug)]
+pub struct AttestationStore {
+    records: VecDeque<AttestationRecord>,
+    max_attestations: usize,
+}
+
+impl AttestationStore {
+    pub fn new(max_attestations: usize) -> Self {
+        Self {
+            records: VecDeque::new(),
+            max_attestations,
+        }
+    }
+
+    /// Add an attestation, dropping the oldest one if the store is full
+    pub fn record(&mut self, record: AttestationRecord) {
+        if self.max_attestations == 0 {
+            return;
+        }
+        if self.records.len() == self.max_attestations {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+    }
+
+    /// Attestations from the newest to the oldest
+    pub fn iter(&self) -> impl Iterator<Item = &AttestationRecord> {
+        self.records.iter().rev()
+    }
+
+    /// Find the attestation with the given id
+    pub fn find(&self, attestation_id: &str) -> Option<&AttestationRecord> {
+        self.records
+            .iter()
+            .find(|record| record.attestation_id == attestation_id)
+    }
+}
+
+/// Response object of an attestation, with its revocation status
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(flatten)]
+    pub record: AttestationRecord,
+    pub revoked: bool,
+}
+
+/// Response object of the /explorer/api/verify API
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyResponse {
+    /// Dialect of the proof, see [`tlsn_verifier::compat::Dialect`]
+    pub dialect: String,
+    pub valid: bool,
+    pub report: VerificationReport,
+    /// Attestation of the proof, if it is still in the store
+    pub attestation: Option<AttestationResponse>,
+}
+
+/// Handler of /explorer, serving the explorer page
+pub async fn explorer_page() -> impl IntoResponse {
+    Html(EXPLORER_HTML)
+}
+
+/// Handler of /explorer/api/attestations, listing the stored attestations
+pub async fn list_attestations(
+    State(notary_globals): State<NotaryGlobals>,
+) -> Json<Vec<AttestationResponse>> {
+    let store = notary_globals.attestations.lock().unwrap();
+    Json(
+        store
+            .iter()
+            .map(|record| attestation_response(&notary_globals, record))
+            .collect(),
+    )
+}
+
+/// Handler of /explorer/api/verify, verifying an uploaded proof file against the key of this
+/// notary and the webpki roots
+pub async fn verify_proof(
+    State(notary_globals): State<NotaryGlobals>,
+    body: Bytes,
+) -> Result<Json<VerifyResponse>, NotaryServerError> {
+    let proof = CompatProof::parse(&body)
+        .map_err(|err| NotaryServerError::BadProverRequest(format!("Invalid proof file: {err}")))?;
+    let dialect = format!("{:?}", proof.dialect());
+
+    let public_key = NotaryPublicKey::from(p256::PublicKey::from(
+        notary_globals.notary_signing_key.verifying_key(),
+    ));
+    let attestation_id = attestation_id(&proof.proof().session.header, &public_key);
+    let (report, _) = proof.verify_with_report(public_key, &default_cert_verifier());
+    debug!(?report, "Verified uploaded proof");
+
+    let attestation = notary_globals
+        .attestations
+        .lock()
+        .unwrap()
+        .find(&attestation_id)
+        .map(|record| attestation_response(&notary_globals, record));
+    let revoked = attestation
+        .as_ref()
+        .is_some_and(|attestation| attestation.revoked);
+
+    Ok(Json(VerifyResponse {
+        dialect,
+        valid: report.is_valid() && !revoked,
+        report,
+        attestation,
+    }))
+}
+
+fn attestation_response(
+    notary_globals: &NotaryGlobals,
+    record: &AttestationRecord,
+) -> AttestationResponse {
+    AttestationResponse {
+        revoked: notary_globals
+            .explorer
+            .revoked_sessions
+            .contains(&record.session_id),
+        record: record.clone(),
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(session_id: &str) -> AttestationRecord {
+        AttestationRecord {
+            attestation_id: to_hex(session_id.as_bytes()),
+            session_id: session_id.to_string(),
+            notarized_at: Utc::now(),
+            session_time: 1_700_000_000,
+            sent_len: 128,
+            recv_len: 1024,
+            merkle_root: to_hex(session_id.as_bytes()),
+            handshake_only: false,
+        }
+    }
+
+    #[test]
+    fn test_attestation_store_drops_oldest() {
+        let mut store = AttestationStore::new(2);
+        store.record(record("a"));
+        store.record(record("b"));
+        store.record(record("c"));
+
+        let session_ids: Vec<_> = store.iter().map(|r| r.session_id.as_str()).collect();
+        assert_eq!(session_ids, vec!["c", "b"]);
+        assert!(store.find(&to_hex(b"a")).is_none());
+        assert_eq!(store.find(&to_hex(b"b")).unwrap().session_id, "b");
+    }
+
+    #[test]
+    fn test_attestation_store_handshake_only() {
+        // Handshake-only sessions share the placeholder Merkle root
+        let mut store = AttestationStore::new(2);
+        for session_id in ["a", "b"] {
+            store.record(AttestationRecord {
+                merkle_root: to_hex(&[0u8; 32]),
+                handshake_only: true,
+                ..record(session_id)
+            });
+        }
+
+        assert_eq!(store.find(&to_hex(b"a")).unwrap().session_id, "a");
+        assert_eq!(store.find(&to_hex(b"b")).unwrap().session_id, "b");
+    }
+
+    #[test]
+    fn test_attestation_store_disabled() {
+        let mut store = AttestationStore::new(0);
+        store.record(record("a"));
+        assert_eq!(store.iter().count(), 0);
+    }
+}
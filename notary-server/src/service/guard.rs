@@ -0,0 +1,296 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use crate::NotaryServerError;
+
+/// The limits a [ResourceGuard] enforces, `None` disables a limit.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResourceLimits {
+    /// Maximum time the session may spend being polled.
+    pub poll_time: Option<Duration>,
+    /// Maximum number of bytes the session may have allocated at once.
+    pub memory: Option<usize>,
+}
+
+/// A future which aborts a notarization once it exceeds its [ResourceLimits].
+///
+/// Only the work done while the session's future is polled is counted, on the thread polling it:
+/// - The poll time is the wall time spent in `poll`. Unlike a wall-clock timeout, time spent
+///   waiting on the prover is not counted, so slow links are not penalized while runaway
+///   computations are. It is not CPU time, a thread preempted during `poll` is still counted.
+/// - The memory is the high-water mark of the bytes allocated minus the bytes freed during `poll`,
+///   tracked by the [TrackingAllocator] which must be installed as the global allocator.
+///
+/// Garbling, OT and other work offloaded to backend or rayon threads is not attributed to the
+/// session, so both limits bound the session's own task rather than the total it costs the host.
+pub struct ResourceGuard<F> {
+    fut: Pin<Box<F>>,
+    limits: ResourceLimits,
+    poll_time: Duration,
+    // Boxed so that its address stays the same while the allocator refers to it.
+    memory: Box<MemoryUsage>,
+}
+
+impl<F> ResourceGuard<F> {
+    /// Wraps a future, enforcing the provided limits.
+    pub fn new(fut: F, limits: ResourceLimits) -> Self {
+        Self {
+            fut: Box::pin(fut),
+            limits,
+            poll_time: Duration::ZERO,
+            memory: Box::default(),
+        }
+    }
+
+    /// Returns the high-water mark of the bytes allocated by the session.
+    pub fn peak_memory(&self) -> usize {
+        self.memory.peak.get()
+    }
+}
+
+impl<F, T> Future for ResourceGuard<F>
+where
+    F: Future<Output = Result<T, NotaryServerError>>,
+{
+    type Output = Result<T, NotaryServerError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        let start = Instant::now();
+        let current = CurrentUsage::enter(&this.memory);
+        let poll = this.fut.as_mut().poll(cx);
+        drop(current);
+        this.poll_time += start.elapsed();
+
+        if poll.is_ready() {
+            return poll;
+        }
+
+        match this.limits {
+            ResourceLimits {
+                poll_time: Some(limit),
+                ..
+            } if this.poll_time > limit => {
+                Poll::Ready(Err(NotaryServerError::PollTimeExceeded(limit)))
+            }
+            ResourceLimits {
+                memory: Some(limit),
+                ..
+            } if this.memory.peak.get() > limit => {
+                Poll::Ready(Err(NotaryServerError::MemoryExceeded(limit)))
+            }
+            _ => poll,
+        }
+    }
+}
+
+/// The bytes allocated by a session.
+#[derive(Default)]
+struct MemoryUsage {
+    /// Bytes allocated minus bytes freed, negative if the session freed memory allocated
+    /// elsewhere.
+    current: Cell<isize>,
+    peak: Cell<usize>,
+}
+
+impl MemoryUsage {
+    fn add(&self, delta: isize) {
+        let current = self.current.get().saturating_add(delta);
+        self.current.set(current);
+        self.peak.set(self.peak.get().max(current.max(0) as usize));
+    }
+}
+
+thread_local! {
+    /// The usage of the session polled on this thread, if any.
+    static CURRENT: Cell<*const MemoryUsage> = const { Cell::new(std::ptr::null()) };
+}
+
+/// Attributes the allocations on this thread to a session until dropped.
+///
+/// The previous usage is restored on drop, so also when the session's future panics and the
+/// runtime keeps using the thread, after which the allocator must not refer to the freed guard.
+struct CurrentUsage {
+    previous: *const MemoryUsage,
+}
+
+impl CurrentUsage {
+    fn enter(usage: &MemoryUsage) -> Self {
+        Self {
+            previous: CURRENT.with(|current| current.replace(usage)),
+        }
+    }
+}
+
+impl Drop for CurrentUsage {
+    fn drop(&mut self) {
+        let _ = CURRENT.try_with(|current| current.set(self.previous));
+    }
+}
+
+fn track(delta: isize) {
+    // The thread local may already be destroyed while the thread exits.
+    let _ = CURRENT.try_with(|current| {
+        let usage = current.get();
+        if !usage.is_null() {
+            // SAFETY: the pointer is only set while the guard owning the usage is polled on this
+            // thread, which keeps it alive, and is reset by `CurrentUsage` even if the poll panics.
+            unsafe { (*usage).add(delta) }
+        }
+    });
+}
+
+/// A global allocator which attributes the memory allocated while polling a [ResourceGuard] to
+/// its session.
+///
+/// It wraps the system allocator and must be installed with `#[global_allocator]` for memory
+/// limits to take effect.
+pub struct TrackingAllocator;
+
+// SAFETY: all allocations are delegated to the system allocator, only their sizes are tracked.
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        track(-(layout.size() as isize));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            track(new_size as isize - layout.size() as isize);
+        }
+        new_ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+    fn poll_time_limit(limit: Duration) -> ResourceLimits {
+        ResourceLimits {
+            poll_time: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    fn memory_limit(limit: usize) -> ResourceLimits {
+        ResourceLimits {
+            memory: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_poll_time_guard() {
+        let busy = async {
+            for _ in 0..100 {
+                std::thread::sleep(Duration::from_millis(5));
+                tokio::task::yield_now().await;
+            }
+            Ok(())
+        };
+
+        let result = ResourceGuard::new(busy, poll_time_limit(Duration::from_millis(20))).await;
+
+        assert!(matches!(
+            result,
+            Err(NotaryServerError::PollTimeExceeded(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_poll_time_guard_idle() {
+        let idle = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(())
+        };
+
+        ResourceGuard::new(idle, poll_time_limit(Duration::from_millis(20)))
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_usage_reset_after_panic() {
+        let session = async {
+            if true {
+                panic!("session panicked");
+            }
+            Ok(())
+        };
+        let mut guard = ResourceGuard::new(session, ResourceLimits::default());
+        let mut cx = Context::from_waker(futures_util::task::noop_waker_ref());
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            Pin::new(&mut guard).poll(&mut cx)
+        }));
+        assert!(result.is_err());
+        drop(guard);
+
+        // Allocations after the panic are not attributed to the freed guard.
+        assert!(CURRENT.with(|current| current.get().is_null()));
+        drop(vec![1u8; 1 << 20]);
+    }
+
+    #[tokio::test]
+    async fn test_memory_guard() {
+        let hungry = async {
+            let mut buffers = Vec::new();
+            for _ in 0..100 {
+                buffers.push(vec![1u8; 1 << 20]);
+                tokio::task::yield_now().await;
+            }
+            Ok(buffers.len())
+        };
+
+        let result = ResourceGuard::new(hungry, memory_limit(8 << 20)).await;
+
+        assert!(matches!(result, Err(NotaryServerError::MemoryExceeded(_))));
+    }
+
+    #[tokio::test]
+    async fn test_memory_guard_freed() {
+        // Memory freed in between polls does not add up.
+        let churn = async {
+            for _ in 0..100 {
+                let buffer = vec![1u8; 1 << 20];
+                tokio::task::yield_now().await;
+                drop(buffer);
+            }
+            Ok(())
+        };
+
+        let mut guard = ResourceGuard::new(churn, memory_limit(8 << 20));
+        (&mut guard).await.unwrap();
+
+        assert!(guard.peak_memory() >= 1 << 20);
+        assert!(guard.peak_memory() < 8 << 20);
+    }
+}
@@ -84,17 +84,24 @@ pub async fn tcp_notarize(
     max_recv_data: Option<usize>,
 ) {
     debug!(?session_id, "Upgraded to tcp connection");
+    // Take a snapshot so that a hot reload doesn't change the limits mid-session
+    let notarization_config = notary_globals.notarization_config.lock().unwrap().clone();
+    let limits = notary_globals.limits.lock().unwrap().clone();
     match notary_service(
         stream,
         &notary_globals.notary_signing_key,
         &session_id,
         max_sent_data,
         max_recv_data,
+        &notarization_config,
+        &limits,
+        notary_globals.thread_pool.as_ref(),
     )
     .await
     {
-        Ok(_) => {
+        Ok(header) => {
             info!(?session_id, "Successful notarization using tcp!");
+            notary_globals.record_attestation(&session_id, &header);
         }
         Err(err) => {
             error!(?session_id, "Failed notarization using tcp: {err}");
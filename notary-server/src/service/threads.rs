@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// A budget of VM threads shared by all notarizations.
+///
+/// Each session reserves its thread limit from the pool for as long as it runs, so the total
+/// number of VM threads across sessions never exceeds the size of the pool. Sessions which don't
+/// fit wait in arrival order, a session asking for many threads is not overtaken by later ones
+/// asking for fewer, so no session is starved.
+#[derive(Clone, Debug)]
+pub struct ThreadPool {
+    semaphore: Arc<Semaphore>,
+    size: usize,
+}
+
+/// The threads reserved by a session, returned to the [ThreadPool] when dropped.
+#[derive(Debug)]
+pub struct ThreadLease {
+    _permit: OwnedSemaphorePermit,
+    threads: usize,
+}
+
+impl ThreadPool {
+    /// Creates a pool of `size` threads, at least one.
+    pub fn new(size: usize) -> Self {
+        let size = size.clamp(1, Semaphore::MAX_PERMITS);
+
+        Self {
+            semaphore: Arc::new(Semaphore::new(size)),
+            size,
+        }
+    }
+
+    /// Returns the number of threads of the pool.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the number of threads not reserved by any session.
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+
+    /// Waits until `threads` threads are free and reserves them.
+    ///
+    /// Requests for more threads than the pool has are capped to its size, so that they can
+    /// eventually be granted.
+    pub async fn acquire(&self, threads: usize) -> ThreadLease {
+        let threads = threads.clamp(1, self.size);
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_many_owned(threads as u32)
+            .await
+            .expect("semaphore is never closed");
+
+        ThreadLease {
+            _permit: permit,
+            threads,
+        }
+    }
+}
+
+impl ThreadLease {
+    /// Returns the number of threads reserved.
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::FutureExt;
+
+    #[tokio::test]
+    async fn test_thread_pool_caps_request() {
+        let pool = ThreadPool::new(4);
+
+        let lease = pool.acquire(16).await;
+
+        assert_eq!(lease.threads(), 4);
+        assert_eq!(pool.available(), 0);
+
+        drop(lease);
+        assert_eq!(pool.available(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_thread_pool_fifo() {
+        let pool = ThreadPool::new(4);
+        let first = pool.acquire(3).await;
+
+        // The large request queues first, the small one must not overtake it even though a
+        // thread is free.
+        let mut large = Box::pin(pool.acquire(4));
+        assert!((&mut large).now_or_never().is_none());
+        let mut small = Box::pin(pool.acquire(1));
+        assert!((&mut small).now_or_never().is_none());
+
+        drop(first);
+        let large = large.await;
+        assert!((&mut small).now_or_never().is_none());
+
+        drop(large);
+        assert_eq!(small.await.threads(), 1);
+    }
+}
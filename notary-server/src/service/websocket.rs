@@ -17,17 +17,24 @@ pub async fn websocket_notarize(
     debug!(?session_id, "Upgraded to websocket connection");
     // Wrap the websocket in WsStream so that we have AsyncRead and AsyncWrite implemented
     let stream = WsStream::new(socket.into_inner());
+    // Take a snapshot so that a hot reload doesn't change the limits mid-session
+    let notarization_config = notary_globals.notarization_config.lock().unwrap().clone();
+    let limits = notary_globals.limits.lock().unwrap().clone();
     match notary_service(
         stream,
         &notary_globals.notary_signing_key,
         &session_id,
         max_sent_data,
         max_recv_data,
+        &notarization_config,
+        &limits,
+        notary_globals.thread_pool.as_ref(),
     )
     .await
     {
-        Ok(_) => {
+        Ok(header) => {
             info!(?session_id, "Successful notarization using websocket!");
+            notary_globals.record_attestation(&session_id, &header);
         }
         Err(err) => {
             error!(?session_id, "Failed notarization using websocket: {err}");
@@ -1,11 +1,142 @@
-use eyre::Result;
+use eyre::{ensure, eyre, Result};
 use serde::de::DeserializeOwned;
+use serde_yaml::{Mapping, Value};
 
-/// Parse a yaml configuration file into a struct
+/// Prefix of the environment variables that override fields of the configuration file
+pub const CONFIG_ENV_PREFIX: &str = "NOTARY__";
+
+/// Key of the section of the configuration file that contains the named profiles
+const PROFILES_KEY: &str = "profiles";
+/// Key in a profile that names the profile it inherits from, it inherits from the base otherwise
+const INHERITS_KEY: &str = "inherits";
+
+/// Parse a yaml configuration file into a struct, with fields overridden by environment variables
+/// following the `NOTARY__SECTION__FIELD` convention
 pub fn parse_config_file<T: DeserializeOwned>(location: &str) -> Result<T> {
+    parse_config_file_with_profile(location, None)
+}
+
+/// Parse a yaml configuration file into a struct like [parse_config_file], with the fields of the
+/// given profile applied over the base configuration before the environment variable overrides
+pub fn parse_config_file_with_profile<T: DeserializeOwned>(
+    location: &str,
+    profile: Option<&str>,
+) -> Result<T> {
     let file = std::fs::File::open(location)?;
-    let config: T = serde_yaml::from_reader(file)?;
-    Ok(config)
+    let mut config: Value = serde_yaml::from_reader(file)?;
+    apply_profile(&mut config, profile)?;
+    apply_env_overrides(&mut config, std::env::vars())?;
+    Ok(serde_yaml::from_value(config)?)
+}
+
+/// Apply a named profile of a yaml configuration over its base, i.e. everything outside of the
+/// `profiles` section, which is removed
+///
+/// A profile can inherit from another profile, whose fields are applied first
+fn apply_profile(config: &mut Value, profile: Option<&str>) -> Result<()> {
+    let profiles = match config {
+        Value::Mapping(config) => config.remove(PROFILES_KEY),
+        _ => None,
+    };
+    let Some(profile) = profile else {
+        return Ok(());
+    };
+    let profiles = match profiles {
+        Some(Value::Mapping(profiles)) => profiles,
+        _ => return Err(eyre!("Config profile {profile} is not defined")),
+    };
+
+    // Collect the profiles from the selected one up to the base
+    let mut chain: Vec<(String, Mapping)> = Vec::new();
+    let mut next = Some(profile.to_string());
+    while let Some(name) = next {
+        ensure!(
+            chain.iter().all(|(inherited, _)| *inherited != name),
+            "Config profile {name} inherits from itself"
+        );
+        let mut fields = match profiles.get(name.as_str()) {
+            Some(Value::Mapping(fields)) => fields.clone(),
+            Some(Value::Null) => Mapping::new(),
+            Some(_) => return Err(eyre!("Config profile {name} is not a mapping")),
+            None => return Err(eyre!("Config profile {name} is not defined")),
+        };
+        next = match fields.remove(INHERITS_KEY) {
+            Some(Value::String(parent)) => Some(parent),
+            Some(_) => return Err(eyre!("Config profile {name} must inherit from a profile name")),
+            None => None,
+        };
+        chain.push((name, fields));
+    }
+
+    for (_, fields) in chain.into_iter().rev() {
+        merge(config, Value::Mapping(fields));
+    }
+    Ok(())
+}
+
+/// Merge a yaml value into another, where mappings are merged recursively and other values replace
+/// the existing ones
+fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Mapping(base), Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Override fields of a yaml configuration with environment variables
+///
+/// The variable name is the path of the field joined by double underscores, where each segment is
+/// the field name in upper snake case, e.g. `NOTARY__NOTARY_KEY__PRIVATE_KEY_PEM_PATH` overrides
+/// `private-key-pem-path` under `notary-key`. The value of a field that holds a string in the file
+/// is kept as is, otherwise it is parsed as yaml so numbers and booleans are typed, and a value can
+/// be quoted to force it to be a string
+fn apply_env_overrides(
+    config: &mut Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<()> {
+    for (name, value) in vars {
+        let Some(path) = name.strip_prefix(CONFIG_ENV_PREFIX) else {
+            continue;
+        };
+        let keys: Vec<String> = path
+            .split("__")
+            .map(|segment| segment.to_lowercase().replace('_', "-"))
+            .collect();
+        if keys.iter().any(|key| key.is_empty()) {
+            return Err(eyre!("Invalid config override environment variable: {name}"));
+        }
+
+        // Walk down to the field, creating the sections that are missing from the file
+        let mut field = &mut *config;
+        for key in &keys {
+            if field.is_null() {
+                *field = Value::Mapping(Mapping::new());
+            }
+            let Value::Mapping(section) = field else {
+                return Err(eyre!(
+                    "Config override environment variable {name} does not refer to a field"
+                ));
+            };
+            field = section
+                .entry(Value::String(key.clone()))
+                .or_insert(Value::Null);
+        }
+
+        *field = match field {
+            Value::String(_) => Value::String(value),
+            _ => serde_yaml::from_str(&value).unwrap_or(Value::String(value)),
+        };
+    }
+    Ok(())
 }
 
 /// Parse a csv file into a vec of structs
@@ -28,7 +159,7 @@ mod test {
         util::parse_csv_file,
     };
 
-    use super::{parse_config_file, Result};
+    use super::{apply_env_overrides, apply_profile, parse_config_file, Result, Value};
 
     #[test]
     fn test_parse_config_file() {
@@ -40,6 +171,108 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_apply_env_overrides() {
+        let file = std::fs::File::open("./config/config.yaml").unwrap();
+        let mut config: Value = serde_yaml::from_reader(file).unwrap();
+        let vars = [
+            ("NOTARY__SERVER__PORT", "8080"),
+            ("NOTARY__TLS__ENABLED", "false"),
+            (
+                "NOTARY__NOTARY_KEY__PRIVATE_KEY_PEM_PATH",
+                "/keys/notary.key",
+            ),
+            ("NOTARY__NOTARIZATION__MAX_THREADS", "4"),
+            ("NOTARY__LOGGING__FILTER", "notary_server=TRACE"),
+            ("NOTARY__SERVER__NAME", "1234"),
+            ("NOT_NOTARY__SERVER__PORT", "1"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+        apply_env_overrides(&mut config, vars).unwrap();
+
+        let config: NotaryServerProperties = serde_yaml::from_value(config).unwrap();
+        assert_eq!(config.server.port, 8080);
+        assert!(!config.tls.enabled);
+        assert_eq!(config.notary_key.private_key_pem_path, "/keys/notary.key");
+        assert_eq!(config.notarization.max_threads, Some(4));
+        assert_eq!(
+            config.logging.filter.as_deref(),
+            Some("notary_server=TRACE")
+        );
+        assert_eq!(config.server.name, "1234");
+    }
+
+    #[test]
+    fn test_apply_env_overrides_rejects_non_field() {
+        let mut config: Value = serde_yaml::from_str("server:\n  port: 7047").unwrap();
+        let vars = [("NOTARY__SERVER__PORT__VALUE".to_string(), "1".to_string())];
+        assert!(apply_env_overrides(&mut config, vars).is_err());
+    }
+
+    #[test]
+    fn test_parse_endpoints() {
+        let file = std::fs::File::open("./config/config.yaml").unwrap();
+        let mut config: Value = serde_yaml::from_reader(file).unwrap();
+        let vars = [("NOTARY__ENDPOINTS__TCP".to_string(), "false".to_string())];
+        apply_env_overrides(&mut config, vars).unwrap();
+
+        // Endpoints that are not set in the config stay turned on
+        let config: NotaryServerProperties = serde_yaml::from_value(config).unwrap();
+        assert!(!config.endpoints.tcp);
+        assert!(config.endpoints.websocket && config.endpoints.info);
+    }
+
+    #[test]
+    fn test_apply_profile() {
+        let config = r#"
+server:
+  host: 0.0.0.0
+  port: 7047
+logging:
+  level: DEBUG
+profiles:
+  dev:
+    server:
+      host: 127.0.0.1
+  staging:
+    logging:
+      level: INFO
+  prod:
+    inherits: staging
+    server:
+      port: 443
+"#;
+
+        let mut base: Value = serde_yaml::from_str(config).unwrap();
+        apply_profile(&mut base, None).unwrap();
+        assert!(base.get("profiles").is_none());
+        assert_eq!(base["server"]["host"], "0.0.0.0");
+
+        let mut dev: Value = serde_yaml::from_str(config).unwrap();
+        apply_profile(&mut dev, Some("dev")).unwrap();
+        assert_eq!(dev["server"]["host"], "127.0.0.1");
+        assert_eq!(dev["server"]["port"], 7047);
+        assert_eq!(dev["logging"]["level"], "DEBUG");
+
+        let mut prod: Value = serde_yaml::from_str(config).unwrap();
+        apply_profile(&mut prod, Some("prod")).unwrap();
+        assert_eq!(prod["server"]["host"], "0.0.0.0");
+        assert_eq!(prod["server"]["port"], 443);
+        assert_eq!(prod["logging"]["level"], "INFO");
+        assert!(prod.get("inherits").is_none());
+
+        let mut missing: Value = serde_yaml::from_str(config).unwrap();
+        assert!(apply_profile(&mut missing, Some("test")).is_err());
+    }
+
+    #[test]
+    fn test_apply_profile_rejects_cycle() {
+        let mut config: Value =
+            serde_yaml::from_str("profiles:\n  a:\n    inherits: b\n  b:\n    inherits: a\n")
+                .unwrap();
+        assert!(apply_profile(&mut config, Some("a")).is_err());
+    }
+
     #[test]
     fn test_parse_csv_file() {
         let location = "./fixture/auth/whitelist.csv";
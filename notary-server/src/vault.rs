@@ -0,0 +1,336 @@
+use eyre::{eyre, Result};
+use p256::{ecdsa::SigningKey, pkcs8::DecodePrivateKey};
+use serde::Deserialize;
+use std::{collections::HashMap, time::Duration};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, info};
+
+use crate::{
+    config::VaultProperties,
+    domain::auth::{authorization_whitelist_vec_into_hashmap, AuthorizationWhitelistRecord},
+    server::{parse_tls_key_and_cert, tls_acceptor_from_key_and_cert},
+};
+
+/// Environment variable holding the Vault token, which is kept out of the config file
+pub const VAULT_TOKEN_ENV: &str = "VAULT_TOKEN";
+/// Field of a secret holding a PEM-formatted private key
+const PRIVATE_KEY_PEM_FIELD: &str = "private-key-pem";
+/// Field of a secret holding a PEM-formatted certificate chain
+const CERTIFICATE_PEM_FIELD: &str = "certificate-pem";
+/// Field of a secret holding the whitelist in csv format
+const WHITELIST_CSV_FIELD: &str = "whitelist-csv";
+/// Minimum interval between renewals of the token lease, so that very short leases don't make the
+/// renewal spin
+const MIN_RENEW_INTERVAL: Duration = Duration::from_secs(1);
+/// Maximum interval between retries of a failed renewal
+const MAX_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Client of the KV version 2 secrets engine of HashiCorp Vault
+#[derive(Clone)]
+pub(crate) struct VaultClient {
+    http: reqwest::Client,
+    address: String,
+    kv_mount: String,
+    token: String,
+}
+
+/// Response of reading a KV version 2 secret
+#[derive(Deserialize)]
+struct KvResponse {
+    data: KvData,
+}
+
+#[derive(Deserialize)]
+struct KvData {
+    data: HashMap<String, String>,
+}
+
+/// Response of renewing the token lease
+#[derive(Deserialize)]
+struct RenewResponse {
+    auth: RenewAuth,
+}
+
+#[derive(Deserialize)]
+struct RenewAuth {
+    lease_duration: u64,
+    renewable: bool,
+}
+
+impl VaultClient {
+    /// Create a client using the token from the environment
+    ///
+    /// The address must use https, as the token is sent with every request
+    pub(crate) fn new(config: &VaultProperties) -> Result<Self> {
+        let token = std::env::var(VAULT_TOKEN_ENV)
+            .map_err(|_| eyre!("{VAULT_TOKEN_ENV} must be set to load secrets from Vault"))?;
+        Self::with_token(config, token)
+    }
+
+    /// Create a client using the given token
+    fn with_token(config: &VaultProperties, token: String) -> Result<Self> {
+        let address = reqwest::Url::parse(&config.address)
+            .map_err(|err| eyre!("Invalid Vault address {}: {err}", config.address))?;
+        if address.scheme() != "https" {
+            return Err(eyre!(
+                "Vault address {} must use https to keep the token confidential",
+                config.address
+            ));
+        }
+        // Refuse redirects to plain http as well
+        let http = reqwest::Client::builder()
+            .https_only(true)
+            .build()
+            .map_err(|err| eyre!("Failed to build Vault client: {err}"))?;
+        Ok(Self {
+            http,
+            address: config.address.trim_end_matches('/').to_string(),
+            kv_mount: config.kv_mount.trim_matches('/').to_string(),
+            token,
+        })
+    }
+
+    /// Read the fields of a secret
+    async fn read_secret(&self, path: &str) -> Result<HashMap<String, String>> {
+        let url = format!(
+            "{}/v1/{}/data/{}",
+            self.address,
+            self.kv_mount,
+            path.trim_matches('/')
+        );
+        let response = self
+            .http
+            .get(&url)
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| eyre!("Failed to read secret {path} from Vault: {err}"))?;
+        let secret: KvResponse = response
+            .json()
+            .await
+            .map_err(|err| eyre!("Failed to parse secret {path} from Vault: {err}"))?;
+        Ok(secret.data.data)
+    }
+
+    /// Read a single field of a secret
+    async fn read_secret_field(&self, path: &str, field: &str) -> Result<String> {
+        self.read_secret(path)
+            .await?
+            .remove(field)
+            .ok_or_else(|| eyre!("Secret {path} in Vault has no {field} field"))
+    }
+
+    /// Load the notary signing key from the `private-key-pem` field of a secret
+    pub(crate) async fn load_notary_signing_key(&self, path: &str) -> Result<SigningKey> {
+        debug!("Loading notary server's signing key from Vault");
+        let private_key_pem = self.read_secret_field(path, PRIVATE_KEY_PEM_FIELD).await?;
+        SigningKey::from_pkcs8_pem(&private_key_pem)
+            .map_err(|err| eyre!("Failed to load notary signing key for notarization: {err}"))
+    }
+
+    /// Build TLS acceptor using the `private-key-pem` and `certificate-pem` fields of a secret
+    pub(crate) async fn build_tls_acceptor(&self, path: &str) -> Result<TlsAcceptor> {
+        debug!("Loading notary server's tls private key and certificate from Vault");
+        let mut secret = self.read_secret(path).await?;
+        let mut take_field = |field: &str| {
+            secret
+                .remove(field)
+                .ok_or_else(|| eyre!("Secret {path} in Vault has no {field} field"))
+        };
+        let private_key_pem = take_field(PRIVATE_KEY_PEM_FIELD)?;
+        let certificate_pem = take_field(CERTIFICATE_PEM_FIELD)?;
+
+        let (private_key, certificates) =
+            parse_tls_key_and_cert(private_key_pem.as_bytes(), certificate_pem.as_bytes())?;
+        tls_acceptor_from_key_and_cert(private_key, certificates)
+    }
+
+    /// Load the authorization whitelist from the `whitelist-csv` field of a secret
+    pub(crate) async fn load_authorization_whitelist(
+        &self,
+        path: &str,
+    ) -> Result<HashMap<String, AuthorizationWhitelistRecord>> {
+        debug!("Loading authorization whitelist from Vault");
+        let whitelist_csv = self.read_secret_field(path, WHITELIST_CSV_FIELD).await?;
+        let whitelist = csv::Reader::from_reader(whitelist_csv.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<AuthorizationWhitelistRecord>, _>>()
+            .map_err(|err| eyre!("Failed to parse authorization whitelist csv: {:?}", err))?;
+        Ok(authorization_whitelist_vec_into_hashmap(whitelist))
+    }
+
+    /// Renew the lease of the token, returning its new duration if it can be renewed again
+    async fn renew_token(&self) -> Result<Option<Duration>> {
+        let response = self
+            .http
+            .post(format!("{}/v1/auth/token/renew-self", self.address))
+            .header("X-Vault-Token", &self.token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|err| eyre!("Failed to renew Vault token: {err}"))?;
+        let renewal: RenewResponse = response
+            .json()
+            .await
+            .map_err(|err| eyre!("Failed to parse Vault token renewal: {err}"))?;
+
+        // Tokens without a lease (e.g. root tokens) never expire
+        Ok((renewal.auth.renewable && renewal.auth.lease_duration > 0)
+            .then_some(Duration::from_secs(renewal.auth.lease_duration)))
+    }
+}
+
+/// Interval until the next renewal of a lease with the given duration
+fn renew_interval(lease_duration: Duration) -> Duration {
+    (lease_duration / 2).max(MIN_RENEW_INTERVAL)
+}
+
+/// Interval until a failed renewal is retried, given the interval since the last renewal
+///
+/// Retries are spread over the remaining half of the lease, so that several of them happen before
+/// it expires
+fn retry_interval(interval: Duration) -> Duration {
+    (interval / 4).clamp(MIN_RENEW_INTERVAL, MAX_RETRY_INTERVAL)
+}
+
+/// Keep renewing the lease of the token at half of its duration, so that secrets can still be
+/// read when they are reloaded
+pub(crate) fn spawn_token_renewal(client: VaultClient) {
+    tokio::spawn(async move {
+        // Until the first renewal the lease duration is unknown
+        let mut interval = MAX_RETRY_INTERVAL * 4;
+        loop {
+            let sleep = match client.renew_token().await {
+                Ok(Some(lease_duration)) => {
+                    debug!("Renewed Vault token for {:?}", lease_duration);
+                    interval = renew_interval(lease_duration);
+                    interval
+                }
+                Ok(None) => {
+                    info!("Vault token has no lease to renew");
+                    return;
+                }
+                // Retry later as Vault may be temporarily unavailable
+                Err(err) => {
+                    error!("{err}");
+                    retry_interval(interval)
+                }
+            };
+            tokio::time::sleep(sleep).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Response, Server,
+    };
+    use std::{convert::Infallible, net::SocketAddr};
+
+    use super::*;
+
+    /// Start a mock Vault server which returns the given secret for any request with the token
+    fn mock_vault(secret: serde_json::Value) -> SocketAddr {
+        let make_service = make_service_fn(move |_| {
+            let secret = secret.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    let secret = secret.clone();
+                    async move {
+                        let response = if request.headers().get("X-Vault-Token").unwrap() == "token"
+                            && request.uri().path() == "/v1/secret/data/notary/whitelist"
+                        {
+                            Response::new(Body::from(
+                                serde_json::json!({ "data": { "data": secret } }).to_string(),
+                            ))
+                        } else {
+                            Response::builder().status(403).body(Body::empty()).unwrap()
+                        };
+                        Ok::<_, Infallible>(response)
+                    }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let address = server.local_addr();
+        tokio::spawn(server);
+        address
+    }
+
+    fn client(address: SocketAddr, token: &str) -> VaultClient {
+        VaultClient {
+            http: reqwest::Client::new(),
+            address: format!("http://{address}"),
+            kv_mount: "secret".to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_load_authorization_whitelist() {
+        let whitelist_csv = std::fs::read_to_string("./fixture/auth/whitelist.csv").unwrap();
+        let address = mock_vault(serde_json::json!({ "whitelist-csv": whitelist_csv }));
+
+        let whitelist = client(address, "token")
+            .load_authorization_whitelist("/notary/whitelist")
+            .await
+            .unwrap();
+        assert!(!whitelist.is_empty());
+
+        assert!(client(address, "wrong-token")
+            .load_authorization_whitelist("notary/whitelist")
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_renew_interval() {
+        assert_eq!(
+            renew_interval(Duration::from_secs(3600)),
+            Duration::from_secs(1800)
+        );
+        // Short leases are renewed before they expire
+        assert_eq!(
+            renew_interval(Duration::from_secs(30)),
+            Duration::from_secs(15)
+        );
+        assert_eq!(renew_interval(Duration::from_secs(1)), MIN_RENEW_INTERVAL);
+
+        assert_eq!(
+            retry_interval(renew_interval(Duration::from_secs(3600))),
+            MAX_RETRY_INTERVAL
+        );
+        assert!(retry_interval(renew_interval(Duration::from_secs(30))) < Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_reject_plain_http_address() {
+        let new_client = |address: &str| {
+            let config = VaultProperties {
+                address: address.to_string(),
+                kv_mount: "secret".to_string(),
+                notary_key_path: None,
+                tls_path: None,
+                whitelist_path: None,
+            };
+            VaultClient::with_token(&config, "token".to_string())
+        };
+
+        assert!(new_client("http://vault.example.com:8200").is_err());
+        assert!(new_client("vault.example.com:8200").is_err());
+        assert!(new_client("https://vault.example.com:8200").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_missing_secret_field() {
+        let address = mock_vault(serde_json::json!({ "other": "value" }));
+
+        assert!(client(address, "token")
+            .load_authorization_whitelist("notary/whitelist")
+            .await
+            .is_err());
+    }
+}
@@ -44,9 +44,17 @@ fn get_server_config(port: u16, tls_enabled: bool) -> NotaryServerProperties {
             host: "127.0.0.1".to_string(),
             port,
             html_info: "example html response".to_string(),
+            additional_listeners: vec![],
         },
         notarization: NotarizationProperties {
             max_transcript_size: 1 << 14,
+            memory_budget: None,
+            max_threads: None,
+            max_total_threads: None,
+            max_poll_time_secs: None,
+            max_memory_bytes: None,
+            #[cfg(feature = "deterministic")]
+            rng_seed: None,
         },
         tls: TLSProperties {
             enabled: tls_enabled,
@@ -65,6 +73,10 @@ fn get_server_config(port: u16, tls_enabled: bool) -> NotaryServerProperties {
             enabled: false,
             whitelist_csv_path: "./fixture/auth/whitelist.csv".to_string(),
         },
+        endpoints: Default::default(),
+        vault: None,
+        limits: Default::default(),
+        explorer: Default::default(),
     }
 }
 
@@ -81,7 +93,7 @@ async fn setup_config_and_server(
 
     // Run the notary server
     tokio::spawn(async move {
-        run_server(&config).await.unwrap();
+        run_server(&config, None).await.unwrap();
     });
 
     // Sleep for a while to allow notary server to finish set up and start listening
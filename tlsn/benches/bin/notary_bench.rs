@@ -0,0 +1,260 @@
+//! Runs many concurrent simulated prover sessions against a notary server and reports latency
+//! percentiles and error rates.
+//!
+//! Configured with environment variables:
+//!
+//! - `NOTARY_HOST`, `NOTARY_PORT` - the notary server, reached over TCP without TLS
+//!   (default `127.0.0.1:7047`).
+//! - `SESSIONS` - the number of sessions to run (default 10).
+//! - `CONCURRENCY` - the number of sessions running at once (default `SESSIONS`).
+//! - `MODE` - `full` runs a whole notarization of a request to an in-process fixture server,
+//!   `session` only requests a session and upgrades the connection, without running any MPC
+//!   (default `full`).
+//! - `API_KEY` - sent as the `Authorization` header if the notary requires one.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context};
+use futures::{AsyncReadExt, AsyncWriteExt};
+use http_body_util::{BodyExt as _, Either, Empty, Full};
+use hyper::{body::Bytes, client::conn::http1::Parts, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use notary_server::{ClientType, NotarizationSessionRequest, NotarizationSessionResponse};
+use tlsn_prover::tls::{Prover, ProverConfig};
+use tlsn_server_fixture::{CA_CERT_DER, SERVER_DOMAIN};
+use tokio::{net::TcpStream, sync::Semaphore};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+/// Maximum data sent by a simulated prover.
+const MAX_SENT_DATA: usize = 1 << 10;
+/// Maximum data received by a simulated prover.
+const MAX_RECV_DATA: usize = 1 << 12;
+
+/// What a simulated prover session does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// A whole notarization.
+    Full,
+    /// Only the session request and the upgrade of the connection.
+    Session,
+}
+
+#[derive(Debug, Clone)]
+struct Target {
+    host: String,
+    port: u16,
+    api_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let target = Target {
+        host: std::env::var("NOTARY_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+        port: env_or("NOTARY_PORT", 7047)?,
+        api_key: std::env::var("API_KEY").ok(),
+    };
+    let sessions: usize = env_or("SESSIONS", 10)?;
+    let concurrency: usize = env_or("CONCURRENCY", sessions)?;
+    let mode = match std::env::var("MODE").as_deref() {
+        Ok("full") | Err(_) => Mode::Full,
+        Ok("session") => Mode::Session,
+        Ok(mode) => return Err(anyhow!("unknown mode: {mode}")),
+    };
+
+    println!(
+        "Running {sessions} {mode:?} sessions against {}:{}, {concurrency} at once",
+        target.host, target.port
+    );
+
+    let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+    let start_time = Instant::now();
+
+    let mut tasks = Vec::with_capacity(sessions);
+    for _ in 0..sessions {
+        let permit = semaphore.clone().acquire_owned().await?;
+        let target = target.clone();
+        tasks.push(tokio::spawn(async move {
+            let start_time = Instant::now();
+            let result = match mode {
+                Mode::Full => run_full_session(&target).await,
+                Mode::Session => request_notarization(&target).await.map(|_| ()),
+            };
+            drop(permit);
+
+            result.map(|_| start_time.elapsed())
+        }));
+    }
+
+    let mut latencies = Vec::with_capacity(sessions);
+    let mut errors = Vec::new();
+    for task in tasks {
+        match task.await? {
+            Ok(latency) => latencies.push(latency),
+            Err(e) => errors.push(e),
+        }
+    }
+    let elapsed = start_time.elapsed();
+
+    for e in &errors {
+        eprintln!("session failed: {e:#}");
+    }
+    print_report(&mut latencies, errors.len(), elapsed);
+
+    Ok(())
+}
+
+/// Reads a variable from the environment, falling back to a default if it is not set.
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> anyhow::Result<T> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|_| anyhow!("invalid value of {name}: {value}")),
+        Err(_) => Ok(default),
+    }
+}
+
+/// Prints the latency percentiles, error rate and throughput of the sessions.
+fn print_report(latencies: &mut [Duration], errors: usize, elapsed: Duration) {
+    latencies.sort();
+    let total = latencies.len() + errors;
+
+    println!("sessions:   {total}");
+    println!(
+        "errors:     {errors} ({:.1}%)",
+        100.0 * errors as f64 / total.max(1) as f64
+    );
+    println!("elapsed:    {:.2?}", elapsed);
+    println!(
+        "throughput: {:.2} sessions/s",
+        latencies.len() as f64 / elapsed.as_secs_f64()
+    );
+    for (name, p) in [("p50", 0.5), ("p90", 0.9), ("p99", 0.99), ("max", 1.0)] {
+        match percentile(latencies, p) {
+            Some(latency) => println!("{name}:        {latency:.2?}"),
+            None => println!("{name}:        -"),
+        }
+    }
+}
+
+/// Returns the latency below which the fraction `p` of the sorted latencies fall.
+fn percentile(sorted: &[Duration], p: f64) -> Option<Duration> {
+    let last = sorted.len().checked_sub(1)?;
+    let idx = ((last as f64) * p).round() as usize;
+
+    sorted.get(idx.min(last)).copied()
+}
+
+/// Runs a whole notarization of a request to an in-process fixture server.
+async fn run_full_session(target: &Target) -> anyhow::Result<()> {
+    let (notary_socket, session_id) = request_notarization(target).await?;
+
+    let mut root_store = tls_core::anchors::RootCertStore::empty();
+    root_store.add(&tls_core::key::Certificate(CA_CERT_DER.to_vec()))?;
+
+    let prover = Prover::new(
+        ProverConfig::builder()
+            .id(session_id)
+            .server_dns(SERVER_DOMAIN)
+            .root_cert_store(root_store)
+            .max_sent_data(MAX_SENT_DATA)
+            .max_recv_data(MAX_RECV_DATA)
+            .build()
+            .context("invalid prover config")?,
+    )
+    .setup(notary_socket.compat())
+    .await?;
+
+    let (client_conn, server_conn) = tokio::io::duplex(1 << 16);
+    tokio::spawn(tlsn_server_fixture::bind(server_conn.compat()));
+
+    let (mut mpc_tls_connection, prover_fut) = prover.connect(client_conn.compat()).await?;
+    let prover_task = tokio::spawn(prover_fut);
+
+    mpc_tls_connection
+        .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+        .await?;
+    mpc_tls_connection.close().await?;
+
+    let mut response = vec![];
+    mpc_tls_connection.read_to_end(&mut response).await?;
+
+    let mut prover = prover_task.await??.start_notarize();
+
+    let sent_len = prover.sent_transcript().data().len();
+    let recv_len = prover.recv_transcript().data().len();
+    let builder = prover.commitment_builder();
+    builder.commit_sent(&(0..sent_len))?;
+    builder.commit_recv(&(0..recv_len))?;
+
+    prover.finalize().await?;
+
+    Ok(())
+}
+
+/// Requests a session from the notary and upgrades the connection to run the protocol on.
+async fn request_notarization(target: &Target) -> anyhow::Result<(TcpStream, String)> {
+    let Target {
+        host,
+        port,
+        api_key,
+    } = target;
+
+    let socket = TcpStream::connect((host.as_str(), *port)).await?;
+    let (mut request_sender, connection) =
+        hyper::client::conn::http1::handshake(TokioIo::new(socket)).await?;
+    let connection_task = tokio::spawn(connection.without_shutdown());
+
+    let payload = serde_json::to_string(&NotarizationSessionRequest {
+        client_type: ClientType::Tcp,
+        max_sent_data: Some(MAX_SENT_DATA),
+        max_recv_data: Some(MAX_RECV_DATA),
+    })?;
+
+    let mut request = Request::builder()
+        .uri(format!("http://{host}:{port}/session"))
+        .method("POST")
+        .header("Host", host.as_str())
+        .header("Content-Type", "application/json");
+    if let Some(api_key) = api_key {
+        request = request.header("Authorization", api_key.as_str());
+    }
+    let request = request.body(Either::Left(Full::new(Bytes::from(payload))))?;
+
+    let response = request_sender.send_request(request).await?;
+    if response.status() != StatusCode::OK {
+        return Err(anyhow!(
+            "session request failed with status {}",
+            response.status()
+        ));
+    }
+
+    let payload = response.into_body().collect().await?.to_bytes();
+    let NotarizationSessionResponse { session_id } = serde_json::from_slice(&payload)?;
+
+    let request = Request::builder()
+        .uri(format!(
+            "http://{host}:{port}/notarize?sessionId={session_id}"
+        ))
+        .method("GET")
+        .header("Host", host.as_str())
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP")
+        .body(Either::Right(Empty::<Bytes>::new()))?;
+
+    let response = request_sender.send_request(request).await?;
+    if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+        return Err(anyhow!(
+            "notarize request failed with status {}",
+            response.status()
+        ));
+    }
+
+    let Parts { io, .. } = connection_task.await??;
+
+    Ok((io.into_inner(), session_id))
+}
@@ -0,0 +1,175 @@
+// Runs a Prover which notarizes a request to an authenticated JSON API served by the local
+// `tlsn-server-fixture`. The bearer token sent to the server is redacted and only the `balance`
+// field of the JSON response is disclosed. The proof is written to disk.
+
+use std::{env, ops::Range};
+
+use http_body_util::{BodyExt as _, Empty};
+use hyper::{body::Bytes, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use tls_core::{anchors::RootCertStore, key::Certificate};
+use tlsn_core::proof::TlsProof;
+use tlsn_server_fixture::{CA_CERT_DER, SERVER_DOMAIN};
+use tokio::io::AsyncWriteExt as _;
+use tokio_util::compat::{FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt};
+
+use tlsn_examples::run_notary;
+use tlsn_prover::tls::{Prover, ProverConfig};
+
+// The secret which authenticates the Prover to the server, it is never disclosed
+const AUTH_TOKEN: &str = "Bearer 5c1f0e7d-secret-token";
+// The field of the JSON response which is disclosed
+const DISCLOSED_FIELD: &str = "\"balance\": \"1234.56\"";
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let port: u16 = env::var("PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(3000);
+
+    let (prover_socket, notary_socket) = tokio::io::duplex(1 << 16);
+
+    // Start a local simple notary service
+    tokio::spawn(run_notary(notary_socket.compat()));
+
+    // The fixture server presents a certificate signed by its own CA, so it is the only
+    // root the Prover trusts.
+    let mut root_store = RootCertStore::empty();
+    root_store.add(&Certificate(CA_CERT_DER.to_vec())).unwrap();
+
+    let config = ProverConfig::builder()
+        .id("example")
+        .server_dns(SERVER_DOMAIN)
+        .root_cert_store(root_store)
+        .build()
+        .unwrap();
+
+    // Create a Prover and set it up with the Notary
+    let prover = Prover::new(config)
+        .setup(prover_socket.compat())
+        .await
+        .unwrap();
+
+    // Connect to the fixture server via TCP. This is the TLS client socket.
+    let client_socket = tokio::net::TcpStream::connect(("127.0.0.1", port))
+        .await
+        .unwrap();
+
+    // Bind the Prover to the server connection
+    let (mpc_tls_connection, prover_fut) = prover.connect(client_socket.compat()).await.unwrap();
+    let mpc_tls_connection = TokioIo::new(mpc_tls_connection.compat());
+
+    // Spawn the Prover task to be run concurrently
+    let prover_task = tokio::spawn(prover_fut);
+
+    // Attach the hyper HTTP client to the MPC TLS connection
+    let (mut request_sender, connection) =
+        hyper::client::conn::http1::handshake(mpc_tls_connection)
+            .await
+            .unwrap();
+
+    // Spawn the HTTP task to be run concurrently
+    tokio::spawn(connection);
+
+    let request = Request::builder()
+        .uri("/api/account")
+        .header("Host", SERVER_DOMAIN)
+        .header("Accept", "application/json")
+        // TLSNotary tooling does not support compression
+        .header("Accept-Encoding", "identity")
+        .header("Connection", "close")
+        .header("Authorization", AUTH_TOKEN)
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+
+    println!("Starting an MPC TLS connection with the server");
+
+    let response = request_sender.send_request(request).await.unwrap();
+
+    println!("Got a response from the server");
+
+    assert!(response.status() == StatusCode::OK);
+
+    // Read the whole body so that it is part of the transcript
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    println!("{}", String::from_utf8_lossy(&body));
+
+    // The Prover task should be done now, so we can grab the Prover.
+    let prover = prover_task.await.unwrap().unwrap();
+
+    // Prepare for notarization
+    let mut prover = prover.start_notarize();
+
+    // Everything sent is disclosed, except for the bearer token
+    let sent = prover.sent_transcript().data();
+    let sent_public_ranges = public_ranges(sent.len(), &[find(sent, AUTH_TOKEN.as_bytes())]);
+
+    // Only the status line and headers of the response, and the single JSON field are disclosed
+    let recv = prover.recv_transcript().data();
+    let head_end = find(recv, b"\r\n\r\n").end;
+    let recv_public_ranges = [0..head_end, find(recv, DISCLOSED_FIELD.as_bytes())];
+
+    let builder = prover.commitment_builder();
+
+    let sent_commitments: Vec<_> = sent_public_ranges
+        .iter()
+        .map(|range| builder.commit_sent(range).unwrap())
+        .collect();
+    let recv_commitments: Vec<_> = recv_public_ranges
+        .iter()
+        .map(|range| builder.commit_recv(range).unwrap())
+        .collect();
+
+    // Finalize, returning the notarized session
+    let notarized_session = prover.finalize().await.unwrap();
+
+    // Reveal the committed ranges, the rest of the transcript stays redacted
+    let mut proof_builder = notarized_session.data().build_substrings_proof();
+    for commitment_id in sent_commitments.into_iter().chain(recv_commitments) {
+        proof_builder.reveal_by_id(commitment_id).unwrap();
+    }
+
+    let proof = TlsProof {
+        session: notarized_session.session_proof(),
+        substrings: proof_builder.build().unwrap(),
+    };
+
+    let mut file = tokio::fs::File::create("json_proof.json").await.unwrap();
+    file.write_all(serde_json::to_string_pretty(&proof).unwrap().as_bytes())
+        .await
+        .unwrap();
+
+    println!("Notarization completed successfully!");
+    println!("The proof has been written to `json_proof.json`");
+}
+
+/// Returns the range of the first occurrence of `needle` in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Range<usize> {
+    let start = haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .expect("transcript should contain the needle");
+
+    start..start + needle.len()
+}
+
+/// Returns the ranges of `0..len` which are not covered by the sorted `private` ranges.
+fn public_ranges(len: usize, private: &[Range<usize>]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut last_end = 0;
+    for range in private {
+        if range.start > last_end {
+            ranges.push(last_end..range.start);
+        }
+        last_end = range.end;
+    }
+
+    if last_end < len {
+        ranges.push(last_end..len);
+    }
+
+    ranges
+}
@@ -0,0 +1,71 @@
+use std::{str, time::Duration};
+
+use elliptic_curve::pkcs8::DecodePublicKey;
+use tls_core::{anchors::RootCertStore, key::Certificate, verify::WebPkiVerifier};
+use tlsn_core::proof::{SessionProof, TlsProof};
+use tlsn_server_fixture::CA_CERT_DER;
+
+/// A verifier which reads the proof generated by `json_prover.rs` from "json_proof.json",
+/// verifies it and prints the disclosed data to the console.
+fn main() {
+    let proof = std::fs::read_to_string("json_proof.json").unwrap();
+    let proof: TlsProof = serde_json::from_str(proof.as_str()).unwrap();
+
+    let TlsProof {
+        session,
+        substrings,
+    } = proof;
+
+    // Verify the session proof against the Notary's public key. The server certificate is
+    // checked against the CA of the fixture server, rather than the default webpki roots.
+    session.verify(notary_pubkey(), &cert_verifier()).unwrap();
+
+    let SessionProof {
+        header,
+        session_info,
+        ..
+    } = session;
+
+    // The time at which the session was recorded
+    let time = chrono::DateTime::UNIX_EPOCH + Duration::from_secs(header.time());
+
+    // Verify the substrings proof against the session header
+    let (mut sent, mut recv) = substrings.verify(&header).unwrap();
+
+    // Replace the bytes which the Prover chose not to disclose with 'X'
+    sent.set_redacted(b'X');
+    recv.set_redacted(b'X');
+
+    println!("-------------------------------------------------------------------");
+    println!(
+        "Successfully verified that the bytes below came from a session with {:?} at {}.",
+        session_info.server_name, time
+    );
+    println!("Note that the bytes which the Prover chose not to disclose are shown as X.");
+    println!();
+    println!("Bytes sent:");
+    println!();
+    print!("{}", String::from_utf8(sent.data().to_vec()).unwrap());
+    println!();
+    println!("Bytes received:");
+    println!();
+    println!("{}", String::from_utf8(recv.data().to_vec()).unwrap());
+    println!("-------------------------------------------------------------------");
+}
+
+/// Returns a certificate verifier which trusts the CA of the fixture server
+fn cert_verifier() -> WebPkiVerifier {
+    let mut root_store = RootCertStore::empty();
+    root_store.add(&Certificate(CA_CERT_DER.to_vec())).unwrap();
+
+    WebPkiVerifier::new(root_store, None)
+}
+
+/// Returns a Notary pubkey trusted by this Verifier
+fn notary_pubkey() -> p256::PublicKey {
+    let pem_file = str::from_utf8(include_bytes!(
+        "../../../notary-server/fixture/notary/notary.pub"
+    ))
+    .unwrap();
+    p256::PublicKey::from_public_key_pem(pem_file).unwrap()
+}
@@ -0,0 +1,13 @@
+//! Deserialization of the MPC-TLS messages the follower receives from the leader.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tls_mpc::msg::{MpcTlsFollowerMsg, MpcTlsMessage};
+
+fuzz_target!(|data: &[u8]| {
+    // Messages are bincode encoded by the multiplexer codec
+    if let Ok(msg) = bincode::deserialize::<MpcTlsMessage>(data) {
+        _ = MpcTlsFollowerMsg::try_from(msg);
+    }
+});
@@ -0,0 +1,12 @@
+//! Parsing and validation of the JSON payload of the notary server's /session API.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use notary_server::NotarizationSessionRequest;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(request) = serde_json::from_slice::<NotarizationSessionRequest>(data) {
+        _ = request.requested_transcript_size();
+    }
+});
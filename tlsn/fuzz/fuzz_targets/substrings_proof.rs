@@ -0,0 +1,26 @@
+//! Verification of substrings proofs against a session header.
+//!
+//! `tls_proof` only reaches this verification for proofs signed by the notary, which the fuzzer
+//! can not produce, so the header is part of the input here.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tlsn_core::{proof::SubstringsProof, SessionHeader};
+
+/// The maximum transcript length of a header, as the transcripts are allocated before the
+/// openings are checked. Notaries sign headers within their configured limits.
+const MAX_TRANSCRIPT_LEN: usize = 1 << 16;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((header, substrings)) = bincode::deserialize::<(SessionHeader, SubstringsProof)>(data)
+    else {
+        return;
+    };
+
+    if header.sent_len() > MAX_TRANSCRIPT_LEN || header.recv_len() > MAX_TRANSCRIPT_LEN {
+        return;
+    }
+
+    _ = substrings.verify(&header);
+});
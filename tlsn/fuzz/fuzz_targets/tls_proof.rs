@@ -0,0 +1,19 @@
+//! Parsing and verification of proof files.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use p256::{ecdsa::SigningKey, PublicKey};
+use tlsn_core::proof::{default_cert_verifier, TlsProof};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(proof) = serde_json::from_slice::<TlsProof>(data) else {
+        return;
+    };
+
+    let notary_key = SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+    _ = proof.verify_with_report(
+        PublicKey::from(*notary_key.verifying_key()),
+        &default_cert_verifier(),
+    );
+});
@@ -0,0 +1,11 @@
+//! Deserialization of the protocol messages the notary receives from the prover.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tlsn_core::msg::TlsnMessage;
+
+fuzz_target!(|data: &[u8]| {
+    // Messages are bincode encoded by the multiplexer codec
+    _ = bincode::deserialize::<TlsnMessage>(data);
+});
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tlsn_common::mux::chaos::{ChaosConfig, ChaosStream};
+use tlsn_prover::tls::{Prover, ProverConfig};
+use tlsn_server_fixture::{CA_CERT_DER, SERVER_DOMAIN};
+use tlsn_verifier::tls::{Verifier, VerifierConfig};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+use tracing::instrument;
+
+type Error = Box<dyn std::error::Error>;
+
+/// Seed of the fault schedule, set `CHAOS_SEED` to reproduce a failure.
+fn seed() -> u64 {
+    std::env::var("CHAOS_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn notarize_with_delays_and_partial_io() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let config = ChaosConfig {
+        seed: seed(),
+        delay_rate: 0.05,
+        max_delay: Duration::from_millis(5),
+        partial_rate: 0.2,
+        ..Default::default()
+    };
+
+    let (socket_0, socket_1) = tokio::io::duplex(2 << 23);
+    let prover_socket = ChaosStream::new(socket_0.compat(), config.clone());
+    let notary_socket = ChaosStream::new(
+        socket_1.compat(),
+        ChaosConfig {
+            seed: config.seed + 1,
+            ..config
+        },
+    );
+
+    let (prover, notary) = tokio::join!(prover(prover_socket), notary(notary_socket));
+
+    prover.unwrap();
+    notary.unwrap();
+}
+
+// Ignored as it can wait up to a minute for both parties to fail
+#[tokio::test]
+#[ignore]
+async fn notarize_with_disconnect() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let config = ChaosConfig {
+        seed: seed(),
+        disconnect_rate: 0.01,
+        ..Default::default()
+    };
+
+    let (socket_0, socket_1) = tokio::io::duplex(2 << 23);
+    let prover_socket = ChaosStream::new(socket_0.compat(), config);
+
+    // Both parties must fail instead of waiting for each other forever.
+    let (prover, notary) = tokio::time::timeout(
+        Duration::from_secs(60),
+        futures::future::join(prover(prover_socket), notary(socket_1.compat())),
+    )
+    .await
+    .expect("parties should fail after the connection is reset");
+
+    assert!(prover.is_err());
+    assert!(notary.is_err());
+}
+
+#[instrument(skip(notary_socket))]
+async fn prover<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
+    notary_socket: T,
+) -> Result<(), Error> {
+    let (client_socket, server_socket) = tokio::io::duplex(2 << 16);
+
+    let server_task = tokio::spawn(tlsn_server_fixture::bind(server_socket.compat()));
+
+    let mut root_store = tls_core::anchors::RootCertStore::empty();
+    root_store
+        .add(&tls_core::key::Certificate(CA_CERT_DER.to_vec()))
+        .unwrap();
+
+    let prover = Prover::new(
+        ProverConfig::builder()
+            .id("test")
+            .server_dns(SERVER_DOMAIN)
+            .root_cert_store(root_store)
+            .build()
+            .unwrap(),
+    )
+    .setup(notary_socket)
+    .await?;
+
+    let (mut tls_connection, prover_fut) = prover.connect(client_socket.compat()).await?;
+    let prover_task = tokio::spawn(prover_fut);
+
+    tls_connection
+        .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+        .await?;
+    let mut response = Vec::new();
+    tls_connection.read_to_end(&mut response).await?;
+    tls_connection.close().await?;
+
+    _ = server_task.await;
+
+    let mut prover = prover_task.await??.start_notarize();
+    let sent_tx_len = prover.sent_transcript().data().len();
+    let recv_tx_len = prover.recv_transcript().data().len();
+
+    let builder = prover.commitment_builder();
+
+    // Commit to everything
+    builder.commit_sent(&(0..sent_tx_len))?;
+    builder.commit_recv(&(0..recv_tx_len))?;
+
+    prover.finalize().await?;
+
+    Ok(())
+}
+
+#[instrument(skip(socket))]
+async fn notary<T: AsyncWrite + AsyncRead + Send + Sync + Unpin + 'static>(
+    socket: T,
+) -> Result<(), Error> {
+    let verifier = Verifier::new(VerifierConfig::builder().id("test").build().unwrap());
+    let signing_key = p256::ecdsa::SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+
+    verifier
+        .notarize::<_, p256::ecdsa::Signature>(socket, &signing_key)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, Future};
+use tlsn_common::{
+    mux::{
+        attach_mux_with_recorder, attach_replay, recorder::Recorder, replay::Recording, MuxControl,
+    },
+    Role,
+};
+use tlsn_prover::tls::{Prover, ProverConfig};
+use tlsn_server_fixture::{CA_CERT_DER, SERVER_DOMAIN};
+use tlsn_verifier::tls::{Verifier, VerifierConfig, VerifierConfigBuilder};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+use tracing::instrument;
+use uid_mux::recorder::SharedBuf;
+use utils_aio::mux::MuxerError;
+
+type Error = Box<dyn std::error::Error>;
+
+const SEED: u64 = 1;
+
+#[tokio::test]
+async fn replay_notary() {
+    let _ = tracing_subscriber::fmt::try_init();
+
+    let recording = record_notary().await;
+
+    // The hello exchange does not depend on any randomness, so a notary with a different config
+    // diverges from the recording right away.
+    let (mux_fut, mux_ctrl) = attach_replay(recording.clone());
+    let err = notary(config().max_threads(1).build().unwrap(), mux_ctrl, mux_fut)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("stream hello diverged"), "{err}");
+
+    // The same notary replays the hello exchange and draws the same randomness for the base OTs,
+    // but the OT extension draws randomness inside of mpz which can not be seeded, see
+    // `tlsn_common::rng`. So the replay always diverges on one of the OT streams, before any
+    // garbled circuit or TLS traffic.
+    let (mux_fut, mux_ctrl) = attach_replay(recording);
+    let err = notary(config().build().unwrap(), mux_ctrl, mux_fut)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        ["stream ot/0 diverged", "stream ot/1 diverged"]
+            .iter()
+            .any(|divergence| err.contains(divergence)),
+        "{err}"
+    );
+}
+
+/// Notarizes a session, returning the recording of the notary's streams.
+async fn record_notary() -> Recording {
+    let (socket_0, socket_1) = tokio::io::duplex(2 << 23);
+
+    let buf = SharedBuf::default();
+    let recorder = Recorder::new(buf.clone()).strip_payload(false);
+    let config = config().build().unwrap();
+    let (mut mux, mux_ctrl) = attach_mux_with_recorder(
+        socket_1.compat(),
+        Role::Verifier,
+        config.max_buffer_size(),
+        recorder,
+    );
+
+    let (prover, notary) = tokio::join!(
+        prover(socket_0.compat()),
+        notary(config, mux_ctrl, async move { mux.run().await })
+    );
+    prover.unwrap();
+    notary.unwrap();
+
+    Recording::parse(&buf.contents()).unwrap()
+}
+
+fn config() -> VerifierConfigBuilder {
+    VerifierConfig::builder().id("replay").rng_seed(SEED)
+}
+
+#[instrument(skip(notary_socket))]
+async fn prover<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
+    notary_socket: T,
+) -> Result<(), Error> {
+    let (client_socket, server_socket) = tokio::io::duplex(2 << 16);
+
+    let server_task = tokio::spawn(tlsn_server_fixture::bind(server_socket.compat()));
+
+    let mut root_store = tls_core::anchors::RootCertStore::empty();
+    root_store
+        .add(&tls_core::key::Certificate(CA_CERT_DER.to_vec()))
+        .unwrap();
+
+    let prover = Prover::new(
+        ProverConfig::builder()
+            .id("replay")
+            .server_dns(SERVER_DOMAIN)
+            .root_cert_store(root_store)
+            .build()
+            .unwrap(),
+    )
+    .setup(notary_socket)
+    .await?;
+
+    let (mut tls_connection, prover_fut) = prover.connect(client_socket.compat()).await?;
+    let prover_task = tokio::spawn(prover_fut);
+
+    tls_connection
+        .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+        .await?;
+    let mut response = Vec::new();
+    tls_connection.read_to_end(&mut response).await?;
+    tls_connection.close().await?;
+
+    _ = server_task.await;
+
+    let mut prover = prover_task.await??.start_notarize();
+    let sent_tx_len = prover.sent_transcript().data().len();
+    let recv_tx_len = prover.recv_transcript().data().len();
+
+    let builder = prover.commitment_builder();
+    builder.commit_sent(&(0..sent_tx_len))?;
+    builder.commit_recv(&(0..recv_tx_len))?;
+
+    prover.finalize().await?;
+
+    Ok(())
+}
+
+#[instrument(skip(mux_ctrl, mux_fut))]
+async fn notary(
+    config: VerifierConfig,
+    mux_ctrl: MuxControl,
+    mux_fut: impl Future<Output = Result<(), MuxerError>> + Send + 'static,
+) -> Result<(), Error> {
+    let signing_key = p256::ecdsa::SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+
+    Verifier::new(config)
+        .setup_with_mux(mux_ctrl, mux_fut)
+        .await?
+        .run()
+        .await?
+        .start_notarize()
+        .finalize::<p256::ecdsa::Signature>(&signing_key)
+        .await?;
+
+    Ok(())
+}
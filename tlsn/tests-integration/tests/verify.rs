@@ -1,7 +1,9 @@
+use std::ops::Range;
+
 use futures::AsyncWriteExt;
 use hyper::{body::to_bytes, Body, Request, StatusCode};
 use tls_core::{anchors::RootCertStore, verify::WebPkiVerifier};
-use tlsn_core::{proof::SessionInfo, Direction, RedactedTranscript};
+use tlsn_core::{msg::MAX_REVEALED_RECORD_LEN, proof::SessionInfo, Direction, RedactedTranscript};
 use tlsn_prover::tls::{Prover, ProverConfig};
 use tlsn_server_fixture::{CA_CERT_DER, SERVER_DOMAIN};
 use tlsn_verifier::tls::{Verifier, VerifierConfig};
@@ -29,6 +31,42 @@ async fn verify() {
     assert_eq!(received.redacted(), &RangeSet::from(0..2));
 }
 
+#[tokio::test]
+#[ignore]
+async fn verify_with_hook() {
+    let (socket_0, socket_1) = tokio::io::duplex(2 << 23);
+
+    let (_, records) = tokio::join!(prover(socket_0), verifier_with_hook(socket_1));
+
+    // Records are passed in order, each at most the maximum length, and cover the revealed ranges
+    assert!(records.iter().all(
+        |(_, range, data)| data.len() == range.len() && range.len() <= MAX_REVEALED_RECORD_LEN
+    ));
+    let covered = |direction| {
+        records
+            .iter()
+            .filter(|(record_direction, ..)| *record_direction == direction)
+            .fold(None, |covered: Option<Range<usize>>, (_, range, _)| {
+                Some(match covered {
+                    Some(covered) => {
+                        assert_eq!(covered.end, range.start);
+                        covered.start..range.end
+                    }
+                    None => range.clone(),
+                })
+            })
+            .unwrap()
+    };
+    let sent = covered(Direction::Sent);
+    let recv = covered(Direction::Received);
+    assert_eq!(sent.start, 0);
+    assert_eq!(recv.start, 2);
+    // Sent records come before received records
+    assert!(records
+        .windows(2)
+        .all(|records| !(records[0].0 == Direction::Received && records[1].0 == Direction::Sent)));
+}
+
 #[instrument(skip(notary_socket))]
 async fn prover<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(notary_socket: T) {
     let (client_socket, server_socket) = tokio::io::duplex(2 << 16);
@@ -117,3 +155,36 @@ async fn verifier<T: AsyncWrite + AsyncRead + Send + Sync + Unpin + 'static>(
     let (sent, received, session_info) = verifier.verify(socket.compat()).await.unwrap();
     (sent, received, session_info)
 }
+
+#[instrument(skip(socket))]
+async fn verifier_with_hook<T: AsyncWrite + AsyncRead + Send + Sync + Unpin + 'static>(
+    socket: T,
+) -> Vec<(Direction, Range<usize>, Vec<u8>)> {
+    let mut root_store = RootCertStore::empty();
+    root_store
+        .add(&tls_core::key::Certificate(CA_CERT_DER.to_vec()))
+        .unwrap();
+
+    let verifier_config = VerifierConfig::builder()
+        .id("test")
+        .cert_verifier(WebPkiVerifier::new(root_store, None))
+        .build()
+        .unwrap();
+    let mut verifier = Verifier::new(verifier_config)
+        .setup(socket.compat())
+        .await
+        .unwrap()
+        .run()
+        .await
+        .unwrap()
+        .start_verify();
+
+    let mut records = Vec::new();
+    verifier
+        .receive_with_hook(|direction, range, data| records.push((direction, range, data.to_vec())))
+        .await
+        .unwrap();
+    verifier.finalize().await.unwrap();
+
+    records
+}
@@ -0,0 +1,147 @@
+use std::{ops::Range, str::FromStr};
+
+use crate::{error::CliError, request::RawRequest};
+
+/// Selects data of the transcript to redact from the proof.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Selector {
+    /// The values of the request headers with the given name, e.g. `header:authorization`.
+    Header(String),
+    /// Every occurrence of the text in the request, e.g. `sent:secret`.
+    Sent(String),
+    /// Every occurrence of the text in the response, e.g. `recv:"email": "alice@example.com"`.
+    Recv(String),
+}
+
+impl FromStr for Selector {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            CliError::InvalidArgument(format!(
+                "selector must be `header:<name>`, `sent:<text>` or `recv:<text>`: {s}"
+            ))
+        };
+
+        let (kind, value) = s.split_once(':').ok_or_else(invalid)?;
+        if value.is_empty() {
+            return Err(invalid());
+        }
+
+        match kind {
+            "header" => Ok(Self::Header(value.to_string())),
+            "sent" => Ok(Self::Sent(value.to_string())),
+            "recv" => Ok(Self::Recv(value.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Returns the ranges of the request and response to disclose, which is everything not redacted
+/// by the selectors.
+pub(crate) fn disclosed_ranges(
+    selectors: &[Selector],
+    request: &RawRequest,
+    response: &[u8],
+) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let mut sent = Vec::new();
+    let mut recv = Vec::new();
+    for selector in selectors {
+        match selector {
+            Selector::Header(name) => sent.extend(request.header_values(name)),
+            Selector::Sent(text) => sent.extend(find_all(&request.data, text.as_bytes())),
+            Selector::Recv(text) => recv.extend(find_all(response, text.as_bytes())),
+        }
+    }
+
+    (
+        complement(request.data.len(), sent),
+        complement(response.len(), recv),
+    )
+}
+
+/// Returns the ranges of every occurrence of `needle` in `haystack`.
+fn find_all<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = Range<usize>> + 'a {
+    haystack
+        .windows(needle.len())
+        .enumerate()
+        .filter(move |(_, window)| *window == needle)
+        .map(move |(start, _)| start..start + needle.len())
+}
+
+/// Returns the ranges of `0..len` which are not covered by `ranges`.
+fn complement(len: usize, mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut complement = Vec::new();
+    let mut last_end = 0;
+    for range in ranges {
+        if range.start > last_end {
+            complement.push(last_end..range.start);
+        }
+        last_end = last_end.max(range.end);
+    }
+
+    if last_end < len {
+        complement.push(last_end..len);
+    }
+
+    complement
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::Target;
+
+    #[test]
+    fn test_parse_selector() {
+        assert_eq!(
+            "header:Authorization".parse::<Selector>().unwrap(),
+            Selector::Header("Authorization".to_string())
+        );
+        assert_eq!(
+            "recv:a:b".parse::<Selector>().unwrap(),
+            Selector::Recv("a:b".to_string())
+        );
+        assert!("sent:".parse::<Selector>().is_err());
+        assert!("body:x".parse::<Selector>().is_err());
+        assert!("x".parse::<Selector>().is_err());
+    }
+
+    #[test]
+    fn test_complement() {
+        assert_eq!(complement(10, vec![]), vec![0..10]);
+        assert_eq!(complement(10, vec![6..8, 0..2, 1..4]), vec![4..6, 8..10]);
+        assert_eq!(complement(4, vec![0..4]), vec![]);
+    }
+
+    #[test]
+    fn test_disclosed_ranges() {
+        let target = Target::parse("https://example.com").unwrap();
+        let request = RawRequest::new(
+            "GET",
+            &target,
+            &["Authorization: Bearer secret".to_string()],
+            None,
+        )
+        .unwrap();
+        let response = b"HTTP/1.1 200 OK\r\n\r\n{\"a\": 1, \"b\": 2}";
+
+        let (sent, recv) = disclosed_ranges(
+            &[
+                Selector::Header("authorization".to_string()),
+                Selector::Recv("\"b\": 2".to_string()),
+            ],
+            &request,
+            response,
+        );
+
+        let secret = request.header_values("authorization").next().unwrap();
+        assert_eq!(sent, vec![0..secret.start, secret.end..request.data.len()]);
+        assert_eq!(
+            recv,
+            vec![0..response.len() - 7, response.len() - 1..response.len()]
+        );
+    }
+}
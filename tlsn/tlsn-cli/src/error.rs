@@ -0,0 +1,36 @@
+use tls_core::anchors::RootCertStoreError;
+use tlsn_core::{commitment::TranscriptCommitmentBuilderError, proof::SubstringsProofBuilderError};
+#[cfg(feature = "recipe")]
+use tlsn_plugin::RecipeError;
+use tlsn_prover::tls::{ProverConfigBuilderError, ProverError};
+
+/// An error that can occur while notarizing.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum CliError {
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Http(#[from] hyper::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("notary error: {0}")]
+    Notary(String),
+    #[error("invalid CA certificate: {0}")]
+    Certificate(#[from] RootCertStoreError),
+    #[error(transparent)]
+    Config(#[from] ProverConfigBuilderError),
+    #[error(transparent)]
+    Prover(#[from] ProverError),
+    #[error(transparent)]
+    Commitment(#[from] TranscriptCommitmentBuilderError),
+    #[error(transparent)]
+    Proof(#[from] SubstringsProofBuilderError),
+    #[cfg(feature = "recipe")]
+    #[error(transparent)]
+    Recipe(#[from] RecipeError),
+    #[cfg(feature = "recipe")]
+    #[error("the disclosure was not confirmed")]
+    NotConfirmed,
+}
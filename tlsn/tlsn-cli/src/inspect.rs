@@ -0,0 +1,167 @@
+use std::{fmt, path::Path};
+
+use mpz_core::serialize::CanonicalSerialize;
+use p256::pkcs8::DecodePublicKey;
+use utils::range::RangeSet;
+
+use tlsn_core::{
+    proof::{SessionProof, TlsProof},
+    Direction, NotaryPublicKey,
+};
+
+use crate::error::CliError;
+
+/// Describes the structure of a proof when displayed.
+///
+/// Nothing is verified except for the notary signature, and only if a notary key is given.
+pub(crate) struct Inspection<'a> {
+    pub(crate) proof: &'a TlsProof,
+    pub(crate) notary_key: Option<&'a NotaryPublicKey>,
+}
+
+impl fmt::Display for Inspection<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let TlsProof {
+            session,
+            substrings,
+        } = self.proof;
+        let SessionProof {
+            header,
+            signature,
+            session_info,
+        } = session;
+
+        writeln!(f, "Session")?;
+        writeln!(f, "  server name:     {}", session_info.server_name.as_str())?;
+        writeln!(f, "  time:            {}", format_time(header.time()))?;
+        writeln!(f, "  sent bytes:      {}", header.sent_len())?;
+        writeln!(f, "  received bytes:  {}", header.recv_len())?;
+        writeln!(f, "  handshake only:  {}", header.is_handshake_only())?;
+        writeln!(
+            f,
+            "  merkle root:     {}",
+            hex::encode(header.merkle_root().to_inner())
+        )?;
+        writeln!(
+            f,
+            "  encoder seed:    {}",
+            hex::encode(header.encoder_seed())
+        )?;
+
+        writeln!(f, "Notary")?;
+        match signature {
+            Some(signature) => writeln!(f, "  signature:       {:?}", signature.algorithm())?,
+            None => writeln!(f, "  signature:       missing")?,
+        }
+        match self.notary_key {
+            Some(key) => {
+                let signed = signature.as_ref().is_some_and(|signature| {
+                    signature.verify(&header.to_bytes(), key.clone()).is_ok()
+                });
+                writeln!(f, "  key:             {}", hex::encode(key.to_bytes()))?;
+                writeln!(
+                    f,
+                    "  signed by key:   {}",
+                    if signed { "yes" } else { "no" }
+                )?;
+            }
+            None => writeln!(
+                f,
+                "  key:             not included in the proof, pass --notary-key to check it"
+            )?,
+        }
+
+        let mut commitments: Vec<_> = substrings.opened_commitments().collect();
+        commitments.sort_by_key(|(id, _)| **id);
+
+        writeln!(
+            f,
+            "Commitments ({} of {} opened)",
+            commitments.len(),
+            substrings.total_commitments()
+        )?;
+        for (id, info) in commitments {
+            let direction = match info.direction() {
+                Direction::Sent => "sent",
+                Direction::Received => "recv",
+            };
+            write!(
+                f,
+                "  {:?} {direction} {:?} {}",
+                id,
+                info.kind(),
+                format_ranges(info.ranges())
+            )?;
+            if let Some(label) = substrings.label(id) {
+                write!(f, " label={label:?}")?;
+            }
+            writeln!(f)?;
+        }
+
+        let certs = session_info
+            .handshake_decommitment
+            .data()
+            .server_cert_details()
+            .cert_chain();
+        writeln!(f, "Certificates ({})", certs.len())?;
+        for (idx, cert) in certs.iter().enumerate() {
+            match x509_parser::parse_x509_certificate(&cert.0) {
+                Ok((_, cert)) => {
+                    writeln!(f, "  [{idx}] subject:    {}", cert.subject())?;
+                    writeln!(f, "      issuer:     {}", cert.issuer())?;
+                    writeln!(f, "      serial:     {}", cert.raw_serial_as_string())?;
+                    writeln!(f, "      not before: {}", cert.validity().not_before)?;
+                    writeln!(f, "      not after:  {}", cert.validity().not_after)?;
+                }
+                Err(e) => writeln!(f, "  [{idx}] unparsable certificate: {e}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads a PEM encoded P-256 notary public key.
+pub(crate) async fn load_notary_key(path: &Path) -> Result<NotaryPublicKey, CliError> {
+    let pem = tokio::fs::read_to_string(path).await?;
+    let key = p256::PublicKey::from_public_key_pem(&pem)
+        .map_err(|e| CliError::InvalidArgument(format!("invalid notary key: {e}")))?;
+
+    Ok(key.into())
+}
+
+/// Formats a unix timestamp as UTC.
+fn format_time(time: u64) -> String {
+    match chrono::DateTime::from_timestamp(time as i64, 0) {
+        Some(date) => format!("{date} ({time})"),
+        None => time.to_string(),
+    }
+}
+
+/// Formats ranges as `[a..b, c..d]`.
+fn format_ranges(ranges: &RangeSet<usize>) -> String {
+    let ranges: Vec<_> = ranges
+        .iter_ranges()
+        .map(|range| format!("{}..{}", range.start, range.end))
+        .collect();
+
+    format!("[{}]", ranges.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::range::RangeUnion;
+
+    #[test]
+    fn test_format_ranges() {
+        let ranges = RangeSet::from(0..4).union(&(10..12));
+        assert_eq!(format_ranges(&ranges), "[0..4, 10..12]");
+        assert_eq!(format_ranges(&RangeSet::default()), "[]");
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(0), "1970-01-01 00:00:00 UTC (0)");
+    }
+}
@@ -0,0 +1,441 @@
+//! A command line tool which notarizes a request to an HTTPS server in one shot.
+//!
+//! `tlsn notarize <url>` requests a session from a notary, sends the request to the server over
+//! MPC-TLS, commits to everything but the data redacted with `--redact` and writes the proof to
+//! a file. The notary is reached over TCP without TLS.
+//!
+//! With the `recipe` feature and `--recipe`, a WASM recipe plugin builds the request and selects
+//! the data to disclose instead, see `tlsn-plugin`.
+//!
+//! `tlsn inspect <proof>` prints the structure of a proof file without any network calls.
+//!
+//! `tlsn gen-vectors` writes test vectors for verifier implementations in other languages.
+
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+mod disclose;
+mod error;
+mod inspect;
+mod notary;
+#[cfg(feature = "recipe")]
+mod recipe;
+mod request;
+mod vectors;
+
+#[cfg(feature = "recipe")]
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use futures::{AsyncReadExt as _, AsyncWriteExt as _};
+use structopt::StructOpt;
+use tls_core::{anchors::RootCertStore, key::Certificate};
+use tokio::net::TcpStream;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+use tracing::info;
+
+use tlsn_core::proof::TlsProof;
+#[cfg(feature = "recipe")]
+use tlsn_plugin::Recipe;
+use tlsn_prover::tls::{Prover, ProverConfig};
+
+use crate::{
+    disclose::{disclosed_ranges, Selector},
+    error::CliError,
+    notary::request_notarization,
+    request::{RawRequest, Target},
+};
+
+/// The TLSNotary command line tool
+#[derive(Debug, StructOpt)]
+#[structopt(name = "tlsn")]
+enum Cli {
+    /// Notarize a request to an HTTPS server and write the proof to a file
+    Notarize(NotarizeArgs),
+    /// Print the structure of a proof file
+    Inspect(InspectArgs),
+    /// Write test vectors for verifier implementations in other languages
+    GenVectors(GenVectorsArgs),
+}
+
+/// Arguments of `tlsn notarize`
+#[derive(Debug, StructOpt)]
+struct NotarizeArgs {
+    /// URL to request, e.g. https://example.com/api
+    #[cfg_attr(
+        feature = "recipe",
+        structopt(required_unless = "recipe", conflicts_with = "recipe")
+    )]
+    #[cfg_attr(not(feature = "recipe"), structopt(required = true))]
+    url: Option<String>,
+    /// Request method, defaults to POST if a body is given and GET otherwise
+    #[structopt(short = "X", long)]
+    method: Option<String>,
+    /// Request header as `name: value`, can be repeated
+    #[structopt(short = "H", long = "header")]
+    headers: Vec<String>,
+    /// Request body
+    #[structopt(short = "d", long)]
+    data: Option<String>,
+    /// Address of the notary server as `host:port`
+    #[structopt(long, default_value = "127.0.0.1:7047")]
+    notary: String,
+    /// Data to redact from the proof, as `header:<name>`, `sent:<text>` or `recv:<text>`, can be
+    /// repeated. Everything else is disclosed
+    #[structopt(long)]
+    redact: Vec<Selector>,
+    /// WASM recipe plugin which builds the request and selects the data to disclose
+    #[cfg(feature = "recipe")]
+    #[structopt(
+        long,
+        parse(from_os_str),
+        conflicts_with_all = &["method", "headers", "data", "redact"]
+    )]
+    recipe: Option<PathBuf>,
+    /// Recipe parameter as `name=value`, can be repeated
+    #[cfg(feature = "recipe")]
+    #[structopt(long = "param", parse(try_from_str = parse_param), requires = "recipe")]
+    params: Vec<(String, String)>,
+    /// Request header whose value the recipe must not disclose, can be repeated
+    #[cfg(feature = "recipe")]
+    #[structopt(long = "secret-header", requires = "recipe")]
+    secret_headers: Vec<String>,
+    /// Disclose the data selected by the recipe without asking for confirmation
+    #[cfg(feature = "recipe")]
+    #[structopt(short, long, requires = "recipe")]
+    yes: bool,
+    /// CA certificate (PEM or DER) to trust instead of the webpki roots
+    #[structopt(long, parse(from_os_str))]
+    ca_cert: Option<PathBuf>,
+    /// Maximum data that can be sent to the server
+    #[structopt(long)]
+    max_sent_data: Option<usize>,
+    /// Maximum data that can be received from the server
+    #[structopt(long)]
+    max_recv_data: Option<usize>,
+    /// File to write the proof to
+    #[structopt(short, long, default_value = "proof.json", parse(from_os_str))]
+    output: PathBuf,
+}
+
+/// Arguments of `tlsn inspect`
+#[derive(Debug, StructOpt)]
+struct InspectArgs {
+    /// Proof file, as written by `tlsn notarize`
+    #[structopt(parse(from_os_str))]
+    proof: PathBuf,
+    /// PEM encoded P-256 public key of the notary, to check the signature of the proof against
+    #[structopt(long, parse(from_os_str))]
+    notary_key: Option<PathBuf>,
+}
+
+/// Arguments of `tlsn gen-vectors`
+#[derive(Debug, StructOpt)]
+struct GenVectorsArgs {
+    /// File to write the vectors to, defaults to stdout
+    #[structopt(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    let result = match Cli::from_args() {
+        Cli::Notarize(args) => notarize(args).await,
+        Cli::Inspect(args) => inspect(args).await,
+        Cli::GenVectors(args) => gen_vectors(args).await,
+    };
+
+    if let Err(e) = result {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Parses a recipe parameter given as `name=value`.
+#[cfg(feature = "recipe")]
+fn parse_param(s: &str) -> Result<(String, String), CliError> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| CliError::InvalidArgument(format!("parameter must be `name=value`: {s}")))
+}
+
+/// Notarizes a request and writes the proof to the output file.
+async fn notarize(args: NotarizeArgs) -> Result<(), CliError> {
+    #[cfg(feature = "recipe")]
+    let params: BTreeMap<String, String> = args.params.iter().cloned().collect();
+    #[cfg(feature = "recipe")]
+    let (mut recipe, (url, method, headers, data)) = match &args.recipe {
+        Some(path) => {
+            let mut recipe = Recipe::new(&tokio::fs::read(path).await?)?;
+            let request = recipe.build_request(&params)?;
+            let headers = request
+                .headers
+                .iter()
+                .map(|(name, value)| format!("{name}: {value}"))
+                .collect();
+
+            (
+                Some(recipe),
+                (request.url, request.method, headers, request.body),
+            )
+        }
+        None => (None, request_args(&args)?),
+    };
+    #[cfg(not(feature = "recipe"))]
+    let (url, method, headers, data) = request_args(&args)?;
+
+    let target = Target::parse(&url)?;
+    let request = RawRequest::new(&method, &target, &headers, data.as_deref())?;
+
+    let (notary_host, notary_port) = args
+        .notary
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| {
+            CliError::InvalidArgument(format!(
+                "notary address must be `host:port`: {}",
+                args.notary
+            ))
+        })?;
+
+    let (notary_socket, session_id) = request_notarization(
+        notary_host,
+        notary_port,
+        args.max_sent_data,
+        args.max_recv_data,
+    )
+    .await?;
+    info!("Started notarization session {session_id}");
+
+    let mut config = ProverConfig::builder();
+    config
+        .id(session_id.as_str())
+        .server_dns(target.host.as_str());
+    if let Some(path) = &args.ca_cert {
+        config.root_cert_store(load_root_store(path).await?);
+    }
+    if let Some(max_sent_data) = args.max_sent_data {
+        config.max_sent_data(max_sent_data);
+    }
+    if let Some(max_recv_data) = args.max_recv_data {
+        config.max_recv_data(max_recv_data);
+    }
+
+    let prover = Prover::new(config.build()?)
+        .setup(notary_socket.compat())
+        .await?;
+
+    let server_socket = TcpStream::connect((target.host.as_str(), target.port)).await?;
+    let (mut conn, prover_fut) = prover.connect(server_socket.compat()).await?;
+    let prover_task = tokio::spawn(prover_fut);
+
+    conn.write_all(&request.data).await?;
+    let mut response = Vec::new();
+    conn.read_to_end(&mut response).await?;
+    conn.close().await?;
+    info!("Received {} bytes from the server", response.len());
+
+    let mut prover = prover_task
+        .await
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))??
+        .start_notarize();
+
+    #[cfg(feature = "recipe")]
+    let (sent, recv) = match &mut recipe {
+        Some(recipe) => recipe::select_disclosure(
+            recipe,
+            &params,
+            &args.secret_headers,
+            args.yes,
+            &request,
+            &response,
+        )?,
+        None => disclosed_ranges(&args.redact, &request, &response),
+    };
+    #[cfg(not(feature = "recipe"))]
+    let (sent, recv) = disclosed_ranges(&args.redact, &request, &response);
+    let builder = prover.commitment_builder();
+    let mut commitments = Vec::with_capacity(sent.len() + recv.len());
+    for range in &sent {
+        commitments.push(builder.commit_sent(range)?);
+    }
+    for range in &recv {
+        commitments.push(builder.commit_recv(range)?);
+    }
+
+    let notarized_session = prover.finalize().await?;
+
+    let mut proof_builder = notarized_session.data().build_substrings_proof();
+    for id in commitments {
+        proof_builder.reveal_by_id(id)?;
+    }
+
+    let proof = TlsProof {
+        session: notarized_session.session_proof(),
+        substrings: proof_builder.build()?,
+    };
+
+    tokio::fs::write(&args.output, serde_json::to_vec_pretty(&proof)?).await?;
+    println!("The proof has been written to {}", args.output.display());
+
+    Ok(())
+}
+
+/// Returns the URL, method, headers and body of the request given on the command line.
+fn request_args(
+    args: &NotarizeArgs,
+) -> Result<(String, String, Vec<String>, Option<String>), CliError> {
+    let url = args
+        .url
+        .clone()
+        .ok_or_else(|| CliError::InvalidArgument("a URL or a recipe is required".to_string()))?;
+    let method = args
+        .method
+        .clone()
+        .unwrap_or_else(|| if args.data.is_some() { "POST" } else { "GET" }.to_string());
+
+    Ok((url, method, args.headers.clone(), args.data.clone()))
+}
+
+/// Prints the structure of a proof file.
+async fn inspect(args: InspectArgs) -> Result<(), CliError> {
+    let proof: TlsProof = serde_json::from_slice(&tokio::fs::read(&args.proof).await?)?;
+    let notary_key = match &args.notary_key {
+        Some(path) => Some(inspect::load_notary_key(path).await?),
+        None => None,
+    };
+
+    print!(
+        "{}",
+        inspect::Inspection {
+            proof: &proof,
+            notary_key: notary_key.as_ref(),
+        }
+    );
+
+    Ok(())
+}
+
+/// Writes the test vectors to the output file or stdout.
+async fn gen_vectors(args: GenVectorsArgs) -> Result<(), CliError> {
+    let vectors = serde_json::to_string_pretty(&vectors::Vectors::generate())?;
+
+    match &args.output {
+        Some(path) => {
+            tokio::fs::write(path, vectors).await?;
+            info!("Wrote test vectors to {}", path.display());
+        }
+        None => println!("{vectors}"),
+    }
+
+    Ok(())
+}
+
+/// Loads a root store which only trusts the given CA certificate.
+async fn load_root_store(path: &Path) -> Result<RootCertStore, CliError> {
+    let cert = tokio::fs::read(path).await?;
+
+    let mut root_store = RootCertStore::empty();
+    match std::str::from_utf8(&cert) {
+        Ok(pem) if pem.contains("-----BEGIN") => root_store.add_pem(pem)?,
+        _ => root_store.add(&Certificate(cert))?,
+    }
+
+    Ok(root_store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_args() {
+        let Cli::Notarize(args) = Cli::from_iter_safe([
+            "tlsn",
+            "notarize",
+            "https://example.com/api",
+            "-H",
+            "Authorization: Bearer secret",
+            "--redact",
+            "header:authorization",
+            "--redact",
+            "recv:secret",
+        ])
+        .unwrap() else {
+            panic!("expected notarize");
+        };
+
+        assert_eq!(args.url.as_deref(), Some("https://example.com/api"));
+        assert_eq!(args.headers, vec!["Authorization: Bearer secret"]);
+        assert_eq!(args.redact.len(), 2);
+        assert_eq!(args.notary, "127.0.0.1:7047");
+        assert_eq!(args.output, PathBuf::from("proof.json"));
+
+        assert!(
+            Cli::from_iter_safe(["tlsn", "notarize", "https://a.com", "--redact", "x"]).is_err()
+        );
+    }
+
+    #[cfg(feature = "recipe")]
+    #[test]
+    fn test_parse_recipe_args() {
+        let Cli::Notarize(args) = Cli::from_iter_safe([
+            "tlsn",
+            "notarize",
+            "--recipe",
+            "account.wasm",
+            "--param",
+            "user=alice",
+            "--param",
+            "query=a=b",
+            "--secret-header",
+            "authorization",
+        ])
+        .unwrap() else {
+            panic!("expected notarize");
+        };
+
+        assert_eq!(args.url, None);
+        assert_eq!(args.recipe, Some(PathBuf::from("account.wasm")));
+        assert_eq!(
+            args.params,
+            vec![
+                ("user".to_string(), "alice".to_string()),
+                ("query".to_string(), "a=b".to_string()),
+            ]
+        );
+        assert_eq!(args.secret_headers, vec!["authorization"]);
+        assert!(!args.yes);
+
+        assert!(Cli::from_iter_safe([
+            "tlsn",
+            "notarize",
+            "https://a.com",
+            "--recipe",
+            "account.wasm"
+        ])
+        .is_err());
+        assert!(Cli::from_iter_safe(["tlsn", "notarize", "--param", "user"]).is_err());
+        assert!(Cli::from_iter_safe(["tlsn", "notarize", "https://a.com", "--yes"]).is_err());
+        assert!(Cli::from_iter_safe(["tlsn", "notarize"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_inspect_args() {
+        let Cli::Inspect(args) = Cli::from_iter_safe([
+            "tlsn",
+            "inspect",
+            "proof.json",
+            "--notary-key",
+            "notary.pub",
+        ])
+        .unwrap() else {
+            panic!("expected inspect");
+        };
+
+        assert_eq!(args.proof, PathBuf::from("proof.json"));
+        assert_eq!(args.notary_key, Some(PathBuf::from("notary.pub")));
+    }
+}
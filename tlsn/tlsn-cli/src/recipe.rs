@@ -0,0 +1,71 @@
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    ops::Range,
+};
+
+use tlsn_plugin::Recipe;
+
+use crate::{error::CliError, request::RawRequest};
+
+/// Returns the ranges of the request and response the recipe discloses.
+///
+/// The recipe sees the whole request, so the values of the secret headers are never disclosed
+/// and, unless `confirmed` is set, the disclosed request is printed and has to be confirmed on
+/// stdin.
+pub(crate) fn select_disclosure(
+    recipe: &mut Recipe,
+    params: &BTreeMap<String, String>,
+    secret_headers: &[String],
+    confirmed: bool,
+    request: &RawRequest,
+    response: &[u8],
+) -> Result<(Vec<Range<usize>>, Vec<Range<usize>>), CliError> {
+    let secret_sent: Vec<_> = secret_headers
+        .iter()
+        .flat_map(|name| request.header_values(name))
+        .collect();
+
+    let disclosure = recipe.select_disclosure(params, &request.data, response, &secret_sent)?;
+
+    if !confirmed {
+        eprintln!("The recipe discloses this part of the request, `X` is redacted:\n");
+        eprintln!("{}\n", disclosed_text(&request.data, &disclosure.sent));
+        eprint!("Disclose it in the proof? [y/N] ");
+        std::io::stderr().flush()?;
+
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        if !matches!(answer.trim(), "y" | "Y" | "yes") {
+            return Err(CliError::NotConfirmed);
+        }
+    }
+
+    Ok((disclosure.sent, disclosure.recv))
+}
+
+/// Returns the data with every byte outside of the ranges replaced by `X`.
+fn disclosed_text(data: &[u8], ranges: &[Range<usize>]) -> String {
+    let mut disclosed = vec![b'X'; data.len()];
+    for range in ranges {
+        disclosed[range.clone()].copy_from_slice(&data[range.clone()]);
+    }
+
+    String::from_utf8_lossy(&disclosed).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disclosed_text() {
+        let data = b"GET /?token=secret HTTP/1.1";
+
+        assert_eq!(
+            disclosed_text(data, &[0..12, 18..27]),
+            "GET /?token=XXXXXX HTTP/1.1"
+        );
+        assert_eq!(disclosed_text(data, &[]), "X".repeat(data.len()));
+    }
+}
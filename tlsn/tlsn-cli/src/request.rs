@@ -0,0 +1,185 @@
+use std::ops::Range;
+
+use hyper::Uri;
+
+use crate::error::CliError;
+
+/// The default port of HTTPS servers.
+const DEFAULT_PORT: u16 = 443;
+
+/// The target of a request, parsed from an `https` URL.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Target {
+    /// The DNS name of the server.
+    pub(crate) host: String,
+    /// The port of the server.
+    pub(crate) port: u16,
+    /// The path and query of the request.
+    pub(crate) path: String,
+}
+
+impl Target {
+    /// Parses the target from a URL.
+    pub(crate) fn parse(url: &str) -> Result<Self, CliError> {
+        let uri: Uri = url
+            .parse()
+            .map_err(|_| CliError::InvalidArgument(format!("invalid URL: {url}")))?;
+
+        if uri.scheme_str() != Some("https") {
+            return Err(CliError::InvalidArgument(format!(
+                "only https URLs can be notarized: {url}"
+            )));
+        }
+
+        let host = uri
+            .host()
+            .ok_or_else(|| CliError::InvalidArgument(format!("URL has no host: {url}")))?;
+
+        Ok(Self {
+            host: host.to_string(),
+            port: uri.port_u16().unwrap_or(DEFAULT_PORT),
+            path: uri
+                .path_and_query()
+                .map(|path| path.as_str())
+                .filter(|path| !path.is_empty())
+                .unwrap_or("/")
+                .to_string(),
+        })
+    }
+}
+
+/// A raw HTTP/1.1 request.
+#[derive(Debug)]
+pub(crate) struct RawRequest {
+    /// The bytes of the request.
+    pub(crate) data: Vec<u8>,
+    /// The names of the headers, lowercased, with the ranges of their values in `data`.
+    pub(crate) headers: Vec<(String, Range<usize>)>,
+}
+
+impl RawRequest {
+    /// Builds a request which asks the server to close the connection after responding.
+    ///
+    /// `Host`, `Connection`, `Accept-Encoding` and `Content-Length` are set unless given in
+    /// `headers`, as `name: value` pairs.
+    pub(crate) fn new(
+        method: &str,
+        target: &Target,
+        headers: &[String],
+        body: Option<&str>,
+    ) -> Result<Self, CliError> {
+        let mut parsed = Vec::with_capacity(headers.len());
+        for header in headers {
+            let (name, value) = header.split_once(':').ok_or_else(|| {
+                CliError::InvalidArgument(format!("header must be `name: value`: {header}"))
+            })?;
+            parsed.push((name.trim().to_string(), value.trim().to_string()));
+        }
+
+        let has = |name: &str| parsed.iter().any(|(n, _)| n.eq_ignore_ascii_case(name));
+        let mut defaults = Vec::new();
+        if !has("host") {
+            defaults.push(("Host".to_string(), target.host.clone()));
+        }
+        // The TLSNotary tooling does not support compression.
+        if !has("accept-encoding") {
+            defaults.push(("Accept-Encoding".to_string(), "identity".to_string()));
+        }
+        // The transcript is only complete once the server closes the connection.
+        if !has("connection") {
+            defaults.push(("Connection".to_string(), "close".to_string()));
+        }
+        if let Some(body) = body {
+            if !has("content-length") {
+                defaults.push(("Content-Length".to_string(), body.len().to_string()));
+            }
+        }
+
+        let mut data = format!("{method} {} HTTP/1.1\r\n", target.path).into_bytes();
+        let mut ranges = Vec::new();
+        for (name, value) in defaults.into_iter().chain(parsed) {
+            data.extend_from_slice(format!("{name}: ").as_bytes());
+            let start = data.len();
+            data.extend_from_slice(value.as_bytes());
+            ranges.push((name.to_ascii_lowercase(), start..data.len()));
+            data.extend_from_slice(b"\r\n");
+        }
+        data.extend_from_slice(b"\r\n");
+        if let Some(body) = body {
+            data.extend_from_slice(body.as_bytes());
+        }
+
+        Ok(Self {
+            data,
+            headers: ranges,
+        })
+    }
+
+    /// Returns the ranges of the values of the headers with the given name.
+    pub(crate) fn header_values<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = Range<usize>> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, range)| range.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let target = Target::parse("https://example.com:8443/api?id=1").unwrap();
+        assert_eq!(
+            target,
+            Target {
+                host: "example.com".to_string(),
+                port: 8443,
+                path: "/api?id=1".to_string(),
+            }
+        );
+
+        let target = Target::parse("https://example.com").unwrap();
+        assert_eq!(target.port, DEFAULT_PORT);
+        assert_eq!(target.path, "/");
+
+        assert!(Target::parse("http://example.com").is_err());
+        assert!(Target::parse("example.com").is_err());
+    }
+
+    #[test]
+    fn test_raw_request() {
+        let target = Target::parse("https://example.com/api").unwrap();
+        let request = RawRequest::new(
+            "POST",
+            &target,
+            &["Authorization: Bearer secret".to_string()],
+            Some("{}"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            request.data,
+            b"POST /api HTTP/1.1\r\nHost: example.com\r\nAccept-Encoding: identity\r\n\
+              Connection: close\r\nContent-Length: 2\r\nAuthorization: Bearer secret\r\n\r\n{}"
+        );
+
+        let values: Vec<_> = request.header_values("Authorization").collect();
+        assert_eq!(values.len(), 1);
+        assert_eq!(&request.data[values[0].clone()], b"Bearer secret");
+    }
+
+    #[test]
+    fn test_raw_request_keeps_given_headers() {
+        let target = Target::parse("https://example.com").unwrap();
+        let request =
+            RawRequest::new("GET", &target, &["host: other.com".to_string()], None).unwrap();
+
+        assert_eq!(request.header_values("host").count(), 1);
+        assert!(RawRequest::new("GET", &target, &["invalid".to_string()], None).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use mpz_core::{hash::Hash, serialize::CanonicalSerialize};
+use p256::ecdsa::signature::Signer;
+use rs_merkle::{algorithms::Sha256, MerkleTree};
+use serde::Serialize;
+use tls_core::{key::PublicKey, msgs::enums::NamedGroup};
+
+use tlsn_core::{
+    core::{verify_merkle_proof, verify_signature, SignatureAlgorithm},
+    merkle::MerkleRoot,
+    HandshakeSummary, NotaryPublicKey, SessionHeader, Signature,
+};
+
+/// The version of the format of the vectors, bumped on breaking changes.
+const VERSION: u32 = 1;
+/// The message signed by the signature vectors.
+const MESSAGE: &[u8] = b"tlsn test vector";
+/// The scope of the nullifier vectors.
+const NULLIFIER_SCOPE: &[u8] = b"tlsn test scope";
+
+/// Test vectors for verifier implementations in other languages.
+///
+/// Every input is fixed, so the vectors are the same on every run. The expected results are those
+/// of the verification primitives of `tlsn-core`.
+#[derive(Debug, Serialize)]
+pub(crate) struct Vectors {
+    version: u32,
+    signatures: Vec<SignatureVector>,
+    merkle_proofs: Vec<MerkleVector>,
+    session_headers: Vec<HeaderVector>,
+}
+
+/// The expected result of a verification.
+#[derive(Debug, Serialize)]
+struct Expected {
+    valid: bool,
+    /// The error, if the verification fails.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl<E: ToString> From<Result<(), E>> for Expected {
+    fn from(result: Result<(), E>) -> Self {
+        Self {
+            valid: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// A signature over a message, see [`verify_signature`].
+#[derive(Debug, Serialize)]
+struct SignatureVector {
+    description: &'static str,
+    algorithm: String,
+    /// SEC1 encoded for ECDSA keys, the compressed point for Ed25519 keys.
+    public_key: String,
+    message: String,
+    /// Fixed-size encoded.
+    signature: String,
+    expected: Expected,
+}
+
+/// An inclusion proof of leaves in a SHA-256 Merkle tree, see [`verify_merkle_proof`].
+#[derive(Debug, Serialize)]
+struct MerkleVector {
+    description: &'static str,
+    root: String,
+    proof_hashes: Vec<String>,
+    leaf_indices: Vec<usize>,
+    leaf_hashes: Vec<String>,
+    total_leaves: usize,
+    expected: Expected,
+}
+
+/// A signed session header, the attestation of a session.
+#[derive(Debug, Serialize)]
+struct HeaderVector {
+    description: &'static str,
+    encoder_seed: String,
+    merkle_root: String,
+    sent_len: usize,
+    recv_len: usize,
+    time: u64,
+    /// The canonical encoding of the header, which is signed.
+    header_bytes: String,
+    algorithm: String,
+    public_key: String,
+    signature: String,
+    /// The nullifier of the header for [`NULLIFIER_SCOPE`].
+    nullifier_scope: String,
+    nullifier: String,
+    expected: Expected,
+}
+
+/// Signs a message with a notary key.
+type SignFn = Box<dyn Fn(&[u8]) -> Signature>;
+
+/// A notary key of each supported algorithm, derived from fixed bytes.
+fn notary_keys() -> Vec<(NotaryPublicKey, SignFn)> {
+    let p256_key = p256::ecdsa::SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+    let k256_key = k256::ecdsa::SigningKey::from_bytes(&[2u8; 32].into()).unwrap();
+    let ed25519_key = ed25519_dalek::SigningKey::from_bytes(&[3u8; 32]);
+
+    vec![
+        (
+            p256::PublicKey::from(*p256_key.verifying_key()).into(),
+            Box::new(move |msg: &[u8]| {
+                let sig: p256::ecdsa::Signature = p256_key.sign(msg);
+                sig.into()
+            }) as SignFn,
+        ),
+        (
+            k256::PublicKey::from(*k256_key.verifying_key()).into(),
+            Box::new(move |msg: &[u8]| {
+                let sig: k256::ecdsa::Signature = k256_key.sign(msg);
+                sig.into()
+            }) as SignFn,
+        ),
+        (
+            ed25519_key.verifying_key().into(),
+            Box::new(move |msg: &[u8]| ed25519_key.sign(msg).into()) as SignFn,
+        ),
+    ]
+}
+
+fn algorithm_name(algorithm: SignatureAlgorithm) -> String {
+    format!("{algorithm:?}").to_lowercase()
+}
+
+impl Vectors {
+    /// Generates the vectors.
+    pub(crate) fn generate() -> Self {
+        Self {
+            version: VERSION,
+            signatures: signature_vectors(),
+            merkle_proofs: merkle_vectors(),
+            session_headers: header_vectors(),
+        }
+    }
+}
+
+fn signature_vectors() -> Vec<SignatureVector> {
+    let mut vectors = Vec::new();
+    for (key, sign) in notary_keys() {
+        let signature = sign(MESSAGE).to_bytes();
+        let mut tampered = MESSAGE.to_vec();
+        tampered[0] ^= 1;
+
+        for (description, message) in [
+            ("valid signature", MESSAGE.to_vec()),
+            ("message does not match", tampered),
+        ] {
+            vectors.push(SignatureVector {
+                description,
+                algorithm: algorithm_name(key.algorithm()),
+                public_key: hex::encode(key.to_bytes()),
+                message: hex::encode(&message),
+                signature: hex::encode(&signature),
+                expected: verify_signature(key.algorithm(), &key.to_bytes(), &message, &signature)
+                    .into(),
+            });
+        }
+    }
+
+    vectors
+}
+
+fn merkle_vectors() -> Vec<MerkleVector> {
+    let leaves: Vec<[u8; 32]> = (0..5u8).map(|i| [i; 32]).collect();
+    let tree = MerkleTree::<Sha256>::from_leaves(&leaves);
+    let root = tree.root().expect("tree has leaves");
+
+    let proof = |description: &'static str, indices: &[usize], leaf_hashes: Vec<[u8; 32]>| {
+        let proof_hashes = tree.proof(indices).proof_hashes().to_vec();
+        let expected =
+            verify_merkle_proof(&root, &proof_hashes, indices, &leaf_hashes, leaves.len()).into();
+
+        MerkleVector {
+            description,
+            root: hex::encode(root),
+            proof_hashes: proof_hashes.iter().map(hex::encode).collect(),
+            leaf_indices: indices.to_vec(),
+            leaf_hashes: leaf_hashes.iter().map(hex::encode).collect(),
+            total_leaves: leaves.len(),
+            expected,
+        }
+    };
+
+    vec![
+        proof("single leaf", &[0], vec![leaves[0]]),
+        proof("last leaf", &[4], vec![leaves[4]]),
+        proof("multiple leaves", &[1, 3], vec![leaves[1], leaves[3]]),
+        proof("leaf does not match", &[2], vec![leaves[1]]),
+        proof("fewer hashes than indices", &[1, 3], vec![leaves[1]]),
+    ]
+}
+
+fn header_vectors() -> Vec<HeaderVector> {
+    let header = SessionHeader::new(
+        [4u8; 32],
+        MerkleRoot::from([5u8; 32]),
+        128,
+        1024,
+        HandshakeSummary::new(
+            1_700_000_000,
+            PublicKey::new(NamedGroup::secp256r1, &[6u8; 65]),
+            Hash::from([7u8; 32]),
+        ),
+    );
+    let header_bytes = header.to_bytes();
+
+    let mut vectors = Vec::new();
+    for (key, sign) in notary_keys() {
+        let alg = key.algorithm();
+
+        for (description, signature) in [
+            ("valid attestation", sign(&header_bytes).to_bytes()),
+            (
+                "signature of another header",
+                sign(b"another header").to_bytes(),
+            ),
+        ] {
+            vectors.push(HeaderVector {
+                description,
+                encoder_seed: hex::encode(header.encoder_seed()),
+                merkle_root: hex::encode(header.merkle_root().to_inner()),
+                sent_len: header.sent_len(),
+                recv_len: header.recv_len(),
+                time: header.time(),
+                header_bytes: hex::encode(&header_bytes),
+                algorithm: algorithm_name(alg),
+                public_key: hex::encode(key.to_bytes()),
+                signature: hex::encode(&signature),
+                nullifier_scope: hex::encode(NULLIFIER_SCOPE),
+                nullifier: hex::encode(header.nullifier(&key, NULLIFIER_SCOPE)),
+                expected: verify_signature(alg, &key.to_bytes(), &header_bytes, &signature).into(),
+            });
+        }
+    }
+
+    vectors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors_are_deterministic() {
+        let a = serde_json::to_string(&Vectors::generate()).unwrap();
+        let b = serde_json::to_string(&Vectors::generate()).unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_vectors_expected_results() {
+        let vectors = Vectors::generate();
+
+        for vector in &vectors.signatures {
+            assert_eq!(
+                vector.expected.valid,
+                vector.description == "valid signature"
+            );
+        }
+        for vector in &vectors.session_headers {
+            assert_eq!(
+                vector.expected.valid,
+                vector.description == "valid attestation"
+            );
+        }
+        let valid: Vec<_> = vectors
+            .merkle_proofs
+            .iter()
+            .map(|vector| vector.expected.valid)
+            .collect();
+        assert_eq!(valid, vec![true, true, true, false, false]);
+    }
+}
@@ -6,6 +6,8 @@ use crate::Role;
 pub const DEFAULT_MAX_SENT_LIMIT: usize = 1 << 12;
 /// Default for the maximum number of bytes that can be received (16Kb).
 pub const DEFAULT_MAX_RECV_LIMIT: usize = 1 << 14;
+/// Default for the maximum number of VM threads used by a session.
+pub const DEFAULT_MAX_THREADS: usize = 8;
 
 // Determined experimentally, will be subject to change if underlying protocols are modified.
 const KE_OTS: usize = 3360;
@@ -42,3 +44,32 @@ pub fn ot_recv_estimate(role: Role, max_sent_data: usize, max_recv_data: usize)
         Role::Verifier => KE_OTS + GHASH_OTS + EXTRA_OTS,
     }
 }
+
+// Size of a single OT message, a 128-bit block.
+const BYTES_PER_OT_MSG: usize = 16;
+// The plaintext, keystream and ciphertext of each transcript byte are encoded with a 128-bit label
+// per bit.
+const ENCODING_BYTES_PER_BYTE: usize = 3 * 8 * 16;
+// The half-gates tables of an AES-128 circuit, ~6400 AND gates of two 128-bit ciphertexts each.
+const GARBLED_BYTES_PER_THREAD: usize = 6400 * 2 * 16;
+
+/// Returns an estimate of the number of bytes held in memory by a party for the duration of a
+/// session.
+///
+/// The estimate covers the preprocessed OT material, where the sender stores two messages per OT
+/// and the receiver one, the encodings of the transcript and the garbled tables in flight on each
+/// VM thread. These are held by the MPC backend and can not be spilled to disk.
+///
+/// It does not cover the received records buffered for deferred decryption, which the verifier
+/// spills to disk once they exceed its budget, nor the per-stream mux buffers.
+pub fn memory_estimate(
+    role: Role,
+    max_sent_data: usize,
+    max_recv_data: usize,
+    max_threads: usize,
+) -> usize {
+    ot_send_estimate(role, max_sent_data, max_recv_data) * 2 * BYTES_PER_OT_MSG
+        + ot_recv_estimate(role, max_sent_data, max_recv_data) * BYTES_PER_OT_MSG
+        + (max_sent_data + max_recv_data) * ENCODING_BYTES_PER_BYTE
+        + max_threads * GARBLED_BYTES_PER_THREAD
+}
@@ -0,0 +1,252 @@
+//! Version negotiation performed before any 2PC sub-protocol is set up.
+//!
+//! Both parties send a [`Hello`] on a dedicated channel and check the other party's before any
+//! OT, garbled circuit or PRF messages are exchanged, so incompatible builds fail immediately with
+//! a clear error.
+//!
+//! The hello is sent as two frames. The first only carries the protocol version and its encoding
+//! must never change, so that a peer running any other version reads it and reports a
+//! [`HelloError::VersionMismatch`]. The remaining parameters are only exchanged once both versions
+//! match, so they may change freely along with [`PROTOCOL_VERSION`].
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use utils_aio::duplex::Duplex;
+
+use crate::config::DEFAULT_MAX_THREADS;
+
+/// Version of the 2PC protocol, must be bumped on any incompatible change to the message framing
+/// or to the sub-protocols.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// Optional protocol features which both parties must support to be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Feature {
+    /// The HMAC-SHA384 PRF family.
+    PrfSha384,
+    /// The extended master secret (RFC 7627).
+    ExtendedMasterSecret,
+    /// The TLS 1.3 key schedule.
+    Tls13,
+}
+
+/// The first message sent by each party.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    /// The protocol version.
+    pub version: u32,
+    /// The optional features supported by this party.
+    pub features: Vec<Feature>,
+    /// The maximum number of VM threads this party allows per session.
+    pub max_threads: usize,
+}
+
+/// A frame of the hello exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HelloMessage {
+    /// The protocol version, always sent first.
+    ///
+    /// This must stay the first variant with a single `u32` field, as its encoding is relied on
+    /// by every version of the protocol.
+    Version(u32),
+    /// The remaining parameters of the hello, sent once both versions match.
+    Params {
+        /// The optional features supported by this party.
+        features: Vec<Feature>,
+        /// The maximum number of VM threads this party allows per session.
+        max_threads: usize,
+    },
+}
+
+/// The parameters agreed on by both parties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    /// The optional features supported by both parties.
+    pub features: Vec<Feature>,
+    /// The number of VM threads to use for the session.
+    pub threads: usize,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            features: Vec::new(),
+            max_threads: DEFAULT_MAX_THREADS,
+        }
+    }
+}
+
+impl Hello {
+    /// Checks the other party's hello, returning the parameters agreed on by both parties.
+    ///
+    /// The session uses the features supported by both parties and the smaller of the two thread
+    /// limits.
+    pub fn negotiate(&self, peer: &Hello) -> Result<Negotiated, HelloError> {
+        if self.version != peer.version {
+            return Err(HelloError::VersionMismatch {
+                ours: self.version,
+                theirs: peer.version,
+            });
+        }
+
+        Ok(Negotiated {
+            features: self
+                .features
+                .iter()
+                .filter(|feature| peer.features.contains(feature))
+                .copied()
+                .collect(),
+            threads: self.max_threads.min(peer.max_threads).max(1),
+        })
+    }
+}
+
+/// An error that can occur during version negotiation.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum HelloError {
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    #[error("incompatible protocol version: ours is {ours}, theirs is {theirs}")]
+    VersionMismatch { ours: u32, theirs: u32 },
+    #[error("unexpected hello message: {0:?}")]
+    UnexpectedMessage(HelloMessage),
+}
+
+/// Sends our hello and receives the other party's, returning the negotiated parameters.
+///
+/// The versions are exchanged and compared first, the remaining parameters are only exchanged
+/// if they match.
+///
+/// # Arguments
+///
+/// * `channel` - The channel to exchange the hello messages on.
+/// * `hello` - Our hello.
+pub async fn exchange_hello<C: Duplex<HelloMessage> + Unpin>(
+    channel: &mut C,
+    hello: Hello,
+) -> Result<Negotiated, HelloError> {
+    channel.send(HelloMessage::Version(hello.version)).await?;
+
+    let version = match recv(channel).await? {
+        HelloMessage::Version(version) => version,
+        msg => return Err(HelloError::UnexpectedMessage(msg)),
+    };
+
+    if version != hello.version {
+        return Err(HelloError::VersionMismatch {
+            ours: hello.version,
+            theirs: version,
+        });
+    }
+
+    channel
+        .send(HelloMessage::Params {
+            features: hello.features.clone(),
+            max_threads: hello.max_threads,
+        })
+        .await?;
+
+    let peer = match recv(channel).await? {
+        HelloMessage::Params {
+            features,
+            max_threads,
+        } => Hello {
+            version,
+            features,
+            max_threads,
+        },
+        msg => return Err(HelloError::UnexpectedMessage(msg)),
+    };
+
+    hello.negotiate(&peer)
+}
+
+async fn recv<C: Duplex<HelloMessage> + Unpin>(
+    channel: &mut C,
+) -> Result<HelloMessage, HelloError> {
+    Ok(channel
+        .next()
+        .await
+        .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof))??)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils_aio::duplex::MemoryDuplex;
+
+    #[test]
+    fn test_exchange_hello() {
+        let (mut a, mut b) = MemoryDuplex::new();
+
+        let ours = Hello {
+            version: PROTOCOL_VERSION,
+            features: vec![Feature::PrfSha384, Feature::Tls13],
+            max_threads: 8,
+        };
+        let theirs = Hello {
+            version: PROTOCOL_VERSION,
+            features: vec![Feature::Tls13],
+            max_threads: 4,
+        };
+
+        let (ours, theirs) = futures::executor::block_on(futures::future::join(
+            exchange_hello(&mut a, ours),
+            exchange_hello(&mut b, theirs),
+        ));
+
+        let expected = Negotiated {
+            features: vec![Feature::Tls13],
+            threads: 4,
+        };
+
+        assert_eq!(ours.unwrap(), expected);
+        assert_eq!(theirs.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_exchange_hello_version_mismatch() {
+        let (mut a, mut b) = MemoryDuplex::new();
+
+        let theirs = Hello {
+            version: PROTOCOL_VERSION + 1,
+            ..Default::default()
+        };
+
+        let (ours, theirs) = futures::executor::block_on(futures::future::join(
+            exchange_hello(&mut a, Hello::default()),
+            exchange_hello(&mut b, theirs),
+        ));
+
+        assert!(matches!(
+            ours,
+            Err(HelloError::VersionMismatch { ours, theirs })
+                if ours == PROTOCOL_VERSION && theirs == PROTOCOL_VERSION + 1
+        ));
+        assert!(matches!(theirs, Err(HelloError::VersionMismatch { .. })));
+    }
+
+    #[test]
+    fn test_version_frame_encoding() {
+        // Peers of any version rely on this encoding to detect a mismatch.
+        assert_eq!(
+            bincode::serialize(&HelloMessage::Version(1)).unwrap(),
+            [0, 0, 0, 0, 1, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_version_mismatch() {
+        let peer = Hello {
+            version: PROTOCOL_VERSION + 1,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            Hello::default().negotiate(&peer),
+            Err(HelloError::VersionMismatch { .. })
+        ));
+    }
+}
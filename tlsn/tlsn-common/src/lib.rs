@@ -5,7 +5,10 @@
 #![forbid(unsafe_code)]
 
 pub mod config;
+pub mod hello;
 pub mod mux;
+pub mod registry;
+pub mod rng;
 
 /// The party's role in the TLSN protocol.
 ///
@@ -1,20 +1,45 @@
 //! Multiplexer used in the TLSNotary protocol.
 
-use utils_aio::codec::BincodeMux;
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_trait::async_trait;
+use utils_aio::{
+    codec::BincodeMux,
+    mux::{MuxStream, MuxerError},
+};
 
 use futures::{AsyncRead, AsyncWrite};
-use uid_mux::{yamux, UidYamux, UidYamuxControl};
+use uid_mux::{yamux, UidStream, UidYamux, UidYamuxControl};
 
 use crate::Role;
 
+/// Fault injection into the transport of the multiplexer, eg. by wrapping the socket passed to
+/// the prover or verifier in a [`ChaosStream`](chaos::ChaosStream).
+#[cfg(feature = "chaos")]
+pub use uid_mux::chaos;
+/// Recording of the traffic of the multiplexed streams and its replay, see
+/// [`attach_mux_with_recorder`] and [`attach_replay`].
+#[cfg(feature = "recorder")]
+pub use uid_mux::{recorder, replay};
+
 /// Multiplexer supporting unique deterministic stream IDs.
 pub type Mux<T> = UidYamux<T>;
 /// Multiplexer controller providing streams with a codec attached.
-pub type MuxControl = BincodeMux<UidYamuxControl>;
+pub type MuxControl = BincodeMux<MuxStreams>;
 
 const KB: usize = 1024;
 const MB: usize = 1024 * KB;
 
+/// Default for the maximum number of bytes buffered per stream (16MB).
+pub const DEFAULT_MAX_BUFFER_SIZE: usize = 16 * MB;
+/// Minimum for the maximum number of bytes buffered per stream (256KB).
+///
+/// The buffer size is used as the yamux receive window, which must be at least 256KB.
+pub const MIN_MAX_BUFFER_SIZE: usize = 256 * KB;
+
 /// Attaches a multiplexer to the provided socket.
 ///
 /// Returns the multiplexer and a controller for creating streams with a codec attached.
@@ -27,19 +52,195 @@ pub fn attach_mux<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
     socket: T,
     role: Role,
 ) -> (Mux<T>, MuxControl) {
+    attach_mux_with_buffer_size(socket, role, DEFAULT_MAX_BUFFER_SIZE)
+}
+
+/// Attaches a multiplexer to the provided socket, limiting the number of bytes buffered per stream.
+///
+/// Returns the multiplexer and a controller for creating streams with a codec attached.
+///
+/// # Arguments
+///
+/// * `socket` - The socket to attach the multiplexer to.
+/// * `role` - The role of the party using the multiplexer.
+/// * `max_buffer_size` - The maximum number of bytes buffered per stream, raised to
+///   [`MIN_MAX_BUFFER_SIZE`] if below it.
+pub fn attach_mux_with_buffer_size<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
+    socket: T,
+    role: Role,
+    max_buffer_size: usize,
+) -> (Mux<T>, MuxControl) {
+    let mux = new_mux(socket, role, max_buffer_size);
+    let ctrl = BincodeMux::new(MuxStreams::Live(mux.control()));
+
+    (mux, ctrl)
+}
+
+/// Attaches a multiplexer to the provided socket, recording the traffic of all streams.
+///
+/// Returns the multiplexer and a controller for creating streams with a codec attached.
+///
+/// # Arguments
+///
+/// * `socket` - The socket to attach the multiplexer to.
+/// * `role` - The role of the party using the multiplexer.
+/// * `max_buffer_size` - The maximum number of bytes buffered per stream, raised to
+///   [`MIN_MAX_BUFFER_SIZE`] if below it.
+/// * `recorder` - The recorder capturing the traffic.
+#[cfg(feature = "recorder")]
+pub fn attach_mux_with_recorder<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
+    socket: T,
+    role: Role,
+    max_buffer_size: usize,
+    recorder: recorder::Recorder,
+) -> (Mux<T>, MuxControl) {
+    let mut mux = new_mux(socket, role, max_buffer_size);
+    mux.set_recorder(recorder);
+    let ctrl = BincodeMux::new(MuxStreams::Live(mux.control()));
+
+    (mux, ctrl)
+}
+
+/// Attaches a multiplexer which replays a recording of one party's streams, eg. captured with
+/// [`attach_mux_with_recorder`].
+///
+/// The returned controller stands in for the party's peer: components driven with it read what the
+/// party received and their writes are checked against what the party sent, see
+/// [`replay`]. The returned future takes the place of the multiplexer future, it
+/// resolves once every recorded stream was replayed.
+///
+/// Only the setup of a seeded session can be replayed: the hello exchange and the base OTs
+/// reproduce the recording, but the OT extension draws randomness which can not be seeded (see
+/// [`rng`](crate::rng)), so the replay diverges on the `ot/0` or `ot/1` stream right after the base
+/// OTs.
+///
+/// # Arguments
+///
+/// * `recording` - The recording of the party's streams.
+#[cfg(feature = "recorder")]
+pub fn attach_replay(recording: replay::Recording) -> (replay::ReplayFinished, MuxControl) {
+    let mux = replay::ReplayMux::new(recording);
+
+    (mux.finished(), BincodeMux::new(MuxStreams::Replay(mux)))
+}
+
+/// The streams of a [MuxControl], opened on a live connection or replayed from a recording.
+#[derive(Debug, Clone)]
+pub enum MuxStreams {
+    /// Streams of a live connection.
+    Live(UidYamuxControl),
+    /// Streams replayed from a recording.
+    #[cfg(feature = "recorder")]
+    Replay(replay::ReplayMux),
+}
+
+impl MuxStreams {
+    /// Closes the connection, replayed streams need no closing.
+    pub async fn close(&mut self) -> Result<(), MuxerError> {
+        match self {
+            MuxStreams::Live(ctrl) => ctrl.close().await,
+            #[cfg(feature = "recorder")]
+            MuxStreams::Replay(_) => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl MuxStream for MuxStreams {
+    type Stream = Stream;
+
+    async fn get_stream(&mut self, id: &str) -> Result<Self::Stream, MuxerError> {
+        match self {
+            MuxStreams::Live(ctrl) => ctrl.get_stream(id).await.map(Stream::Live),
+            #[cfg(feature = "recorder")]
+            MuxStreams::Replay(mux) => mux.get_stream(id).await.map(Stream::Replay),
+        }
+    }
+}
+
+/// A stream opened by [MuxStreams].
+#[derive(Debug)]
+pub enum Stream {
+    /// A stream of a live connection.
+    Live(UidStream),
+    /// A replayed stream.
+    #[cfg(feature = "recorder")]
+    Replay(replay::ReplayStream),
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Live(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(feature = "recorder")]
+            Stream::Replay(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Live(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(feature = "recorder")]
+            Stream::Replay(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Live(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(feature = "recorder")]
+            Stream::Replay(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.get_mut() {
+            Stream::Live(stream) => Pin::new(stream).poll_close(cx),
+            #[cfg(feature = "recorder")]
+            Stream::Replay(stream) => Pin::new(stream).poll_close(cx),
+        }
+    }
+}
+
+fn new_mux<T: AsyncWrite + AsyncRead + Send + Unpin + 'static>(
+    socket: T,
+    role: Role,
+    max_buffer_size: usize,
+) -> Mux<T> {
+    // yamux panics on a receive window below its minimum.
+    let max_buffer_size = max_buffer_size.max(MIN_MAX_BUFFER_SIZE);
+
     let mut mux_config = yamux::Config::default();
     // See PR #418
     mux_config.set_max_num_streams(40);
-    mux_config.set_max_buffer_size(16 * MB);
-    mux_config.set_receive_window(16 * MB as u32);
+    mux_config.set_max_buffer_size(max_buffer_size);
+    mux_config.set_receive_window(max_buffer_size.min(u32::MAX as usize) as u32);
 
     let mux_role = match role {
         Role::Prover => yamux::Mode::Client,
         Role::Verifier => yamux::Mode::Server,
     };
 
-    let mux = UidYamux::new(mux_config, socket, mux_role);
-    let ctrl = BincodeMux::new(mux.control());
+    UidYamux::new(mux_config, socket, mux_role)
+}
 
-    (mux, ctrl)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_size_below_minimum() {
+        // yamux would panic on a receive window below its minimum.
+        _ = attach_mux_with_buffer_size(futures::io::Cursor::new(Vec::new()), Role::Prover, 0);
+    }
 }
@@ -0,0 +1,566 @@
+//! A client for a signed registry of known notaries.
+//!
+//! The registry lists the notaries a deployment trusts: where to reach them, their public keys,
+//! the protocol features they support and their policies. It is signed with a registry key, so
+//! that only the registry key has to be distributed out of band instead of every notary key.
+//!
+//! A prover uses [`Registry::select`] to pick a notary for a session, and a verifier uses
+//! [`Registry::resolve_signer`] to find the known notary which signed a session.
+//!
+//! The registry is fetched through a [`RegistrySource`], which leaves the transport up to the
+//! caller, and cached by a [`RegistryClient`].
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tlsn_core::{NotaryPublicKey, Signature};
+use web_time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::hello::Feature;
+
+/// Version of the registry format.
+pub const REGISTRY_VERSION: u32 = 1;
+/// Default for how long a fetched registry is used before it is fetched again (1 hour).
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A registry signed with the registry key, as served by a [`RegistrySource`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedRegistry {
+    /// The JSON encoded [`Registry`].
+    ///
+    /// The registry is kept encoded so that the signature is checked against the exact bytes that
+    /// were signed.
+    pub registry: String,
+    /// The signature of the registry key over the bytes of `registry`.
+    pub signature: Signature,
+}
+
+impl SignedRegistry {
+    /// Checks the signature and the validity period of the registry and returns it.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry_key` - The key the registry must be signed with.
+    pub fn verify(&self, registry_key: &NotaryPublicKey) -> Result<Registry, RegistryError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time is after the unix epoch")
+            .as_secs();
+
+        self.verify_at(registry_key, now)
+    }
+
+    fn verify_at(
+        &self,
+        registry_key: &NotaryPublicKey,
+        now: u64,
+    ) -> Result<Registry, RegistryError> {
+        self.signature
+            .verify(self.registry.as_bytes(), registry_key.clone())
+            .map_err(|e| RegistryError::InvalidSignature(e.to_string()))?;
+
+        let registry: Registry = serde_json::from_str(&self.registry)?;
+
+        if registry.version != REGISTRY_VERSION {
+            return Err(RegistryError::UnsupportedVersion(registry.version));
+        }
+
+        if now < registry.issued_at || now >= registry.expires_at {
+            return Err(RegistryError::Expired {
+                issued_at: registry.issued_at,
+                expires_at: registry.expires_at,
+            });
+        }
+
+        Ok(registry)
+    }
+}
+
+/// A registry of known notaries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Registry {
+    /// The version of the registry format.
+    pub version: u32,
+    /// The time the registry was issued, in seconds since the unix epoch.
+    pub issued_at: u64,
+    /// The time the registry expires, in seconds since the unix epoch.
+    pub expires_at: u64,
+    /// The known notaries, in order of preference.
+    pub notaries: Vec<NotaryEntry>,
+}
+
+/// A notary listed in the registry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotaryEntry {
+    /// The name of the notary, unique within the registry.
+    pub name: String,
+    /// The URL of the notary server.
+    pub url: String,
+    /// The public keys the notary signs with.
+    ///
+    /// A notary may list several keys while rotating them.
+    pub public_keys: Vec<NotaryPublicKey>,
+    /// The optional protocol features the notary supports.
+    #[serde(default)]
+    pub features: Vec<Feature>,
+    /// The policy of the notary.
+    #[serde(default)]
+    pub policy: NotaryPolicy,
+}
+
+/// The policy of a notary.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotaryPolicy {
+    /// The maximum number of bytes that can be sent to the server in a session.
+    pub max_sent_data: Option<usize>,
+    /// The maximum number of bytes that can be received from the server in a session.
+    pub max_recv_data: Option<usize>,
+    /// Whether an API key is required to request a session.
+    #[serde(default)]
+    pub requires_api_key: bool,
+}
+
+/// The requirements of a prover on the notary of a session.
+#[derive(Debug, Clone, Default)]
+pub struct NotaryRequirements {
+    /// The features the notary must support.
+    pub features: Vec<Feature>,
+    /// The number of bytes the prover needs to send to the server.
+    pub sent_data: usize,
+    /// The number of bytes the prover needs to receive from the server.
+    pub recv_data: usize,
+    /// Whether the prover has an API key.
+    pub has_api_key: bool,
+}
+
+impl NotaryEntry {
+    /// Returns whether the notary meets the requirements.
+    pub fn meets(&self, requirements: &NotaryRequirements) -> bool {
+        let within = |limit: Option<usize>, needed: usize| limit.is_none_or(|max| needed <= max);
+
+        requirements
+            .features
+            .iter()
+            .all(|feature| self.features.contains(feature))
+            && within(self.policy.max_sent_data, requirements.sent_data)
+            && within(self.policy.max_recv_data, requirements.recv_data)
+            && (!self.policy.requires_api_key || requirements.has_api_key)
+    }
+}
+
+impl Registry {
+    /// Returns the notary with the given name.
+    pub fn get(&self, name: &str) -> Option<&NotaryEntry> {
+        self.notaries.iter().find(|notary| notary.name == name)
+    }
+
+    /// Returns the most preferred notary which meets the requirements.
+    pub fn select(&self, requirements: &NotaryRequirements) -> Option<&NotaryEntry> {
+        self.notaries
+            .iter()
+            .find(|notary| notary.meets(requirements))
+    }
+
+    /// Returns the notary which signs with the given key.
+    pub fn resolve_key(&self, key: &NotaryPublicKey) -> Option<&NotaryEntry> {
+        let key = key.to_bytes();
+
+        self.notaries.iter().find(|notary| {
+            notary
+                .public_keys
+                .iter()
+                .any(|candidate| candidate.to_bytes() == key)
+        })
+    }
+
+    /// Returns the notary and its key which produced the signature over the message.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg` - The signed message.
+    /// * `signature` - The signature over the message.
+    pub fn resolve_signer(
+        &self,
+        msg: &[u8],
+        signature: &Signature,
+    ) -> Option<(&NotaryEntry, &NotaryPublicKey)> {
+        self.notaries.iter().find_map(|notary| {
+            notary
+                .public_keys
+                .iter()
+                .find(|key| signature.verify(msg, (*key).clone()).is_ok())
+                .map(|key| (notary, key))
+        })
+    }
+}
+
+/// A source the registry is fetched from.
+#[async_trait]
+pub trait RegistrySource {
+    /// Fetches the signed registry.
+    async fn fetch(&self) -> Result<SignedRegistry, RegistryError>;
+}
+
+/// A client which fetches the registry from a source and caches it.
+///
+/// The cached registry is used until it is older than the maximum age. If fetching fails after
+/// that, the cached registry keeps being used until it expires.
+pub struct RegistryClient<S> {
+    source: S,
+    registry_key: NotaryPublicKey,
+    max_age: Duration,
+    cached: Option<(Registry, Instant)>,
+    /// The issue time of the newest registry accepted, kept when the cached registry is dropped.
+    latest_issued_at: Option<u64>,
+}
+
+impl<S> RegistryClient<S>
+where
+    S: RegistrySource,
+{
+    /// Creates a new client.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source to fetch the registry from.
+    /// * `registry_key` - The key the registry must be signed with.
+    pub fn new(source: S, registry_key: NotaryPublicKey) -> Self {
+        Self {
+            source,
+            registry_key,
+            max_age: DEFAULT_MAX_AGE,
+            cached: None,
+            latest_issued_at: None,
+        }
+    }
+
+    /// Sets how long a fetched registry is used before it is fetched again.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Returns the cached registry, if any, without fetching it.
+    pub fn cached(&self) -> Option<&Registry> {
+        self.cached.as_ref().map(|(registry, _)| registry)
+    }
+
+    /// Returns the registry, fetching it if the cached one is missing or too old.
+    pub async fn registry(&mut self) -> Result<&Registry, RegistryError> {
+        let fresh = matches!(
+            &self.cached,
+            Some((_, fetched_at)) if fetched_at.elapsed() < self.max_age
+        );
+
+        if !fresh {
+            if let Err(e) = self.refresh().await {
+                let usable = self
+                    .cached
+                    .as_ref()
+                    .is_some_and(|(registry, _)| !is_expired(registry));
+
+                if !usable {
+                    self.cached = None;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(self.cached().expect("registry is cached"))
+    }
+
+    /// Fetches the registry, replacing the cached one.
+    ///
+    /// A registry issued before any registry accepted earlier is rejected, even if that one is no
+    /// longer cached, so that a stale registry replayed by the transport can not bring back a
+    /// notary key which was revoked since.
+    pub async fn refresh(&mut self) -> Result<(), RegistryError> {
+        let registry = self.source.fetch().await?.verify(&self.registry_key)?;
+
+        if let Some(latest_issued_at) = self.latest_issued_at {
+            if registry.issued_at < latest_issued_at {
+                return Err(RegistryError::Rollback {
+                    issued_at: registry.issued_at,
+                    cached_issued_at: latest_issued_at,
+                });
+            }
+        }
+
+        self.latest_issued_at = Some(registry.issued_at);
+        self.cached = Some((registry, Instant::now()));
+
+        Ok(())
+    }
+}
+
+fn is_expired(registry: &Registry) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the unix epoch")
+        .as_secs();
+
+    now >= registry.expires_at
+}
+
+/// An error that can occur while fetching or checking the registry.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum RegistryError {
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+    #[error("failed to fetch the registry: {0}")]
+    Fetch(String),
+    #[error("invalid registry: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid registry signature: {0}")]
+    InvalidSignature(String),
+    #[error("unsupported registry version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("registry is only valid from {issued_at} until {expires_at}")]
+    Expired { issued_at: u64, expires_at: u64 },
+    #[error(
+        "registry issued at {issued_at} is older than one accepted before, issued at {cached_issued_at}"
+    )]
+    Rollback {
+        issued_at: u64,
+        cached_issued_at: u64,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[1u8; 32].into()).unwrap()
+    }
+
+    fn notary_key(byte: u8) -> NotaryPublicKey {
+        let key = SigningKey::from_bytes(&[byte; 32].into()).unwrap();
+        p256::PublicKey::from(key.verifying_key()).into()
+    }
+
+    fn registry_key() -> NotaryPublicKey {
+        p256::PublicKey::from(signing_key().verifying_key()).into()
+    }
+
+    fn registry(expires_at: u64) -> Registry {
+        Registry {
+            version: REGISTRY_VERSION,
+            issued_at: NOW,
+            expires_at,
+            notaries: vec![
+                NotaryEntry {
+                    name: "small".to_string(),
+                    url: "https://small.example.com".to_string(),
+                    public_keys: vec![notary_key(2)],
+                    features: vec![],
+                    policy: NotaryPolicy {
+                        max_sent_data: Some(1 << 12),
+                        max_recv_data: Some(1 << 14),
+                        requires_api_key: false,
+                    },
+                },
+                NotaryEntry {
+                    name: "large".to_string(),
+                    url: "https://large.example.com".to_string(),
+                    public_keys: vec![notary_key(3), notary_key(4)],
+                    features: vec![Feature::Tls13],
+                    policy: NotaryPolicy {
+                        requires_api_key: true,
+                        ..Default::default()
+                    },
+                },
+            ],
+        }
+    }
+
+    fn sign(registry: &Registry) -> SignedRegistry {
+        let registry = serde_json::to_string(registry).unwrap();
+        let signature: p256::ecdsa::Signature = signing_key().sign(registry.as_bytes());
+
+        SignedRegistry {
+            registry,
+            signature: signature.into(),
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let signed = sign(&registry(NOW + 60));
+
+        assert!(signed.verify_at(&registry_key(), NOW).is_ok());
+        assert!(matches!(
+            signed.verify_at(&notary_key(2), NOW),
+            Err(RegistryError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            signed.verify_at(&registry_key(), NOW + 60),
+            Err(RegistryError::Expired { .. })
+        ));
+    }
+
+    #[test]
+    fn test_verify_tampered() {
+        let mut signed = sign(&registry(NOW + 60));
+        signed.registry = signed
+            .registry
+            .replace("small.example.com", "evil.example.com");
+
+        assert!(matches!(
+            signed.verify_at(&registry_key(), NOW),
+            Err(RegistryError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_select() {
+        let registry = registry(NOW + 60);
+
+        let small = NotaryRequirements {
+            sent_data: 1 << 10,
+            recv_data: 1 << 12,
+            ..Default::default()
+        };
+        assert_eq!(registry.select(&small).unwrap().name, "small");
+
+        let large = NotaryRequirements {
+            recv_data: 1 << 20,
+            has_api_key: true,
+            ..small.clone()
+        };
+        assert_eq!(registry.select(&large).unwrap().name, "large");
+
+        let tls13 = NotaryRequirements {
+            features: vec![Feature::Tls13],
+            ..small
+        };
+        assert!(registry.select(&tls13).is_none());
+    }
+
+    #[test]
+    fn test_resolve_key() {
+        let registry = registry(NOW + 60);
+
+        assert_eq!(registry.resolve_key(&notary_key(4)).unwrap().name, "large");
+        assert!(registry.resolve_key(&notary_key(5)).is_none());
+    }
+
+    #[test]
+    fn test_resolve_signer() {
+        let registry = registry(NOW + 60);
+        let msg = b"session header";
+
+        let key = SigningKey::from_bytes(&[4u8; 32].into()).unwrap();
+        let signature: p256::ecdsa::Signature = key.sign(msg);
+        let (notary, key) = registry.resolve_signer(msg, &signature.into()).unwrap();
+        assert_eq!(notary.name, "large");
+        assert_eq!(key.to_bytes(), notary_key(4).to_bytes());
+
+        let signature: p256::ecdsa::Signature = signing_key().sign(msg);
+        assert!(registry.resolve_signer(msg, &signature.into()).is_none());
+    }
+
+    struct CountingSource {
+        signed: Option<SignedRegistry>,
+        fetches: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl RegistrySource for CountingSource {
+        async fn fetch(&self) -> Result<SignedRegistry, RegistryError> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            self.signed
+                .clone()
+                .ok_or_else(|| RegistryError::Fetch("unavailable".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_client_caches() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut registry = registry(now + 60);
+        registry.issued_at = now - 60;
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let mut client = RegistryClient::new(
+            CountingSource {
+                signed: Some(sign(&registry)),
+                fetches: fetches.clone(),
+            },
+            registry_key(),
+        );
+
+        futures::executor::block_on(async {
+            client.registry().await.unwrap();
+            client.registry().await.unwrap();
+        });
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // A stale registry is still used while the source is unavailable.
+        client.max_age = Duration::ZERO;
+        client.source.signed = None;
+        let name = futures::executor::block_on(client.registry())
+            .unwrap()
+            .notaries[0]
+            .name
+            .clone();
+        assert_eq!(name, "small");
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_client_rejects_rollback() {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut old = registry(now + 60);
+        old.issued_at = now - 60;
+        let mut new = old.clone();
+        new.issued_at = now - 30;
+        new.notaries.remove(0);
+
+        let mut client = RegistryClient::new(
+            CountingSource {
+                signed: Some(sign(&new)),
+                fetches: Arc::new(AtomicUsize::new(0)),
+            },
+            registry_key(),
+        );
+        futures::executor::block_on(client.refresh()).unwrap();
+
+        // Replaying the older registry must not bring back the removed notary.
+        client.source.signed = Some(sign(&old));
+        assert!(matches!(
+            futures::executor::block_on(client.refresh()),
+            Err(RegistryError::Rollback { .. })
+        ));
+        assert_eq!(client.cached().unwrap().notaries[0].name, "large");
+
+        // A registry issued at the same time is accepted, eg. when fetched again.
+        client.source.signed = Some(sign(&new));
+        futures::executor::block_on(client.refresh()).unwrap();
+
+        // The older registry is still rejected once the cached one is dropped, as when it expires
+        // while the source is unavailable.
+        client.cached = None;
+        client.source.signed = Some(sign(&old));
+        assert!(matches!(
+            futures::executor::block_on(client.registry()),
+            Err(RegistryError::Rollback { .. })
+        ));
+        assert!(client.cached().is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! Randomness of the TLSNotary protocol, which can be seeded to reproduce a session.
+//!
+//! Seeding is meant for testing only, e.g. to reproduce a failure of an integration test from its
+//! seed. A seeded session is not secure, as the other party may know the seed.
+//!
+//! The randomness of a session is derived from the seed and the session id, so sessions sharing
+//! a seed, e.g. every session of a seeded notary, still draw different randomness.
+//!
+//! The garbled circuits, the encoder seed, the commitment nonces, the base OTs and the TLS client
+//! random and key shares are seeded. The OT extension and the share conversion draw from the
+//! thread-local generator inside `mpz`, which can not be seeded, so a seeded session is not
+//! reproduced byte for byte.
+
+use rand::{rngs::OsRng, Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+/// Independent streams of randomness derived from the same seed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    /// Generation of session ids.
+    SessionId,
+    /// Randomness of the garbled circuits of the prover.
+    Garbling,
+    /// Seed of the encodings of the transcript, chosen by the notary.
+    EncoderSeed,
+    /// Nonces salting the transcript commitments.
+    CommitmentNonces,
+    /// Base OTs of the OT extension in which the party is the sender.
+    BaseOtSender,
+    /// Base OTs of the OT extension in which the party is the receiver.
+    BaseOtReceiver,
+    /// Client random and key shares of the TLS connection.
+    Tls,
+}
+
+impl RngStream {
+    fn id(&self) -> u64 {
+        match self {
+            Self::SessionId => 0,
+            Self::Garbling => 1,
+            Self::EncoderSeed => 2,
+            Self::CommitmentNonces => 3,
+            Self::BaseOtSender => 4,
+            Self::BaseOtReceiver => 5,
+            Self::Tls => 6,
+        }
+    }
+}
+
+/// Returns a generator of the randomness of `stream`, derived from `seed`.
+pub fn seeded_rng(seed: u64, stream: RngStream) -> ChaCha20Rng {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    rng.set_stream(stream.id());
+    rng
+}
+
+/// Returns a generator of the randomness of `stream` in the session, derived from `seed` and
+/// the session id.
+pub fn session_rng(seed: u64, session_id: &str, stream: RngStream) -> ChaCha20Rng {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&seed.to_le_bytes());
+    hasher.update(session_id.as_bytes());
+
+    let mut rng = ChaCha20Rng::from_seed(*hasher.finalize().as_bytes());
+    rng.set_stream(stream.id());
+    rng
+}
+
+/// Generates a 32 byte seed for `stream` in the session, derived from `seed` and the session id
+/// if set, otherwise from the OS.
+pub fn gen_seed(seed: Option<u64>, session_id: &str, stream: RngStream) -> [u8; 32] {
+    match seed {
+        Some(seed) => session_rng(seed, session_id, stream).gen(),
+        None => OsRng.gen(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gen_seed() {
+        assert_eq!(
+            gen_seed(Some(0), "a", RngStream::Garbling),
+            gen_seed(Some(0), "a", RngStream::Garbling)
+        );
+        assert_ne!(
+            gen_seed(Some(0), "a", RngStream::Garbling),
+            gen_seed(Some(1), "a", RngStream::Garbling)
+        );
+        assert_ne!(
+            gen_seed(Some(0), "a", RngStream::Garbling),
+            gen_seed(Some(0), "a", RngStream::EncoderSeed)
+        );
+        assert_ne!(
+            gen_seed(Some(0), "a", RngStream::Garbling),
+            gen_seed(Some(0), "b", RngStream::Garbling)
+        );
+        assert_ne!(
+            gen_seed(None, "a", RngStream::Garbling),
+            gen_seed(None, "a", RngStream::Garbling)
+        );
+    }
+}
@@ -26,6 +26,17 @@ impl Blake3Commitment {
         }
     }
 
+    /// Creates a new Blake3 commitment salted with the given nonce
+    #[cfg(feature = "deterministic")]
+    pub(crate) fn new_with_nonce(
+        encodings: &[EncodedValue<encoding_state::Active>],
+        nonce: Nonce,
+    ) -> Self {
+        let hash = Decommitment::new_with_nonce(encodings.to_vec(), nonce).commit();
+
+        Self { hash, nonce }
+    }
+
     /// Returns the hash of this commitment
     pub fn hash(&self) -> &Hash {
         &self.hash
@@ -1,13 +1,13 @@
 use std::collections::HashMap;
 
 use bimap::BiMap;
-use mpz_core::hash::Hash;
+use mpz_garble_core::{encoding_state, EncodedValue};
 use utils::range::{RangeSet, ToRangeSet};
 
 use crate::{
     commitment::{
-        blake3::Blake3Commitment, Commitment, CommitmentId, CommitmentInfo, CommitmentKind,
-        TranscriptCommitments,
+        blake3::Blake3Commitment, merkle_leaf, placeholder_tree, Commitment, CommitmentId,
+        CommitmentInfo, CommitmentKind, TranscriptCommitments,
     },
     merkle::MerkleTree,
     transcript::get_value_ids,
@@ -34,21 +34,38 @@ pub enum TranscriptCommitmentBuilderError {
     /// Duplicate commitment
     #[error("attempted to create a duplicate commitment, overwriting: {0:?}")]
     Duplicate(CommitmentId),
-    /// No commitments were added
-    #[error("no commitments were added")]
-    NoCommitments,
+    /// Duplicate label
+    #[error("a commitment with label {0:?} already exists")]
+    DuplicateLabel(String),
 }
 
+/// The number of transcript bytes whose encodings are buffered before their commitments are
+/// computed in parallel, about 8MB of encodings.
+const MAX_PENDING_BYTES: usize = 1 << 16;
+
 /// A builder for [`TranscriptCommitments`].
+///
+/// With the `rayon` feature enabled, commitments are computed in parallel in batches of up to
+/// 64KB of committed transcript data, on the global rayon thread pool. Its size can be set with
+/// the `RAYON_NUM_THREADS` environment variable or `rayon::ThreadPoolBuilder::build_global`.
+/// Otherwise each commitment is computed as soon as it is added, so the encodings are not kept.
 pub struct TranscriptCommitmentBuilder {
-    commitments: HashMap<CommitmentId, Commitment>,
-    /// Information about the above `commitments`.
+    /// Information about the commitments.
     commitment_info: BiMap<CommitmentId, CommitmentInfo>,
-    merkle_leaves: Vec<Hash>,
+    /// Labels of the labeled commitments.
+    labels: HashMap<CommitmentId, String>,
+    /// The computed commitments, indexed by commitment id.
+    commitments: Vec<Blake3Commitment>,
+    /// The encodings of the commitments which are not computed yet, following `commitments`.
+    pending: Vec<Vec<EncodedValue<encoding_state::Active>>>,
+    /// The number of transcript bytes in `pending`.
+    pending_bytes: usize,
     /// A function that returns the encodings for the provided transcript byte ids.
     encoding_provider: EncodingProvider,
     sent_len: usize,
     recv_len: usize,
+    /// The seed of the commitment nonces, random nonces are used if `None`.
+    nonce_seed: Option<[u8; 32]>,
 }
 
 opaque_debug::implement!(TranscriptCommitmentBuilder);
@@ -62,21 +79,35 @@ impl TranscriptCommitmentBuilder {
     #[doc(hidden)]
     pub fn new(encoding_provider: EncodingProvider, sent_len: usize, recv_len: usize) -> Self {
         Self {
-            commitments: HashMap::default(),
             commitment_info: BiMap::default(),
-            merkle_leaves: Vec::default(),
+            labels: HashMap::default(),
+            commitments: Vec::default(),
+            pending: Vec::default(),
+            pending_bytes: 0,
             encoding_provider,
             sent_len,
             recv_len,
+            nonce_seed: None,
         }
     }
 
+    /// Sets the seed from which the commitment nonces are derived, instead of generating random
+    /// ones.
+    ///
+    /// This is meant for reproducing a session in tests only, as the nonces hide the committed
+    /// data from anyone knowing the encodings.
+    #[cfg(feature = "deterministic")]
+    pub fn set_nonce_seed(&mut self, seed: [u8; 32]) -> &mut Self {
+        self.nonce_seed = Some(seed);
+        self
+    }
+
     /// Commits to the provided ranges of the `sent` transcript.
     pub fn commit_sent(
         &mut self,
         ranges: &dyn ToRangeSet<usize>,
     ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
-        self.add_substrings_commitment(&ranges.to_range_set(), Direction::Sent)
+        self.add_substrings_commitment(&ranges.to_range_set(), Direction::Sent, None)
     }
 
     /// Commits to the provided ranges of the `received` transcript.
@@ -84,7 +115,7 @@ impl TranscriptCommitmentBuilder {
         &mut self,
         ranges: &dyn ToRangeSet<usize>,
     ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
-        self.add_substrings_commitment(&ranges.to_range_set(), Direction::Received)
+        self.add_substrings_commitment(&ranges.to_range_set(), Direction::Received, None)
     }
 
     /// Commits to the provided ranges of the transcript.
@@ -99,6 +130,24 @@ impl TranscriptCommitmentBuilder {
         }
     }
 
+    /// Commits to the provided ranges of the transcript, attaching a label to the commitment.
+    ///
+    /// The label is bound to the attestation, so verifiers can refer to the committed data by
+    /// name, e.g. "balance", instead of by byte offsets.
+    pub fn commit_labeled(
+        &mut self,
+        ranges: &dyn ToRangeSet<usize>,
+        direction: Direction,
+        label: impl Into<String>,
+    ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
+        let label = label.into();
+        if self.labels.values().any(|candidate| *candidate == label) {
+            return Err(TranscriptCommitmentBuilderError::DuplicateLabel(label));
+        }
+
+        self.add_substrings_commitment(&ranges.to_range_set(), direction, Some(label))
+    }
+
     /// Gets the commitment id for the provided commitment info.
     pub fn get_id(
         &self,
@@ -107,11 +156,7 @@ impl TranscriptCommitmentBuilder {
         direction: Direction,
     ) -> Option<CommitmentId> {
         self.commitment_info
-            .get_by_right(&CommitmentInfo {
-                kind,
-                ranges: ranges.into(),
-                direction,
-            })
+            .get_by_right(&CommitmentInfo::new(kind, ranges.into(), direction))
             .copied()
     }
 
@@ -120,6 +165,7 @@ impl TranscriptCommitmentBuilder {
         &mut self,
         ranges: &RangeSet<usize>,
         direction: Direction,
+        label: Option<String>,
     ) -> Result<CommitmentId, TranscriptCommitmentBuilderError> {
         let max = ranges
             .max()
@@ -143,49 +189,174 @@ impl TranscriptCommitmentBuilder {
         let encodings = (self.encoding_provider)(&id_refs)
             .ok_or(TranscriptCommitmentBuilderError::MissingEncodings)?;
 
-        // We only support BLAKE3 for now
-        let commitment = Blake3Commitment::new(&encodings);
-        let hash = *commitment.hash();
-
-        let id = CommitmentId::new(self.merkle_leaves.len() as u32);
-
-        let commitment: Commitment = commitment.into();
+        let id = CommitmentId::new((self.commitments.len() + self.pending.len()) as u32);
 
-        // Store commitment with its id
+        // We only support BLAKE3 for now
         self.commitment_info
             .insert_no_overwrite(
                 id,
-                CommitmentInfo::new(commitment.kind(), ranges.clone(), direction),
+                CommitmentInfo::new(CommitmentKind::Blake3, ranges.clone(), direction),
             )
             .map_err(|(id, _)| TranscriptCommitmentBuilderError::Duplicate(id))?;
 
-        if self.commitments.insert(id, commitment).is_some() {
-            // This shouldn't be possible, as we check for duplicates above.
-            panic!("commitment id already exists");
+        if let Some(label) = label {
+            self.labels.insert(id, label);
         }
 
-        // Insert commitment hash into the merkle tree
-        self.merkle_leaves.push(hash);
+        self.pending_bytes += encodings.len();
+        self.pending.push(encodings);
+        // Without parallelism there is nothing to gain from buffering.
+        if !cfg!(feature = "rayon") || self.pending_bytes >= MAX_PENDING_BYTES {
+            self.flush();
+        }
 
         Ok(id)
     }
 
+    /// Computes the pending commitments, dropping their encodings.
+    fn flush(&mut self) {
+        let first_id = self.commitments.len();
+        let pending = std::mem::take(&mut self.pending);
+        self.pending_bytes = 0;
+
+        self.commitments
+            .extend(Self::commit(self.nonce_seed, first_id, pending));
+    }
+
     /// Builds the [`TranscriptCommitments`]
-    pub fn build(self) -> Result<TranscriptCommitments, TranscriptCommitmentBuilderError> {
-        let Self {
-            commitments,
-            commitment_info,
-            merkle_leaves,
-            ..
-        } = self;
+    ///
+    /// If no commitments were added the session is handshake-only: its attestation proves the
+    /// connection to the server but no transcript data can ever be opened.
+    pub fn build(mut self) -> Result<TranscriptCommitments, TranscriptCommitmentBuilderError> {
+        self.flush();
+        let commitments = self.commitments;
 
-        let merkle_tree = MerkleTree::from_leaves(&merkle_leaves)
-            .map_err(|_| TranscriptCommitmentBuilderError::NoCommitments)?;
+        let merkle_leaves = commitments
+            .iter()
+            .enumerate()
+            .map(|(id, commitment)| {
+                let label = self.labels.get(&CommitmentId::new(id as u32));
+                merkle_leaf(*commitment.hash(), label.map(String::as_str))
+            })
+            .collect::<Vec<_>>();
+
+        let commitments = commitments
+            .into_iter()
+            .enumerate()
+            .map(|(id, commitment)| (CommitmentId::new(id as u32), commitment.into()))
+            .collect::<HashMap<CommitmentId, Commitment>>();
+
+        let merkle_tree = if merkle_leaves.is_empty() {
+            placeholder_tree()
+        } else {
+            MerkleTree::from_leaves(&merkle_leaves).expect("leaves are not empty")
+        };
 
         Ok(TranscriptCommitments {
             merkle_tree,
             commitments,
-            commitment_info,
+            commitment_info: self.commitment_info,
+            labels: self.labels,
         })
     }
+
+    /// Computes the commitment with the given id, deriving its nonce from `nonce_seed` if set.
+    #[cfg_attr(not(feature = "deterministic"), allow(unused_variables))]
+    fn commit_encodings(
+        nonce_seed: Option<[u8; 32]>,
+        id: usize,
+        encodings: &[EncodedValue<encoding_state::Active>],
+    ) -> Blake3Commitment {
+        #[cfg(feature = "deterministic")]
+        if let Some(seed) = nonce_seed {
+            use rand::{Rng, SeedableRng};
+
+            // Each commitment uses its own stream, so that the nonces don't depend on the order
+            // in which the commitments are computed
+            let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+            rng.set_stream(id as u64);
+            return Blake3Commitment::new_with_nonce(encodings, rng.gen());
+        }
+
+        Blake3Commitment::new(encodings)
+    }
+
+    /// Computes the commitments to the encodings, in order of their ids starting at `first_id`.
+    #[cfg(not(feature = "rayon"))]
+    fn commit(
+        nonce_seed: Option<[u8; 32]>,
+        first_id: usize,
+        encodings: Vec<Vec<EncodedValue<encoding_state::Active>>>,
+    ) -> Vec<Blake3Commitment> {
+        encodings
+            .iter()
+            .enumerate()
+            .map(|(idx, encodings)| Self::commit_encodings(nonce_seed, first_id + idx, encodings))
+            .collect()
+    }
+
+    /// Computes the commitments to the encodings in parallel, in order of their ids starting at
+    /// `first_id`.
+    #[cfg(feature = "rayon")]
+    fn commit(
+        nonce_seed: Option<[u8; 32]>,
+        first_id: usize,
+        encodings: Vec<Vec<EncodedValue<encoding_state::Active>>>,
+    ) -> Vec<Blake3Commitment> {
+        use rayon::prelude::*;
+
+        encodings
+            .into_par_iter()
+            .enumerate()
+            .map(|(idx, encodings)| Self::commit_encodings(nonce_seed, first_id + idx, &encodings))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_core::commit::Decommitment;
+
+    use super::*;
+    use crate::fixtures::encoding_provider;
+
+    /// The commitments computed by the builder, in parallel with the `rayon` feature, must match
+    /// the ones computed serially from the same encodings.
+    #[test]
+    fn test_commitments_match_serial() {
+        let sent = [7u8; 1 << 10];
+        let recv = [9u8; 1 << 17];
+        let mut builder = TranscriptCommitmentBuilder::new(
+            encoding_provider(&sent, &recv),
+            sent.len(),
+            recv.len(),
+        );
+
+        // Enough commitments to span several batches.
+        let mut ranges = vec![(0..sent.len(), Direction::Sent)];
+        ranges.extend(
+            (0..recv.len())
+                .step_by(1 << 12)
+                .map(|start| (start..start + (1 << 12), Direction::Received)),
+        );
+        let ids = ranges
+            .iter()
+            .map(|(range, direction)| builder.commit(range, *direction).unwrap())
+            .collect::<Vec<_>>();
+
+        let commitments = builder.build().unwrap();
+        let provider = encoding_provider(&sent, &recv);
+        for (id, (range, direction)) in ids.iter().zip(ranges) {
+            let Some(Commitment::Blake3(commitment)) = commitments.get(id) else {
+                panic!("commitment {id:?} is missing");
+            };
+
+            let ids: Vec<_> = get_value_ids(&range.into(), direction).collect();
+            let ids: Vec<_> = ids.iter().map(String::as_str).collect();
+            let encodings = provider(&ids).unwrap();
+            let expected = Decommitment::new_with_nonce(encodings, *commitment.nonce()).commit();
+
+            assert_eq!(commitment.hash(), &expected);
+        }
+    }
 }
@@ -10,7 +10,7 @@ use bimap::BiMap;
 use mpz_core::hash::Hash;
 use mpz_garble_core::{encoding_state::Full, EncodedValue};
 use serde::{Deserialize, Serialize};
-use utils::range::RangeSet;
+use utils::range::{RangeDisjoint, RangeSet};
 
 use crate::{
     merkle::{MerkleRoot, MerkleTree},
@@ -19,6 +19,16 @@ use crate::{
 
 pub use builder::{TranscriptCommitmentBuilder, TranscriptCommitmentBuilderError};
 
+/// The only leaf of the Merkle tree of a handshake-only session.
+///
+/// No commitment hashes to it, so nothing can be opened against such a tree.
+const PLACEHOLDER_LEAF: [u8; 32] = [0u8; 32];
+
+/// Returns the Merkle tree of a session without transcript commitments.
+pub(crate) fn placeholder_tree() -> MerkleTree {
+    MerkleTree::from_leaves(&[Hash::from(PLACEHOLDER_LEAF)]).expect("tree has a leaf")
+}
+
 /// A commitment id.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct CommitmentId(u32);
@@ -35,6 +45,13 @@ impl CommitmentId {
     }
 }
 
+/// Returns the Merkle leaf of a commitment with the provided hash and label.
+///
+/// See [`commitment_leaf`](crate::verify::commitment_leaf).
+pub(crate) fn merkle_leaf(hash: Hash, label: Option<&str>) -> Hash {
+    Hash::from(crate::verify::commitment_leaf(hash.as_bytes(), label))
+}
+
 /// Info of a transcript commitment
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct CommitmentInfo {
@@ -107,13 +124,44 @@ pub enum CommitmentKind {
 pub enum CommitmentOpening {
     /// An opening to a BLAKE3 commitment
     Blake3(blake3::Blake3Opening),
+    /// An opening to a labeled BLAKE3 commitment.
+    ///
+    /// Labeled openings are a separate variant, so that proofs without labels keep the encoding
+    /// of earlier versions.
+    LabeledBlake3 {
+        /// The label of the commitment.
+        label: String,
+        /// The opening of the commitment.
+        opening: blake3::Blake3Opening,
+    },
 }
 
 impl CommitmentOpening {
     /// Returns the kind of this opening
     pub fn kind(&self) -> CommitmentKind {
         match self {
-            CommitmentOpening::Blake3(_) => CommitmentKind::Blake3,
+            CommitmentOpening::Blake3(_) | CommitmentOpening::LabeledBlake3 { .. } => {
+                CommitmentKind::Blake3
+            }
+        }
+    }
+
+    /// Returns the label of the opened commitment, if any
+    pub fn label(&self) -> Option<&str> {
+        match self {
+            CommitmentOpening::Blake3(_) => None,
+            CommitmentOpening::LabeledBlake3 { label, .. } => Some(label),
+        }
+    }
+
+    /// Attaches a label to this opening.
+    pub(crate) fn with_label(self, label: Option<&str>) -> Self {
+        match (self, label) {
+            (CommitmentOpening::Blake3(opening), Some(label)) => CommitmentOpening::LabeledBlake3 {
+                label: label.to_string(),
+                opening,
+            },
+            (opening, _) => opening,
         }
     }
 
@@ -127,21 +175,24 @@ impl CommitmentOpening {
     /// - If an encoding is not for a u8.
     pub fn recover(&self, encodings: &[EncodedValue<Full>]) -> Commitment {
         match self {
-            CommitmentOpening::Blake3(opening) => opening.recover(encodings).into(),
+            CommitmentOpening::Blake3(opening)
+            | CommitmentOpening::LabeledBlake3 { opening, .. } => opening.recover(encodings).into(),
         }
     }
 
     /// Returns the transcript data corresponding to this opening
     pub fn data(&self) -> &[u8] {
         match self {
-            CommitmentOpening::Blake3(opening) => opening.data(),
+            CommitmentOpening::Blake3(opening)
+            | CommitmentOpening::LabeledBlake3 { opening, .. } => opening.data(),
         }
     }
 
     /// Returns the transcript data corresponding to this opening
     pub fn into_data(self) -> Vec<u8> {
         match self {
-            CommitmentOpening::Blake3(opening) => opening.into_data(),
+            CommitmentOpening::Blake3(opening)
+            | CommitmentOpening::LabeledBlake3 { opening, .. } => opening.into_data(),
         }
     }
 }
@@ -154,6 +205,12 @@ pub struct TranscriptCommitments {
     commitments: HashMap<CommitmentId, Commitment>,
     /// Information about the above `commitments`.
     commitment_info: BiMap<CommitmentId, CommitmentInfo>,
+    /// Labels of the labeled commitments.
+    ///
+    /// Labels are kept apart from the [`CommitmentInfo`], so that commitments can still be
+    /// looked up by their info.
+    #[serde(default)]
+    labels: HashMap<CommitmentId, String>,
 }
 
 opaque_debug::implement!(TranscriptCommitments);
@@ -169,6 +226,12 @@ impl TranscriptCommitments {
         self.merkle_tree.root()
     }
 
+    /// Returns `true` if there are no commitments, in which case the session only attests to
+    /// the TLS handshake.
+    pub fn is_handshake_only(&self) -> bool {
+        self.commitment_info.is_empty()
+    }
+
     /// Returns a commitment if it exists.
     pub fn get(&self, id: &CommitmentId) -> Option<&Commitment> {
         self.commitments.get(id)
@@ -186,8 +249,77 @@ impl TranscriptCommitments {
             .copied()
     }
 
+    /// Returns the id of the commitment with the provided label, if it exists.
+    pub fn get_id_by_label(&self, label: &str) -> Option<CommitmentId> {
+        self.labels
+            .iter()
+            .find(|(_, candidate)| candidate.as_str() == label)
+            .map(|(id, _)| *id)
+    }
+
+    /// Returns the label of a commitment, if it has one.
+    pub fn get_label(&self, id: &CommitmentId) -> Option<&str> {
+        self.labels.get(id).map(String::as_str)
+    }
+
     /// Returns commitment info, if it exists.
     pub fn get_info(&self, id: &CommitmentId) -> Option<&CommitmentInfo> {
         self.commitment_info.get_by_left(id)
     }
+
+    /// Returns the ids of the commitments to data overlapping the provided ranges, in ascending
+    /// order.
+    pub fn ids_overlapping(
+        &self,
+        direction: Direction,
+        ranges: &RangeSet<usize>,
+    ) -> Vec<CommitmentId> {
+        let mut ids = self
+            .commitment_info
+            .iter()
+            .filter(|(id, info)| {
+                self.commitments.contains_key(id)
+                    && info.direction == direction
+                    && !info.ranges.is_disjoint(ranges)
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Forgets a commitment, dropping its nonce.
+    ///
+    /// The commitment stays in the Merkle tree, but its data can no longer be opened. Returns
+    /// `false` if the commitment does not exist.
+    pub fn forget(&mut self, id: &CommitmentId) -> bool {
+        self.commitments.remove(id).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Proofs without labels must keep the encoding of earlier versions
+    #[test]
+    fn test_unlabeled_encoding() {
+        let info = CommitmentInfo::new(CommitmentKind::Blake3, (0..4).into(), Direction::Sent);
+        assert_eq!(
+            bincode::serialize(&info).unwrap(),
+            bincode::serialize(&(
+                CommitmentKind::Blake3,
+                RangeSet::from(0..4),
+                Direction::Sent
+            ))
+            .unwrap()
+        );
+
+        let opening: CommitmentOpening =
+            blake3::Blake3Opening::new(b"data".to_vec(), [0u8; 32]).into();
+        let labeled = opening.clone().with_label(Some("balance"));
+        assert_eq!(bincode::serialize(&opening).unwrap()[..4], [0, 0, 0, 0]);
+        assert_eq!(bincode::serialize(&labeled).unwrap()[..4], [1, 0, 0, 0]);
+        assert_eq!(labeled.label(), Some("balance"));
+    }
 }
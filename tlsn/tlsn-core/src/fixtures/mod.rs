@@ -7,7 +7,7 @@ use std::collections::HashMap;
 
 use hex::FromHex;
 use mpz_circuits::types::ValueType;
-use mpz_core::{commit::HashCommit, hash::Hash, utils::blake3};
+use mpz_core::{commit::HashCommit, hash::Hash, serialize::CanonicalSerialize, utils::blake3};
 use mpz_garble_core::{ChaChaEncoder, Encoder};
 use tls_core::{
     cert::ServerCertDetails,
@@ -21,12 +21,14 @@ use tls_core::{
     },
 };
 
-use p256::ecdsa::SigningKey;
+use p256::ecdsa::{signature::Signer, Signature as P256Signature, SigningKey};
 
 use crate::{
+    commitment::TranscriptCommitmentBuilder,
     merkle::MerkleRoot,
-    session::{HandshakeSummary, SessionHeader},
-    EncodingProvider,
+    proof::TlsProof,
+    session::{HandshakeSummary, NotarizedSession, SessionData, SessionHeader},
+    EncodingProvider, ServerName, Transcript,
 };
 
 fn value_id(id: &str) -> u64 {
@@ -167,3 +169,56 @@ pub fn server_ephemeral_key() -> PublicKey {
 pub fn notary_signing_key() -> SigningKey {
     SigningKey::from_slice(&[1; 32]).unwrap()
 }
+
+/// Returns a proof fixture of a session with the tlsnotary.org handshake fixtures, signed with
+/// [`notary_signing_key`] and revealing both transcripts in full.
+///
+/// The session time is the time of the handshake fixtures, so the proof must be verified with a
+/// validity window which accepts it.
+///
+/// # Arguments
+///
+/// * `sent` - The sent transcript, must not be empty.
+/// * `recv` - The received transcript, must not be empty.
+pub fn tls_proof(sent: &[u8], recv: &[u8]) -> TlsProof {
+    let mut builder =
+        TranscriptCommitmentBuilder::new(encoding_provider(sent, recv), sent.len(), recv.len());
+    let sent_id = builder.commit_sent(&(0..sent.len())).unwrap();
+    let recv_id = builder.commit_recv(&(0..recv.len())).unwrap();
+    let commitments = builder.build().unwrap();
+
+    let (handshake_decommitment, handshake_commitment) = handshake_data().hash_commit();
+    let data = SessionData::new(
+        ServerName::Dns("tlsnotary.org".to_string()),
+        handshake_decommitment,
+        Transcript::new(sent.to_vec()),
+        Transcript::new(recv.to_vec()),
+        commitments,
+    );
+
+    let header = SessionHeader::new(
+        encoder_seed(),
+        data.commitments().merkle_root(),
+        sent.len(),
+        recv.len(),
+        HandshakeSummary::new(
+            handshake_summary().time(),
+            server_ephemeral_key(),
+            handshake_commitment,
+        ),
+    );
+    let signature: P256Signature = notary_signing_key().sign(&header.to_bytes());
+    let session = NotarizedSession::new(header, Some(signature.into()), data);
+
+    let mut substrings = session.data().build_substrings_proof();
+    substrings
+        .reveal_by_id(sent_id)
+        .unwrap()
+        .reveal_by_id(recv_id)
+        .unwrap();
+
+    TlsProof {
+        session: session.session_proof(),
+        substrings: substrings.build().unwrap(),
+    }
+}
@@ -1,26 +1,47 @@
 //! TLSNotary core protocol library.
 //!
 //! This crate contains core types for the TLSNotary protocol, including some functionality for selective disclosure.
+//!
+//! # Features
+//!
+//! The `std` feature is enabled by default. Without it only the [`verify`] module is available,
+//! which contains verification primitives that can be used in `no_std` environments.
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod commitment;
-#[cfg(any(test, feature = "fixtures"))]
+#[cfg(all(feature = "std", any(test, feature = "fixtures")))]
 pub mod fixtures;
+#[cfg(feature = "std")]
 pub mod merkle;
+#[cfg(feature = "std")]
 pub mod msg;
+#[cfg(feature = "std")]
 pub mod proof;
+#[cfg(feature = "std")]
 pub mod session;
+#[cfg(feature = "std")]
 mod signature;
+#[cfg(feature = "std")]
 pub mod transcript;
+pub mod verify;
 
+#[cfg(feature = "std")]
 pub use session::{HandshakeSummary, NotarizedSession, SessionData, SessionHeader};
+#[cfg(feature = "std")]
 pub use signature::{NotaryPublicKey, Signature};
+#[cfg(feature = "std")]
 pub use transcript::{Direction, RedactedTranscript, Transcript, TranscriptSlice};
 
+#[cfg(feature = "std")]
 use mpz_garble_core::{encoding_state, EncodedValue};
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
 
 /// The maximum allowed total bytelength of all committed data. Used to prevent DoS during verification.
@@ -28,18 +49,22 @@ use serde::{Deserialize, Serialize};
 /// commitment type is [crate::commitment::Blake3]).
 ///
 /// This value must not exceed bcs's MAX_SEQUENCE_LENGTH limit (which is (1 << 31) - 1 by default)
+#[cfg(feature = "std")]
 const MAX_TOTAL_COMMITTED_DATA: usize = 1_000_000_000;
 
 /// A provider of plaintext encodings.
+#[cfg(feature = "std")]
 pub(crate) type EncodingProvider =
     Box<dyn Fn(&[&str]) -> Option<Vec<EncodedValue<encoding_state::Active>>> + Send>;
 
 /// The encoding id
 ///
 /// A 64 bit Blake3 hash which is used for the plaintext encodings
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub(crate) struct EncodingId(u64);
 
+#[cfg(feature = "std")]
 impl EncodingId {
     /// Create a new encoding ID.
     pub(crate) fn new(id: &str) -> Self {
@@ -54,12 +79,14 @@ impl EncodingId {
 }
 
 /// A Server's name.
+#[cfg(feature = "std")]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ServerName {
     /// A DNS name.
     Dns(String),
 }
 
+#[cfg(feature = "std")]
 impl ServerName {
     /// Returns a reference to the server name as a string slice.
     pub fn as_str(&self) -> &str {
@@ -69,6 +96,7 @@ impl ServerName {
     }
 }
 
+#[cfg(feature = "std")]
 impl AsRef<str> for ServerName {
     fn as_ref(&self) -> &str {
         match self {
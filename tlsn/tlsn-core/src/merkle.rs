@@ -57,6 +57,11 @@ pub struct MerkleProof {
 }
 
 impl MerkleProof {
+    /// Returns the number of leaves of the tree this proof is for.
+    pub fn total_leaves(&self) -> usize {
+        self.total_leaves
+    }
+
     /// Checks if indices, hashes and leaves count are valid for the provided root
     ///
     /// # Panics
@@ -80,24 +85,16 @@ impl MerkleProof {
             leaf_indices
         );
 
-        // zip indices and hashes
-        let mut tuples: Vec<(usize, [u8; 32])> = leaf_indices
-            .iter()
-            .cloned()
-            .zip(leaf_hashes.iter().cloned().map(|h| *h.as_bytes()))
-            .collect();
-
-        // sort by index and unzip
-        tuples.sort_by(|(a, _), (b, _)| a.cmp(b));
-        let (indices, hashes): (Vec<usize>, Vec<[u8; 32]>) = tuples.into_iter().unzip();
-
-        if !self
-            .proof
-            .verify(root.to_inner(), &indices, &hashes, self.total_leaves)
-        {
-            return Err(MerkleError::MerkleProofVerificationFailed);
-        }
-        Ok(())
+        let leaf_hashes: Vec<[u8; 32]> = leaf_hashes.iter().map(|h| *h.as_bytes()).collect();
+
+        crate::verify::verify_merkle_proof(
+            &root.to_inner(),
+            self.proof.proof_hashes(),
+            leaf_indices,
+            &leaf_hashes,
+            self.total_leaves,
+        )
+        .map_err(|_| MerkleError::MerkleProofVerificationFailed)
     }
 }
 
@@ -237,6 +234,23 @@ mod test {
             .is_ok(),);
     }
 
+    #[test]
+    fn test_verify_fail_index_out_of_bounds() {
+        let leaf0 = Hash::from([0u8; 32]);
+        let leaf1 = Hash::from([1u8; 32]);
+        let tree = MerkleTree::from_leaves(&[leaf0, leaf1]).unwrap();
+        let proof = tree.proof(&[1]);
+
+        // fail because the index is not in the tree
+        assert_eq!(
+            proof
+                .verify(&tree.root(), &[usize::MAX], &[leaf1])
+                .err()
+                .unwrap(),
+            MerkleError::MerkleProofVerificationFailed
+        );
+    }
+
     #[test]
     fn test_verify_fail_wrong_leaf() {
         let leaf0 = Hash::from([0u8; 32]);
@@ -1,9 +1,17 @@
 //! Protocol message types.
 
+use std::ops::Range;
+
 use serde::{Deserialize, Serialize};
 use utils::range::RangeSet;
 
-use crate::{merkle::MerkleRoot, proof::SessionInfo, signature::Signature, SessionHeader};
+use crate::{
+    merkle::MerkleRoot, proof::SessionInfo, signature::Signature, Direction, SessionHeader,
+};
+
+/// The maximum length of a revealed record, which is the maximum length of a TLS record's
+/// plaintext.
+pub const MAX_REVEALED_RECORD_LEN: usize = 1 << 14;
 
 /// Top-level enum for all messages
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +26,8 @@ pub enum TlsnMessage {
     SessionInfo(SessionInfo),
     /// Information about the values the prover wants to prove
     ProvingInfo(ProvingInfo),
+    /// Purported cleartext of a revealed record, see [`ProvingInfo::records`]
+    RevealedRecord(Vec<u8>),
 }
 
 /// A signed session header.
@@ -30,12 +40,75 @@ pub struct SignedSessionHeader {
 }
 
 /// Information about the values the prover wants to prove
+///
+/// The purported cleartext follows in a [`TlsnMessage::RevealedRecord`] per record, so that the
+/// verifier does not need to hold all of it at once.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ProvingInfo {
     /// The ids for the sent transcript
     pub sent_ids: RangeSet<usize>,
     /// The ids for the received transcript
     pub recv_ids: RangeSet<usize>,
-    /// Purported cleartext values
-    pub cleartext: Vec<u8>,
+}
+
+impl ProvingInfo {
+    /// Returns the revealed ranges split into records of at most [`MAX_REVEALED_RECORD_LEN`]
+    /// bytes, in the order in which they are proven.
+    ///
+    /// Sent ranges come first, followed by received ranges, each in ascending order.
+    pub fn records(&self) -> Vec<(Direction, Range<usize>)> {
+        let sent = self
+            .sent_ids
+            .iter_ranges()
+            .map(|range| (Direction::Sent, range));
+        let recv = self
+            .recv_ids
+            .iter_ranges()
+            .map(|range| (Direction::Received, range));
+
+        sent.chain(recv)
+            .flat_map(|(direction, range)| {
+                range
+                    .clone()
+                    .step_by(MAX_REVEALED_RECORD_LEN)
+                    .map(move |start| {
+                        (
+                            direction,
+                            start..(start + MAX_REVEALED_RECORD_LEN).min(range.end),
+                        )
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proving_info_records() {
+        let proving_info = ProvingInfo {
+            sent_ids: RangeSet::from([0..10, 20..30]),
+            recv_ids: RangeSet::from(5..5 + 2 * MAX_REVEALED_RECORD_LEN + 1),
+        };
+
+        assert_eq!(
+            proving_info.records(),
+            vec![
+                (Direction::Sent, 0..10),
+                (Direction::Sent, 20..30),
+                (Direction::Received, 5..5 + MAX_REVEALED_RECORD_LEN),
+                (
+                    Direction::Received,
+                    5 + MAX_REVEALED_RECORD_LEN..5 + 2 * MAX_REVEALED_RECORD_LEN
+                ),
+                (
+                    Direction::Received,
+                    5 + 2 * MAX_REVEALED_RECORD_LEN..6 + 2 * MAX_REVEALED_RECORD_LEN
+                ),
+            ]
+        );
+        assert!(ProvingInfo::default().records().is_empty());
+    }
 }
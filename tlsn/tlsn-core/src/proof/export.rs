@@ -0,0 +1,278 @@
+//! Export of committed transcript data for external zero-knowledge proving systems.
+
+use mpz_circuits::types::ValueType;
+use mpz_core::commit::Nonce;
+use mpz_garble_core::{encoding_state, EncodedValue, Encoder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commitment::{blake3::Blake3Opening, merkle_leaf, Commitment, CommitmentId, CommitmentInfo},
+    merkle::MerkleProof,
+    transcript::get_value_ids,
+    Direction, EncodingId, SessionData, SessionHeader,
+};
+
+/// An error for [`CommitmentExport`].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CommitmentExportError {
+    /// Invalid commitment id.
+    #[error("invalid commitment id: {0:?}")]
+    InvalidCommitmentId(CommitmentId),
+    /// The exported data does not match the commitment.
+    #[error("exported data does not match commitment {0:?}")]
+    InvalidOpening(CommitmentId),
+    /// The commitment is not included in the session's Merkle tree.
+    #[error("invalid inclusion proof: {0}")]
+    InvalidInclusionProof(String),
+}
+
+/// The number of data bytes packed into one field element by [`CommitmentExport::packed_data`].
+///
+/// 31 bytes fit in the scalar fields of both BN254 and BLS12-381.
+pub const PACKED_BYTES_PER_ELEMENT: usize = 31;
+
+/// A transcript commitment exported together with the witness needed to prove statements about
+/// its data in zero knowledge.
+///
+/// A zk proof of a predicate over the committed data takes the [`SessionHeader`]'s Merkle root and
+/// encoder seed as public inputs, and the data and nonce as private witness. It must show that:
+///
+/// 1. The encodings of the data, see [`CommitmentExport::encodings`], hashed together with the
+///    nonce give the commitment.
+/// 2. The commitment, hashed together with its label if it has one, is included in the Merkle
+///    tree under the root, see
+///    [`CommitmentExport::inclusion_proof`].
+/// 3. The predicate holds over the data.
+///
+/// [`CommitmentExport::verify`] checks 1 and 2 in the clear, and is the reference any zk circuit
+/// must agree with.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommitmentExport {
+    id: CommitmentId,
+    info: CommitmentInfo,
+    label: Option<String>,
+    data: Vec<u8>,
+    nonce: Nonce,
+    inclusion_proof: MerkleProof,
+}
+
+opaque_debug::implement!(CommitmentExport);
+
+impl CommitmentExport {
+    /// Exports the commitment with the provided id.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The data of the notarized session.
+    /// * `id` - The id of the commitment to export.
+    pub fn new(session: &SessionData, id: CommitmentId) -> Result<Self, CommitmentExportError> {
+        let commitments = session.commitments();
+
+        let Some(Commitment::Blake3(commitment)) = commitments.get(&id) else {
+            return Err(CommitmentExportError::InvalidCommitmentId(id));
+        };
+
+        let info = commitments
+            .get_info(&id)
+            .expect("info exists if commitment exists")
+            .clone();
+
+        let transcript = match info.direction() {
+            Direction::Sent => session.sent_transcript(),
+            Direction::Received => session.recv_transcript(),
+        };
+
+        Ok(Self {
+            id,
+            data: transcript.get_bytes_in_ranges(info.ranges()),
+            nonce: *commitment.nonce(),
+            inclusion_proof: commitments.merkle_tree().proof(&[id.to_inner() as usize]),
+            label: commitments.get_label(&id).map(str::to_string),
+            info,
+        })
+    }
+
+    /// Returns the id of the commitment, which is its leaf index in the Merkle tree.
+    pub fn id(&self) -> CommitmentId {
+        self.id
+    }
+
+    /// Returns the commitment info, containing the committed ranges.
+    pub fn info(&self) -> &CommitmentInfo {
+        &self.info
+    }
+
+    /// Returns the label of the commitment, if it has one.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Returns the committed data, in order of the committed ranges.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the committed data packed into field elements.
+    ///
+    /// Each element is a 32-byte big-endian integer holding [`PACKED_BYTES_PER_ELEMENT`] bytes of
+    /// data, so it is smaller than the BN254 and BLS12-381 scalar field moduli. The first byte of
+    /// an element is always zero, followed by the data bytes in order. The last element is padded
+    /// with trailing zeros; the data length is given by the committed ranges.
+    ///
+    /// Circuits can take these elements as inputs and decompose them into bits, instead of
+    /// taking one input per byte.
+    pub fn packed_data(&self) -> Vec<[u8; 32]> {
+        self.data
+            .chunks(PACKED_BYTES_PER_ELEMENT)
+            .map(|chunk| {
+                let mut element = [0u8; 32];
+                element[1..1 + chunk.len()].copy_from_slice(chunk);
+                element
+            })
+            .collect()
+    }
+
+    /// Returns the nonce, which salts the commitment.
+    pub fn nonce(&self) -> &Nonce {
+        &self.nonce
+    }
+
+    /// Returns the proof that the commitment is included in the session's Merkle tree.
+    pub fn inclusion_proof(&self) -> &MerkleProof {
+        &self.inclusion_proof
+    }
+
+    /// Returns the encodings of the committed data, one per byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The session header, containing the encoder seed.
+    pub fn encodings(&self, header: &SessionHeader) -> Vec<EncodedValue<encoding_state::Active>> {
+        self.full_encodings(header)
+            .into_iter()
+            .zip(&self.data)
+            .map(|(encoding, byte)| encoding.select(*byte).expect("encoding is for a u8"))
+            .collect()
+    }
+
+    /// Verifies that the exported data is committed to in the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The session header.
+    pub fn verify(&self, header: &SessionHeader) -> Result<(), CommitmentExportError> {
+        let encodings = self.full_encodings(header);
+        if encodings.len() != self.data.len() {
+            return Err(CommitmentExportError::InvalidOpening(self.id));
+        }
+
+        let commitment = Blake3Opening::new(self.data.clone(), self.nonce).recover(&encodings);
+
+        self.inclusion_proof
+            .verify(
+                header.merkle_root(),
+                &[self.id.to_inner() as usize],
+                &[merkle_leaf(*commitment.hash(), self.label())],
+            )
+            .map_err(|e| CommitmentExportError::InvalidInclusionProof(e.to_string()))
+    }
+
+    fn full_encodings(&self, header: &SessionHeader) -> Vec<EncodedValue<encoding_state::Full>> {
+        get_value_ids(self.info.ranges(), *self.info.direction())
+            .map(|id| {
+                header
+                    .encoder()
+                    .encode_by_type(EncodingId::new(&id).to_inner(), &ValueType::U8)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mpz_core::commit::HashCommit;
+    use tls_core::dns::ServerName;
+
+    use super::*;
+    use crate::{commitment::TranscriptCommitmentBuilder, fixtures, Transcript};
+
+    fn session() -> SessionData {
+        let sent = b"GET /balance HTTP/1.1".to_vec();
+        let recv = b"HTTP/1.1 200 OK\r\n\r\n{\"balance\":1234}".to_vec();
+
+        let mut builder = TranscriptCommitmentBuilder::new(
+            fixtures::encoding_provider(&sent, &recv),
+            sent.len(),
+            recv.len(),
+        );
+        builder.commit_sent(&(0..3)).unwrap();
+        builder.commit_recv(&(30..34)).unwrap();
+
+        let (hs_decommitment, _) = fixtures::handshake_data().hash_commit();
+
+        SessionData::new(
+            ServerName::try_from("tlsnotary.org").unwrap(),
+            hs_decommitment,
+            Transcript::new(sent),
+            Transcript::new(recv),
+            builder.build().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_export() {
+        let session = session();
+        let header = fixtures::session_header(
+            session.commitments().merkle_root(),
+            session.sent_transcript().data().len(),
+            session.recv_transcript().data().len(),
+        );
+
+        let export = CommitmentExport::new(&session, CommitmentId::new(1)).unwrap();
+
+        assert_eq!(export.data(), b"1234");
+        assert_eq!(export.encodings(&header).len(), 4);
+        export.verify(&header).unwrap();
+    }
+
+    #[test]
+    fn test_export_tampered_data() {
+        let session = session();
+        let header = fixtures::session_header(
+            session.commitments().merkle_root(),
+            session.sent_transcript().data().len(),
+            session.recv_transcript().data().len(),
+        );
+
+        let mut export = CommitmentExport::new(&session, CommitmentId::new(1)).unwrap();
+        export.data = b"9999".to_vec();
+
+        assert!(matches!(
+            export.verify(&header),
+            Err(CommitmentExportError::InvalidInclusionProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_packed_data() {
+        let mut export = CommitmentExport::new(&session(), CommitmentId::new(1)).unwrap();
+        export.data = (0..40).collect();
+
+        let packed = export.packed_data();
+
+        assert_eq!(packed.len(), 2);
+        assert!(packed.iter().all(|element| element[0] == 0));
+        assert_eq!(packed[0][1..], export.data[..31]);
+        assert_eq!(packed[1][1..10], export.data[31..]);
+        assert!(packed[1][10..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn test_export_invalid_id() {
+        assert!(matches!(
+            CommitmentExport::new(&session(), CommitmentId::new(2)),
+            Err(CommitmentExportError::InvalidCommitmentId(_))
+        ));
+    }
+}
@@ -1,15 +1,24 @@
 //! Different types of proofs used in the TLSNotary protocol.
 
+mod export;
+mod report;
 mod session;
 mod substrings;
+mod validity;
 
+pub use export::{CommitmentExport, CommitmentExportError, PACKED_BYTES_PER_ELEMENT};
+pub use report::{Check, CheckResult, CheckStatus, VerificationReport};
 pub use session::{default_cert_verifier, SessionInfo, SessionProof, SessionProofError};
 pub use substrings::{
     SubstringsProof, SubstringsProofBuilder, SubstringsProofBuilderError, SubstringsProofError,
 };
+pub use validity::{ValidityError, ValidityWindow, DEFAULT_MAX_AGE, DEFAULT_MAX_CLOCK_SKEW};
 
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use tls_core::verify::ServerCertVerifier;
+
+use crate::{NotaryPublicKey, RedactedTranscript};
 
 /// Proof that a transcript of communications took place between a Prover and Server.
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,3 +28,82 @@ pub struct TlsProof {
     /// Proof regarding the contents of the transcript.
     pub substrings: SubstringsProof,
 }
+
+impl TlsProof {
+    /// Verifies the proof, returning a report of every check performed.
+    ///
+    /// The session time is checked against the default [`ValidityWindow`]. The redacted sent and
+    /// received transcripts are only returned if every check passed.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    pub fn verify_with_report(
+        self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+    ) -> (
+        VerificationReport,
+        Option<(RedactedTranscript, RedactedTranscript)>,
+    ) {
+        self.verify_with_validity_report(
+            notary_public_key,
+            cert_verifier,
+            &ValidityWindow::default(),
+            validity::now(),
+        )
+    }
+
+    /// Verifies the proof against the validity window, returning a report of every check
+    /// performed.
+    ///
+    /// The redacted sent and received transcripts are only returned if every check passed.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    /// * `validity_window` - The window in which the session is accepted.
+    /// * `now` - The current time, in seconds since the UNIX epoch.
+    pub fn verify_with_validity_report(
+        self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+        validity_window: &ValidityWindow,
+        now: u64,
+    ) -> (
+        VerificationReport,
+        Option<(RedactedTranscript, RedactedTranscript)>,
+    ) {
+        let Self {
+            session,
+            substrings,
+        } = self;
+
+        let mut report = session.verify_with_validity_report(
+            notary_public_key,
+            cert_verifier,
+            validity_window,
+            now,
+        );
+
+        // The transcript lengths of an unsigned header are untrusted, and would size the
+        // transcripts allocated by the substrings verification
+        let signature_passed = report
+            .get(&Check::NotarySignature)
+            .is_some_and(CheckResult::is_passed);
+        let transcripts = if signature_passed {
+            report.record(Check::Substrings, substrings.verify(&session.header))
+        } else {
+            report.skip(Check::Substrings, Check::NotarySignature);
+            None
+        };
+
+        if report.is_valid() {
+            (report, transcripts)
+        } else {
+            (report, None)
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Structured reports of proof verification.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// A check performed while verifying a proof.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum Check {
+    /// The Notary signature over the session header.
+    NotarySignature,
+    /// The server name is a valid DNS name.
+    ServerName,
+    /// The handshake data matches the commitment in the session header.
+    Handshake,
+    /// The server certificate chain and key exchange signature.
+    ServerCertificate,
+    /// The session time is within the accepted validity window.
+    Validity,
+    /// The commitment openings of a substrings proof.
+    Substrings,
+    /// An application defined policy rule, identified by name.
+    Policy(String),
+}
+
+/// The status of a [`Check`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    /// The check passed.
+    Passed,
+    /// The check failed for the given reason.
+    Failed(String),
+    /// The check was not performed because the check it depends on did not pass.
+    Skipped(Check),
+}
+
+/// The result of a single [`Check`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// The check which was performed.
+    pub check: Check,
+    /// The status of the check.
+    pub status: CheckStatus,
+}
+
+impl CheckResult {
+    /// Returns `true` if the check passed.
+    pub fn is_passed(&self) -> bool {
+        matches!(self.status, CheckStatus::Passed)
+    }
+}
+
+/// A report of every check performed while verifying a proof.
+///
+/// A proof must only be accepted if [`VerificationReport::is_valid`] returns `true`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    results: Vec<CheckResult>,
+}
+
+impl VerificationReport {
+    /// Records the outcome of a check, returning the value if it passed.
+    pub fn record<T, E: Display>(&mut self, check: Check, result: Result<T, E>) -> Option<T> {
+        let (status, value) = match result {
+            Ok(value) => (CheckStatus::Passed, Some(value)),
+            Err(e) => (CheckStatus::Failed(e.to_string()), None),
+        };
+
+        self.results.push(CheckResult { check, status });
+
+        value
+    }
+
+    /// Records that a check was skipped because `dependency` did not pass.
+    pub fn skip(&mut self, check: Check, dependency: Check) {
+        self.results.push(CheckResult {
+            check,
+            status: CheckStatus::Skipped(dependency),
+        });
+    }
+
+    /// Records the outcome of an application defined policy rule.
+    pub fn record_policy(&mut self, name: impl Into<String>, result: Result<(), String>) {
+        self.record(Check::Policy(name.into()), result);
+    }
+
+    /// Returns `true` if at least one check was performed and every check passed.
+    pub fn is_valid(&self) -> bool {
+        !self.results.is_empty() && self.results.iter().all(CheckResult::is_passed)
+    }
+
+    /// Returns the results of all checks, in the order they were performed.
+    pub fn results(&self) -> &[CheckResult] {
+        &self.results
+    }
+
+    /// Returns an iterator over the checks which did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.results.iter().filter(|result| !result.is_passed())
+    }
+
+    /// Returns the result of the given check, if it was performed.
+    pub fn get(&self, check: &Check) -> Option<&CheckResult> {
+        self.results.iter().find(|result| &result.check == check)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_valid() {
+        let mut report = VerificationReport::default();
+
+        assert!(!report.is_valid());
+
+        assert_eq!(
+            report.record(Check::NotarySignature, Ok::<_, String>(1)),
+            Some(1)
+        );
+        report.record_policy("max_age", Ok(()));
+
+        assert!(report.is_valid());
+        assert_eq!(report.failures().count(), 0);
+    }
+
+    #[test]
+    fn test_report_failures() {
+        let mut report = VerificationReport::default();
+
+        report.record(Check::NotarySignature, Ok::<_, String>(()));
+        assert_eq!(
+            report.record(Check::ServerName, Err::<(), _>("bad name")),
+            None
+        );
+        report.skip(Check::ServerCertificate, Check::ServerName);
+
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.failures().map(|r| &r.check).collect::<Vec<_>>(),
+            vec![&Check::ServerName, &Check::ServerCertificate]
+        );
+        assert_eq!(
+            report.get(&Check::ServerName).unwrap().status,
+            CheckStatus::Failed("bad name".to_string())
+        );
+    }
+}
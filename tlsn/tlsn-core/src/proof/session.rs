@@ -11,6 +11,10 @@ use tls_core::{
 };
 
 use crate::{
+    proof::{
+        report::{Check, VerificationReport},
+        validity::{now, ValidityError, ValidityWindow},
+    },
     session::SessionHeader,
     signature::{Signature, SignatureVerifyError},
     HandshakeSummary, NotaryPublicKey, ServerName,
@@ -35,6 +39,9 @@ pub enum SessionProofError {
     /// Invalid server certificate
     #[error("server certificate verification failed: {0}")]
     InvalidServerCertificate(String),
+    /// The session is outside of the accepted validity window
+    #[error(transparent)]
+    InvalidTime(#[from] ValidityError),
 }
 
 /// A session proof which is created from a [crate::session::NotarizedSession]
@@ -53,6 +60,9 @@ pub struct SessionProof {
 impl SessionProof {
     /// Verify the session proof.
     ///
+    /// The session is rejected if it is outside of the default [`ValidityWindow`], see
+    /// [`SessionProof::verify_with_validity`] to use another window.
+    ///
     /// # Arguments
     ///
     /// * `notary_public_key` - The public key of the notary.
@@ -61,6 +71,29 @@ impl SessionProof {
         &self,
         notary_public_key: impl Into<NotaryPublicKey>,
         cert_verifier: &impl ServerCertVerifier,
+    ) -> Result<(), SessionProofError> {
+        self.verify_with_validity(
+            notary_public_key,
+            cert_verifier,
+            &ValidityWindow::default(),
+            now(),
+        )
+    }
+
+    /// Verify the session proof, checking that the session time is within the validity window.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    /// * `validity_window` - The window in which the session is accepted.
+    /// * `now` - The current time, in seconds since the UNIX epoch.
+    pub fn verify_with_validity(
+        &self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+        validity_window: &ValidityWindow,
+        now: u64,
     ) -> Result<(), SessionProofError> {
         // Verify notary signature
         let signature = self
@@ -71,6 +104,7 @@ impl SessionProof {
         signature.verify(&self.header.to_bytes(), notary_public_key)?;
         self.session_info
             .verify(self.header.handshake_summary(), cert_verifier)?;
+        validity_window.check(self.header.time(), now)?;
 
         Ok(())
     }
@@ -86,6 +120,70 @@ impl SessionProof {
     ) -> Result<(), SessionProofError> {
         self.verify(notary_public_key, &default_cert_verifier())
     }
+
+    /// Verify the session proof, returning a report of every check performed.
+    ///
+    /// Unlike [`SessionProof::verify`], this does not stop at the first failed check. The
+    /// session time is checked against the default [`ValidityWindow`].
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    pub fn verify_with_report(
+        &self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+    ) -> VerificationReport {
+        self.verify_with_validity_report(
+            notary_public_key,
+            cert_verifier,
+            &ValidityWindow::default(),
+            now(),
+        )
+    }
+
+    /// Verify the session proof against the validity window, returning a report of every check
+    /// performed.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    /// * `validity_window` - The window in which the session is accepted.
+    /// * `now` - The current time, in seconds since the UNIX epoch.
+    pub fn verify_with_validity_report(
+        &self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+        validity_window: &ValidityWindow,
+        now: u64,
+    ) -> VerificationReport {
+        let mut report = VerificationReport::default();
+
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or(SessionProofError::MissingNotarySignature)
+            .and_then(|signature| {
+                signature
+                    .verify(&self.header.to_bytes(), notary_public_key)
+                    .map_err(SessionProofError::from)
+            });
+        report.record(Check::NotarySignature, signature);
+
+        self.session_info.verify_with_report(
+            self.header.handshake_summary(),
+            cert_verifier,
+            &mut report,
+        );
+        report.record(
+            Check::Validity,
+            validity_window.check(self.header.time(), now),
+        );
+
+        report
+    }
 }
 
 /// Contains information about the session
@@ -106,24 +204,9 @@ impl SessionInfo {
         handshake_summary: &HandshakeSummary,
         cert_verifier: &impl ServerCertVerifier,
     ) -> Result<(), SessionProofError> {
-        // Verify server name
-        let server_name = TlsServerName::try_from(self.server_name.as_ref())
-            .map_err(|e| SessionProofError::InvalidServerName(e.to_string()))?;
-
-        // Verify handshake
-        self.handshake_decommitment
-            .verify(handshake_summary.handshake_commitment())
-            .map_err(|e| SessionProofError::InvalidHandshake(e.to_string()))?;
-
-        // Verify server certificate
-        self.handshake_decommitment
-            .data()
-            .verify(
-                cert_verifier,
-                UNIX_EPOCH + Duration::from_secs(handshake_summary.time()),
-                &server_name,
-            )
-            .map_err(|e| SessionProofError::InvalidServerCertificate(e.to_string()))?;
+        let server_name = self.verify_server_name()?;
+        self.verify_handshake(handshake_summary)?;
+        self.verify_server_cert(handshake_summary, cert_verifier, &server_name)?;
 
         Ok(())
     }
@@ -139,6 +222,64 @@ impl SessionInfo {
     ) -> Result<(), SessionProofError> {
         self.verify(handshake_summary, &default_cert_verifier())
     }
+
+    /// Verify the session info, recording the outcome of each check in `report`.
+    ///
+    /// Checks which depend on a failed check are recorded as skipped.
+    pub fn verify_with_report(
+        &self,
+        handshake_summary: &HandshakeSummary,
+        cert_verifier: &impl ServerCertVerifier,
+        report: &mut VerificationReport,
+    ) {
+        let server_name = report.record(Check::ServerName, self.verify_server_name());
+        let handshake = report.record(Check::Handshake, self.verify_handshake(handshake_summary));
+
+        // The certificate is only meaningful for the committed handshake
+        match (server_name, handshake) {
+            (Some(server_name), Some(())) => {
+                report.record(
+                    Check::ServerCertificate,
+                    self.verify_server_cert(handshake_summary, cert_verifier, &server_name),
+                );
+            }
+            (None, _) => report.skip(Check::ServerCertificate, Check::ServerName),
+            (_, None) => report.skip(Check::ServerCertificate, Check::Handshake),
+        }
+    }
+
+    /// Verifies the server name.
+    fn verify_server_name(&self) -> Result<TlsServerName, SessionProofError> {
+        TlsServerName::try_from(self.server_name.as_ref())
+            .map_err(|e| SessionProofError::InvalidServerName(e.to_string()))
+    }
+
+    /// Verifies the handshake data against the commitment in the handshake summary.
+    fn verify_handshake(
+        &self,
+        handshake_summary: &HandshakeSummary,
+    ) -> Result<(), SessionProofError> {
+        self.handshake_decommitment
+            .verify(handshake_summary.handshake_commitment())
+            .map_err(|e| SessionProofError::InvalidHandshake(e.to_string()))
+    }
+
+    /// Verifies the server certificate.
+    fn verify_server_cert(
+        &self,
+        handshake_summary: &HandshakeSummary,
+        cert_verifier: &impl ServerCertVerifier,
+        server_name: &TlsServerName,
+    ) -> Result<(), SessionProofError> {
+        self.handshake_decommitment
+            .data()
+            .verify(
+                cert_verifier,
+                UNIX_EPOCH + Duration::from_secs(handshake_summary.time()),
+                server_name,
+            )
+            .map_err(|e| SessionProofError::InvalidServerCertificate(e.to_string()))
+    }
 }
 
 /// Create a new [`WebPkiVerifier`] with the default trust anchors from the `webpki-roots` crate.
@@ -160,10 +301,109 @@ mod tests {
     use super::*;
     use rstest::*;
 
-    use crate::fixtures::cert::{appliedzkp, tlsnotary, TestData};
+    use crate::{
+        fixtures::{
+            self,
+            cert::{appliedzkp, tlsnotary, TestData},
+        },
+        proof::CheckStatus,
+    };
+    use mpz_core::{commit::HashCommit, hash::Hash};
     use tls_core::{dns::ServerName, key::Certificate};
     use web_time::SystemTime;
 
+    fn session_info(server_name: &str) -> SessionInfo {
+        let (handshake_decommitment, _) = fixtures::handshake_data().hash_commit();
+
+        SessionInfo {
+            server_name: crate::ServerName::Dns(server_name.to_string()),
+            handshake_decommitment,
+        }
+    }
+
+    fn status(report: &VerificationReport, check: Check) -> CheckStatus {
+        report.get(&check).unwrap().status.clone()
+    }
+
+    /// Expect every check of a valid session to pass
+    #[test]
+    fn test_verify_with_report_success() {
+        let mut report = VerificationReport::default();
+        session_info("tlsnotary.org").verify_with_report(
+            &fixtures::handshake_summary(),
+            &default_cert_verifier(),
+            &mut report,
+        );
+
+        assert!(report.is_valid());
+        assert_eq!(report.results().len(), 3);
+    }
+
+    /// Expect the certificate check to be skipped if the handshake doesn't match the commitment
+    #[test]
+    fn test_verify_with_report_invalid_handshake() {
+        let summary = fixtures::handshake_summary();
+        let summary = HandshakeSummary::new(
+            summary.time(),
+            fixtures::server_ephemeral_key(),
+            Hash::from([0u8; 32]),
+        );
+
+        let mut report = VerificationReport::default();
+        session_info("tlsnotary.org").verify_with_report(
+            &summary,
+            &default_cert_verifier(),
+            &mut report,
+        );
+
+        assert_eq!(status(&report, Check::ServerName), CheckStatus::Passed);
+        assert!(matches!(
+            status(&report, Check::Handshake),
+            CheckStatus::Failed(_)
+        ));
+        assert_eq!(
+            status(&report, Check::ServerCertificate),
+            CheckStatus::Skipped(Check::Handshake)
+        );
+    }
+
+    /// Expect the certificate check to be skipped if the server name is invalid
+    #[test]
+    fn test_verify_with_report_invalid_server_name() {
+        let mut report = VerificationReport::default();
+        session_info("not a dns name").verify_with_report(
+            &fixtures::handshake_summary(),
+            &default_cert_verifier(),
+            &mut report,
+        );
+
+        assert!(matches!(
+            status(&report, Check::ServerName),
+            CheckStatus::Failed(_)
+        ));
+        assert_eq!(status(&report, Check::Handshake), CheckStatus::Passed);
+        assert_eq!(
+            status(&report, Check::ServerCertificate),
+            CheckStatus::Skipped(Check::ServerName)
+        );
+    }
+
+    /// Expect the certificate check to fail for a different server name
+    #[test]
+    fn test_verify_with_report_wrong_server_name() {
+        let mut report = VerificationReport::default();
+        session_info("appliedzkp.org").verify_with_report(
+            &fixtures::handshake_summary(),
+            &default_cert_verifier(),
+            &mut report,
+        );
+
+        assert!(matches!(
+            status(&report, Check::ServerCertificate),
+            CheckStatus::Failed(_)
+        ));
+    }
+
     /// Expect chain verification to succeed
     #[rstest]
     #[case::tlsnotary(tlsnotary())]
@@ -2,7 +2,7 @@
 
 use crate::{
     commitment::{
-        Commitment, CommitmentId, CommitmentInfo, CommitmentKind, CommitmentOpening,
+        merkle_leaf, Commitment, CommitmentId, CommitmentInfo, CommitmentKind, CommitmentOpening,
         TranscriptCommitments,
     },
     merkle::MerkleProof,
@@ -26,12 +26,18 @@ pub enum SubstringsProofBuilderError {
     /// Missing commitment.
     #[error("missing commitment")]
     MissingCommitment,
+    /// Missing commitment with the provided label.
+    #[error("missing commitment with label {0:?}")]
+    MissingLabel(String),
     /// Invalid commitment type.
     #[error("commitment {0:?} is not a substrings commitment")]
     InvalidCommitmentType(CommitmentId),
     /// Attempted to add a commitment with a duplicate id.
     #[error("commitment with id {0:?} already exists")]
     DuplicateCommitmentId(CommitmentId),
+    /// The commitment range splits a multi-byte UTF-8 character.
+    #[error("commitment {0:?} splits a multi-byte character at index {1}")]
+    SplitsCharacter(CommitmentId, usize),
 }
 
 /// A builder for [`SubstringsProof`]
@@ -97,7 +103,23 @@ impl<'a> SubstringsProofBuilder<'a> {
         self.reveal_by_id(com)
     }
 
-    /// Reveals data corresponding to the provided commitment id
+    /// Reveals data corresponding to the commitment with the provided label.
+    pub fn reveal_label(&mut self, label: &str) -> Result<&mut Self, SubstringsProofBuilderError> {
+        let com = self
+            .commitments
+            .get_id_by_label(label)
+            .ok_or_else(|| SubstringsProofBuilderError::MissingLabel(label.to_string()))?;
+
+        self.reveal_by_id(com)
+    }
+
+    /// Reveals data corresponding to the provided commitment id.
+    ///
+    /// Redaction decisions can be made at any time after notarization, as long as the
+    /// [`TranscriptCommitments`] are available. The committed ranges must not split a valid
+    /// multi-byte UTF-8 character, otherwise the revealed text could not be decoded by the
+    /// verifier. Bytes which are not part of a valid UTF-8 sequence, eg. binary data, may be split
+    /// anywhere, so transcripts mixing text and binary data are checked around each boundary.
     pub fn reveal_by_id(
         &mut self,
         id: CommitmentId,
@@ -123,12 +145,28 @@ impl<'a> SubstringsProofBuilder<'a> {
             Direction::Received => self.transcript_rx,
         };
 
+        if let Some(index) = info
+            .ranges()
+            .iter_ranges()
+            .flat_map(|range| [range.start, range.end])
+            .find(|index| splits_character(transcript.data(), *index))
+        {
+            return Err(SubstringsProofBuilderError::SplitsCharacter(id, index));
+        }
+
         let data = transcript.get_bytes_in_ranges(info.ranges());
 
         // add commitment to openings and return an error if it is already present
         if self
             .openings
-            .insert(id, (info.clone(), commitment.open(data).into()))
+            .insert(
+                id,
+                (
+                    info.clone(),
+                    CommitmentOpening::from(commitment.open(data))
+                        .with_label(self.commitments.get_label(&id)),
+                ),
+            )
             .is_some()
         {
             return Err(SubstringsProofBuilderError::DuplicateCommitmentId(id));
@@ -160,6 +198,38 @@ impl<'a> SubstringsProofBuilder<'a> {
     }
 }
 
+/// Returns `true` if `index` falls inside a valid multi-byte UTF-8 character of `data`.
+///
+/// Only the bytes around `index` are inspected, so invalid UTF-8 elsewhere in `data` does not
+/// prevent the check.
+fn splits_character(data: &[u8], index: usize) -> bool {
+    let is_continuation = |byte: u8| byte & 0xC0 == 0x80;
+
+    if index == 0 || index >= data.len() || !is_continuation(data[index]) {
+        return false;
+    }
+
+    // A character is at most 4 bytes long, so its first byte is at most 3 bytes before `index`.
+    let Some(start) = (index.saturating_sub(3)..index)
+        .rev()
+        .find(|&start| !is_continuation(data[start]))
+    else {
+        return false;
+    };
+
+    let len = match data[start] {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return false,
+    };
+
+    start + len > index
+        && data
+            .get(start..start + len)
+            .is_some_and(|char| std::str::from_utf8(char).is_ok())
+}
+
 /// An error relating to [`SubstringsProof`]
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
@@ -197,6 +267,39 @@ pub struct SubstringsProof {
 opaque_debug::implement!(SubstringsProof);
 
 impl SubstringsProof {
+    /// Returns the labels of the opened commitments, with the direction and ranges of the data
+    /// they refer to.
+    ///
+    /// The labels are only authentic if [`SubstringsProof::verify`] succeeds.
+    pub fn labels(&self) -> impl Iterator<Item = (&str, Direction, &RangeSet<usize>)> {
+        self.openings.values().filter_map(|(info, opening)| {
+            opening
+                .label()
+                .map(|label| (label, *info.direction(), info.ranges()))
+        })
+    }
+
+    /// Returns the label of an opened commitment, if it has one.
+    ///
+    /// The label is only authentic if [`SubstringsProof::verify`] succeeds.
+    pub fn label(&self, id: &CommitmentId) -> Option<&str> {
+        self.openings
+            .get(id)
+            .and_then(|(_, opening)| opening.label())
+    }
+
+    /// Returns the opened commitments with their ids.
+    ///
+    /// The commitments are only authentic if [`SubstringsProof::verify`] succeeds.
+    pub fn opened_commitments(&self) -> impl Iterator<Item = (&CommitmentId, &CommitmentInfo)> {
+        self.openings.iter().map(|(id, (info, _))| (id, info))
+    }
+
+    /// Returns the total number of commitments to the transcript, opened or not.
+    pub fn total_commitments(&self) -> usize {
+        self.inclusion_proof.total_leaves()
+    }
+
     /// Verifies this proof and, if successful, returns the redacted sent and received transcripts.
     ///
     /// # Arguments
@@ -221,7 +324,7 @@ impl SubstringsProof {
         for (id, (info, opening)) in openings {
             let CommitmentInfo {
                 ranges, direction, ..
-            } = info;
+            } = info.clone();
 
             let opened_len = ranges.len();
 
@@ -277,7 +380,10 @@ impl SubstringsProof {
             // Compute the expected hash of the commitment to make sure it is
             // present in the merkle tree.
             indices.push(id.to_inner() as usize);
-            expected_hashes.push(opening.recover(&encodings).hash());
+            expected_hashes.push(merkle_leaf(
+                opening.recover(&encodings).hash(),
+                opening.label(),
+            ));
 
             // Make sure the length of data from the opening matches the commitment.
             let mut data = opening.into_data();
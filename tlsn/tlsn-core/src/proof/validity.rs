@@ -0,0 +1,175 @@
+//! Validity windows for attestations.
+
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// The default maximum age of an attestation, in seconds (7 days).
+pub const DEFAULT_MAX_AGE: u64 = 7 * 24 * 60 * 60;
+/// The default tolerance for attestations with a timestamp in the future, in seconds.
+pub const DEFAULT_MAX_CLOCK_SKEW: u64 = 300;
+
+/// An error that can occur when checking an attestation against a [`ValidityWindow`].
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidityError {
+    /// The attestation was issued before the earliest accepted time.
+    #[error("attestation time {time} is before the earliest accepted time {not_before}")]
+    NotBefore {
+        /// The time of the attestation.
+        time: u64,
+        /// The earliest accepted time.
+        not_before: u64,
+    },
+    /// The attestation is older than the maximum age.
+    #[error(
+        "attestation is {age} seconds old, which exceeds the maximum age of {max_age} seconds"
+    )]
+    Expired {
+        /// The age of the attestation, in seconds.
+        age: u64,
+        /// The maximum accepted age, in seconds.
+        max_age: u64,
+    },
+    /// The attestation claims a time in the future.
+    #[error("attestation time {time} is in the future (now: {now})")]
+    FutureTimestamp {
+        /// The time of the attestation.
+        time: u64,
+        /// The current time.
+        now: u64,
+    },
+}
+
+/// The window of time in which an attestation is accepted.
+///
+/// By default attestations older than [`DEFAULT_MAX_AGE`] and attestations claiming a time more
+/// than [`DEFAULT_MAX_CLOCK_SKEW`] in the future are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidityWindow {
+    not_before: Option<u64>,
+    max_age: Option<u64>,
+    max_clock_skew: u64,
+}
+
+impl Default for ValidityWindow {
+    fn default() -> Self {
+        Self {
+            not_before: None,
+            max_age: Some(DEFAULT_MAX_AGE),
+            max_clock_skew: DEFAULT_MAX_CLOCK_SKEW,
+        }
+    }
+}
+
+impl ValidityWindow {
+    /// Creates a window which accepts attestations from any time in the past.
+    ///
+    /// Attestations claiming a time in the future are still rejected.
+    pub fn unbounded() -> Self {
+        Self {
+            not_before: None,
+            max_age: None,
+            max_clock_skew: DEFAULT_MAX_CLOCK_SKEW,
+        }
+    }
+
+    /// Sets the earliest accepted attestation time, in seconds since the UNIX epoch.
+    pub fn not_before(mut self, not_before: u64) -> Self {
+        self.not_before = Some(not_before);
+        self
+    }
+
+    /// Sets the maximum accepted age of an attestation, in seconds.
+    pub fn max_age(mut self, max_age: u64) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the tolerance for attestations with a timestamp in the future, in seconds.
+    pub fn max_clock_skew(mut self, max_clock_skew: u64) -> Self {
+        self.max_clock_skew = max_clock_skew;
+        self
+    }
+
+    /// Checks that an attestation made at `time` is valid at `now`.
+    ///
+    /// # Arguments
+    ///
+    /// * `time` - The time of the attestation, in seconds since the UNIX epoch.
+    /// * `now` - The current time, in seconds since the UNIX epoch.
+    pub fn check(&self, time: u64, now: u64) -> Result<(), ValidityError> {
+        if time > now.saturating_add(self.max_clock_skew) {
+            return Err(ValidityError::FutureTimestamp { time, now });
+        }
+
+        if let Some(not_before) = self.not_before {
+            if time < not_before {
+                return Err(ValidityError::NotBefore { time, not_before });
+            }
+        }
+
+        if let Some(max_age) = self.max_age {
+            let age = now.saturating_sub(time);
+            if age > max_age {
+                return Err(ValidityError::Expired { age, max_age });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the current time, in seconds since the UNIX epoch.
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the UNIX epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    #[test]
+    fn test_default_window() {
+        let window = ValidityWindow::default();
+
+        assert!(window.check(NOW, NOW).is_ok());
+        assert!(window.check(NOW - DEFAULT_MAX_AGE, NOW).is_ok());
+        assert!(window.check(NOW + DEFAULT_MAX_CLOCK_SKEW, NOW).is_ok());
+
+        assert_eq!(
+            window.check(NOW - DEFAULT_MAX_AGE - 1, NOW),
+            Err(ValidityError::Expired {
+                age: DEFAULT_MAX_AGE + 1,
+                max_age: DEFAULT_MAX_AGE
+            })
+        );
+        assert_eq!(
+            window.check(NOW + DEFAULT_MAX_CLOCK_SKEW + 1, NOW),
+            Err(ValidityError::FutureTimestamp {
+                time: NOW + DEFAULT_MAX_CLOCK_SKEW + 1,
+                now: NOW
+            })
+        );
+    }
+
+    #[test]
+    fn test_not_before() {
+        let window = ValidityWindow::unbounded().not_before(NOW - 10);
+
+        assert!(window.check(NOW - 10, NOW).is_ok());
+        assert!(matches!(
+            window.check(NOW - 11, NOW),
+            Err(ValidityError::NotBefore { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unbounded() {
+        assert!(ValidityWindow::unbounded().check(0, NOW).is_ok());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    commitment::TranscriptCommitments,
+    commitment::{CommitmentId, TranscriptCommitments},
     proof::{SessionInfo, SubstringsProofBuilder},
     ServerName, Transcript,
 };
@@ -68,6 +68,13 @@ impl SessionData {
         &self.commitments
     }
 
+    /// Forgets a commitment, so that its data can never be disclosed from this session.
+    ///
+    /// See [`TranscriptCommitments::forget`].
+    pub fn forget_commitment(&mut self, id: &CommitmentId) -> bool {
+        self.commitments.forget(id)
+    }
+
     /// Returns a substrings proof builder.
     pub fn build_substrings_proof(&self) -> SubstringsProofBuilder {
         SubstringsProofBuilder::new(&self.commitments, &self.transcript_tx, &self.transcript_rx)
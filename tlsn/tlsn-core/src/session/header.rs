@@ -1,10 +1,13 @@
-use mpz_core::commit::Decommitment;
+use mpz_core::{commit::Decommitment, serialize::CanonicalSerialize, utils::blake3};
 use serde::{Deserialize, Serialize};
 
 use mpz_garble_core::ChaChaEncoder;
 use tls_core::{handshake::HandshakeData, key::PublicKey};
 
-use crate::{merkle::MerkleRoot, HandshakeSummary};
+use crate::{commitment::placeholder_tree, merkle::MerkleRoot, HandshakeSummary, NotaryPublicKey};
+
+/// Domain separator of session nullifiers.
+const NULLIFIER_DOMAIN: &[u8] = b"tlsn/session-nullifier";
 
 /// An error that can occur while verifying a session header
 #[derive(Debug, thiserror::Error)]
@@ -94,6 +97,32 @@ impl SessionHeader {
         &self.merkle_root
     }
 
+    /// Returns a nullifier of this session, unique per notary and scope.
+    ///
+    /// Downstream protocols can record nullifiers to reject reuse of the same attestation within a
+    /// scope, e.g. claiming an airdrop twice. It is derived from the signed header, which contains
+    /// a random encoder seed, so it is bound to the attestation without being signed separately.
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_key` - The public key of the notary which signed this header.
+    /// * `scope` - The application scope, e.g. the name of an airdrop.
+    pub fn nullifier(&self, notary_key: &NotaryPublicKey, scope: &[u8]) -> [u8; 32] {
+        let mut bytes = NULLIFIER_DOMAIN.to_vec();
+        for part in [notary_key.to_bytes().as_slice(), &self.to_bytes(), scope] {
+            bytes.extend_from_slice(&(part.len() as u64).to_be_bytes());
+            bytes.extend_from_slice(part);
+        }
+
+        blake3(&bytes)
+    }
+
+    /// Returns `true` if the session only attests to the TLS handshake, with no transcript
+    /// commitments.
+    pub fn is_handshake_only(&self) -> bool {
+        self.merkle_root == placeholder_tree().root()
+    }
+
     /// Returns the [HandshakeSummary] of the TLS session between prover and server
     pub fn handshake_summary(&self) -> &HandshakeSummary {
         &self.handshake_summary
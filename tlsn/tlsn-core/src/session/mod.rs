@@ -63,4 +63,9 @@ impl NotarizedSession {
     pub fn data(&self) -> &SessionData {
         &self.data
     }
+
+    /// Returns a mutable reference to the [SessionData]
+    pub fn data_mut(&mut self) -> &mut SessionData {
+        &mut self.data
+    }
 }
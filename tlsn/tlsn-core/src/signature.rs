@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use p256::ecdsa::{signature::Verifier, VerifyingKey};
+use crate::verify::{verify_signature, SignatureAlgorithm};
 
 /// A Notary public key.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -8,6 +8,32 @@ use p256::ecdsa::{signature::Verifier, VerifyingKey};
 pub enum NotaryPublicKey {
     /// A NIST P-256 public key.
     P256(p256::PublicKey),
+    /// A secp256k1 public key.
+    Secp256k1(k256::PublicKey),
+    /// An Ed25519 public key.
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+impl NotaryPublicKey {
+    /// Returns the signature algorithm of this key.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            Self::P256(_) => SignatureAlgorithm::P256,
+            Self::Secp256k1(_) => SignatureAlgorithm::Secp256k1,
+            Self::Ed25519(_) => SignatureAlgorithm::Ed25519,
+        }
+    }
+
+    /// Returns the encoded bytes of this key.
+    ///
+    /// ECDSA keys are SEC1 encoded, Ed25519 keys are the 32 byte compressed point.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Self::P256(key) => key.to_sec1_bytes().to_vec(),
+            Self::Secp256k1(key) => key.to_sec1_bytes().to_vec(),
+            Self::Ed25519(key) => key.to_bytes().to_vec(),
+        }
+    }
 }
 
 impl From<p256::PublicKey> for NotaryPublicKey {
@@ -16,6 +42,18 @@ impl From<p256::PublicKey> for NotaryPublicKey {
     }
 }
 
+impl From<k256::PublicKey> for NotaryPublicKey {
+    fn from(key: k256::PublicKey) -> Self {
+        Self::Secp256k1(key)
+    }
+}
+
+impl From<ed25519_dalek::VerifyingKey> for NotaryPublicKey {
+    fn from(key: ed25519_dalek::VerifyingKey) -> Self {
+        Self::Ed25519(key)
+    }
+}
+
 /// An error occurred while verifying a signature.
 #[derive(Debug, thiserror::Error)]
 #[error("signature verification failed: {0}")]
@@ -27,6 +65,10 @@ pub struct SignatureVerifyError(String);
 pub enum Signature {
     /// A secp256r1 signature.
     P256(p256::ecdsa::Signature),
+    /// A secp256k1 signature.
+    Secp256k1(k256::ecdsa::Signature),
+    /// An Ed25519 signature.
+    Ed25519(ed25519_dalek::Signature),
 }
 
 impl From<p256::ecdsa::Signature> for Signature {
@@ -35,16 +77,42 @@ impl From<p256::ecdsa::Signature> for Signature {
     }
 }
 
+impl From<k256::ecdsa::Signature> for Signature {
+    fn from(sig: k256::ecdsa::Signature) -> Self {
+        Self::Secp256k1(sig)
+    }
+}
+
+impl From<ed25519_dalek::Signature> for Signature {
+    fn from(sig: ed25519_dalek::Signature) -> Self {
+        Self::Ed25519(sig)
+    }
+}
+
 impl Signature {
+    /// Returns the signature algorithm of this signature.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            Self::P256(_) => SignatureAlgorithm::P256,
+            Self::Secp256k1(_) => SignatureAlgorithm::Secp256k1,
+            Self::Ed25519(_) => SignatureAlgorithm::Ed25519,
+        }
+    }
+
     /// Returns the bytes of this signature.
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
             Self::P256(sig) => sig.to_vec(),
+            Self::Secp256k1(sig) => sig.to_vec(),
+            Self::Ed25519(sig) => sig.to_bytes().to_vec(),
         }
     }
 
     /// Verifies the signature.
     ///
+    /// The signature is verified with the algorithm embedded in it, which must match the
+    /// algorithm of the provided key.
+    ///
     /// # Arguments
     ///
     /// * `msg` - The message to verify.
@@ -54,10 +122,18 @@ impl Signature {
         msg: &[u8],
         notary_public_key: impl Into<NotaryPublicKey>,
     ) -> Result<(), SignatureVerifyError> {
-        match (self, notary_public_key.into()) {
-            (Self::P256(sig), NotaryPublicKey::P256(key)) => VerifyingKey::from(key)
-                .verify(msg, sig)
-                .map_err(|e| SignatureVerifyError(e.to_string())),
+        let notary_public_key = notary_public_key.into();
+
+        let alg = self.algorithm();
+        if alg != notary_public_key.algorithm() {
+            return Err(SignatureVerifyError(format!(
+                "signature algorithm {:?} does not match key algorithm {:?}",
+                alg,
+                notary_public_key.algorithm()
+            )));
         }
+
+        verify_signature(alg, &notary_public_key.to_bytes(), msg, &self.to_bytes())
+            .map_err(|e| SignatureVerifyError(e.to_string()))
     }
 }
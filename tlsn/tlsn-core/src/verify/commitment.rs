@@ -0,0 +1,140 @@
+use super::{verify_merkle_proof, VerifyError};
+
+/// Domain separator of the Merkle leaves of labeled commitments.
+const LABEL_DOMAIN: &[u8] = b"tlsn/commitment-label";
+
+/// Returns the Merkle leaf of a commitment with the provided hash and label.
+///
+/// The label is hashed into the leaf so that it is bound to the signed Merkle root. Leaves of
+/// unlabeled commitments are the commitment hash itself.
+pub fn commitment_leaf(hash: &[u8; 32], label: Option<&str>) -> [u8; 32] {
+    let Some(label) = label else {
+        return *hash;
+    };
+
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(LABEL_DOMAIN);
+    hasher.update(&(label.len() as u64).to_be_bytes());
+    hasher.update(label.as_bytes());
+    hasher.update(hash);
+
+    hasher.finalize().into()
+}
+
+/// Verifies that an opened commitment is included in the Merkle tree with the given root.
+///
+/// The commitment hash is recovered from the opened data, its nonce and the encodings of the
+/// session by the caller, as deriving the encodings requires the `std` parts of this crate.
+///
+/// # Arguments
+///
+/// * `root` - The Merkle root signed by the Notary.
+/// * `proof_hashes` - The sibling hashes of the inclusion proof.
+/// * `index` - The index of the commitment in the tree, i.e. its id.
+/// * `total_leaves` - The total number of leaves in the tree.
+/// * `hash` - The commitment hash recovered from the opening.
+/// * `label` - The label of the commitment, if any.
+pub fn verify_commitment_opening(
+    root: &[u8; 32],
+    proof_hashes: &[[u8; 32]],
+    index: usize,
+    total_leaves: usize,
+    hash: &[u8; 32],
+    label: Option<&str>,
+) -> Result<(), VerifyError> {
+    verify_merkle_proof(
+        root,
+        proof_hashes,
+        &[index],
+        &[commitment_leaf(hash, label)],
+        total_leaves,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use rs_merkle::{algorithms::Sha256, MerkleTree};
+
+    const TOTAL_LEAVES: usize = 4;
+
+    // Builds a tree of commitments where the commitment at index 1 is labeled.
+    fn tree() -> (MerkleTree<Sha256>, Vec<[u8; 32]>) {
+        let hashes: Vec<[u8; 32]> = (0..TOTAL_LEAVES as u8).map(|i| [i; 32]).collect();
+        let leaves: Vec<[u8; 32]> = hashes
+            .iter()
+            .enumerate()
+            .map(|(i, hash)| commitment_leaf(hash, (i == 1).then_some("balance")))
+            .collect();
+
+        (MerkleTree::<Sha256>::from_leaves(&leaves), hashes)
+    }
+
+    fn verify(index: usize, hash: &[u8; 32], label: Option<&str>) -> Result<(), VerifyError> {
+        let (tree, _) = tree();
+        let proof = tree.proof(&[index]);
+
+        verify_commitment_opening(
+            &tree.root().unwrap(),
+            proof.proof_hashes(),
+            index,
+            TOTAL_LEAVES,
+            hash,
+            label,
+        )
+    }
+
+    #[test]
+    fn test_verify_commitment_opening() {
+        let (_, hashes) = tree();
+
+        assert!(verify(0, &hashes[0], None).is_ok());
+        assert!(verify(1, &hashes[1], Some("balance")).is_ok());
+    }
+
+    #[test]
+    fn test_verify_commitment_opening_fail_wrong_hash() {
+        let (_, hashes) = tree();
+
+        assert_eq!(
+            verify(0, &hashes[2], None),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+    }
+
+    #[test]
+    fn test_verify_commitment_opening_fail_wrong_label() {
+        let (_, hashes) = tree();
+
+        assert_eq!(
+            verify(1, &hashes[1], None),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+        assert_eq!(
+            verify(1, &hashes[1], Some("other")),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+        assert_eq!(
+            verify(0, &hashes[0], Some("balance")),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+    }
+
+    #[test]
+    fn test_verify_commitment_opening_fail_out_of_range() {
+        let (_, hashes) = tree();
+
+        assert_eq!(
+            verify_commitment_opening(
+                &[0u8; 32],
+                &[],
+                TOTAL_LEAVES,
+                TOTAL_LEAVES,
+                &hashes[0],
+                None
+            ),
+            Err(VerifyError::InvalidMerkleProof)
+        );
+    }
+}
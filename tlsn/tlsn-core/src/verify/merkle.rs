@@ -0,0 +1,49 @@
+use alloc::vec::Vec;
+
+use rs_merkle::{algorithms::Sha256, MerkleProof};
+
+use super::VerifyError;
+
+/// Verifies that the leaves are included in the Merkle tree with the given root.
+///
+/// # Arguments
+///
+/// * `root` - The Merkle root.
+/// * `proof_hashes` - The sibling hashes of the inclusion proof.
+/// * `leaf_indices` - The indices of the leaves in the tree.
+/// * `leaf_hashes` - The hashes of the leaves, in the same order as `leaf_indices`.
+/// * `total_leaves` - The total number of leaves in the tree.
+pub fn verify_merkle_proof(
+    root: &[u8; 32],
+    proof_hashes: &[[u8; 32]],
+    leaf_indices: &[usize],
+    leaf_hashes: &[[u8; 32]],
+    total_leaves: usize,
+) -> Result<(), VerifyError> {
+    if leaf_indices.len() != leaf_hashes.len() {
+        return Err(VerifyError::LeafCountMismatch);
+    }
+
+    // Reject indices outside of the tree before handing them to `rs_merkle`
+    if leaf_indices.iter().any(|index| *index >= total_leaves) {
+        return Err(VerifyError::InvalidMerkleProof);
+    }
+
+    // zip indices and hashes
+    let mut tuples: Vec<(usize, [u8; 32])> = leaf_indices
+        .iter()
+        .copied()
+        .zip(leaf_hashes.iter().copied())
+        .collect();
+
+    // sort by index and unzip
+    tuples.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let (indices, hashes): (Vec<usize>, Vec<[u8; 32]>) = tuples.into_iter().unzip();
+
+    let proof = MerkleProof::<Sha256>::new(proof_hashes.to_vec());
+    if !proof.verify(*root, &indices, &hashes, total_leaves) {
+        return Err(VerifyError::InvalidMerkleProof);
+    }
+
+    Ok(())
+}
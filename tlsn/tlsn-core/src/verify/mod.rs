@@ -0,0 +1,50 @@
+//! Verification primitives which do not depend on `std`.
+//!
+//! This covers the Notary signature, the inclusion proofs of the Merkle tree and the openings of
+//! transcript commitments.
+//!
+//! This module only depends on `core`, `alloc` and crates which support `no_std`, so attestations
+//! can be verified in constrained environments such as embedded devices, zkVM guests or Substrate
+//! runtimes. Build `tlsn-core` with `default-features = false` to use it on its own.
+//!
+//! All inputs are plain bytes. Parsing of the higher level types (eg. [`SessionHeader`](crate::SessionHeader))
+//! is left to the `std` parts of this crate, or to the caller.
+
+mod commitment;
+mod merkle;
+mod signature;
+
+pub use commitment::{commitment_leaf, verify_commitment_opening};
+pub use merkle::verify_merkle_proof;
+pub use signature::{verify_signature, SignatureAlgorithm};
+
+/// An error that can occur during verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VerifyError {
+    /// The public key could not be parsed.
+    InvalidPublicKey,
+    /// The signature could not be parsed.
+    InvalidSignature,
+    /// The signature does not match the message and public key.
+    SignatureMismatch,
+    /// The number of leaf indices does not match the number of leaf hashes.
+    LeafCountMismatch,
+    /// The Merkle proof is not valid for the provided root.
+    InvalidMerkleProof,
+}
+
+impl core::fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidPublicKey => write!(f, "invalid public key"),
+            Self::InvalidSignature => write!(f, "invalid signature encoding"),
+            Self::SignatureMismatch => write!(f, "signature does not match message"),
+            Self::LeafCountMismatch => write!(f, "leaf indices and leaf hashes length mismatch"),
+            Self::InvalidMerkleProof => write!(f, "failed to verify a Merkle proof"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for VerifyError {}
@@ -0,0 +1,148 @@
+use p256::ecdsa::{signature::Verifier, Signature as P256Signature, VerifyingKey};
+
+use super::VerifyError;
+
+/// A signature algorithm supported by the Notary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SignatureAlgorithm {
+    /// ECDSA over NIST P-256.
+    P256,
+    /// ECDSA over secp256k1.
+    Secp256k1,
+    /// Ed25519.
+    Ed25519,
+}
+
+/// Verifies a Notary signature over `msg`.
+///
+/// # Arguments
+///
+/// * `alg` - The signature algorithm.
+/// * `public_key` - The public key of the Notary. SEC1 encoded for ECDSA keys, or the 32 byte
+///   compressed point for Ed25519 keys.
+/// * `msg` - The signed message.
+/// * `sig` - The fixed-size encoded signature.
+pub fn verify_signature(
+    alg: SignatureAlgorithm,
+    public_key: &[u8],
+    msg: &[u8],
+    sig: &[u8],
+) -> Result<(), VerifyError> {
+    match alg {
+        SignatureAlgorithm::P256 => {
+            let key = VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|_| VerifyError::InvalidPublicKey)?;
+            let sig = P256Signature::from_slice(sig).map_err(|_| VerifyError::InvalidSignature)?;
+
+            key.verify(msg, &sig)
+                .map_err(|_| VerifyError::SignatureMismatch)
+        }
+        SignatureAlgorithm::Secp256k1 => {
+            let key = k256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)
+                .map_err(|_| VerifyError::InvalidPublicKey)?;
+            let sig = k256::ecdsa::Signature::from_slice(sig)
+                .map_err(|_| VerifyError::InvalidSignature)?;
+
+            key.verify(msg, &sig)
+                .map_err(|_| VerifyError::SignatureMismatch)
+        }
+        SignatureAlgorithm::Ed25519 => {
+            let public_key: &[u8; 32] = public_key
+                .try_into()
+                .map_err(|_| VerifyError::InvalidPublicKey)?;
+            let key = ed25519_dalek::VerifyingKey::from_bytes(public_key)
+                .map_err(|_| VerifyError::InvalidPublicKey)?;
+            let sig = ed25519_dalek::Signature::from_slice(sig)
+                .map_err(|_| VerifyError::InvalidSignature)?;
+
+            key.verify(msg, &sig)
+                .map_err(|_| VerifyError::SignatureMismatch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    fn key_and_sig(msg: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let signing_key = SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+        let sig: P256Signature = signing_key.sign(msg);
+        let public_key = signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec();
+
+        (public_key, sig.to_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let (public_key, sig) = key_and_sig(b"header");
+
+        assert!(verify_signature(SignatureAlgorithm::P256, &public_key, b"header", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_verify_signature_fail_wrong_msg() {
+        let (public_key, sig) = key_and_sig(b"header");
+
+        assert_eq!(
+            verify_signature(SignatureAlgorithm::P256, &public_key, b"other", &sig),
+            Err(VerifyError::SignatureMismatch)
+        );
+    }
+
+    #[test]
+    fn test_verify_signature_secp256k1() {
+        let signing_key = k256::ecdsa::SigningKey::from_bytes(&[1u8; 32].into()).unwrap();
+        let sig: k256::ecdsa::Signature = signing_key.sign(b"header");
+        let public_key = signing_key.verifying_key().to_encoded_point(true);
+
+        assert!(verify_signature(
+            SignatureAlgorithm::Secp256k1,
+            public_key.as_bytes(),
+            b"header",
+            &sig.to_bytes()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_verify_signature_ed25519() {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[1u8; 32]);
+        let sig: ed25519_dalek::Signature = signing_key.sign(b"header");
+        let public_key = signing_key.verifying_key().to_bytes();
+
+        assert!(verify_signature(
+            SignatureAlgorithm::Ed25519,
+            &public_key,
+            b"header",
+            &sig.to_bytes()
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_verify_signature_fail_wrong_algorithm() {
+        let (public_key, sig) = key_and_sig(b"header");
+
+        assert!(
+            verify_signature(SignatureAlgorithm::Secp256k1, &public_key, b"header", &sig).is_err()
+        );
+    }
+
+    #[test]
+    fn test_verify_signature_fail_bad_key() {
+        let (_, sig) = key_and_sig(b"header");
+
+        assert_eq!(
+            verify_signature(SignatureAlgorithm::P256, &[0u8; 33], b"header", &sig),
+            Err(VerifyError::InvalidPublicKey)
+        );
+    }
+}
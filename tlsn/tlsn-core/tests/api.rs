@@ -20,12 +20,15 @@ use tls_core::{
 use mpz_core::{commit::HashCommit, serialize::CanonicalSerialize};
 
 use tlsn_core::{
-    commitment::TranscriptCommitmentBuilder,
+    commitment::{CommitmentKind, TranscriptCommitmentBuilder},
     fixtures,
     msg::SignedSessionHeader,
-    proof::{SessionProof, SubstringsProof},
-    HandshakeSummary, NotarizedSession, ServerName, SessionData, SessionHeader, Signature,
-    Transcript,
+    proof::{
+        default_cert_verifier, SessionProof, SubstringsProof, SubstringsProofBuilderError,
+        ValidityWindow,
+    },
+    Direction, HandshakeSummary, NotarizedSession, NotaryPublicKey, ServerName, SessionData,
+    SessionHeader, Signature, Transcript,
 };
 
 #[test]
@@ -170,7 +173,12 @@ fn test_api() {
 
     // The Verifier does:
     session_proof
-        .verify_with_default_cert_verifier(notary_pubkey)
+        .verify_with_validity(
+            notary_pubkey,
+            &default_cert_verifier(),
+            &ValidityWindow::unbounded(),
+            time + 60,
+        )
         .unwrap();
 
     let SessionProof {
@@ -190,3 +198,212 @@ fn test_api() {
     assert_eq!(&sent.data()[range1], b"se".as_slice());
     assert_eq!(&recv.data()[range2], b"ec".as_slice());
 }
+
+#[test]
+/// Tests that a session without commitments yields a handshake-only attestation
+fn test_handshake_only() {
+    let commitments = TranscriptCommitmentBuilder::new(fixtures::encoding_provider(b"", b""), 0, 0)
+        .build()
+        .unwrap();
+
+    assert!(commitments.is_handshake_only());
+
+    let header = fixtures::session_header(commitments.merkle_root(), 0, 0);
+
+    assert!(header.is_handshake_only());
+}
+
+#[test]
+/// Tests that nullifiers are stable and separated by scope
+fn test_nullifier() {
+    let commitments = TranscriptCommitmentBuilder::new(fixtures::encoding_provider(b"", b""), 0, 0)
+        .build()
+        .unwrap();
+    let header = fixtures::session_header(commitments.merkle_root(), 0, 0);
+    let notary_key = NotaryPublicKey::from(PublicKey::from(
+        *fixtures::notary_signing_key().verifying_key(),
+    ));
+
+    let nullifier = header.nullifier(&notary_key, b"airdrop");
+
+    assert_eq!(nullifier, header.nullifier(&notary_key, b"airdrop"));
+    assert_ne!(nullifier, header.nullifier(&notary_key, b"other airdrop"));
+}
+
+#[test]
+/// Tests that forgotten commitments can no longer be opened
+fn test_forget_commitment() {
+    let data_sent = b"sent data";
+    let data_recv = b"received data";
+
+    let mut builder = commitment_builder(data_sent, data_recv);
+    let id_1 = builder.commit_recv(&(0..4)).unwrap();
+    let id_2 = builder.commit_recv(&(4..8)).unwrap();
+
+    let mut session_data = session_data(data_sent, data_recv, builder);
+
+    assert_eq!(
+        session_data
+            .commitments()
+            .ids_overlapping(Direction::Received, &(2..6).into()),
+        vec![id_1, id_2]
+    );
+
+    assert!(session_data.forget_commitment(&id_1));
+    assert!(!session_data.forget_commitment(&id_1));
+
+    assert_eq!(
+        session_data
+            .commitments()
+            .ids_overlapping(Direction::Received, &(2..6).into()),
+        vec![id_2]
+    );
+    assert!(session_data
+        .build_substrings_proof()
+        .reveal_by_id(id_1)
+        .is_err());
+}
+
+#[test]
+/// Tests that the reveal set can be chosen after notarization, and that a redaction splitting a
+/// multi-byte character is rejected
+fn test_reveal_after_notarization() {
+    let data_sent = "sent data".as_bytes();
+    let data_recv = "préfix données".as_bytes();
+
+    let mut builder = commitment_builder(data_sent, data_recv);
+    let valid_id = builder.commit_recv(&(0..7)).unwrap();
+    // "é" is encoded in bytes 2..4
+    let split_id = builder.commit_recv(&(0..3)).unwrap();
+
+    let session_data = session_data(data_sent, data_recv, builder);
+
+    // The session data may be moved elsewhere before deciding what to reveal.
+    let session_data: SessionData =
+        bincode::deserialize(&bincode::serialize(&session_data).unwrap()).unwrap();
+
+    let mut proof_builder = session_data.build_substrings_proof();
+    proof_builder.reveal_by_id(valid_id).unwrap();
+    assert!(matches!(
+        proof_builder.reveal_by_id(split_id),
+        Err(SubstringsProofBuilderError::SplitsCharacter(id, 3)) if id == split_id
+    ));
+}
+
+#[test]
+/// Tests that a transcript mixing binary data and UTF-8 text is only checked for split characters
+/// around the boundaries of the revealed ranges
+fn test_reveal_mixed_binary_and_utf8() {
+    let data_sent = b"sent data";
+    // Invalid UTF-8, followed by "é" in bytes 2..4 and a stray continuation and leading byte
+    let data_recv = b"\xff\x00\xc3\xa9\x80\xc3";
+
+    let mut builder = commitment_builder(data_sent, data_recv);
+    let text_id = builder.commit_recv(&(1..4)).unwrap();
+    let binary_id = builder.commit_recv(&(4..5)).unwrap();
+    let split_id = builder.commit_recv(&(0..3)).unwrap();
+
+    let session_data = session_data(data_sent, data_recv, builder);
+
+    let mut proof_builder = session_data.build_substrings_proof();
+    proof_builder.reveal_by_id(text_id).unwrap();
+    proof_builder.reveal_by_id(binary_id).unwrap();
+    assert!(matches!(
+        proof_builder.reveal_by_id(split_id),
+        Err(SubstringsProofBuilderError::SplitsCharacter(id, 3)) if id == split_id
+    ));
+}
+
+#[test]
+/// Tests that labeled commitments can be revealed and verified by name
+fn test_labeled_commitments() {
+    let data_sent = b"GET /balance HTTP/1.1";
+    let data_recv = b"HTTP/1.1 200 OK\r\n\r\n{\"balance\":1234}";
+
+    let mut builder = commitment_builder(data_sent, data_recv);
+    builder.commit_sent(&(0..3)).unwrap();
+    builder
+        .commit_labeled(&(30..34), Direction::Received, "balance")
+        .unwrap();
+    assert!(builder
+        .commit_labeled(&(0..4), Direction::Received, "balance")
+        .is_err());
+
+    let session_data = session_data(data_sent, data_recv, builder);
+    let header = fixtures::session_header(
+        session_data.commitments().merkle_root(),
+        data_sent.len(),
+        data_recv.len(),
+    );
+
+    let mut proof_builder = session_data.build_substrings_proof();
+    proof_builder.reveal_label("balance").unwrap();
+    assert!(proof_builder.reveal_label("account_id").is_err());
+    let proof = proof_builder.build().unwrap();
+
+    let labels = proof
+        .labels()
+        .map(|(label, direction, ranges)| (label.to_string(), direction, ranges.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        labels,
+        vec![("balance".to_string(), Direction::Received, (30..34).into())]
+    );
+
+    let (_, recv) = proof.verify(&header).unwrap();
+    assert_eq!(&recv.data()[30..34], b"1234");
+
+    // Labeled commitments can still be revealed by their ranges
+    let mut proof_builder = session_data.build_substrings_proof();
+    proof_builder
+        .reveal_recv(&(30..34), CommitmentKind::Blake3)
+        .unwrap();
+    let proof = proof_builder.build().unwrap();
+    assert_eq!(proof.labels().count(), 1);
+    assert!(proof.verify(&header).is_ok());
+}
+
+#[cfg(feature = "deterministic")]
+#[test]
+/// Tests that commitments with a nonce seed are reproducible
+fn test_deterministic_commitments() {
+    let data_sent = b"sent data";
+    let data_recv = b"received data";
+
+    let build = |seed: [u8; 32]| {
+        let mut builder = commitment_builder(data_sent, data_recv);
+        builder.set_nonce_seed(seed);
+        builder.commit_sent(&(0..4)).unwrap();
+        builder.commit_recv(&(0..4)).unwrap();
+        builder.build().unwrap().merkle_root()
+    };
+
+    assert_eq!(build([0u8; 32]), build([0u8; 32]));
+    assert_ne!(build([0u8; 32]), build([1u8; 32]));
+}
+
+/// Returns a commitment builder for a session with the provided transcripts
+fn commitment_builder(data_sent: &[u8], data_recv: &[u8]) -> TranscriptCommitmentBuilder {
+    TranscriptCommitmentBuilder::new(
+        fixtures::encoding_provider(data_sent, data_recv),
+        data_sent.len(),
+        data_recv.len(),
+    )
+}
+
+/// Returns the data of a session with the provided transcripts and commitments
+fn session_data(
+    data_sent: &[u8],
+    data_recv: &[u8],
+    builder: TranscriptCommitmentBuilder,
+) -> SessionData {
+    let (hs_decommitment, _) = fixtures::handshake_data().hash_commit();
+
+    SessionData::new(
+        ServerName::Dns("tlsnotary.org".to_string()),
+        hs_decommitment,
+        Transcript::new(data_sent.to_vec()),
+        Transcript::new(data_recv.to_vec()),
+        builder.build().unwrap(),
+    )
+}
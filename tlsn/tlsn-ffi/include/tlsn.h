@@ -0,0 +1,50 @@
+/* C ABI for verifying TLSNotary proofs. */
+
+#ifndef TLSN_H
+#define TLSN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Every check passed and the proof satisfies the policy. */
+#define TLSN_VALID 0
+/* A check failed or the proof does not satisfy the policy, see the report for details. */
+#define TLSN_INVALID 1
+/* A pointer argument was null or a string was not valid UTF-8. */
+#define TLSN_ERR_ARGUMENT -1
+/* The proof could not be deserialized. */
+#define TLSN_ERR_PROOF -2
+/* The public key could not be parsed. */
+#define TLSN_ERR_PUBKEY -3
+/* The policy could not be parsed. */
+#define TLSN_ERR_POLICY -4
+/* Verification panicked, which is a bug in this library. */
+#define TLSN_ERR_PANIC -5
+
+/*
+ * Verifies a JSON serialized TLSNotary proof against the PEM encoded public key of the Notary.
+ *
+ * `policy_json` may be NULL, otherwise it is a JSON object with the optional fields
+ * `server_name` and `validity`.
+ *
+ * Returns TLSN_VALID or TLSN_INVALID and writes a JSON report to `out_report`, which must be
+ * freed with `tlsn_report_free`. On any other return value no report is written.
+ */
+int tlsn_verify(const uint8_t *proof_bytes,
+                size_t len,
+                const char *pubkey,
+                const char *policy_json,
+                char **out_report);
+
+/* Frees a report written by `tlsn_verify`. */
+void tlsn_report_free(char *report);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TLSN_H */
@@ -0,0 +1,310 @@
+//! A C ABI for verifying TLSNotary proofs.
+//!
+//! The ABI consists of two functions, see `include/tlsn.h`:
+//!
+//! - [`tlsn_verify`] verifies a JSON serialized [`TlsProof`] against a Notary public key and a
+//!   policy, and writes a JSON report.
+//! - [`tlsn_report_free`] frees a report.
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+
+use std::{
+    ffi::{c_char, c_int, CStr, CString},
+    panic, ptr, slice,
+};
+
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use tlsn_core::{
+    proof::{default_cert_verifier, TlsProof, ValidityWindow, VerificationReport},
+    NotaryPublicKey, RedactedTranscript,
+};
+
+/// Every check passed and the proof satisfies the policy.
+pub const TLSN_VALID: c_int = 0;
+/// A check failed or the proof does not satisfy the policy, see the report for details.
+pub const TLSN_INVALID: c_int = 1;
+/// A pointer argument was null or a string was not valid UTF-8.
+pub const TLSN_ERR_ARGUMENT: c_int = -1;
+/// The proof could not be deserialized.
+pub const TLSN_ERR_PROOF: c_int = -2;
+/// The public key could not be parsed.
+pub const TLSN_ERR_PUBKEY: c_int = -3;
+/// The policy could not be parsed.
+pub const TLSN_ERR_POLICY: c_int = -4;
+/// Verification panicked, which is a bug in this library.
+pub const TLSN_ERR_PANIC: c_int = -5;
+
+/// A verification policy, in addition to the checks of the proof itself.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Policy {
+    /// The expected server name.
+    server_name: Option<String>,
+    /// The window in which the attestation is accepted, [`ValidityWindow::default`] if absent.
+    validity: Option<ValidityWindow>,
+}
+
+/// The report written by [`tlsn_verify`].
+#[derive(Debug, Serialize)]
+struct Report {
+    /// Whether the proof is valid and satisfies the policy.
+    valid: bool,
+    /// The result of every check performed.
+    report: VerificationReport,
+    /// The sent transcript with redacted bytes replaced by `X`, if the proof is valid.
+    sent: Option<String>,
+    /// The received transcript with redacted bytes replaced by `X`, if the proof is valid.
+    recv: Option<String>,
+}
+
+/// Verifies a TLSNotary proof.
+///
+/// Returns [`TLSN_VALID`] or [`TLSN_INVALID`] and writes a NUL-terminated JSON report to
+/// `out_report`, which must be freed with [`tlsn_report_free`]. On any other return value no
+/// report is written.
+///
+/// # Arguments
+///
+/// * `proof_bytes` - The JSON serialized [`TlsProof`].
+/// * `len` - The length of `proof_bytes`.
+/// * `pubkey` - The PEM encoded public key of the Notary. P-256, secp256k1 and Ed25519 keys are
+///   supported.
+/// * `policy_json` - The policy, e.g. `{"server_name": "example.com"}`. May be null, in which case
+///   the default policy is used.
+/// * `out_report` - Where to write the report.
+///
+/// # Safety
+///
+/// `proof_bytes` must be valid for reads of `len` bytes, `pubkey` and `policy_json`, if not null,
+/// must point to NUL-terminated strings and `out_report` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn tlsn_verify(
+    proof_bytes: *const u8,
+    len: usize,
+    pubkey: *const c_char,
+    policy_json: *const c_char,
+    out_report: *mut *mut c_char,
+) -> c_int {
+    if proof_bytes.is_null() || pubkey.is_null() || out_report.is_null() {
+        return TLSN_ERR_ARGUMENT;
+    }
+    *out_report = ptr::null_mut();
+
+    let proof = slice::from_raw_parts(proof_bytes, len);
+    let Ok(pubkey) = CStr::from_ptr(pubkey).to_str() else {
+        return TLSN_ERR_ARGUMENT;
+    };
+    let policy = if policy_json.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(policy_json).to_str() {
+            Ok(policy) => Some(policy),
+            Err(_) => return TLSN_ERR_ARGUMENT,
+        }
+    };
+
+    // The proof is untrusted input, a panic while verifying it must not unwind into the caller
+    let result = panic::catch_unwind(|| {
+        verify(proof, pubkey, policy).map(|report| {
+            let json = serde_json::to_string(&report).expect("report is serializable");
+            let json = CString::new(json).expect("JSON does not contain NUL bytes");
+
+            (json, report.valid)
+        })
+    });
+
+    match result {
+        Ok(Ok((json, valid))) => {
+            *out_report = json.into_raw();
+
+            if valid {
+                TLSN_VALID
+            } else {
+                TLSN_INVALID
+            }
+        }
+        Ok(Err(code)) => code,
+        Err(_) => TLSN_ERR_PANIC,
+    }
+}
+
+/// Frees a report written by [`tlsn_verify`].
+///
+/// # Safety
+///
+/// `report` must be null or a report returned by [`tlsn_verify`] which was not freed before.
+#[no_mangle]
+pub unsafe extern "C" fn tlsn_report_free(report: *mut c_char) {
+    if !report.is_null() {
+        drop(CString::from_raw(report));
+    }
+}
+
+fn verify(proof: &[u8], pubkey: &str, policy: Option<&str>) -> Result<Report, c_int> {
+    let proof: TlsProof = serde_json::from_slice(proof).map_err(|_| TLSN_ERR_PROOF)?;
+    let pubkey = parse_public_key(pubkey).ok_or(TLSN_ERR_PUBKEY)?;
+    let policy: Policy = match policy {
+        Some(policy) => serde_json::from_str(policy).map_err(|_| TLSN_ERR_POLICY)?,
+        None => Policy::default(),
+    };
+
+    let server_name = proof.session.session_info.server_name.as_str().to_string();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is after the UNIX epoch")
+        .as_secs();
+
+    let (mut report, transcripts) = proof.verify_with_validity_report(
+        pubkey,
+        &default_cert_verifier(),
+        &policy.validity.unwrap_or_default(),
+        now,
+    );
+
+    if let Some(expected) = &policy.server_name {
+        report.record_policy(
+            "server_name",
+            if &server_name == expected {
+                Ok(())
+            } else {
+                Err(format!("expected {expected}, got {server_name}"))
+            },
+        );
+    }
+
+    let valid = report.is_valid();
+    let (sent, recv) = match transcripts {
+        Some((sent, recv)) if valid => (Some(redacted_string(sent)), Some(redacted_string(recv))),
+        _ => (None, None),
+    };
+
+    Ok(Report {
+        valid,
+        report,
+        sent,
+        recv,
+    })
+}
+
+/// Parses a PEM encoded public key of any supported algorithm.
+fn parse_public_key(pem: &str) -> Option<NotaryPublicKey> {
+    use ed25519_dalek::pkcs8::DecodePublicKey as _;
+    use p256::pkcs8::DecodePublicKey;
+
+    if let Ok(key) = p256::PublicKey::from_public_key_pem(pem) {
+        return Some(NotaryPublicKey::P256(key));
+    }
+    if let Ok(key) = k256::PublicKey::from_public_key_pem(pem) {
+        return Some(NotaryPublicKey::Secp256k1(key));
+    }
+    ed25519_dalek::VerifyingKey::from_public_key_pem(pem)
+        .ok()
+        .map(NotaryPublicKey::Ed25519)
+}
+
+fn redacted_string(mut transcript: RedactedTranscript) -> String {
+    transcript.set_redacted(b'X');
+    String::from_utf8_lossy(transcript.data()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use p256::pkcs8::{EncodePublicKey, LineEnding};
+    use tlsn_core::fixtures;
+
+    use super::*;
+
+    fn notary_pem() -> CString {
+        let key = p256::PublicKey::from(fixtures::notary_signing_key().verifying_key());
+        CString::new(key.to_public_key_pem(LineEnding::LF).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_public_key() {
+        assert!(matches!(
+            parse_public_key(notary_pem().to_str().unwrap()),
+            Some(NotaryPublicKey::P256(_))
+        ));
+        assert!(parse_public_key("not a key").is_none());
+    }
+
+    #[test]
+    fn test_verify_errors() {
+        let pem = notary_pem();
+        let mut report = ptr::null_mut();
+
+        let code = unsafe { tlsn_verify(ptr::null(), 0, pem.as_ptr(), ptr::null(), &mut report) };
+        assert_eq!(code, TLSN_ERR_ARGUMENT);
+
+        let proof = b"{}";
+        let code = unsafe {
+            tlsn_verify(
+                proof.as_ptr(),
+                proof.len(),
+                pem.as_ptr(),
+                ptr::null(),
+                &mut report,
+            )
+        };
+        assert_eq!(code, TLSN_ERR_PROOF);
+        assert!(report.is_null());
+    }
+
+    #[test]
+    fn test_verify_valid() {
+        let pem = notary_pem();
+        let proof = serde_json::to_vec(&fixtures::tls_proof(b"GET / HTTP/1.1", b"HTTP/1.1 200 OK"))
+            .unwrap();
+        // The fixture session is older than the default validity window
+        let policy = CString::new(
+            r#"{"server_name": "tlsnotary.org", "validity": {"not_before": null, "max_age": null, "max_clock_skew": 300}}"#,
+        )
+        .unwrap();
+        let mut report = ptr::null_mut();
+
+        let code = unsafe {
+            tlsn_verify(
+                proof.as_ptr(),
+                proof.len(),
+                pem.as_ptr(),
+                policy.as_ptr(),
+                &mut report,
+            )
+        };
+        assert_eq!(code, TLSN_VALID);
+
+        let json: serde_json::Value =
+            serde_json::from_str(unsafe { CStr::from_ptr(report) }.to_str().unwrap()).unwrap();
+        assert_eq!(json["valid"], true);
+        assert_eq!(json["sent"], "GET / HTTP/1.1");
+        assert_eq!(json["recv"], "HTTP/1.1 200 OK");
+        unsafe { tlsn_report_free(report) };
+
+        // With the default policy the same proof is too old
+        let mut report = ptr::null_mut();
+        let code = unsafe {
+            tlsn_verify(
+                proof.as_ptr(),
+                proof.len(),
+                pem.as_ptr(),
+                ptr::null(),
+                &mut report,
+            )
+        };
+        assert_eq!(code, TLSN_INVALID);
+        assert!(!report.is_null());
+        unsafe { tlsn_report_free(report) };
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy: Policy = serde_json::from_str(r#"{"server_name": "tlsnotary.org"}"#).unwrap();
+        assert_eq!(policy.server_name.as_deref(), Some("tlsnotary.org"));
+        assert!(policy.validity.is_none());
+
+        assert!(serde_json::from_str::<Policy>(r#"{"unknown": 1}"#).is_err());
+    }
+}
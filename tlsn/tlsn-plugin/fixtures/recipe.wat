@@ -0,0 +1,40 @@
+;; A minimal recipe used by the tests. `build_request` returns a fixed request and
+;; `select_disclosure` reads the sent transcript through `tlsn_transcript` and discloses the
+;; request line and the response status.
+(module
+  (import "extism:host/env" "alloc" (func $alloc (param i64) (result i64)))
+  (import "extism:host/env" "length" (func $length (param i64) (result i64)))
+  (import "extism:host/env" "store_u8" (func $store_u8 (param i64 i32)))
+  (import "extism:host/env" "output_set" (func $output_set (param i64 i64)))
+  (import "extism:host/user" "tlsn_transcript" (func $tlsn_transcript (param i64) (result i64)))
+
+  (memory 1)
+  (data (i32.const 0) "{\"method\":\"GET\",\"url\":\"https://example.com/api\",\"headers\":[[\"Accept\",\"application/json\"]]}")
+  (data (i32.const 256) "{\"sent\":[{\"start\":0,\"end\":27}],\"recv\":[{\"start\":9,\"end\":15}]}")
+  (data (i32.const 512) "\"sent\"")
+
+  ;; Copies `len` bytes at `ptr` into a new block of host memory and returns its offset.
+  (func $store (param $ptr i32) (param $len i32) (result i64)
+    (local $offset i64)
+    (local $i i32)
+    (local.set $offset (call $alloc (i64.extend_i32_u (local.get $len))))
+    (block $done
+      (loop $copy
+        (br_if $done (i32.ge_u (local.get $i) (local.get $len)))
+        (call $store_u8
+          (i64.add (local.get $offset) (i64.extend_i32_u (local.get $i)))
+          (i32.load8_u (i32.add (local.get $ptr) (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $copy)))
+    (local.get $offset))
+
+  (func (export "build_request") (result i32)
+    (call $output_set (call $store (i32.const 0) (i32.const 90)) (i64.const 90))
+    (i32.const 0))
+
+  (func (export "select_disclosure") (result i32)
+    ;; Fails if the host does not give access to the sent transcript
+    (if (i64.eqz (call $length (call $tlsn_transcript (call $store (i32.const 512) (i32.const 6)))))
+      (then (return (i32.const 1))))
+    (call $output_set (call $store (i32.const 256) (i32.const 61)) (i64.const 61))
+    (i32.const 0)))
@@ -0,0 +1,433 @@
+//! Host for sandboxed WASM recipe plugins.
+//!
+//! A recipe implements the site specific parts of a notarization: which request to send to the
+//! server and which parts of the transcript to disclose. Shipping recipes as [Extism](https://extism.org)
+//! plugins lets them be distributed and updated independently of the prover.
+//!
+//! # Plugin interface
+//!
+//! A recipe exports two functions, both of which take the recipe parameters as a JSON object of
+//! strings as input:
+//!
+//! - `build_request` returns the request to send as a JSON [`RecipeRequest`].
+//! - `select_disclosure` returns the ranges of the transcript to disclose as a JSON
+//!   [`Disclosure`]. Everything else is redacted.
+//!
+//! While `select_disclosure` runs, the plugin can call these host functions:
+//!
+//! - `tlsn_transcript(direction)` returns the bytes sent to (`"sent"`) or received from
+//!   (`"recv"`) the server.
+//! - `tlsn_find(query)` returns the ranges of every occurrence of `query.needle` in the
+//!   transcript of `query.direction`.
+//!
+//! # Sandbox
+//!
+//! Plugins run without WASI and without network access, and every call is bounded by the
+//! [`RecipeLimits`].
+//!
+//! The sandbox does not keep the transcript from the plugin: `select_disclosure` reads the
+//! request, including any credentials in it, and decides what is disclosed. Ranges the host
+//! marks as secret are never disclosed, see [`Recipe::select_disclosure`], and everything else
+//! the plugin selects should be shown to the user before it is committed to.
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+use std::{collections::BTreeMap, ops::Range, time::Duration};
+
+use extism::{convert::Json, host_fn, Manifest, Plugin, PluginBuilder, UserData, Wasm, PTR};
+use serde::{Deserialize, Serialize};
+
+/// Default for the maximum duration of a plugin call (5 seconds).
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Default for the maximum memory of a plugin, in 64KiB WASM pages (16MiB).
+pub const DEFAULT_MAX_MEMORY_PAGES: u32 = 256;
+
+/// The request a recipe asks the prover to send.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecipeRequest {
+    /// The request method, e.g. `GET`.
+    pub method: String,
+    /// The URL to request.
+    pub url: String,
+    /// The request headers as name and value pairs.
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    /// The request body.
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+/// The ranges of the transcript a recipe discloses.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Disclosure {
+    /// The ranges of the sent data to disclose.
+    #[serde(default)]
+    pub sent: Vec<Range<usize>>,
+    /// The ranges of the received data to disclose.
+    #[serde(default)]
+    pub recv: Vec<Range<usize>>,
+}
+
+/// The resource limits of a plugin.
+#[derive(Debug, Clone)]
+pub struct RecipeLimits {
+    /// The maximum duration of a plugin call.
+    pub timeout: Duration,
+    /// The maximum memory of a plugin, in 64KiB WASM pages.
+    pub max_memory_pages: u32,
+}
+
+impl Default for RecipeLimits {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            max_memory_pages: DEFAULT_MAX_MEMORY_PAGES,
+        }
+    }
+}
+
+/// An error that can occur while running a recipe.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum RecipeError {
+    #[error("failed to load plugin: {0}")]
+    Load(String),
+    #[error("plugin does not export `{0}`")]
+    MissingExport(&'static str),
+    #[error("plugin call to `{name}` failed: {error}")]
+    Call { name: &'static str, error: String },
+    #[error("plugin returned an invalid disclosure: {0}")]
+    InvalidDisclosure(String),
+    #[error("plugin disclosed the secret sent range {0:?}")]
+    SecretDisclosed(Range<usize>),
+}
+
+/// The transcript the host functions give access to.
+#[derive(Debug, Default)]
+struct Transcript {
+    sent: Vec<u8>,
+    recv: Vec<u8>,
+}
+
+/// The direction of a transcript, as passed by plugins.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Direction {
+    Sent,
+    Recv,
+}
+
+/// The argument of `tlsn_find`.
+#[derive(Debug, Deserialize)]
+struct FindQuery {
+    direction: Direction,
+    needle: String,
+}
+
+impl Transcript {
+    fn get(&self, direction: Direction) -> &[u8] {
+        match direction {
+            Direction::Sent => &self.sent,
+            Direction::Recv => &self.recv,
+        }
+    }
+}
+
+host_fn!(tlsn_transcript(user_data: Transcript; direction: Json<Direction>) -> Vec<u8> {
+    let transcript = user_data.get()?;
+    let transcript = transcript.lock().unwrap();
+
+    Ok(transcript.get(direction.0).to_vec())
+});
+
+host_fn!(tlsn_find(user_data: Transcript; query: Json<FindQuery>) -> Json<Vec<Range<usize>>> {
+    let transcript = user_data.get()?;
+    let transcript = transcript.lock().unwrap();
+
+    Ok(Json(find_all(transcript.get(query.0.direction), query.0.needle.as_bytes())))
+});
+
+/// Returns the ranges of every non-overlapping occurrence of `needle` in `data`.
+fn find_all(data: &[u8], needle: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    if needle.is_empty() {
+        return ranges;
+    }
+
+    let mut pos = 0;
+    while let Some(offset) = data[pos..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+    {
+        let start = pos + offset;
+        ranges.push(start..start + needle.len());
+        pos = start + needle.len();
+    }
+
+    ranges
+}
+
+/// A loaded recipe plugin.
+pub struct Recipe {
+    plugin: Plugin,
+    transcript: UserData<Transcript>,
+}
+
+impl std::fmt::Debug for Recipe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recipe").finish_non_exhaustive()
+    }
+}
+
+impl Recipe {
+    /// Loads a recipe from a WASM module with the default limits.
+    pub fn new(wasm: &[u8]) -> Result<Self, RecipeError> {
+        Self::with_limits(wasm, RecipeLimits::default())
+    }
+
+    /// Loads a recipe from a WASM module.
+    ///
+    /// # Arguments
+    ///
+    /// * `wasm` - The WASM module of the plugin.
+    /// * `limits` - The resource limits of the plugin.
+    pub fn with_limits(wasm: &[u8], limits: RecipeLimits) -> Result<Self, RecipeError> {
+        let manifest = Manifest::new([Wasm::data(wasm.to_vec())])
+            .with_timeout(limits.timeout)
+            .with_memory_max(limits.max_memory_pages);
+
+        let transcript = UserData::new(Transcript::default());
+        let plugin = PluginBuilder::new(manifest)
+            .with_wasi(false)
+            .with_function(
+                "tlsn_transcript",
+                [PTR],
+                [PTR],
+                transcript.clone(),
+                tlsn_transcript,
+            )
+            .with_function("tlsn_find", [PTR], [PTR], transcript.clone(), tlsn_find)
+            .build()
+            .map_err(|e| RecipeError::Load(format!("{e:#}")))?;
+
+        for name in ["build_request", "select_disclosure"] {
+            if !plugin.function_exists(name) {
+                return Err(RecipeError::MissingExport(name));
+            }
+        }
+
+        Ok(Self { plugin, transcript })
+    }
+
+    /// Returns the request to send to the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The recipe parameters, e.g. the account to notarize.
+    pub fn build_request(
+        &mut self,
+        params: &BTreeMap<String, String>,
+    ) -> Result<RecipeRequest, RecipeError> {
+        self.call("build_request", params)
+    }
+
+    /// Returns the ranges of the transcript to disclose.
+    ///
+    /// Fails with [`RecipeError::SecretDisclosed`] if a disclosed sent range overlaps a secret
+    /// range.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The recipe parameters.
+    /// * `sent` - The data sent to the server.
+    /// * `recv` - The data received from the server.
+    /// * `secret_sent` - The ranges of the sent data which must not be disclosed, e.g. the
+    ///   values of credential headers.
+    pub fn select_disclosure(
+        &mut self,
+        params: &BTreeMap<String, String>,
+        sent: &[u8],
+        recv: &[u8],
+        secret_sent: &[Range<usize>],
+    ) -> Result<Disclosure, RecipeError> {
+        self.set_transcript(sent.to_vec(), recv.to_vec())?;
+        let disclosure = self.call("select_disclosure", params);
+        self.set_transcript(Vec::new(), Vec::new())?;
+
+        let disclosure: Disclosure = disclosure?;
+        check_ranges("sent", &disclosure.sent, sent.len())?;
+        check_ranges("recv", &disclosure.recv, recv.len())?;
+        check_secrets(&disclosure.sent, secret_sent)?;
+
+        Ok(disclosure)
+    }
+
+    fn call<T: serde::de::DeserializeOwned>(
+        &mut self,
+        name: &'static str,
+        params: &BTreeMap<String, String>,
+    ) -> Result<T, RecipeError> {
+        self.plugin
+            .call::<Json<BTreeMap<String, String>>, Json<T>>(name, Json(params.clone()))
+            .map(|output| output.0)
+            .map_err(|e| RecipeError::Call {
+                name,
+                error: format!("{e:#}"),
+            })
+    }
+
+    fn set_transcript(&self, sent: Vec<u8>, recv: Vec<u8>) -> Result<(), RecipeError> {
+        let transcript = self.transcript.get().map_err(|e| RecipeError::Call {
+            name: "select_disclosure",
+            error: format!("{e:#}"),
+        })?;
+        *transcript.lock().unwrap() = Transcript { sent, recv };
+
+        Ok(())
+    }
+}
+
+/// Checks that the ranges are non-empty and within a transcript of the given length.
+fn check_ranges(direction: &str, ranges: &[Range<usize>], len: usize) -> Result<(), RecipeError> {
+    match ranges
+        .iter()
+        .find(|range| range.start >= range.end || range.end > len)
+    {
+        Some(range) => Err(RecipeError::InvalidDisclosure(format!(
+            "{direction} range {range:?} is empty or out of bounds of {len} bytes"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Checks that none of the ranges overlaps a secret range.
+fn check_secrets(ranges: &[Range<usize>], secrets: &[Range<usize>]) -> Result<(), RecipeError> {
+    match secrets.iter().find(|secret| {
+        ranges
+            .iter()
+            .any(|range| range.start < secret.end && secret.start < range.end)
+    }) {
+        Some(secret) => Err(RecipeError::SecretDisclosed(secret.clone())),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SENT: &[u8] = b"GET /?token=secret HTTP/1.1\r\n\r\n";
+    const RECV: &[u8] = b"HTTP/1.1 200 OK";
+
+    fn fixture_recipe() -> Recipe {
+        let wasm = wat::parse_str(include_str!("../fixtures/recipe.wat")).unwrap();
+
+        Recipe::new(&wasm).unwrap()
+    }
+
+    #[test]
+    fn test_find_all() {
+        let data = b"token=abc; token=def";
+
+        assert_eq!(find_all(data, b"token="), vec![0..6, 11..17]);
+        assert_eq!(find_all(data, b"aaa"), vec![]);
+        assert_eq!(find_all(b"aaaa", b"aa"), vec![0..2, 2..4]);
+        assert_eq!(find_all(data, b""), vec![]);
+    }
+
+    #[test]
+    fn test_check_ranges() {
+        assert!(check_ranges("recv", &[0..4, 6..10], 10).is_ok());
+        assert!(matches!(
+            check_ranges("recv", &[0..11], 10),
+            Err(RecipeError::InvalidDisclosure(_))
+        ));
+        assert!(matches!(
+            check_ranges("sent", &[4..4], 10),
+            Err(RecipeError::InvalidDisclosure(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_secrets() {
+        assert!(check_secrets(&[0..4, 10..12], &[4..10]).is_ok());
+        assert!(check_secrets(&[0..4], &[]).is_ok());
+        assert!(matches!(
+            check_secrets(&[0..5], &[4..10]),
+            Err(RecipeError::SecretDisclosed(secret)) if secret == (4..10)
+        ));
+        assert!(matches!(
+            check_secrets(&[5..6], &[4..10]),
+            Err(RecipeError::SecretDisclosed(_))
+        ));
+    }
+
+    #[test]
+    fn test_fixture_recipe() {
+        let mut recipe = fixture_recipe();
+        let params = BTreeMap::from([("user".to_string(), "alice".to_string())]);
+
+        assert_eq!(
+            recipe.build_request(&params).unwrap(),
+            RecipeRequest {
+                method: "GET".to_string(),
+                url: "https://example.com/api".to_string(),
+                headers: vec![("Accept".to_string(), "application/json".to_string())],
+                body: None,
+            }
+        );
+        assert_eq!(
+            recipe.select_disclosure(&params, SENT, RECV, &[]).unwrap(),
+            Disclosure {
+                sent: vec![0..27],
+                recv: vec![9..15],
+            }
+        );
+    }
+
+    #[test]
+    fn test_fixture_recipe_secret() {
+        let mut recipe = fixture_recipe();
+
+        // The recipe discloses the request line, which contains the token
+        assert!(matches!(
+            recipe.select_disclosure(&BTreeMap::new(), SENT, RECV, &[12..18]),
+            Err(RecipeError::SecretDisclosed(secret)) if secret == (12..18)
+        ));
+    }
+
+    #[test]
+    fn test_fixture_recipe_short_transcript() {
+        let mut recipe = fixture_recipe();
+
+        // The fixed sent range is out of bounds of a shorter transcript
+        assert!(matches!(
+            recipe.select_disclosure(&BTreeMap::new(), b"GET / HTTP/1.1", RECV, &[]),
+            Err(RecipeError::InvalidDisclosure(_))
+        ));
+    }
+
+    #[test]
+    fn test_disclosure_json() {
+        let disclosure: Disclosure =
+            serde_json::from_str(r#"{"recv": [{"start": 0, "end": 15}]}"#).unwrap();
+
+        assert_eq!(
+            disclosure,
+            Disclosure {
+                sent: vec![],
+                recv: vec![0..15],
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_module() {
+        assert!(matches!(
+            Recipe::new(b"not a wasm module"),
+            Err(RecipeError::Load(_))
+        ));
+    }
+}
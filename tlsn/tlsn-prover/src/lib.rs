@@ -3,6 +3,9 @@
 //! This library contains TLSNotary prover implementations:
 //!   * [`tls`] for the low-level API for working with the underlying byte streams of a TLS connection.
 //!   * [`http`] for a higher-level API which provides abstractions for working with HTTP connections.
+//!
+//! With the `websocket` feature enabled on `wasm32`, the `websocket` module provides a transport over
+//! browser WebSockets.
 
 #![deny(missing_docs, unreachable_pub, unused_must_use)]
 #![deny(clippy::all)]
@@ -11,3 +14,5 @@
 #[cfg(feature = "formats")]
 pub mod http;
 pub mod tls;
+#[cfg(all(feature = "websocket", target_arch = "wasm32"))]
+pub mod websocket;
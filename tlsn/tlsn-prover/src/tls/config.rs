@@ -1,11 +1,19 @@
+use std::time::Duration;
+
 use mpz_ot::{chou_orlandi, kos};
 use mpz_share_conversion::{ReceiverConfig, SenderConfig};
 use tls_client::RootCertStore;
 use tls_mpc::{MpcTlsCommonConfig, MpcTlsLeaderConfig, TranscriptConfig};
 use tlsn_common::{
-    config::{ot_recv_estimate, ot_send_estimate, DEFAULT_MAX_RECV_LIMIT, DEFAULT_MAX_SENT_LIMIT},
+    config::{
+        ot_recv_estimate, ot_send_estimate, DEFAULT_MAX_RECV_LIMIT, DEFAULT_MAX_SENT_LIMIT,
+        DEFAULT_MAX_THREADS,
+    },
+    registry::{NotaryEntry, NotaryRequirements, Registry},
+    rng::{gen_seed, RngStream},
     Role,
 };
+use tokio_util::sync::CancellationToken;
 
 /// Configuration for the prover
 #[derive(Debug, Clone, derive_builder::Builder)]
@@ -25,6 +33,22 @@ pub struct ProverConfig {
     /// Maximum number of bytes that can be received.
     #[builder(default = "DEFAULT_MAX_RECV_LIMIT")]
     max_recv_data: usize,
+    /// Maximum number of VM threads used by the session.
+    ///
+    /// The session uses the smaller of this limit and the verifier's.
+    #[builder(default = "DEFAULT_MAX_THREADS")]
+    max_threads: usize,
+    /// Timeout for each step of the MPC PRF, `None` to disable.
+    #[builder(default)]
+    prf_step_timeout: Option<Duration>,
+    /// Token which aborts the step of the MPC PRF in progress once cancelled.
+    #[builder(setter(strip_option), default)]
+    cancellation_token: Option<CancellationToken>,
+    /// Seed from which the randomness of the prover is derived, for reproducing a session in
+    /// tests.
+    #[cfg(feature = "deterministic")]
+    #[builder(setter(strip_option), default)]
+    rng_seed: Option<u64>,
 }
 
 impl ProverConfig {
@@ -43,16 +67,69 @@ impl ProverConfig {
         self.max_recv_data
     }
 
+    /// Returns the maximum number of VM threads used by the session.
+    pub fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+
+    /// Returns the timeout for each step of the MPC PRF.
+    pub fn prf_step_timeout(&self) -> Option<Duration> {
+        self.prf_step_timeout
+    }
+
+    /// Returns the token which aborts the step of the MPC PRF in progress, if set.
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    /// Returns the ID of the notarization session.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     /// Returns the server DNS name.
     pub fn server_dns(&self) -> &str {
         &self.server_dns
     }
 
-    pub(crate) fn build_mpc_tls_config(&self) -> MpcTlsLeaderConfig {
+    /// Returns the most preferred notary of the registry which can notarize sessions within the
+    /// limits of this configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry of known notaries.
+    /// * `has_api_key` - Whether the prover has an API key for the notary.
+    pub fn select_notary<'a>(
+        &self,
+        registry: &'a Registry,
+        has_api_key: bool,
+    ) -> Option<&'a NotaryEntry> {
+        registry.select(&NotaryRequirements {
+            features: Vec::new(),
+            sent_data: self.max_sent_data,
+            recv_data: self.max_recv_data,
+            has_api_key,
+        })
+    }
+
+    /// Returns the seed from which the randomness of the prover is derived.
+    #[cfg(feature = "deterministic")]
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
+    /// Returns the seed from which the randomness of the prover is derived.
+    #[cfg(not(feature = "deterministic"))]
+    pub(crate) fn rng_seed(&self) -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn build_mpc_tls_config(&self, num_threads: usize) -> MpcTlsLeaderConfig {
         MpcTlsLeaderConfig::builder()
             .common(
                 MpcTlsCommonConfig::builder()
                     .id(format!("{}/mpc_tls", &self.id))
+                    .num_threads(num_threads)
                     .tx_config(
                         TranscriptConfig::default_tx()
                             .max_size(self.max_sent_data)
@@ -66,6 +143,9 @@ impl ProverConfig {
                             .unwrap(),
                     )
                     .handshake_commit(true)
+                    .rng_seed(gen_seed(self.rng_seed(), &self.id, RngStream::Tls))
+                    .prf_step_timeout(self.prf_step_timeout)
+                    .cancellation_token(self.cancellation_token.clone())
                     .build()
                     .unwrap(),
             )
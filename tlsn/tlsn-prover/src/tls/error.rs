@@ -14,6 +14,8 @@ pub enum ProverError {
     IOError(#[from] std::io::Error),
     #[error(transparent)]
     MuxerError(#[from] utils_aio::mux::MuxerError),
+    #[error(transparent)]
+    HelloError(#[from] tlsn_common::hello::HelloError),
     #[error("notarization error: {0}")]
     NotarizationError(String),
     #[error(transparent)]
@@ -16,27 +16,28 @@ pub use config::{ProverConfig, ProverConfigBuilder, ProverConfigBuilderError};
 pub use error::ProverError;
 pub use future::ProverFuture;
 use tlsn_common::{
+    hello::{exchange_hello, Hello},
     mux::{attach_mux, MuxControl},
+    rng::{gen_seed, RngStream},
     Role,
 };
 
 use error::OTShutdownError;
 use future::{MuxFuture, OTFuture};
-use futures::{AsyncRead, AsyncWrite, FutureExt, StreamExt, TryFutureExt};
+use futures::{AsyncRead, AsyncWrite, Future, FutureExt, StreamExt, TryFutureExt};
 use mpz_garble::{config::Role as DEAPRole, protocol::deap::DEAPVm};
 use mpz_ot::{
     actor::kos::{ReceiverActor, SenderActor, SharedReceiver, SharedSender},
     chou_orlandi, kos,
 };
 use mpz_share_conversion as ff;
-use rand::Rng;
 use state::{Notarize, Prove};
 use std::sync::Arc;
 use tls_client::{ClientConnection, ServerName as TlsServerName};
 use tls_client_async::{bind_client, ClosedConnection, TlsConnection};
 use tls_mpc::{setup_components, LeaderCtrl, MpcTlsLeader, TlsRole};
 use tlsn_core::transcript::Transcript;
-use utils_aio::mux::MuxChannel;
+use utils_aio::mux::{MuxChannel, MuxerError};
 
 #[cfg(feature = "formats")]
 use crate::http::{state as http_state, HttpProver, HttpProverError};
@@ -69,6 +70,12 @@ impl Prover<state::Initialized> {
     /// This performs all MPC setup prior to establishing the connection to the
     /// application server.
     ///
+    /// This is the offline phase of the protocol: OT extension setup and garbling of the key
+    /// exchange, PRF and cipher circuits all happen here, so it can be run ahead of the actual
+    /// request. Only evaluation remains for [`connect`](Prover::connect). The verifier's TLS
+    /// timeout starts once setup completes, which bounds how long the prover may wait before
+    /// connecting.
+    ///
     /// # Arguments
     ///
     /// * `socket` - The socket to the notary.
@@ -78,8 +85,27 @@ impl Prover<state::Initialized> {
     ) -> Result<Prover<state::Setup>, ProverError> {
         let (mut mux, mux_ctrl) = attach_mux(socket, Role::Prover);
 
+        self.setup_with_mux(mux_ctrl, async move { mux.run().await })
+            .await
+    }
+
+    /// Set up the prover on the provided multiplexer, see [`setup`](Prover::setup).
+    ///
+    /// This allows running the prover on a multiplexer other than the one attached by
+    /// [`setup`](Prover::setup), eg. one replaying a recorded session with
+    /// `tlsn_common::mux::attach_replay`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mux_ctrl` - The controller of the multiplexer.
+    /// * `mux_fut` - The future which must be polled for the multiplexer to make progress.
+    pub async fn setup_with_mux(
+        self,
+        mux_ctrl: MuxControl,
+        mux_fut: impl Future<Output = Result<(), MuxerError>> + Send + 'static,
+    ) -> Result<Prover<state::Setup>, ProverError> {
         let mut mux_fut = MuxFuture {
-            fut: Box::pin(async move { mux.run().await.map_err(ProverError::from) }.fuse()),
+            fut: Box::pin(mux_fut.map_err(ProverError::from).fuse()),
         };
 
         let mpc_setup_fut = setup_mpc_backend(&self.config, mux_ctrl.clone());
@@ -216,9 +242,21 @@ impl Prover<state::Closed> {
     /// If the verifier is a Notary, this function will transition the prover to the next state
     /// where it can generate commitments to the transcript prior to finalization.
     pub fn start_notarize(self) -> Prover<Notarize> {
+        #[cfg_attr(not(feature = "deterministic"), allow(unused_mut))]
+        let mut state: Notarize = self.state.into();
+
+        #[cfg(feature = "deterministic")]
+        if let Some(seed) = self.config.rng_seed() {
+            state.builder.set_nonce_seed(gen_seed(
+                Some(seed),
+                self.config.id(),
+                RngStream::CommitmentNonces,
+            ));
+        }
+
         Prover {
             config: self.config,
-            state: self.state.into(),
+            state,
         }
     }
 
@@ -250,13 +288,27 @@ async fn setup_mpc_backend(
     ),
     ProverError,
 > {
+    // Fail early if the other party runs an incompatible version of the protocol.
+    let mut hello_channel = mux.get_channel("hello").await?;
+    let negotiated = exchange_hello(
+        &mut hello_channel,
+        Hello {
+            max_threads: config.max_threads(),
+            ..Default::default()
+        },
+    )
+    .await?;
+
     let (ot_send_sink, ot_send_stream) = mux.get_channel("ot/0").await?.split();
     let (ot_recv_sink, ot_recv_stream) = mux.get_channel("ot/1").await?.split();
 
     let mut ot_sender_actor = SenderActor::new(
         kos::Sender::new(
             config.build_ot_sender_config(),
-            chou_orlandi::Receiver::new(config.build_base_ot_receiver_config()),
+            chou_orlandi::Receiver::new_with_seed(
+                config.build_base_ot_receiver_config(),
+                gen_seed(config.rng_seed(), config.id(), RngStream::BaseOtSender),
+            ),
         ),
         ot_send_sink,
         ot_send_stream,
@@ -265,7 +317,10 @@ async fn setup_mpc_backend(
     let mut ot_receiver_actor = ReceiverActor::new(
         kos::Receiver::new(
             config.build_ot_receiver_config(),
-            chou_orlandi::Sender::new(config.build_base_ot_sender_config()),
+            chou_orlandi::Sender::new_with_seed(
+                config.build_base_ot_sender_config(),
+                gen_seed(config.rng_seed(), config.id(), RngStream::BaseOtReceiver),
+            ),
         ),
         ot_recv_sink,
         ot_recv_stream,
@@ -306,7 +361,7 @@ async fn setup_mpc_backend(
     let mut vm = DEAPVm::new(
         "vm",
         DEAPRole::Leader,
-        rand::rngs::OsRng.gen(),
+        gen_seed(config.rng_seed(), config.id(), RngStream::Garbling),
         mux.get_channel("vm").await?,
         Box::new(mux.clone()),
         ot_send.clone(),
@@ -327,7 +382,7 @@ async fn setup_mpc_backend(
     let channel = mux.get_channel(gf2_config.id()).await?;
     let gf2 = ff::ConverterSender::<ff::Gf2_128, _>::new(gf2_config, ot_send.clone(), channel);
 
-    let mpc_tls_config = config.build_mpc_tls_config();
+    let mpc_tls_config = config.build_mpc_tls_config(negotiated.threads);
 
     let (ke, prf, encrypter, decrypter) = setup_components(
         mpc_tls_config.common(),
@@ -67,7 +67,7 @@ impl Prover<ProveState> {
 
     /// Prove transcript values
     pub async fn prove(&mut self) -> Result<(), ProverError> {
-        let mut proving_info = std::mem::take(&mut self.state.proving_info);
+        let proving_info = std::mem::take(&mut self.state.proving_info);
 
         let mut prove_fut = Box::pin(async {
             // Create a new channel and vm thread if not already present
@@ -85,46 +85,7 @@ impl Prover<ProveState> {
                 self.state.prove_thread.as_mut().unwrap()
             };
 
-            // Now prove the transcript parts which have been marked for reveal
-            let sent_value_ids = proving_info
-                .sent_ids
-                .iter_ranges()
-                .map(|r| get_value_ids(&r.into(), Direction::Sent).collect::<Vec<String>>());
-            let recv_value_ids = proving_info
-                .recv_ids
-                .iter_ranges()
-                .map(|r| get_value_ids(&r.into(), Direction::Received).collect::<Vec<String>>());
-
-            let value_refs = sent_value_ids
-                .chain(recv_value_ids)
-                .map(|ids| {
-                    let inner_refs = ids
-                        .iter()
-                        .map(|id| {
-                            prove_thread
-                                .get_value(id.as_str())
-                                .expect("Byte should be in VM memory")
-                        })
-                        .collect::<Vec<_>>();
-
-                    prove_thread
-                        .array_from_values(inner_refs.as_slice())
-                        .expect("Byte should be in VM Memory")
-                })
-                .collect::<Vec<_>>();
-
-            // Extract cleartext we want to reveal from transcripts
-            let mut cleartext =
-                Vec::with_capacity(proving_info.sent_ids.len() + proving_info.recv_ids.len());
-            proving_info
-                .sent_ids
-                .iter_ranges()
-                .for_each(|r| cleartext.extend_from_slice(&self.state.transcript_tx.data()[r]));
-            proving_info
-                .recv_ids
-                .iter_ranges()
-                .for_each(|r| cleartext.extend_from_slice(&self.state.transcript_rx.data()[r]));
-            proving_info.cleartext = cleartext;
+            let records = proving_info.records();
 
             // Send the proving info to the verifier
             channel.send(TlsnMessage::ProvingInfo(proving_info)).await?;
@@ -132,8 +93,32 @@ impl Prover<ProveState> {
             #[cfg(feature = "tracing")]
             info!("Sent proving info to verifier");
 
-            // Prove the revealed transcript parts
-            prove_thread.prove(value_refs.as_slice()).await?;
+            // Prove the transcript parts which have been marked for reveal, one record at a time
+            // so that the verifier can process them as they arrive
+            for (direction, range) in records {
+                let transcript = match direction {
+                    Direction::Sent => &self.state.transcript_tx,
+                    Direction::Received => &self.state.transcript_rx,
+                };
+                channel
+                    .send(TlsnMessage::RevealedRecord(
+                        transcript.data()[range.clone()].to_vec(),
+                    ))
+                    .await?;
+
+                let inner_refs = get_value_ids(&range.into(), direction)
+                    .map(|id| {
+                        prove_thread
+                            .get_value(id.as_str())
+                            .expect("Byte should be in VM memory")
+                    })
+                    .collect::<Vec<_>>();
+                let value_ref = prove_thread
+                    .array_from_values(inner_refs.as_slice())
+                    .expect("Byte should be in VM Memory");
+
+                prove_thread.prove(&[value_ref]).await?;
+            }
 
             #[cfg(feature = "tracing")]
             info!("Successfully proved cleartext");
@@ -0,0 +1,130 @@
+//! WebSocket transport for provers running in the browser.
+//!
+//! The notary's WebSocket endpoint carries the raw byte stream of the protocol in binary
+//! messages, so [`WsTransport`] can be passed directly to
+//! [`Prover::setup`](crate::tls::Prover::setup). The same applies to the connection to the
+//! application server through a WebSocket proxy.
+
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{AsyncRead, AsyncWrite, Future};
+use gloo_timers::future::TimeoutFuture;
+use send_wrapper::SendWrapper;
+use ws_stream_wasm::WsMeta;
+
+/// The default number of bytes queued in the browser's send buffer above which writes are paused.
+pub const DEFAULT_HIGH_WATER_MARK: u32 = 1 << 20;
+
+/// How long to wait, in milliseconds, before checking again whether the send buffer has drained.
+const DRAIN_INTERVAL_MS: u32 = 10;
+
+trait Io: AsyncRead + AsyncWrite + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> Io for T {}
+
+struct Inner {
+    meta: WsMeta,
+    io: Box<dyn Io>,
+    high_water_mark: u32,
+    drain: Option<TimeoutFuture>,
+}
+
+/// A byte stream over a browser WebSocket.
+///
+/// Writes are paused while more than the high water mark is queued in the browser's send buffer,
+/// so a fast producer such as the garbler can not grow the buffer without bound.
+///
+/// Browser objects are bound to the thread which created them. The transport is `Send` so it can
+/// be used with the prover, but panics if it is accessed from another thread.
+pub struct WsTransport(SendWrapper<Inner>);
+
+opaque_debug::implement!(WsTransport);
+
+impl WsTransport {
+    /// Connects to the WebSocket at `url`, using [`DEFAULT_HIGH_WATER_MARK`].
+    pub async fn connect(url: &str) -> io::Result<Self> {
+        Self::connect_with_high_water_mark(url, DEFAULT_HIGH_WATER_MARK).await
+    }
+
+    /// Connects to the WebSocket at `url`.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the WebSocket, e.g. `wss://notary.example.com/notarize?sessionId=..`.
+    /// * `high_water_mark` - The number of bytes in the send buffer above which writes are paused.
+    pub async fn connect_with_high_water_mark(url: &str, high_water_mark: u32) -> io::Result<Self> {
+        let (meta, stream) = WsMeta::connect(url, None)
+            .await
+            .map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, e.to_string()))?;
+
+        Ok(Self(SendWrapper::new(Inner {
+            meta,
+            io: Box::new(stream.into_io()),
+            high_water_mark,
+            drain: None,
+        })))
+    }
+
+    /// Returns the number of bytes queued in the browser's send buffer.
+    pub fn buffered_amount(&self) -> u32 {
+        self.0.meta.buffered_amount()
+    }
+}
+
+impl Inner {
+    /// Waits until the send buffer is below the high water mark.
+    fn poll_drained(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        loop {
+            if let Some(drain) = self.drain.as_mut() {
+                if Pin::new(drain).poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.drain = None;
+            }
+
+            if self.meta.buffered_amount() <= self.high_water_mark {
+                return Poll::Ready(());
+            }
+
+            // Browsers do not signal when the send buffer drains, so check periodically.
+            self.drain = Some(TimeoutFuture::new(DRAIN_INTERVAL_MS));
+        }
+    }
+}
+
+impl AsyncRead for WsTransport {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for WsTransport {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let inner = &mut *self.0;
+        if inner.poll_drained(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        Pin::new(&mut inner.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0.io).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0.io).poll_close(cx)
+    }
+}
@@ -0,0 +1,27 @@
+use tlsn_core::{commitment::TranscriptCommitmentBuilderError, proof::SubstringsProofBuilderError};
+use tlsn_prover::tls::{ProverConfigBuilderError, ProverError};
+
+/// An error that can occur while handling a request.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ProverdError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Http(#[from] hyper::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("notary error: {0}")]
+    Notary(String),
+    #[error(transparent)]
+    Config(#[from] ProverConfigBuilderError),
+    #[error(transparent)]
+    Prover(#[from] ProverError),
+    #[error(transparent)]
+    Commitment(#[from] TranscriptCommitmentBuilderError),
+    #[error(transparent)]
+    Proof(#[from] SubstringsProofBuilderError),
+    #[error("unknown session: {0}")]
+    UnknownSession(String),
+    #[error("session {0} is not ready to {1}")]
+    InvalidState(String, &'static str),
+}
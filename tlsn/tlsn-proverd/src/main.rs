@@ -0,0 +1,79 @@
+//! A local daemon which exposes the prover over JSON-RPC 2.0.
+//!
+//! Requests and responses are newline delimited JSON over TCP. The methods, in the order of a
+//! notarization, are:
+//!
+//! - `create_session` - requests a session from a notary and connects to the server.
+//! - `send_request` - sends a raw request to the server and returns its response.
+//! - `set_disclosures` - commits to the ranges of the transcript to disclose.
+//! - `export_proof` - finalizes the notarization and returns the proof.
+//!
+//! The daemon listens on `127.0.0.1:7047` unless another address is given as the first argument.
+//! It does not authenticate clients, so it must only listen on a local address.
+
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+mod error;
+mod notary;
+mod rpc;
+mod session;
+
+use std::sync::Arc;
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+};
+use tracing::{debug, info, warn};
+
+use session::Sessions;
+
+/// The default address to listen on.
+const DEFAULT_LISTEN_ADDRESS: &str = "127.0.0.1:7047";
+
+#[tokio::main]
+async fn main() -> std::io::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_LISTEN_ADDRESS.to_string());
+    let listener = TcpListener::bind(&address).await?;
+    if !listener.local_addr()?.ip().is_loopback() {
+        warn!("Listening on a non-local address, anyone who can connect can drive the prover");
+    }
+    info!("Listening on {}", listener.local_addr()?);
+
+    let sessions = Arc::new(Sessions::default());
+    loop {
+        let (socket, peer) = listener.accept().await?;
+        debug!("Accepted connection from {peer}");
+
+        let sessions = sessions.clone();
+        tokio::spawn(async move {
+            if let Err(e) = serve(socket, &sessions).await {
+                warn!("Connection from {peer} failed: {e}");
+            }
+        });
+    }
+}
+
+/// Serves the requests of a client, one per line.
+async fn serve(socket: TcpStream, sessions: &Sessions) -> std::io::Result<()> {
+    let (reader, mut writer) = socket.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = rpc::handle(sessions, &line).await;
+        let mut response = serde_json::to_vec(&response).expect("response is serializable");
+        response.push(b'\n');
+        writer.write_all(&response).await?;
+    }
+
+    Ok(())
+}
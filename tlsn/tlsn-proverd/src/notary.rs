@@ -0,0 +1,81 @@
+use http_body_util::{BodyExt as _, Either, Empty, Full};
+use hyper::{body::Bytes, client::conn::http1::Parts, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use notary_server::{ClientType, NotarizationSessionRequest, NotarizationSessionResponse};
+use tokio::net::TcpStream;
+
+use crate::error::ProverdError;
+
+/// Requests a notarization session from a notary server.
+///
+/// Returns the socket on which to run the protocol with the notary and the session id.
+///
+/// # Arguments
+///
+/// * `host` - The host of the notary server.
+/// * `port` - The port of the notary server.
+/// * `max_sent_data` - Maximum data that can be sent by the prover.
+/// * `max_recv_data` - Maximum data that can be received by the prover.
+pub(crate) async fn request_notarization(
+    host: &str,
+    port: u16,
+    max_sent_data: Option<usize>,
+    max_recv_data: Option<usize>,
+) -> Result<(TcpStream, String), ProverdError> {
+    let socket = TcpStream::connect((host, port)).await?;
+
+    let (mut request_sender, connection) =
+        hyper::client::conn::http1::handshake(TokioIo::new(socket)).await?;
+    let connection_task = tokio::spawn(connection.without_shutdown());
+
+    let payload = serde_json::to_string(&NotarizationSessionRequest {
+        client_type: ClientType::Tcp,
+        max_sent_data,
+        max_recv_data,
+    })?;
+
+    let request = Request::builder()
+        .uri(format!("http://{host}:{port}/session"))
+        .method("POST")
+        .header("Host", host)
+        .header("Content-Type", "application/json")
+        .body(Either::Left(Full::new(Bytes::from(payload))))
+        .expect("request is valid");
+
+    let response = request_sender.send_request(request).await?;
+    if response.status() != StatusCode::OK {
+        return Err(ProverdError::Notary(format!(
+            "session request failed with status {}",
+            response.status()
+        )));
+    }
+
+    let payload = response.into_body().collect().await?.to_bytes();
+    let NotarizationSessionResponse { session_id } = serde_json::from_slice(&payload)?;
+
+    // The notary takes over the underlying connection after upgrading it.
+    let request = Request::builder()
+        .uri(format!(
+            "http://{host}:{port}/notarize?sessionId={session_id}"
+        ))
+        .method("GET")
+        .header("Host", host)
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "TCP")
+        .body(Either::Right(Empty::<Bytes>::new()))
+        .expect("request is valid");
+
+    let response = request_sender.send_request(request).await?;
+    if response.status() != StatusCode::SWITCHING_PROTOCOLS {
+        return Err(ProverdError::Notary(format!(
+            "notarize request failed with status {}",
+            response.status()
+        )));
+    }
+
+    let Parts { io, .. } = connection_task
+        .await
+        .map_err(|e| ProverdError::Notary(e.to_string()))??;
+
+    Ok((io.into_inner(), session_id))
+}
@@ -0,0 +1,137 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{error::ProverdError, session::Sessions};
+
+/// Invalid JSON was received.
+const PARSE_ERROR: i64 = -32700;
+/// The method does not exist.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Invalid method parameters.
+const INVALID_PARAMS: i64 = -32602;
+/// The method failed.
+const SERVER_ERROR: i64 = -32000;
+
+/// A JSON-RPC 2.0 request.
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// A JSON-RPC 2.0 response.
+#[derive(Debug, Serialize)]
+pub(crate) struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Error>,
+}
+
+/// A JSON-RPC 2.0 error.
+#[derive(Debug, Serialize)]
+pub(crate) struct Error {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(Error {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// Handles a request, serialized as JSON.
+pub(crate) async fn handle(sessions: &Sessions, request: &str) -> Response {
+    let Request { id, method, params } = match serde_json::from_str(request) {
+        Ok(request) => request,
+        Err(e) => return Response::error(Value::Null, PARSE_ERROR, e.to_string()),
+    };
+
+    let result = match method.as_str() {
+        "create_session" => call(params, |params| sessions.create_session(params)).await,
+        "send_request" => call(params, |params| sessions.send_request(params)).await,
+        "set_disclosures" => call(params, |params| sessions.set_disclosures(params)).await,
+        "export_proof" => call(params, |params| sessions.export_proof(params)).await,
+        _ => {
+            return Response::error(id, METHOD_NOT_FOUND, format!("unknown method: {method}"));
+        }
+    };
+
+    match result {
+        Ok(result) => Response {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        },
+        Err((code, message)) => Response::error(id, code, message),
+    }
+}
+
+async fn call<P, R, F>(params: Value, method: impl FnOnce(P) -> F) -> Result<Value, (i64, String)>
+where
+    P: DeserializeOwned,
+    R: Serialize,
+    F: std::future::Future<Output = Result<R, ProverdError>>,
+{
+    let params = serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))?;
+    let result = method(params)
+        .await
+        .map_err(|e| (SERVER_ERROR, e.to_string()))?;
+
+    Ok(serde_json::to_value(result).expect("result is serializable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn handle_json(request: &str) -> Value {
+        serde_json::to_value(handle(&Sessions::default(), request).await).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parse_error() {
+        let response = handle_json("not json").await;
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let response = handle_json(r#"{"jsonrpc":"2.0","id":1,"method":"foo"}"#).await;
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_params() {
+        let response =
+            handle_json(r#"{"jsonrpc":"2.0","id":2,"method":"export_proof","params":{}}"#).await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_session() {
+        let response = handle_json(
+            r#"{"jsonrpc":"2.0","id":3,"method":"set_disclosures","params":{"session_id":"x"}}"#,
+        )
+        .await;
+        assert_eq!(response["error"]["code"], SERVER_ERROR);
+        assert!(response.get("result").is_none());
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::HashMap, ops::Range};
+
+use futures::{AsyncReadExt as _, AsyncWriteExt as _};
+use serde::{Deserialize, Serialize};
+use tokio::{net::TcpStream, sync::Mutex, task::JoinHandle};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+use tls_client_async::TlsConnection;
+use tlsn_core::{commitment::CommitmentId, proof::TlsProof};
+use tlsn_prover::tls::{
+    state::{Closed, Notarize},
+    Prover, ProverConfig, ProverError,
+};
+
+use crate::{error::ProverdError, notary::request_notarization};
+
+/// The default port of the application server.
+const DEFAULT_SERVER_PORT: u16 = 443;
+
+/// The state of a session.
+enum State {
+    /// Connected to the server, ready to send a request.
+    Connected {
+        conn: TlsConnection,
+        prover: JoinHandle<Result<Prover<Closed>, ProverError>>,
+    },
+    /// The connection to the server is closed, ready to set disclosures and export a proof.
+    Notarize {
+        prover: Prover<Notarize>,
+        commitments: Vec<CommitmentId>,
+    },
+}
+
+/// Parameters of `create_session`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct CreateSessionParams {
+    /// The DNS name of the application server.
+    server_dns: String,
+    /// The port of the application server.
+    #[serde(default = "default_server_port")]
+    server_port: u16,
+    /// The host of the notary server.
+    notary_host: String,
+    /// The port of the notary server.
+    notary_port: u16,
+    /// Maximum data that can be sent to the server.
+    max_sent_data: Option<usize>,
+    /// Maximum data that can be received from the server.
+    max_recv_data: Option<usize>,
+}
+
+fn default_server_port() -> u16 {
+    DEFAULT_SERVER_PORT
+}
+
+/// Result of `create_session`.
+#[derive(Debug, Serialize)]
+pub(crate) struct CreateSessionResult {
+    /// The id of the session, assigned by the notary.
+    session_id: String,
+}
+
+/// Parameters of `send_request`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SendRequestParams {
+    session_id: String,
+    /// The raw request to send to the server.
+    ///
+    /// The server must close the connection after responding, e.g. by sending
+    /// `Connection: close` with an HTTP request.
+    request: String,
+}
+
+/// Result of `send_request`.
+#[derive(Debug, Serialize)]
+pub(crate) struct SendRequestResult {
+    /// The raw response of the server.
+    response: String,
+}
+
+/// Parameters of `set_disclosures`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct SetDisclosuresParams {
+    session_id: String,
+    /// Ranges of the sent data to disclose.
+    #[serde(default)]
+    sent: Vec<Range<usize>>,
+    /// Ranges of the received data to disclose.
+    #[serde(default)]
+    recv: Vec<Range<usize>>,
+}
+
+/// Parameters of `export_proof`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct ExportProofParams {
+    session_id: String,
+}
+
+/// The sessions of the daemon.
+#[derive(Default)]
+pub(crate) struct Sessions(Mutex<HashMap<String, State>>);
+
+impl Sessions {
+    /// Requests a session from the notary and connects to the server.
+    pub(crate) async fn create_session(
+        &self,
+        params: CreateSessionParams,
+    ) -> Result<CreateSessionResult, ProverdError> {
+        let (notary_socket, session_id) = request_notarization(
+            &params.notary_host,
+            params.notary_port,
+            params.max_sent_data,
+            params.max_recv_data,
+        )
+        .await?;
+
+        let mut config = ProverConfig::builder();
+        config
+            .id(session_id.as_str())
+            .server_dns(params.server_dns.as_str());
+        if let Some(max_sent_data) = params.max_sent_data {
+            config.max_sent_data(max_sent_data);
+        }
+        if let Some(max_recv_data) = params.max_recv_data {
+            config.max_recv_data(max_recv_data);
+        }
+
+        let prover = Prover::new(config.build()?)
+            .setup(notary_socket.compat())
+            .await?;
+
+        let server_socket =
+            TcpStream::connect((params.server_dns.as_str(), params.server_port)).await?;
+        let (conn, prover_fut) = prover.connect(server_socket.compat()).await?;
+
+        self.0.lock().await.insert(
+            session_id.clone(),
+            State::Connected {
+                conn,
+                prover: tokio::spawn(prover_fut),
+            },
+        );
+
+        Ok(CreateSessionResult { session_id })
+    }
+
+    /// Sends a request to the server and returns its response.
+    ///
+    /// The connection to the server is closed afterwards.
+    pub(crate) async fn send_request(
+        &self,
+        params: SendRequestParams,
+    ) -> Result<SendRequestResult, ProverdError> {
+        let SendRequestParams {
+            session_id,
+            request,
+        } = params;
+
+        let (mut conn, prover) = match self.take(&session_id).await? {
+            State::Connected { conn, prover } => (conn, prover),
+            state => {
+                self.0.lock().await.insert(session_id.clone(), state);
+                return Err(ProverdError::InvalidState(session_id, "send a request"));
+            }
+        };
+
+        conn.write_all(request.as_bytes()).await?;
+        let mut response = Vec::new();
+        conn.read_to_end(&mut response).await?;
+        conn.close().await?;
+
+        let prover = prover
+            .await
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))??
+            .start_notarize();
+
+        self.0.lock().await.insert(
+            session_id,
+            State::Notarize {
+                prover,
+                commitments: Vec::new(),
+            },
+        );
+
+        Ok(SendRequestResult {
+            response: String::from_utf8_lossy(&response).into_owned(),
+        })
+    }
+
+    /// Commits to the ranges of the transcript which will be disclosed in the proof.
+    pub(crate) async fn set_disclosures(
+        &self,
+        params: SetDisclosuresParams,
+    ) -> Result<(), ProverdError> {
+        let SetDisclosuresParams {
+            session_id,
+            sent,
+            recv,
+        } = params;
+
+        let mut sessions = self.0.lock().await;
+        let (prover, commitments) = match sessions.get_mut(&session_id) {
+            Some(State::Notarize {
+                prover,
+                commitments,
+            }) => (prover, commitments),
+            Some(_) => return Err(ProverdError::InvalidState(session_id, "set disclosures")),
+            None => return Err(ProverdError::UnknownSession(session_id)),
+        };
+
+        let builder = prover.commitment_builder();
+        for range in sent {
+            commitments.push(builder.commit_sent(&range)?);
+        }
+        for range in recv {
+            commitments.push(builder.commit_recv(&range)?);
+        }
+
+        Ok(())
+    }
+
+    /// Finalizes the notarization and returns a proof disclosing the committed ranges.
+    ///
+    /// The session is removed afterwards.
+    pub(crate) async fn export_proof(
+        &self,
+        params: ExportProofParams,
+    ) -> Result<TlsProof, ProverdError> {
+        let session_id = params.session_id;
+
+        let (prover, commitments) = match self.take(&session_id).await? {
+            State::Notarize {
+                prover,
+                commitments,
+            } => (prover, commitments),
+            state => {
+                self.0.lock().await.insert(session_id.clone(), state);
+                return Err(ProverdError::InvalidState(session_id, "export a proof"));
+            }
+        };
+
+        let notarized_session = prover.finalize().await?;
+
+        let mut proof_builder = notarized_session.data().build_substrings_proof();
+        for id in commitments {
+            proof_builder.reveal_by_id(id)?;
+        }
+
+        Ok(TlsProof {
+            session: notarized_session.session_proof(),
+            substrings: proof_builder.build()?,
+        })
+    }
+
+    /// Removes a session, to be reinserted after a state transition.
+    async fn take(&self, session_id: &str) -> Result<State, ProverdError> {
+        self.0
+            .lock()
+            .await
+            .remove(session_id)
+            .ok_or_else(|| ProverdError::UnknownSession(session_id.to_string()))
+    }
+}
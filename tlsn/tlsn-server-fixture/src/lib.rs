@@ -6,7 +6,8 @@ use std::{
 use async_rustls::TlsAcceptor;
 use axum::{
     extract::{Query, State},
-    response::{Html, Json},
+    http::{header, HeaderMap},
+    response::{Html, IntoResponse, Json},
     routing::get,
     Router,
 };
@@ -35,6 +36,7 @@ fn app(state: AppState) -> Router {
         .route("/bytes", get(bytes))
         .route("/formats/json", get(json))
         .route("/formats/html", get(html))
+        .route("/api/account", get(account))
         .with_state(Arc::new(Mutex::new(state)))
 }
 
@@ -108,6 +110,25 @@ async fn json(
     }
 }
 
+async fn account(
+    State(state): State<Arc<Mutex<AppState>>>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    if params.get("shutdown").is_some() {
+        _ = state.lock().unwrap().shutdown.take().unwrap().send(());
+    }
+
+    if !headers.contains_key(header::AUTHORIZATION) {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/json")],
+        include_str!("data/account.json"),
+    ))
+}
+
 async fn html(
     State(state): State<Arc<Mutex<AppState>>>,
     Query(params): Query<HashMap<String, String>>,
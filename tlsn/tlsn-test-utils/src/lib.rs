@@ -0,0 +1,14 @@
+//! Utilities for writing end-to-end tests of applications using TLSNotary.
+//!
+//! Provides an in-process [`MockNotary`] and a canned TLS test server, so that a prover can be
+//! run to completion without Docker or network access.
+
+#![deny(missing_docs, unreachable_pub, unused_must_use)]
+#![deny(clippy::all)]
+#![forbid(unsafe_code)]
+
+mod notary;
+mod server;
+
+pub use notary::{MockNotary, NotaryTask};
+pub use server::{root_cert_store, spawn_server, ServerTask, SERVER_DOMAIN};
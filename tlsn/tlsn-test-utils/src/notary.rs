@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use tlsn_core::{NotaryPublicKey, SessionHeader};
+use tlsn_verifier::tls::{Verifier, VerifierConfig, VerifierError};
+use tokio::{io::DuplexStream, task::JoinHandle};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+/// Size of the buffer of the loopback transport between the prover and the notary.
+const NOTARY_BUFFER_SIZE: usize = 1 << 24;
+
+/// Task running a notarization, which resolves to the header of the notarized session.
+pub type NotaryTask = JoinHandle<Result<SessionHeader, VerifierError>>;
+
+/// An in-process notary, which notarizes sessions over a loopback transport.
+///
+/// Every session is signed with the same key, which is generated when the notary is created
+/// unless one is provided.
+#[derive(Clone)]
+pub struct MockNotary {
+    signing_key: Arc<SigningKey>,
+}
+
+opaque_debug::implement!(MockNotary);
+
+impl MockNotary {
+    /// Creates a new notary with an ephemeral signing key.
+    pub fn new() -> Self {
+        Self::with_signing_key(SigningKey::random(&mut rand::thread_rng()))
+    }
+
+    /// Creates a new notary with the given signing key.
+    pub fn with_signing_key(signing_key: SigningKey) -> Self {
+        Self {
+            signing_key: Arc::new(signing_key),
+        }
+    }
+
+    /// Returns the verifying key of the notary.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        *self.signing_key.verifying_key()
+    }
+
+    /// Returns the public key of the notary, used to verify the proofs it signed.
+    pub fn public_key(&self) -> NotaryPublicKey {
+        NotaryPublicKey::from(p256::PublicKey::from(self.verifying_key()))
+    }
+
+    /// Starts notarizing a session with the given id, using the default verifier configuration.
+    ///
+    /// Returns the socket to be passed to the prover, and the task running the notarization.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn connect(&self, id: &str) -> (DuplexStream, NotaryTask) {
+        let config = VerifierConfig::builder()
+            .id(id)
+            .build()
+            .expect("default verifier config is valid");
+        self.connect_with_config(config)
+    }
+
+    /// Starts notarizing a session with the given verifier configuration.
+    ///
+    /// Returns the socket to be passed to the prover, and the task running the notarization.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn connect_with_config(&self, config: VerifierConfig) -> (DuplexStream, NotaryTask) {
+        let (prover_socket, notary_socket) = tokio::io::duplex(NOTARY_BUFFER_SIZE);
+        let signing_key = self.signing_key.clone();
+
+        let task = tokio::spawn(async move {
+            Verifier::new(config)
+                .notarize::<_, Signature>(notary_socket.compat(), signing_key.as_ref())
+                .await
+        });
+
+        (prover_socket, task)
+    }
+}
+
+impl Default for MockNotary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,36 @@
+use tls_core::{anchors::RootCertStore, key::Certificate};
+use tlsn_server_fixture::CA_CERT_DER;
+use tokio::{io::DuplexStream, task::JoinHandle};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+pub use tlsn_server_fixture::SERVER_DOMAIN;
+
+/// Size of the buffer of the loopback transport between the prover and the server.
+const SERVER_BUFFER_SIZE: usize = 1 << 16;
+
+/// Task serving a single connection to the test server.
+pub type ServerTask = JoinHandle<anyhow::Result<()>>;
+
+/// Starts a TLS test server for a single connection, serving the endpoints of
+/// `tlsn-server-fixture` (e.g. `/bytes?size=16000` or `/formats/json`) for [`SERVER_DOMAIN`].
+///
+/// Returns the socket to be passed to the prover, and the task serving the connection.
+///
+/// # Panics
+///
+/// Panics if called outside of a Tokio runtime.
+pub fn spawn_server() -> (DuplexStream, ServerTask) {
+    let (client_socket, server_socket) = tokio::io::duplex(SERVER_BUFFER_SIZE);
+    let task = tokio::spawn(tlsn_server_fixture::bind(server_socket.compat()));
+
+    (client_socket, task)
+}
+
+/// Returns a root certificate store trusting the certificate of the test server.
+pub fn root_cert_store() -> RootCertStore {
+    let mut root_store = RootCertStore::empty();
+    root_store
+        .add(&Certificate(CA_CERT_DER.to_vec()))
+        .expect("fixture CA certificate is valid");
+    root_store
+}
@@ -0,0 +1,56 @@
+use futures::{AsyncReadExt, AsyncWriteExt};
+use tlsn_prover::tls::{Prover, ProverConfig};
+use tlsn_test_utils::{root_cert_store, spawn_server, MockNotary, SERVER_DOMAIN};
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+#[tokio::test]
+#[ignore]
+async fn test_mock_notary() {
+    let notary = MockNotary::new();
+    let (notary_socket, notary_task) = notary.connect("test");
+    let (server_socket, server_task) = spawn_server();
+
+    let prover = Prover::new(
+        ProverConfig::builder()
+            .id("test")
+            .server_dns(SERVER_DOMAIN)
+            .root_cert_store(root_cert_store())
+            .build()
+            .unwrap(),
+    )
+    .setup(notary_socket.compat())
+    .await
+    .unwrap();
+
+    let (mut tls_connection, prover_fut) = prover.connect(server_socket.compat()).await.unwrap();
+    let prover_task = tokio::spawn(prover_fut);
+
+    tls_connection
+        .write_all(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    tls_connection.close().await.unwrap();
+
+    let mut response = Vec::new();
+    tls_connection.read_to_end(&mut response).await.unwrap();
+    server_task.await.unwrap().unwrap();
+
+    let mut prover = prover_task.await.unwrap().unwrap().start_notarize();
+    let sent_tx_len = prover.sent_transcript().data().len();
+    let recv_tx_len = prover.recv_transcript().data().len();
+
+    let builder = prover.commitment_builder();
+    builder.commit_sent(&(0..sent_tx_len)).unwrap();
+    builder.commit_recv(&(0..recv_tx_len)).unwrap();
+
+    let notarized_session = prover.finalize().await.unwrap();
+    let header = notary_task.await.unwrap().unwrap();
+
+    assert_eq!(notarized_session.header().to_bytes(), header.to_bytes());
+    notarized_session
+        .signature()
+        .as_ref()
+        .unwrap()
+        .verify(&header.to_bytes(), notary.public_key())
+        .unwrap();
+}
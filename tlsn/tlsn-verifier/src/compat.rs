@@ -0,0 +1,255 @@
+//! Compatibility with proofs produced by upstream tlsn releases.
+//!
+//! This fork extends the [`TlsProof`] format of upstream `0.1.0-alpha.5`, which it is based on,
+//! with labeled commitments, secp256k1 and Ed25519 notary signatures, and handshake-only
+//! sessions. Proofs which use none of the extensions are upstream proofs. They are encoded
+//! exactly as upstream encodes them, as JSON or bincode, and are verified exactly as upstream
+//! would.
+//!
+//! Later upstream releases replaced [`TlsProof`] with attestations and presentations, a
+//! different protocol. Their proofs are detected, but can not be verified by this crate.
+
+use serde::Deserialize;
+use tls_core::verify::ServerCertVerifier;
+use tlsn_core::{
+    proof::{TlsProof, ValidityWindow, VerificationReport},
+    NotaryPublicKey, RedactedTranscript, Signature,
+};
+
+/// A dialect of the proof format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Dialect {
+    /// The [`TlsProof`] format of upstream `0.1.0-alpha.5`.
+    Upstream,
+    /// The [`TlsProof`] format of this fork, using at least one of its extensions.
+    Fork,
+    /// The presentation format of upstream `0.1.0-alpha.7` and later.
+    UpstreamPresentation,
+}
+
+/// The encoding of a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// JSON, e.g. as written by the examples.
+    Json,
+    /// bincode, e.g. as sent over the wire.
+    Bincode,
+}
+
+/// An error that can occur while parsing a proof.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CompatError {
+    /// The proof is not in any known dialect.
+    #[error("unrecognized proof format")]
+    Unrecognized,
+    /// The proof is in a known dialect which can not be verified.
+    #[error("proofs in the {0:?} dialect can not be verified")]
+    UnsupportedDialect(Dialect),
+}
+
+/// A proof in a supported dialect.
+#[derive(Debug)]
+pub struct CompatProof {
+    dialect: Dialect,
+    encoding: Encoding,
+    proof: TlsProof,
+}
+
+impl CompatProof {
+    /// Parses a proof of any supported dialect, encoded as JSON or bincode.
+    pub fn parse(bytes: &[u8]) -> Result<Self, CompatError> {
+        if let Ok(proof) = serde_json::from_slice::<TlsProof>(bytes) {
+            return Ok(Self::new(proof, Encoding::Json));
+        }
+
+        if serde_json::from_slice::<PresentationShape>(bytes).is_ok() {
+            return Err(CompatError::UnsupportedDialect(
+                Dialect::UpstreamPresentation,
+            ));
+        }
+
+        match bincode::deserialize::<TlsProof>(bytes) {
+            Ok(proof) => Ok(Self::new(proof, Encoding::Bincode)),
+            Err(_) => Err(CompatError::Unrecognized),
+        }
+    }
+
+    fn new(proof: TlsProof, encoding: Encoding) -> Self {
+        Self {
+            dialect: detect_dialect(&proof),
+            encoding,
+            proof,
+        }
+    }
+
+    /// Returns the detected dialect.
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Returns the encoding the proof was parsed from.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns the proof.
+    pub fn proof(&self) -> &TlsProof {
+        &self.proof
+    }
+
+    /// Returns the proof, consuming `self`.
+    pub fn into_proof(self) -> TlsProof {
+        self.proof
+    }
+
+    /// Verifies the proof, returning a report of every check performed.
+    ///
+    /// Every supported dialect is verified the same way, see [`TlsProof::verify_with_report`].
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    pub fn verify_with_report(
+        self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+    ) -> (
+        VerificationReport,
+        Option<(RedactedTranscript, RedactedTranscript)>,
+    ) {
+        self.proof
+            .verify_with_report(notary_public_key, cert_verifier)
+    }
+
+    /// Verifies the proof against the validity window, returning a report of every check
+    /// performed.
+    ///
+    /// Every supported dialect is verified the same way, see
+    /// [`TlsProof::verify_with_validity_report`].
+    ///
+    /// # Arguments
+    ///
+    /// * `notary_public_key` - The public key of the notary.
+    /// * `cert_verifier` - The certificate verifier.
+    /// * `validity_window` - The window in which the session is accepted.
+    /// * `now` - The current time, in seconds since the UNIX epoch.
+    pub fn verify_with_validity_report(
+        self,
+        notary_public_key: impl Into<NotaryPublicKey>,
+        cert_verifier: &impl ServerCertVerifier,
+        validity_window: &ValidityWindow,
+        now: u64,
+    ) -> (
+        VerificationReport,
+        Option<(RedactedTranscript, RedactedTranscript)>,
+    ) {
+        self.proof.verify_with_validity_report(
+            notary_public_key,
+            cert_verifier,
+            validity_window,
+            now,
+        )
+    }
+}
+
+/// The top-level fields of an upstream presentation.
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct PresentationShape {
+    attestation: serde::de::IgnoredAny,
+    identity: Option<serde::de::IgnoredAny>,
+    transcript: Option<serde::de::IgnoredAny>,
+}
+
+/// Returns [`Dialect::Fork`] if the proof uses any extension of this fork.
+fn detect_dialect(proof: &TlsProof) -> Dialect {
+    let fork_signature = !matches!(proof.session.signature, None | Some(Signature::P256(_)));
+    let labeled = proof.substrings.labels().next().is_some();
+    let handshake_only = proof.session.header.is_handshake_only();
+
+    if fork_signature || labeled || handshake_only {
+        Dialect::Fork
+    } else {
+        Dialect::Upstream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tlsn_core::{fixtures, proof::default_cert_verifier};
+
+    use super::*;
+
+    const SENT: &[u8] = b"GET / HTTP/1.1";
+    const RECV: &[u8] = b"HTTP/1.1 200 OK";
+
+    /// Verifies the proof, accepting the fixture session regardless of its age.
+    fn verify(proof: CompatProof) -> (RedactedTranscript, RedactedTranscript) {
+        let (report, transcripts) = proof.verify_with_validity_report(
+            p256::PublicKey::from(*fixtures::notary_signing_key().verifying_key()),
+            &default_cert_verifier(),
+            &ValidityWindow::unbounded(),
+            fixtures::handshake_summary().time(),
+        );
+        assert!(report.is_valid(), "{report:?}");
+
+        transcripts.unwrap()
+    }
+
+    #[test]
+    fn test_parse_upstream_json() {
+        let bytes = serde_json::to_vec(&fixtures::tls_proof(SENT, RECV)).unwrap();
+
+        // Upstream proofs carry no fork specific fields
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(!json.to_string().contains("label"));
+
+        let proof = CompatProof::parse(&bytes).unwrap();
+        assert_eq!(proof.dialect(), Dialect::Upstream);
+        assert_eq!(proof.encoding(), Encoding::Json);
+
+        let (sent, recv) = verify(proof);
+        assert_eq!(sent.data(), SENT);
+        assert_eq!(recv.data(), RECV);
+    }
+
+    #[test]
+    fn test_parse_upstream_bincode() {
+        let bytes = bincode::serialize(&fixtures::tls_proof(SENT, RECV)).unwrap();
+
+        let proof = CompatProof::parse(&bytes).unwrap();
+        assert_eq!(proof.dialect(), Dialect::Upstream);
+        assert_eq!(proof.encoding(), Encoding::Bincode);
+
+        let (sent, recv) = verify(proof);
+        assert_eq!(sent.data(), SENT);
+        assert_eq!(recv.data(), RECV);
+    }
+
+    #[test]
+    fn test_parse_presentation() {
+        let presentation = br#"{"attestation": {"body": {}}, "identity": null}"#;
+
+        assert!(matches!(
+            CompatProof::parse(presentation),
+            Err(CompatError::UnsupportedDialect(
+                Dialect::UpstreamPresentation
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_parse_unrecognized() {
+        assert!(matches!(
+            CompatProof::parse(b"{}"),
+            Err(CompatError::Unrecognized)
+        ));
+        assert!(matches!(
+            CompatProof::parse(&[0u8; 16]),
+            Err(CompatError::Unrecognized)
+        ));
+    }
+}
@@ -4,4 +4,5 @@
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+pub mod compat;
 pub mod tls;
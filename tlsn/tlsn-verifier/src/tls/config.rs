@@ -1,18 +1,32 @@
 use mpz_ot::{chou_orlandi, kos};
 use mpz_share_conversion::{ReceiverConfig, SenderConfig};
-use std::fmt::{Debug, Formatter, Result};
+use std::{
+    fmt::{Debug, Formatter, Result},
+    time::Duration,
+};
 use tls_core::verify::{ServerCertVerifier, WebPkiVerifier};
 use tls_mpc::{MpcTlsCommonConfig, MpcTlsFollowerConfig, TranscriptConfig};
 use tlsn_common::{
-    config::{ot_recv_estimate, ot_send_estimate, DEFAULT_MAX_RECV_LIMIT, DEFAULT_MAX_SENT_LIMIT},
+    config::{
+        memory_estimate, ot_recv_estimate, ot_send_estimate, DEFAULT_MAX_RECV_LIMIT,
+        DEFAULT_MAX_SENT_LIMIT, DEFAULT_MAX_THREADS,
+    },
+    mux::{DEFAULT_MAX_BUFFER_SIZE, MIN_MAX_BUFFER_SIZE},
+    rng::{gen_seed, RngStream},
     Role,
 };
-use tlsn_core::proof::default_cert_verifier;
+use tlsn_core::proof::{default_cert_verifier, ValidityWindow};
+use tokio_util::sync::CancellationToken;
+
+/// Default timeout for the MPC setup phase (5 minutes).
+pub const DEFAULT_SETUP_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Default timeout for each finalization step (5 minutes).
+pub const DEFAULT_FINALIZE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
 /// Configuration for the [`Verifier`](crate::tls::Verifier)
 #[allow(missing_docs)]
 #[derive(derive_builder::Builder)]
-#[builder(pattern = "owned")]
+#[builder(pattern = "owned", build_fn(validate = "Self::validate"))]
 pub struct VerifierConfig {
     #[builder(setter(into))]
     id: String,
@@ -28,16 +42,86 @@ pub struct VerifierConfig {
         default = "Some(default_cert_verifier())"
     )]
     cert_verifier: Option<WebPkiVerifier>,
+    /// The window of time in which attestations are accepted.
+    #[builder(default)]
+    validity_window: ValidityWindow,
+    /// Maximum number of bytes buffered per multiplexed stream, at least
+    /// [`MIN_MAX_BUFFER_SIZE`](tlsn_common::mux::MIN_MAX_BUFFER_SIZE).
+    #[builder(default = "DEFAULT_MAX_BUFFER_SIZE")]
+    max_buffer_size: usize,
+    /// Timeout for the MPC setup phase, `None` to disable.
+    #[builder(default = "Some(DEFAULT_SETUP_TIMEOUT)")]
+    setup_timeout: Option<Duration>,
+    /// Timeout for the TLS session, covering the handshake and data transfer, `None` to disable.
+    ///
+    /// Disabled by default, as how long the prover keeps the connection to the server open depends
+    /// on the application.
+    #[builder(default)]
+    tls_timeout: Option<Duration>,
+    /// Timeout for each finalization step, `None` to disable.
+    #[builder(default = "Some(DEFAULT_FINALIZE_TIMEOUT)")]
+    finalize_timeout: Option<Duration>,
+    /// Maximum number of bytes of memory a session may use, `None` to disable.
+    ///
+    /// Sessions whose [estimated](VerifierConfig::memory_estimate) memory use exceeds the budget
+    /// are rejected before any MPC setup, instead of being allowed to exhaust the memory of the
+    /// process. The received records buffered for deferred decryption are kept in memory up to
+    /// the remainder of the budget and spilled to a temporary file beyond it.
+    #[builder(default)]
+    memory_budget: Option<usize>,
+    /// Maximum number of VM threads used by the session.
+    ///
+    /// The session uses the smaller of this limit and the prover's, capping how many cores a
+    /// single session can occupy.
+    #[builder(default = "DEFAULT_MAX_THREADS")]
+    max_threads: usize,
+    /// Timeout for each step of the MPC PRF, `None` to disable.
+    #[builder(default)]
+    prf_step_timeout: Option<Duration>,
+    /// Token which aborts the step of the MPC PRF in progress once cancelled.
+    #[builder(setter(strip_option), default)]
+    cancellation_token: Option<CancellationToken>,
+    /// Seed from which the randomness of the verifier is derived, for reproducing a session in
+    /// tests.
+    #[cfg(feature = "deterministic")]
+    #[builder(setter(strip_option), default)]
+    rng_seed: Option<u64>,
+}
+
+impl VerifierConfigBuilder {
+    fn validate(&self) -> std::result::Result<(), String> {
+        if let Some(max_buffer_size) = self.max_buffer_size {
+            if max_buffer_size < MIN_MAX_BUFFER_SIZE {
+                return Err(format!(
+                    "max_buffer_size must be at least {MIN_MAX_BUFFER_SIZE}, got {max_buffer_size}"
+                ));
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Debug for VerifierConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        f.debug_struct("VerifierConfig")
+        let mut debug = f.debug_struct("VerifierConfig");
+        debug
             .field("id", &self.id)
             .field("max_sent_data", &self.max_sent_data)
             .field("max_recv_data", &self.max_recv_data)
             .field("cert_verifier", &"_")
-            .finish()
+            .field("validity_window", &self.validity_window)
+            .field("max_buffer_size", &self.max_buffer_size)
+            .field("setup_timeout", &self.setup_timeout)
+            .field("tls_timeout", &self.tls_timeout)
+            .field("finalize_timeout", &self.finalize_timeout)
+            .field("memory_budget", &self.memory_budget)
+            .field("max_threads", &self.max_threads)
+            .field("prf_step_timeout", &self.prf_step_timeout)
+            .field("cancellation_token", &self.cancellation_token);
+        #[cfg(feature = "deterministic")]
+        debug.field("rng_seed", &self.rng_seed);
+        debug.finish()
     }
 }
 
@@ -52,6 +136,28 @@ impl VerifierConfig {
         &self.id
     }
 
+    /// Returns the seed from which the randomness of the verifier is derived.
+    #[cfg(feature = "deterministic")]
+    pub fn rng_seed(&self) -> Option<u64> {
+        self.rng_seed
+    }
+
+    /// Returns the seed from which the randomness of the verifier is derived.
+    #[cfg(not(feature = "deterministic"))]
+    pub(crate) fn rng_seed(&self) -> Option<u64> {
+        None
+    }
+
+    /// Returns the timeout for each step of the MPC PRF.
+    pub fn prf_step_timeout(&self) -> Option<Duration> {
+        self.prf_step_timeout
+    }
+
+    /// Returns the token which aborts the step of the MPC PRF in progress, if set.
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
     /// Returns the maximum number of bytes that can be sent.
     pub fn max_sent_data(&self) -> usize {
         self.max_sent_data
@@ -69,6 +175,58 @@ impl VerifierConfig {
             .expect("Certificate verifier should be set")
     }
 
+    /// Returns the window of time in which attestations are accepted.
+    pub fn validity_window(&self) -> &ValidityWindow {
+        &self.validity_window
+    }
+
+    /// Returns the maximum number of bytes buffered per multiplexed stream.
+    pub fn max_buffer_size(&self) -> usize {
+        self.max_buffer_size
+    }
+
+    /// Returns the timeout for the MPC setup phase.
+    pub fn setup_timeout(&self) -> Option<Duration> {
+        self.setup_timeout
+    }
+
+    /// Returns the timeout for the TLS session.
+    pub fn tls_timeout(&self) -> Option<Duration> {
+        self.tls_timeout
+    }
+
+    /// Returns the timeout for each finalization step.
+    pub fn finalize_timeout(&self) -> Option<Duration> {
+        self.finalize_timeout
+    }
+
+    /// Returns the maximum number of bytes of memory a session may use.
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
+    /// Returns the maximum number of VM threads used by the session.
+    pub fn max_threads(&self) -> usize {
+        self.max_threads
+    }
+
+    /// Returns an estimate of the number of bytes of memory held by a session, see
+    /// [`memory_estimate`].
+    pub fn memory_estimate(&self) -> usize {
+        memory_estimate(
+            Role::Verifier,
+            self.max_sent_data,
+            self.max_recv_data,
+            self.max_threads,
+        )
+    }
+
+    /// Returns the number of bytes of received records kept in memory before spilling to disk.
+    pub(crate) fn max_buffered_bytes(&self) -> Option<usize> {
+        self.memory_budget
+            .map(|budget| budget.saturating_sub(self.memory_estimate()))
+    }
+
     pub(crate) fn build_base_ot_sender_config(&self) -> chou_orlandi::SenderConfig {
         chou_orlandi::SenderConfig::default()
     }
@@ -91,11 +249,12 @@ impl VerifierConfig {
         kos::ReceiverConfig::default()
     }
 
-    pub(crate) fn build_mpc_tls_config(&self) -> MpcTlsFollowerConfig {
+    pub(crate) fn build_mpc_tls_config(&self, num_threads: usize) -> MpcTlsFollowerConfig {
         MpcTlsFollowerConfig::builder()
             .common(
                 MpcTlsCommonConfig::builder()
                     .id(format!("{}/mpc_tls", &self.id))
+                    .num_threads(num_threads)
                     .tx_config(
                         TranscriptConfig::default_tx()
                             .max_size(self.max_sent_data)
@@ -109,9 +268,13 @@ impl VerifierConfig {
                             .unwrap(),
                     )
                     .handshake_commit(true)
+                    .rng_seed(gen_seed(self.rng_seed(), &self.id, RngStream::Tls))
+                    .prf_step_timeout(self.prf_step_timeout)
+                    .cancellation_token(self.cancellation_token.clone())
                     .build()
                     .unwrap(),
             )
+            .max_buffered_bytes(self.max_buffered_bytes())
             .build()
             .unwrap()
     }
@@ -140,3 +303,69 @@ impl VerifierConfig {
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(memory_budget: Option<usize>) -> VerifierConfig {
+        VerifierConfig::builder()
+            .id("test")
+            .memory_budget(memory_budget)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_memory_estimate_covers_transcript() {
+        let small = config(None);
+        let large = VerifierConfig::builder()
+            .id("test")
+            .max_recv_data(DEFAULT_MAX_RECV_LIMIT * 2)
+            .build()
+            .unwrap();
+        let threads = VerifierConfig::builder()
+            .id("test")
+            .max_threads(DEFAULT_MAX_THREADS * 2)
+            .build()
+            .unwrap();
+
+        // The encodings grow with the transcript and the garbled tables with the threads.
+        assert!(large.memory_estimate() > small.memory_estimate());
+        assert!(threads.memory_estimate() > small.memory_estimate());
+    }
+
+    #[test]
+    fn test_max_buffer_size_minimum() {
+        let err = VerifierConfig::builder()
+            .id("test")
+            .max_buffer_size(MIN_MAX_BUFFER_SIZE - 1)
+            .build()
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            VerifierConfigBuilderError::ValidationError(_)
+        ));
+
+        VerifierConfig::builder()
+            .id("test")
+            .max_buffer_size(MIN_MAX_BUFFER_SIZE)
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_max_buffered_bytes() {
+        assert_eq!(config(None).max_buffered_bytes(), None);
+
+        let estimate = config(None).memory_estimate();
+        let config = config(Some(estimate + 1024));
+
+        assert_eq!(config.max_buffered_bytes(), Some(1024));
+        assert_eq!(
+            config.build_mpc_tls_config(1).max_buffered_bytes(),
+            Some(1024)
+        );
+    }
+}
@@ -9,10 +9,20 @@ pub enum VerifierError {
     IOError(#[from] std::io::Error),
     #[error(transparent)]
     MuxerError(#[from] utils_aio::mux::MuxerError),
+    #[error(transparent)]
+    HelloError(#[from] tlsn_common::hello::HelloError),
+    #[error("system time is before the UNIX epoch: {0}")]
+    SystemTimeError(#[from] std::time::SystemTimeError),
     #[error("error occurred in MPC protocol: {0}")]
     MpcError(Box<dyn Error + Send + Sync + 'static>),
     #[error("Range exceeds transcript length")]
     InvalidRange,
+    #[error("{0} phase timed out")]
+    Timeout(&'static str),
+    #[error("session would use an estimated {estimate} bytes of memory, exceeding the budget of {budget} bytes")]
+    MemoryBudgetExceeded { estimate: usize, budget: usize },
+    #[error("session proof is not signed by a notary of the registry")]
+    UnknownNotary,
 }
 
 impl From<MpcTlsError> for VerifierError {
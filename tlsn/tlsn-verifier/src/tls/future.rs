@@ -1,8 +1,9 @@
 //! This module collects futures which are used by the [Verifier](crate::tls::Verifier).
 
 use super::{OTSenderActor, VerifierError};
-use futures::{future::FusedFuture, Future};
-use std::pin::Pin;
+use futures::{future::FusedFuture, Future, FutureExt};
+use futures_timer::Delay;
+use std::{pin::Pin, time::Duration};
 
 /// A future which must be polled for the muxer to make progress.
 pub(crate) struct MuxFuture {
@@ -48,3 +49,25 @@ impl FusedFuture for OTFuture {
         self.fut.is_terminated()
     }
 }
+
+/// Runs the future to completion, returning an error if it does not complete within `timeout`.
+///
+/// # Arguments
+///
+/// * `timeout` - The timeout, if any.
+/// * `phase` - The name of the phase, used in the error.
+/// * `fut` - The future to run.
+pub(crate) async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    phase: &'static str,
+    fut: impl Future<Output = Result<T, VerifierError>>,
+) -> Result<T, VerifierError> {
+    let Some(timeout) = timeout else {
+        return fut.await;
+    };
+
+    futures::select! {
+        res = fut.fuse() => res,
+        _ = Delay::new(timeout).fuse() => Err(VerifierError::Timeout(phase)),
+    }
+}
@@ -7,16 +7,19 @@ mod notarize;
 pub mod state;
 mod verify;
 
-pub use config::{VerifierConfig, VerifierConfigBuilder, VerifierConfigBuilderError};
+pub use config::{
+    VerifierConfig, VerifierConfigBuilder, VerifierConfigBuilderError, DEFAULT_FINALIZE_TIMEOUT,
+    DEFAULT_SETUP_TIMEOUT,
+};
 pub use error::VerifierError;
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::tls::future::OTFuture;
-use future::MuxFuture;
+use future::{with_timeout, MuxFuture};
 use futures::{
     stream::{SplitSink, SplitStream},
-    AsyncRead, AsyncWrite, FutureExt, StreamExt, TryFutureExt,
+    AsyncRead, AsyncWrite, Future, FutureExt, StreamExt, TryFutureExt,
 };
 use mpz_garble::{config::Role as GarbleRole, protocol::deap::DEAPVm};
 use mpz_ot::{
@@ -26,16 +29,24 @@ use mpz_ot::{
     chou_orlandi, kos,
 };
 use mpz_share_conversion as ff;
-use rand::Rng;
 use signature::Signer;
 use state::{Notarize, Verify};
 use tls_mpc::{setup_components, MpcTlsFollower, MpcTlsFollowerData, TlsRole};
 use tlsn_common::{
-    mux::{attach_mux, MuxControl},
+    hello::{exchange_hello, Hello},
+    mux::{attach_mux_with_buffer_size, MuxControl},
+    registry::{NotaryEntry, Registry},
+    rng::{gen_seed, RngStream},
     Role,
 };
-use tlsn_core::{proof::SessionInfo, RedactedTranscript, SessionHeader, Signature};
-use utils_aio::{duplex::Duplex, mux::MuxChannel};
+use tlsn_core::{
+    proof::{SessionInfo, SessionProof, SessionProofError},
+    NotaryPublicKey, RedactedTranscript, SessionHeader, Signature,
+};
+use utils_aio::{
+    duplex::Duplex,
+    mux::{MuxChannel, MuxerError},
+};
 
 #[cfg(feature = "tracing")]
 use tracing::{debug, info, instrument};
@@ -75,14 +86,49 @@ impl Verifier<state::Initialized> {
         self,
         socket: S,
     ) -> Result<Verifier<state::Setup>, VerifierError> {
-        let (mut mux, mux_ctrl) = attach_mux(socket, Role::Verifier);
+        let (mut mux, mux_ctrl) =
+            attach_mux_with_buffer_size(socket, Role::Verifier, self.config.max_buffer_size());
+
+        self.setup_with_mux(mux_ctrl, async move { mux.run().await })
+            .await
+    }
+
+    /// Set up the verifier on the provided multiplexer, see [`setup`](Verifier::setup).
+    ///
+    /// This allows running the verifier on a multiplexer other than the one attached by
+    /// [`setup`](Verifier::setup), eg. one replaying a recorded session with
+    /// `tlsn_common::mux::attach_replay`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mux_ctrl` - The controller of the multiplexer.
+    /// * `mux_fut` - The future which must be polled for the multiplexer to make progress.
+    pub async fn setup_with_mux(
+        self,
+        mux_ctrl: MuxControl,
+        mux_fut: impl Future<Output = Result<(), MuxerError>> + Send + 'static,
+    ) -> Result<Verifier<state::Setup>, VerifierError> {
+        if let Some(budget) = self.config.memory_budget() {
+            let estimate = self.config.memory_estimate();
+            if estimate > budget {
+                return Err(VerifierError::MemoryBudgetExceeded { estimate, budget });
+            }
+        }
 
         let mut mux_fut = MuxFuture {
-            fut: Box::pin(async move { mux.run().await.map_err(VerifierError::from) }.fuse()),
+            fut: Box::pin(mux_fut.map_err(VerifierError::from).fuse()),
         };
 
-        let encoder_seed: [u8; 32] = rand::rngs::OsRng.gen();
-        let mpc_setup_fut = setup_mpc_backend(&self.config, mux_ctrl.clone(), encoder_seed);
+        let encoder_seed = gen_seed(
+            self.config.rng_seed(),
+            self.config.id(),
+            RngStream::EncoderSeed,
+        );
+        let mpc_setup_fut = with_timeout(
+            self.config.setup_timeout(),
+            "setup",
+            setup_mpc_backend(&self.config, mux_ctrl.clone(), encoder_seed),
+        );
         let (mpc_tls, vm, ot_send, ot_recv, gf2, ot_fut) = futures::select! {
             res = mpc_setup_fut.fuse() => res?,
             _ = &mut mux_fut => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
@@ -137,6 +183,58 @@ impl Verifier<state::Initialized> {
         let session_info = verifier.finalize().await?;
         Ok((redacted_sent, redacted_received, session_info))
     }
+
+    /// Verifies a session proof issued by a Notary.
+    ///
+    /// The session is verified using the certificate verifier of this verifier, and rejected if
+    /// it is outside of the configured validity window.
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The session proof.
+    /// * `notary_public_key` - The public key of the notary.
+    pub fn verify_session_proof(
+        &self,
+        proof: &SessionProof,
+        notary_public_key: impl Into<NotaryPublicKey>,
+    ) -> Result<(), VerifierError> {
+        proof.verify_with_validity(
+            notary_public_key,
+            self.config.cert_verifier(),
+            self.config.validity_window(),
+            now()?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Verifies a session proof issued by a notary of the registry, returning the notary.
+    ///
+    /// The notary key is resolved from the registry instead of being provided out of band, the
+    /// session is then verified as with [`verify_session_proof`](Verifier::verify_session_proof).
+    ///
+    /// # Arguments
+    ///
+    /// * `proof` - The session proof.
+    /// * `registry` - The registry of known notaries.
+    pub fn verify_session_proof_with_registry<'a>(
+        &self,
+        proof: &SessionProof,
+        registry: &'a Registry,
+    ) -> Result<&'a NotaryEntry, VerifierError> {
+        let signature = proof
+            .signature
+            .as_ref()
+            .ok_or(SessionProofError::MissingNotarySignature)?;
+
+        let (notary, notary_public_key) = registry
+            .resolve_signer(&proof.header.to_bytes(), signature)
+            .ok_or(VerifierError::UnknownNotary)?;
+
+        self.verify_session_proof(proof, notary_public_key.clone())?;
+
+        Ok(notary)
+    }
 }
 
 impl Verifier<state::Setup> {
@@ -154,12 +252,14 @@ impl Verifier<state::Setup> {
             encoder_seed,
         } = self.state;
 
-        let start_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let start_time = now()?;
 
         let (_, mpc_fut) = mpc_tls.run();
+        let mpc_fut = with_timeout(
+            self.config.tls_timeout(),
+            "tls",
+            mpc_fut.map_err(VerifierError::from),
+        );
 
         let MpcTlsFollowerData {
             handshake_commitment,
@@ -226,6 +326,11 @@ impl Verifier<state::Closed> {
 /// Performs a setup of the various MPC subprotocols.
 #[cfg_attr(feature = "tracing", instrument(level = "debug", skip_all, err))]
 #[allow(clippy::type_complexity)]
+/// Returns the current time, in seconds since the UNIX epoch.
+fn now() -> Result<u64, VerifierError> {
+    Ok(SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs())
+}
+
 async fn setup_mpc_backend(
     config: &VerifierConfig,
     mut mux_ctrl: MuxControl,
@@ -241,13 +346,27 @@ async fn setup_mpc_backend(
     ),
     VerifierError,
 > {
+    // Fail early if the other party runs an incompatible version of the protocol.
+    let mut hello_channel = mux_ctrl.get_channel("hello").await?;
+    let negotiated = exchange_hello(
+        &mut hello_channel,
+        Hello {
+            max_threads: config.max_threads(),
+            ..Default::default()
+        },
+    )
+    .await?;
+
     let (ot_send_sink, ot_send_stream) = mux_ctrl.get_channel("ot/1").await?.split();
     let (ot_recv_sink, ot_recv_stream) = mux_ctrl.get_channel("ot/0").await?.split();
 
     let mut ot_sender_actor = OTSenderActor::new(
         kos::Sender::new(
             config.build_ot_sender_config(),
-            chou_orlandi::Receiver::new(config.build_base_ot_receiver_config()),
+            chou_orlandi::Receiver::new_with_seed(
+                config.build_base_ot_receiver_config(),
+                gen_seed(config.rng_seed(), config.id(), RngStream::BaseOtSender),
+            ),
         ),
         ot_send_sink,
         ot_send_stream,
@@ -256,7 +375,10 @@ async fn setup_mpc_backend(
     let mut ot_receiver_actor = ReceiverActor::new(
         kos::Receiver::new(
             config.build_ot_receiver_config(),
-            chou_orlandi::Sender::new(config.build_base_ot_sender_config()),
+            chou_orlandi::Sender::new_with_seed(
+                config.build_base_ot_sender_config(),
+                gen_seed(config.rng_seed(), config.id(), RngStream::BaseOtReceiver),
+            ),
         ),
         ot_recv_sink,
         ot_recv_stream,
@@ -318,7 +440,7 @@ async fn setup_mpc_backend(
     let channel = mux_ctrl.get_channel(gf2_config.id()).await?;
     let gf2 = ff::ConverterReceiver::<ff::Gf2_128, _>::new(gf2_config, ot_recv.clone(), channel);
 
-    let mpc_tls_config = config.build_mpc_tls_config();
+    let mpc_tls_config = config.build_mpc_tls_config(negotiated.threads);
 
     let (ke, prf, encrypter, decrypter) = setup_components(
         mpc_tls_config.common(),
@@ -2,7 +2,7 @@
 //!
 //! The TLS verifier is only a notary.
 
-use super::{state::Notarize, Verifier, VerifierError};
+use super::{future::with_timeout, state::Notarize, Verifier, VerifierError};
 use futures::{FutureExt, SinkExt, StreamExt, TryFutureExt};
 use mpz_core::serialize::CanonicalSerialize;
 use mpz_share_conversion::ShareConversionVerify;
@@ -93,6 +93,8 @@ impl Verifier<Notarize> {
             Ok::<_, VerifierError>(session_header)
         };
 
+        let notarize_fut = with_timeout(self.config.finalize_timeout(), "finalize", notarize_fut);
+
         let session_header = futures::select! {
             res = notarize_fut.fuse() => res?,
             _ = &mut mux_fut => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
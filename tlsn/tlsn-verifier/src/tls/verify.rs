@@ -2,14 +2,18 @@
 //!
 //! The TLS verifier is an application-specific verifier.
 
-use super::{state::Verify as VerifyState, Verifier, VerifierError};
+use std::ops::Range;
+
+use super::{future::with_timeout, state::Verify as VerifyState, Verifier, VerifierError};
 use futures::{FutureExt, StreamExt, TryFutureExt};
 use mpz_circuits::types::Value;
 use mpz_garble::{Memory, Verify, Vm};
 use mpz_share_conversion::ShareConversionVerify;
 use tlsn_core::{
-    msg::TlsnMessage, proof::SessionInfo, transcript::get_value_ids, Direction, HandshakeSummary,
-    RedactedTranscript, TranscriptSlice,
+    msg::{TlsnMessage, MAX_REVEALED_RECORD_LEN},
+    proof::SessionInfo,
+    transcript::get_value_ids,
+    Direction, HandshakeSummary, RedactedTranscript, TranscriptSlice,
 };
 use utils_aio::{expect_msg_or_err, mux::MuxChannel};
 
@@ -25,6 +29,43 @@ impl Verifier<VerifyState> {
     pub async fn receive(
         &mut self,
     ) -> Result<(RedactedTranscript, RedactedTranscript), VerifierError> {
+        let mut sent_slices = Vec::new();
+        let mut recv_slices = Vec::new();
+
+        self.receive_with_hook(|direction, range, data| {
+            let slice = TranscriptSlice::new(range, data.to_vec());
+            match direction {
+                Direction::Sent => sent_slices.push(slice),
+                Direction::Received => recv_slices.push(slice),
+            }
+        })
+        .await?;
+
+        #[cfg(feature = "tracing")]
+        info!("Successfully created redacted transcripts");
+
+        Ok((
+            RedactedTranscript::new(self.state.sent_len, sent_slices),
+            RedactedTranscript::new(self.state.recv_len, recv_slices),
+        ))
+    }
+
+    /// Receives the **purported** transcript from the Prover, passing each revealed record to
+    /// the provided hook instead of collecting it into a [`RedactedTranscript`].
+    ///
+    /// The revealed ranges are received and verified one record of at most
+    /// [`MAX_REVEALED_RECORD_LEN`] bytes at a time, and the hook is called with the direction,
+    /// the range offsets within the transcript, and the revealed bytes of each record as soon as
+    /// it is verified. Sent ranges are passed first, followed by received ranges, each in
+    /// ascending order.
+    ///
+    /// # Warning
+    ///
+    /// The content passed to the hook can not be considered authentic until after finalization.
+    pub async fn receive_with_hook<F>(&mut self, mut hook: F) -> Result<(), VerifierError>
+    where
+        F: FnMut(Direction, Range<usize>, &[u8]),
+    {
         let verify_fut = async {
             // Create a new channel and vm thread if not already present
             let channel = if let Some(ref mut channel) = self.state.channel {
@@ -42,8 +83,7 @@ impl Verifier<VerifyState> {
             };
 
             // Receive the proving info from the prover
-            let mut proving_info = expect_msg_or_err!(channel, TlsnMessage::ProvingInfo)?;
-            let mut cleartext = proving_info.cleartext.clone();
+            let proving_info = expect_msg_or_err!(channel, TlsnMessage::ProvingInfo)?;
 
             #[cfg(feature = "tracing")]
             info!("Received proving info from prover");
@@ -55,79 +95,47 @@ impl Verifier<VerifyState> {
                 return Err(VerifierError::InvalidRange);
             }
 
-            // Now verify the transcript parts which the prover wants to reveal
-            let sent_value_ids = proving_info
-                .sent_ids
-                .iter_ranges()
-                .map(|r| get_value_ids(&r.into(), Direction::Sent).collect::<Vec<String>>());
-            let recv_value_ids = proving_info
-                .recv_ids
-                .iter_ranges()
-                .map(|r| get_value_ids(&r.into(), Direction::Received).collect::<Vec<String>>());
-
-            let value_refs = sent_value_ids
-                .chain(recv_value_ids)
-                .map(|ids| {
-                    let inner_refs = ids
-                        .iter()
-                        .map(|id| {
-                            verify_thread
-                                .get_value(id.as_str())
-                                .expect("Byte should be in VM memory")
-                        })
-                        .collect::<Vec<_>>();
-
-                    verify_thread
-                        .array_from_values(inner_refs.as_slice())
-                        .expect("Byte should be in VM Memory")
-                })
-                .collect::<Vec<_>>();
-
-            let values = proving_info
-                .sent_ids
-                .iter_ranges()
-                .chain(proving_info.recv_ids.iter_ranges())
-                .map(|range| {
-                    Value::Array(cleartext.drain(..range.len()).map(|b| (b).into()).collect())
-                })
-                .collect::<Vec<_>>();
-
-            // Check that purported values are correct
-            verify_thread.verify(&value_refs, &values).await?;
+            // Verify the transcript parts which the prover wants to reveal, one record at a time
+            for (direction, range) in proving_info.records() {
+                let cleartext = expect_msg_or_err!(channel, TlsnMessage::RevealedRecord)?;
+
+                // Check that the prover sent exactly as much cleartext as it claims to reveal
+                if cleartext.len() != range.len() {
+                    return Err(VerifierError::InvalidRange);
+                }
+
+                let inner_refs = get_value_ids(&range.clone().into(), direction)
+                    .map(|id| {
+                        verify_thread
+                            .get_value(id.as_str())
+                            .expect("Byte should be in VM memory")
+                    })
+                    .collect::<Vec<_>>();
+                let value_ref = verify_thread
+                    .array_from_values(inner_refs.as_slice())
+                    .expect("Byte should be in VM Memory");
+                let value = Value::Array(cleartext.iter().map(|b| (*b).into()).collect());
+
+                // Check that purported values are correct
+                verify_thread.verify(&[value_ref], &[value]).await?;
+
+                hook(direction, range, &cleartext);
+            }
 
             #[cfg(feature = "tracing")]
             info!("Successfully verified purported cleartext");
 
-            // Create redacted transcripts
-            let mut transcripts = proving_info
-                .sent_ids
-                .iter_ranges()
-                .chain(proving_info.recv_ids.iter_ranges())
-                .map(|range| {
-                    TranscriptSlice::new(
-                        range.clone(),
-                        proving_info.cleartext.drain(..range.len()).collect(),
-                    )
-                })
-                .collect::<Vec<_>>();
-
-            let recv_transcripts =
-                transcripts.split_off(proving_info.sent_ids.iter_ranges().count());
-            let (sent_redacted, recv_redacted) = (
-                RedactedTranscript::new(self.state.sent_len, transcripts),
-                RedactedTranscript::new(self.state.recv_len, recv_transcripts),
-            );
-
-            #[cfg(feature = "tracing")]
-            info!("Successfully created redacted transcripts");
-
-            Ok::<_, VerifierError>((sent_redacted, recv_redacted))
+            Ok::<_, VerifierError>(())
         };
 
+        let verify_fut = with_timeout(self.config.finalize_timeout(), "receive", verify_fut);
+
         futures::select! {
-            res = verify_fut.fuse() => res,
+            res = verify_fut.fuse() => res?,
             _ = &mut self.state.mux_fut => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,
-        }
+        };
+
+        Ok(())
     }
 
     /// Verify the TLS session.
@@ -174,6 +182,8 @@ impl Verifier<VerifyState> {
             Ok::<_, VerifierError>(session_info)
         };
 
+        let finalize_fut = with_timeout(self.config.finalize_timeout(), "finalize", finalize_fut);
+
         let session_info = futures::select! {
             res = finalize_fut.fuse() => res?,
             _ = &mut mux_fut => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof))?,